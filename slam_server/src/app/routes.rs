@@ -1,18 +1,163 @@
+use crate::model::access_token::{SCOPE_AI_JOBS, SCOPE_SPORTS_READ, SCOPE_SPORTS_WRITE};
+use crate::model::coach::SCOPE_STATS_READ;
+
 pub const API_STATUS: &str = "/api/status";
+pub const API_JWKS: &str = "/.well-known/jwks.json";
 pub const API_IMAGE_PARSE: &str = "/api/ai/image-parse";
 pub const API_AI_JOBS: &str = "/api/ai/jobs";
+pub const API_AI_JOB_EVENTS: &str = "/api/ai/jobs/events";
 pub const API_AI_JOB: &str = "/api/ai/jobs/:id";
 pub const API_AI_JOB_RETRY: &str = "/api/ai/jobs/:id/retry";
 pub const API_AI_ASSET: &str = "/api/ai/assets/:id/content";
 pub const API_AI_ASSET_THUMBNAIL: &str = "/api/ai/assets/:id/thumbnail";
 pub const API_USER_REGISTER: &str = "/api/user/register";
 pub const API_USER_LOGIN: &str = "/api/user/login";
+pub const API_USER_LOGIN_TWO_FACTOR: &str = "/api/user/login/2fa";
 pub const API_USER_INFO: &str = "/api/user/info";
 pub const API_USER_LOGOUT: &str = "/api/user/logout";
+pub const API_USER_LOGOUT_ALL: &str = "/api/user/logout-all";
+pub const API_USER_SESSIONS: &str = "/api/user/sessions";
+pub const API_USER_SESSION: &str = "/api/user/sessions/:id";
+pub const API_USER_TOKENS: &str = "/api/user/tokens";
+pub const API_USER_TOKEN: &str = "/api/user/tokens/:id";
+pub const API_USER_PASSWORD: &str = "/api/user/password";
+pub const API_USER_PASSWORD_RESET: &str = "/api/user/password/reset";
+pub const API_USER_PROFILE: &str = "/api/user/profile";
+pub const API_USER_ACCOUNT: &str = "/api/user/account";
+pub const API_USER_AUDIT: &str = "/api/user/audit";
+pub const API_USER_TWO_FACTOR: &str = "/api/user/2fa";
+pub const API_USER_TWO_FACTOR_SETUP: &str = "/api/user/2fa/setup";
+pub const API_USER_TWO_FACTOR_ENABLE: &str = "/api/user/2fa/enable";
+pub const API_USER_TWO_FACTOR_DISABLE: &str = "/api/user/2fa/disable";
+pub const API_USER_TWO_FACTOR_RECOVERY_CODES: &str = "/api/user/2fa/recovery-codes";
+pub const API_USER_OIDC_LOGIN: &str = "/api/user/oidc/login";
+pub const API_USER_OIDC_LINK: &str = "/api/user/oidc/link";
+pub const API_USER_OIDC_CALLBACK: &str = "/api/user/oidc/callback";
+pub const API_USER_OIDC_IDENTITIES: &str = "/api/user/oidc/identities";
+pub const API_USER_OIDC_IDENTITY: &str = "/api/user/oidc/identities/:id";
 pub const API_USER_AVATAR_UPLOAD: &str = "/api/user/avatar/upload";
+pub const API_USER_AVATAR: &str = "/api/user/avatar/:uid";
+pub const API_USER_ATHLETE: &str = "/api/user/athlete";
+pub const API_USER_ATHLETE_HISTORY: &str = "/api/user/athlete/history";
+pub const API_USER_WEIGHTS: &str = "/api/user/weights";
+pub const API_USER_WEIGHT: &str = "/api/user/weights/:id";
 pub const API_SPORT_INSERT: &str = "/api/sport/insert";
 pub const API_SPORT_LIST: &str = "/api/sport/list";
 pub const API_SPORT_STATS: &str = "/api/sport/stats";
+pub const API_SPORT_METRICS: &str = "/api/sport/metrics";
+pub const API_SPORT_HEATMAP_HOURLY: &str = "/api/sport/heatmap/hourly";
+pub const API_SPORT_HEATMAP_CALENDAR: &str = "/api/sport/heatmap/calendar";
+pub const API_SPORT_REVIEW: &str = "/api/sport/review";
+pub const API_SPORT_REVIEW_CARD: &str = "/api/sport/review/card";
 pub const API_SPORT_UPDATE: &str = "/api/sport/update";
 pub const API_SPORT_IMPORT: &str = "/api/sport/import";
 pub const API_SPORT_DELETE: &str = "/api/sport/delete";
+pub const API_SPORT_SHARES: &str = "/api/sport/shares";
+pub const API_SPORT_SHARE: &str = "/api/sport/shares/:id";
+pub const API_SHARE: &str = "/api/share/:token";
+pub const API_SHARE_CARD: &str = "/api/share/:token/card";
+pub const API_SOCIAL_SETTINGS: &str = "/api/social/settings";
+pub const API_SOCIAL_USERS: &str = "/api/social/users";
+pub const API_SOCIAL_USER: &str = "/api/social/users/:uid";
+pub const API_SOCIAL_USER_SPORTS: &str = "/api/social/users/:uid/sports";
+pub const API_SOCIAL_FOLLOWING: &str = "/api/social/following";
+pub const API_SOCIAL_FOLLOWING_USER: &str = "/api/social/following/:uid";
+pub const API_SOCIAL_FOLLOWERS: &str = "/api/social/followers";
+pub const API_SOCIAL_FOLLOWER: &str = "/api/social/followers/:uid";
+pub const API_SOCIAL_FOLLOWER_APPROVE: &str = "/api/social/followers/:uid/approve";
+pub const API_SOCIAL_FEED: &str = "/api/social/feed";
+pub const API_SOCIAL_SPORT_COMMENTS: &str = "/api/social/sports/:id/comments";
+pub const API_SOCIAL_SPORT_COMMENT: &str = "/api/social/sports/:id/comments/:comment_id";
+pub const API_SOCIAL_SPORT_REACTIONS: &str = "/api/social/sports/:id/reactions";
+pub const API_SOCIAL_SPORT_REACTION: &str = "/api/social/sports/:id/reactions/:kind";
+pub const API_NOTIFICATIONS: &str = "/api/notifications";
+pub const API_NOTIFICATIONS_UNREAD: &str = "/api/notifications/unread-count";
+pub const API_NOTIFICATIONS_READ_ALL: &str = "/api/notifications/read-all";
+pub const API_NOTIFICATION_READ: &str = "/api/notifications/:id/read";
+pub const API_WEBHOOKS: &str = "/api/webhooks";
+pub const API_WEBHOOK: &str = "/api/webhooks/:id";
+pub const API_WEBHOOK_DELIVERIES: &str = "/api/webhooks/:id/deliveries";
+pub const API_WEBHOOK_REDELIVER: &str = "/api/webhooks/:id/deliveries/:delivery_id/redeliver";
+pub const API_CLUBS: &str = "/api/clubs";
+pub const API_CLUBS_JOIN: &str = "/api/clubs/join";
+pub const API_CLUBS_INVITES: &str = "/api/clubs/invites";
+pub const API_CLUB: &str = "/api/clubs/:id";
+pub const API_CLUB_JOIN_CODE: &str = "/api/clubs/:id/join-code";
+pub const API_CLUB_LEAVE: &str = "/api/clubs/:id/leave";
+pub const API_CLUB_MEMBERS: &str = "/api/clubs/:id/members";
+pub const API_CLUB_MEMBER: &str = "/api/clubs/:id/members/:uid";
+pub const API_CLUB_INVITES: &str = "/api/clubs/:id/invites";
+pub const API_CLUB_INVITE_ACCEPT: &str = "/api/clubs/:id/invites/accept";
+pub const API_CLUB_INVITE_DECLINE: &str = "/api/clubs/:id/invites/decline";
+pub const API_CLUB_CHALLENGES: &str = "/api/clubs/:id/challenges";
+pub const API_CLUB_CHALLENGE: &str = "/api/clubs/:id/challenges/:challenge_id";
+pub const API_CLUB_LEADERBOARD: &str = "/api/clubs/:id/challenges/:challenge_id/leaderboard";
+pub const API_COACH_GRANTS: &str = "/api/coach/grants";
+pub const API_COACH_GRANT: &str = "/api/coach/grants/:uid";
+pub const API_COACH_ATHLETES: &str = "/api/coach/athletes";
+pub const API_COACH_ATHLETE: &str = "/api/coach/athletes/:uid";
+pub const API_COACH_ATHLETE_ACCEPT: &str = "/api/coach/athletes/:uid/accept";
+pub const API_ADMIN_USERS: &str = "/api/admin/users";
+pub const API_ADMIN_USER_DISABLE: &str = "/api/admin/users/:id/disable";
+pub const API_ADMIN_USER_ENABLE: &str = "/api/admin/users/:id/enable";
+pub const API_ADMIN_USER_PASSWORD_RESET: &str = "/api/admin/users/:id/password-reset";
+pub const API_ADMIN_AI_JOBS: &str = "/api/admin/ai/jobs";
+pub const API_ADMIN_AI_JOB: &str = "/api/admin/ai/jobs/:id";
+pub const API_ADMIN_AI_JOB_REQUEUE: &str = "/api/admin/ai/jobs/:id/requeue";
+pub const API_ADMIN_AI_QUEUE: &str = "/api/admin/ai/queue";
+pub const API_ADMIN_AUDIT: &str = "/api/admin/audit";
+pub const API_ADMIN_WEBHOOKS: &str = "/api/admin/webhooks";
+
+/// 用户头像的访问地址，带上版本号以便头像更新后浏览器缓存失效
+pub fn avatar_url(uid: i32, version: &str) -> String {
+    format!("/api/user/avatar/{uid}?v={version}")
+}
+
+/// 分享链接的公开查看地址
+pub fn share_url(token: &str) -> String {
+    format!("/api/share/{token}")
+}
+
+/// 分享链接的 PNG 卡片地址
+pub fn share_card_url(token: &str) -> String {
+    format!("/api/share/{token}/card")
+}
+
+/// 个人访问令牌可访问的路由及所需权限范围，未列出的路由只接受 cookie 登录态
+pub fn token_scope(path: &str) -> Option<&'static str> {
+    match path {
+        API_SPORT_LIST
+        | API_SPORT_STATS
+        | API_SPORT_METRICS
+        | API_SPORT_HEATMAP_HOURLY
+        | API_SPORT_HEATMAP_CALENDAR
+        | API_SPORT_REVIEW
+        | API_SPORT_REVIEW_CARD
+        | API_SOCIAL_FEED
+        | API_SOCIAL_USER_SPORTS => Some(SCOPE_SPORTS_READ),
+        API_SPORT_INSERT | API_SPORT_IMPORT | API_SPORT_UPDATE | API_SPORT_DELETE => {
+            Some(SCOPE_SPORTS_WRITE)
+        }
+        API_IMAGE_PARSE
+        | API_AI_JOBS
+        | API_AI_JOB_EVENTS
+        | API_AI_JOB
+        | API_AI_JOB_RETRY
+        | API_AI_ASSET
+        | API_AI_ASSET_THUMBNAIL => Some(SCOPE_AI_JOBS),
+        _ => None,
+    }
+}
+
+/// 教练可以代运动员访问的路由及所需授权范围，未列出的路由不接受代理访问
+pub fn coach_scope(path: &str) -> Option<&'static str> {
+    match path {
+        API_SPORT_LIST | API_SPORT_METRICS => Some(SCOPE_SPORTS_READ),
+        API_SPORT_STATS
+        | API_SPORT_HEATMAP_HOURLY
+        | API_SPORT_HEATMAP_CALENDAR
+        | API_SPORT_REVIEW
+        | API_SPORT_REVIEW_CARD => Some(SCOPE_STATS_READ),
+        _ => None,
+    }
+}
@@ -1,7 +1,8 @@
 use axum::{
-    Router,
+    Extension, Router,
     extract::DefaultBodyLimit,
-    routing::{get, post},
+    middleware,
+    routing::{delete, get, post, put},
 };
 use std::net::SocketAddr;
 use std::sync::Arc;
@@ -17,11 +18,21 @@ use utoipa_swagger_ui::SwaggerUi;
 use crate::config::AppConfig;
 use crate::dao::Repository;
 use crate::dao::cache::memory::MemoryResultCache;
-use crate::handlers::jwt::Jwt;
+use crate::handlers::client::TrustedProxies;
+use crate::handlers::csrf::{CsrfGuard, csrf_protection};
+use crate::handlers::jwt::{Jwt, refresh_session};
+use crate::handlers::jwt_keys::JwtKeySet;
 use crate::service::sport_service::StatSummary;
 use crate::service::{
+    access_token_service::AccessTokenService, admin_service::AdminService,
     ai_job_service::AIJobService, ai_job_worker::start_workers, ai_service::AIService,
-    image_service::ImageService, llm::LLM, sport_service::SportService, user_service::UserService,
+    athlete_service::AthleteService, audit_service::AuditService, club_service::ClubService,
+    coach_service::CoachService, comment_service::CommentService, image_service::ImageService,
+    llm::LLM, login_throttle::LoginThrottle, notification_service::NotificationService,
+    oidc_service::OidcService, session_service::SessionService, share_service::ShareService,
+    social_service::SocialService, sport_service::SportService,
+    two_factor_service::TwoFactorService, user_service::UserService,
+    webhook_service::WebhookService, webhook_worker::start_webhook_workers,
 };
 use std::sync::Arc as StdArc;
 
@@ -29,6 +40,18 @@ use std::sync::Arc as StdArc;
 
 pub async fn run() {
     let config = AppConfig::default();
+    if let Some(kid) = config.security.insecure_jwt_key()
+        && !config.security.allow_insecure_key
+    {
+        tracing::error!(
+            kid,
+            "refusing to start with the placeholder JWT key; configure security.jwt_keys, \
+             or security.allow_insecure_key for local development. Changing security.key instead \
+             locks out accounts still on legacy password hashes unless the old value is kept in \
+             security.legacy_password_key"
+        );
+        std::process::exit(1);
+    }
     let app = create_app(config.clone()).await;
 
     let addr_str = format!("{}:{}", config.server.ip, config.server.port);
@@ -37,7 +60,12 @@ pub async fn run() {
 
     let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
     println!("服务器正在监听 http://{}", listener.local_addr().unwrap());
-    axum::serve(listener, app).await.unwrap();
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
+    .unwrap();
 }
 
 /// 创建应用实例的通用函数
@@ -54,9 +82,11 @@ async fn create_app_inner(config: AppConfig, llm: Option<Arc<dyn LLM + Send + Sy
     #[openapi(
         paths(
             crate::handlers::get_status,
+            crate::handlers::jwks,
             crate::handlers::ai_handler::sports_image_recognition_handler,
             crate::handlers::ai_job_handler::create_ai_job_handler,
             crate::handlers::ai_job_handler::list_ai_jobs_handler,
+            crate::handlers::ai_job_handler::ai_job_events_handler,
             crate::handlers::ai_job_handler::get_ai_job_handler,
             crate::handlers::ai_job_handler::delete_ai_job_handler,
             crate::handlers::ai_job_handler::retry_ai_job_handler,
@@ -67,13 +97,115 @@ async fn create_app_inner(config: AppConfig, llm: Option<Arc<dyn LLM + Send + Sy
             crate::handlers::user_handler::user_login_handler,
             crate::handlers::user_handler::user_info_handler,
             crate::handlers::user_handler::user_logout_handler,
+            crate::handlers::user_handler::user_logout_all_handler,
+            crate::handlers::user_handler::list_sessions_handler,
+            crate::handlers::user_handler::revoke_session_handler,
+            crate::handlers::user_handler::change_password_handler,
+            crate::handlers::user_handler::reset_password_handler,
+            crate::handlers::user_handler::update_profile_handler,
+            crate::handlers::user_handler::delete_account_handler,
+            crate::handlers::user_handler::create_token_handler,
+            crate::handlers::user_handler::list_tokens_handler,
+            crate::handlers::user_handler::revoke_token_handler,
             crate::handlers::user_handler::user_avatar_upload_handler,
+            crate::handlers::user_handler::user_avatar_handler,
+            crate::handlers::user_handler::list_audit_handler,
+            crate::handlers::two_factor_handler::two_factor_status_handler,
+            crate::handlers::two_factor_handler::two_factor_setup_handler,
+            crate::handlers::two_factor_handler::two_factor_enable_handler,
+            crate::handlers::two_factor_handler::two_factor_disable_handler,
+            crate::handlers::two_factor_handler::regenerate_recovery_codes_handler,
+            crate::handlers::two_factor_handler::login_two_factor_handler,
+            crate::handlers::oidc_handler::oidc_login_handler,
+            crate::handlers::oidc_handler::oidc_link_handler,
+            crate::handlers::oidc_handler::oidc_callback_handler,
+            crate::handlers::oidc_handler::list_oidc_identities_handler,
+            crate::handlers::oidc_handler::unlink_oidc_identity_handler,
+            crate::handlers::social_handler::get_social_settings_handler,
+            crate::handlers::social_handler::update_social_settings_handler,
+            crate::handlers::social_handler::find_user_handler,
+            crate::handlers::social_handler::social_profile_handler,
+            crate::handlers::social_handler::user_sports_handler,
+            crate::handlers::social_handler::list_following_handler,
+            crate::handlers::social_handler::follow_handler,
+            crate::handlers::social_handler::unfollow_handler,
+            crate::handlers::social_handler::list_followers_handler,
+            crate::handlers::social_handler::approve_follower_handler,
+            crate::handlers::social_handler::remove_follower_handler,
+            crate::handlers::social_handler::feed_handler,
+            crate::handlers::comment_handler::list_comments_handler,
+            crate::handlers::comment_handler::create_comment_handler,
+            crate::handlers::comment_handler::update_comment_handler,
+            crate::handlers::comment_handler::delete_comment_handler,
+            crate::handlers::comment_handler::list_reactions_handler,
+            crate::handlers::comment_handler::add_reaction_handler,
+            crate::handlers::comment_handler::remove_reaction_handler,
+            crate::handlers::notification_handler::list_notifications_handler,
+            crate::handlers::notification_handler::unread_count_handler,
+            crate::handlers::notification_handler::mark_read_handler,
+            crate::handlers::notification_handler::mark_all_read_handler,
+            crate::handlers::athlete_handler::get_athlete_profile_handler,
+            crate::handlers::athlete_handler::update_athlete_profile_handler,
+            crate::handlers::athlete_handler::athlete_profile_history_handler,
+            crate::handlers::athlete_handler::add_weight_handler,
+            crate::handlers::athlete_handler::list_weights_handler,
+            crate::handlers::athlete_handler::delete_weight_handler,
             crate::handlers::sport_handler::insert_sport_handler,
             crate::handlers::sport_handler::import_sport_handler,
             crate::handlers::sport_handler::update_sport_handler,
             crate::handlers::sport_handler::list_sport_handler,
             crate::handlers::sport_handler::stats_handler,
-            crate::handlers::sport_handler::delete_sport_handler
+            crate::handlers::sport_handler::sport_metrics_handler,
+            crate::handlers::sport_handler::heatmap_hourly_handler,
+            crate::handlers::sport_handler::heatmap_calendar_handler,
+            crate::handlers::sport_handler::year_review_handler,
+            crate::handlers::sport_handler::year_review_card_handler,
+            crate::handlers::sport_handler::delete_sport_handler,
+            crate::handlers::share_handler::create_share_handler,
+            crate::handlers::share_handler::list_shares_handler,
+            crate::handlers::share_handler::revoke_share_handler,
+            crate::handlers::share_handler::shared_sport_handler,
+            crate::handlers::share_handler::shared_sport_card_handler,
+            crate::handlers::club_handler::create_club_handler,
+            crate::handlers::club_handler::list_clubs_handler,
+            crate::handlers::club_handler::join_club_handler,
+            crate::handlers::club_handler::list_club_invites_handler,
+            crate::handlers::club_handler::get_club_handler,
+            crate::handlers::club_handler::delete_club_handler,
+            crate::handlers::club_handler::reset_join_code_handler,
+            crate::handlers::club_handler::leave_club_handler,
+            crate::handlers::club_handler::list_club_members_handler,
+            crate::handlers::club_handler::set_club_member_role_handler,
+            crate::handlers::club_handler::remove_club_member_handler,
+            crate::handlers::club_handler::invite_club_member_handler,
+            crate::handlers::club_handler::accept_club_invite_handler,
+            crate::handlers::club_handler::decline_club_invite_handler,
+            crate::handlers::club_handler::create_challenge_handler,
+            crate::handlers::club_handler::list_challenges_handler,
+            crate::handlers::club_handler::delete_challenge_handler,
+            crate::handlers::club_handler::challenge_leaderboard_handler,
+            crate::handlers::coach_handler::create_coach_grant_handler,
+            crate::handlers::coach_handler::list_coach_grants_handler,
+            crate::handlers::coach_handler::update_coach_grant_handler,
+            crate::handlers::coach_handler::revoke_coach_grant_handler,
+            crate::handlers::coach_handler::list_coach_athletes_handler,
+            crate::handlers::coach_handler::accept_coach_grant_handler,
+            crate::handlers::coach_handler::leave_coach_grant_handler,
+            crate::handlers::admin_handler::list_users_handler,
+            crate::handlers::admin_handler::disable_user_handler,
+            crate::handlers::admin_handler::enable_user_handler,
+            crate::handlers::admin_handler::issue_reset_code_handler,
+            crate::handlers::admin_handler::list_jobs_handler,
+            crate::handlers::admin_handler::requeue_job_handler,
+            crate::handlers::admin_handler::purge_job_handler,
+            crate::handlers::admin_handler::queue_stats_handler,
+            crate::handlers::admin_handler::query_audit_handler,
+            crate::handlers::webhook_handler::create_webhook_handler,
+            crate::handlers::webhook_handler::admin_create_webhook_handler,
+            crate::handlers::webhook_handler::list_webhooks_handler,
+            crate::handlers::webhook_handler::delete_webhook_handler,
+            crate::handlers::webhook_handler::list_deliveries_handler,
+            crate::handlers::webhook_handler::redeliver_handler
         ),
         components(
             schemas(
@@ -83,6 +215,7 @@ async fn create_app_inner(config: AppConfig, llm: Option<Arc<dyn LLM + Send + Sy
                 crate::service::ai_service::ErrorResponse,
                 crate::model::sport::Sport,
                 crate::model::ai_job::AiJobView,
+                crate::model::ai_job::AiJobEvent,
                 crate::model::ai_job::AiJobAsset,
                 crate::handlers::ai_handler::AIResponseText,
                 crate::handlers::user_handler::UserRegisterRequest,
@@ -92,10 +225,87 @@ async fn create_app_inner(config: AppConfig, llm: Option<Arc<dyn LLM + Send + Sy
                 crate::service::sport_service::StatBucket,
                 crate::service::sport_service::TypeBucket,
                 crate::service::sport_service::StatSummary,
+                crate::service::sport_service::HeatCell,
+                crate::service::sport_service::HourlyHeatmap,
+                crate::service::sport_service::CalendarDay,
+                crate::service::sport_service::CalendarHeatmap,
+                crate::service::year_review::YearReview,
+                crate::service::year_review::YearTotals,
+                crate::service::year_review::ReviewSession,
+                crate::service::year_review::PersonalBest,
                 crate::handlers::sport_handler::ActionResponse,
                 crate::handlers::sport_handler::InsertSportRequest,
                 crate::handlers::sport_handler::ImportResponse,
-                crate::handlers::sport_handler::DeleteRequest
+                crate::handlers::sport_handler::DeleteRequest,
+                crate::model::session::SessionView,
+                crate::model::access_token::AccessTokenView,
+                crate::model::access_token::CreatedAccessToken,
+                crate::handlers::user_handler::CreateTokenRequest,
+                crate::handlers::user_handler::ChangePasswordRequest,
+                crate::handlers::user_handler::UpdateProfileRequest,
+                crate::handlers::user_handler::DeleteAccountRequest,
+                crate::model::athlete::AthleteProfile,
+                crate::model::athlete::AthleteProfileView,
+                crate::model::athlete::WeightEntry,
+                crate::model::athlete::HeartRateZone,
+                crate::model::athlete::SportMetrics,
+                crate::handlers::athlete_handler::UpdateAthleteProfileRequest,
+                crate::handlers::athlete_handler::AddWeightRequest,
+                crate::handlers::user_handler::ResetPasswordRequest,
+                crate::model::user::AdminUserView,
+                crate::model::user::IssuedResetCode,
+                crate::model::ai_job::AdminAiJobView,
+                crate::model::ai_job::AiQueueStats,
+                crate::model::audit::AuditEntry,
+                crate::model::two_factor::TwoFactorStatus,
+                crate::model::two_factor::TotpSetup,
+                crate::model::two_factor::RecoveryCodes,
+                crate::model::two_factor::LoginChallengeView,
+                crate::handlers::two_factor_handler::TwoFactorCodeRequest,
+                crate::handlers::two_factor_handler::DisableTwoFactorRequest,
+                crate::handlers::two_factor_handler::TwoFactorLoginRequest,
+                crate::model::oidc::OidcIdentity,
+                crate::model::sport::SportVisibility,
+                crate::model::social::UserCard,
+                crate::model::social::FollowView,
+                crate::model::social::SocialProfile,
+                crate::model::social::SocialSettings,
+                crate::model::social::FeedItem,
+                crate::model::social::FeedPage,
+                crate::handlers::social_handler::FollowResponse,
+                crate::model::comment::CommentView,
+                crate::model::comment::ReactionCount,
+                crate::model::comment::ReactionSummary,
+                crate::handlers::comment_handler::CommentRequest,
+                crate::model::notification::NotificationView,
+                crate::model::notification::NotificationPage,
+                crate::model::notification::UnreadCount,
+                crate::handlers::notification_handler::ReadAllResponse,
+                crate::model::webhook::WebhookView,
+                crate::model::webhook::CreatedWebhook,
+                crate::model::webhook::WebhookDelivery,
+                crate::handlers::webhook_handler::CreateWebhookRequest,
+                crate::model::share::SportShareView,
+                crate::model::share::CreatedSportShare,
+                crate::model::share::SharedSport,
+                crate::handlers::share_handler::CreateShareRequest,
+                crate::model::club::ClubView,
+                crate::model::club::ClubMemberView,
+                crate::model::club::ClubInvite,
+                crate::model::club::ChallengeMetric,
+                crate::model::club::Challenge,
+                crate::model::club::LeaderboardEntry,
+                crate::model::club::Leaderboard,
+                crate::model::sport::SportType,
+                crate::handlers::club_handler::CreateClubRequest,
+                crate::handlers::club_handler::JoinClubRequest,
+                crate::handlers::club_handler::InviteMemberRequest,
+                crate::handlers::club_handler::SetMemberRoleRequest,
+                crate::handlers::club_handler::CreateChallengeRequest,
+                crate::model::coach::CoachGrantView,
+                crate::handlers::coach_handler::CreateCoachGrantRequest,
+                crate::handlers::coach_handler::UpdateCoachGrantRequest,
+                crate::handlers::coach_handler::CoachGrantScopes
             )
           ),
         tags(
@@ -116,7 +326,22 @@ pub struct AppState {
     pub image_service: Arc<ImageService>,
     pub ai_job_service: Arc<AIJobService>,
     pub user_service: UserService,
+    pub session_service: SessionService,
+    pub access_token_service: AccessTokenService,
     pub sport_service: SportService,
+    pub athlete_service: Arc<AthleteService>,
+    pub audit_service: Arc<AuditService>,
+    pub login_throttle: LoginThrottle,
+    pub admin_service: AdminService,
+    pub two_factor_service: TwoFactorService,
+    pub oidc_service: OidcService,
+    pub social_service: SocialService,
+    pub share_service: ShareService,
+    pub club_service: ClubService,
+    pub coach_service: CoachService,
+    pub comment_service: CommentService,
+    pub notification_service: Arc<NotificationService>,
+    pub webhook_service: Arc<WebhookService>,
     pub jwt: Jwt,
 }
 /// 创建生产环境的路由
@@ -131,7 +356,13 @@ async fn create_production_router(
             .await
             .expect("init repository"),
     );
-    let jwt = Jwt::new(config.security.jwt_ttl_seconds, config.security.key.clone());
+    let jwt_keys = JwtKeySet::from_config(&config.security).expect("load jwt keys");
+    let jwt = Jwt::new(config.security.jwt_ttl_seconds, jwt_keys);
+    let csrf_guard = Arc::new(CsrfGuard::new(
+        &config.security.csrf,
+        config.security.jwt_ttl_seconds,
+    ));
+    let trusted_proxies = Arc::new(TrustedProxies::new(&config.security.trusted_proxies));
     let cache_total = StdArc::new(MemoryResultCache::<StatSummary, i32>::new());
     let cache_year = StdArc::new(MemoryResultCache::<StatSummary, String>::new());
     let ai_service = Arc::new(match llm {
@@ -146,6 +377,24 @@ async fn create_production_router(
         config.ai.job_dir.clone(),
         notify,
     ));
+    let athlete_service = Arc::new(AthleteService::new(sqlite_db.clone()));
+    let audit_service = Arc::new(AuditService::new(sqlite_db.clone()));
+    let notification_service = Arc::new(NotificationService::new(sqlite_db.clone()));
+    let webhook_service = Arc::new(WebhookService::new(
+        sqlite_db.clone(),
+        config.webhook.allow_private_targets,
+    ));
+    let admin_service = AdminService::new(
+        sqlite_db.clone(),
+        sqlite_db.clone(),
+        sqlite_db.clone(),
+        ai_job_service.clone(),
+        audit_service.clone(),
+        config.ai.worker_concurrency,
+    );
+    admin_service
+        .promote_configured(&config.security.admins)
+        .await;
     start_workers(
         config.ai.worker_concurrency,
         config.ai.max_attempts,
@@ -153,19 +402,63 @@ async fn create_production_router(
         ai_job_service.clone(),
         ai_service.clone(),
         image_service.clone(),
+        athlete_service.clone(),
+        notification_service.clone(),
+        webhook_service.clone(),
     );
+    start_webhook_workers(config.webhook.clone(), webhook_service.clone());
     let app = Arc::new(AppState {
         ai_service,
-        image_service,
+        image_service: image_service.clone(),
+        user_service: UserService::new(
+            sqlite_db.clone(),
+            config.security.clone(),
+            ai_job_service.clone(),
+            image_service.clone(),
+        ),
         ai_job_service,
-        user_service: UserService::new(sqlite_db.clone(), config.security.clone()),
+        session_service: SessionService::new(sqlite_db.clone(), config.security.jwt_ttl_seconds),
+        access_token_service: AccessTokenService::new(sqlite_db.clone()),
         sport_service: SportService::new(
             sqlite_db.clone(),
             cache_total.clone(),
             cache_year.clone(),
+            athlete_service.clone(),
+            notification_service.clone(),
+            webhook_service.clone(),
         ),
+        athlete_service,
+        login_throttle: LoginThrottle::new(
+            sqlite_db.clone(),
+            sqlite_db.clone(),
+            audit_service.clone(),
+            config.security.login_throttle.clone(),
+        ),
+        audit_service,
+        admin_service,
+        two_factor_service: TwoFactorService::new(sqlite_db.clone(), sqlite_db.clone()),
+        oidc_service: OidcService::new(
+            config.security.clone(),
+            sqlite_db.clone(),
+            sqlite_db.clone(),
+        ),
+        social_service: SocialService::new(sqlite_db.clone(), sqlite_db.clone()),
+        share_service: ShareService::new(sqlite_db.clone(), sqlite_db.clone(), sqlite_db.clone()),
+        club_service: ClubService::new(sqlite_db.clone(), sqlite_db.clone()),
+        coach_service: CoachService::new(sqlite_db.clone(), sqlite_db.clone()),
+        comment_service: CommentService::new(
+            sqlite_db.clone(),
+            sqlite_db.clone(),
+            sqlite_db.clone(),
+            sqlite_db.clone(),
+            sqlite_db.clone(),
+            notification_service.clone(),
+        ),
+        notification_service,
+        webhook_service,
         jwt,
     });
+    app.user_service.migrate_legacy_avatars().await;
     // 导入处理函数
     use crate::app::routes;
     use crate::handlers::*;
@@ -174,6 +467,7 @@ async fn create_production_router(
     Router::new()
         .route("/", get(root))
         .route(routes::API_STATUS, get(get_status))
+        .route(routes::API_JWKS, get(jwks))
         .route(
             routes::API_IMAGE_PARSE,
             post(crate::handlers::ai_handler::sports_image_recognition_handler)
@@ -185,6 +479,10 @@ async fn create_production_router(
                 .get(crate::handlers::ai_job_handler::list_ai_jobs_handler)
                 .layer(DefaultBodyLimit::max(50 * 1024 * 1024)),
         )
+        .route(
+            routes::API_AI_JOB_EVENTS,
+            get(crate::handlers::ai_job_handler::ai_job_events_handler),
+        )
         .route(
             routes::API_AI_JOB,
             get(crate::handlers::ai_job_handler::get_ai_job_handler)
@@ -210,6 +508,10 @@ async fn create_production_router(
             routes::API_USER_LOGIN,
             post(crate::handlers::user_handler::user_login_handler),
         )
+        .route(
+            routes::API_USER_LOGIN_TWO_FACTOR,
+            post(crate::handlers::two_factor_handler::login_two_factor_handler),
+        )
         .route(
             routes::API_USER_INFO,
             get(crate::handlers::user_handler::user_info_handler),
@@ -218,11 +520,191 @@ async fn create_production_router(
             routes::API_USER_LOGOUT,
             post(crate::handlers::user_handler::user_logout_handler),
         )
+        .route(
+            routes::API_USER_LOGOUT_ALL,
+            post(crate::handlers::user_handler::user_logout_all_handler),
+        )
+        .route(
+            routes::API_USER_SESSIONS,
+            get(crate::handlers::user_handler::list_sessions_handler),
+        )
+        .route(
+            routes::API_USER_SESSION,
+            delete(crate::handlers::user_handler::revoke_session_handler),
+        )
+        .route(
+            routes::API_USER_PASSWORD,
+            post(crate::handlers::user_handler::change_password_handler),
+        )
+        .route(
+            routes::API_USER_PASSWORD_RESET,
+            post(crate::handlers::user_handler::reset_password_handler),
+        )
+        .route(
+            routes::API_USER_PROFILE,
+            put(crate::handlers::user_handler::update_profile_handler),
+        )
+        .route(
+            routes::API_USER_ACCOUNT,
+            delete(crate::handlers::user_handler::delete_account_handler),
+        )
+        .route(
+            routes::API_USER_TOKENS,
+            post(crate::handlers::user_handler::create_token_handler)
+                .get(crate::handlers::user_handler::list_tokens_handler),
+        )
+        .route(
+            routes::API_USER_TOKEN,
+            delete(crate::handlers::user_handler::revoke_token_handler),
+        )
+        .route(
+            routes::API_USER_AUDIT,
+            get(crate::handlers::user_handler::list_audit_handler),
+        )
+        .route(
+            routes::API_USER_TWO_FACTOR,
+            get(crate::handlers::two_factor_handler::two_factor_status_handler),
+        )
+        .route(
+            routes::API_USER_TWO_FACTOR_SETUP,
+            post(crate::handlers::two_factor_handler::two_factor_setup_handler),
+        )
+        .route(
+            routes::API_USER_TWO_FACTOR_ENABLE,
+            post(crate::handlers::two_factor_handler::two_factor_enable_handler),
+        )
+        .route(
+            routes::API_USER_TWO_FACTOR_DISABLE,
+            post(crate::handlers::two_factor_handler::two_factor_disable_handler),
+        )
+        .route(
+            routes::API_USER_TWO_FACTOR_RECOVERY_CODES,
+            post(crate::handlers::two_factor_handler::regenerate_recovery_codes_handler),
+        )
+        .route(
+            routes::API_USER_OIDC_LOGIN,
+            get(crate::handlers::oidc_handler::oidc_login_handler),
+        )
+        .route(
+            routes::API_USER_OIDC_LINK,
+            get(crate::handlers::oidc_handler::oidc_link_handler),
+        )
+        .route(
+            routes::API_USER_OIDC_CALLBACK,
+            get(crate::handlers::oidc_handler::oidc_callback_handler),
+        )
+        .route(
+            routes::API_USER_OIDC_IDENTITIES,
+            get(crate::handlers::oidc_handler::list_oidc_identities_handler),
+        )
+        .route(
+            routes::API_USER_OIDC_IDENTITY,
+            delete(crate::handlers::oidc_handler::unlink_oidc_identity_handler),
+        )
         .route(
             routes::API_USER_AVATAR_UPLOAD,
             post(crate::handlers::user_handler::user_avatar_upload_handler)
                 .layer(DefaultBodyLimit::max(20 * 1024 * 1024)),
         )
+        .route(
+            routes::API_SOCIAL_SETTINGS,
+            get(crate::handlers::social_handler::get_social_settings_handler)
+                .put(crate::handlers::social_handler::update_social_settings_handler),
+        )
+        .route(
+            routes::API_SOCIAL_USERS,
+            get(crate::handlers::social_handler::find_user_handler),
+        )
+        .route(
+            routes::API_SOCIAL_USER,
+            get(crate::handlers::social_handler::social_profile_handler),
+        )
+        .route(
+            routes::API_SOCIAL_USER_SPORTS,
+            get(crate::handlers::social_handler::user_sports_handler),
+        )
+        .route(
+            routes::API_SOCIAL_FOLLOWING,
+            get(crate::handlers::social_handler::list_following_handler),
+        )
+        .route(
+            routes::API_SOCIAL_FOLLOWING_USER,
+            post(crate::handlers::social_handler::follow_handler)
+                .delete(crate::handlers::social_handler::unfollow_handler),
+        )
+        .route(
+            routes::API_SOCIAL_FOLLOWERS,
+            get(crate::handlers::social_handler::list_followers_handler),
+        )
+        .route(
+            routes::API_SOCIAL_FOLLOWER,
+            delete(crate::handlers::social_handler::remove_follower_handler),
+        )
+        .route(
+            routes::API_SOCIAL_FOLLOWER_APPROVE,
+            post(crate::handlers::social_handler::approve_follower_handler),
+        )
+        .route(
+            routes::API_SOCIAL_FEED,
+            get(crate::handlers::social_handler::feed_handler),
+        )
+        .route(
+            routes::API_SOCIAL_SPORT_COMMENTS,
+            get(crate::handlers::comment_handler::list_comments_handler)
+                .post(crate::handlers::comment_handler::create_comment_handler),
+        )
+        .route(
+            routes::API_SOCIAL_SPORT_COMMENT,
+            put(crate::handlers::comment_handler::update_comment_handler)
+                .delete(crate::handlers::comment_handler::delete_comment_handler),
+        )
+        .route(
+            routes::API_SOCIAL_SPORT_REACTIONS,
+            get(crate::handlers::comment_handler::list_reactions_handler),
+        )
+        .route(
+            routes::API_SOCIAL_SPORT_REACTION,
+            put(crate::handlers::comment_handler::add_reaction_handler)
+                .delete(crate::handlers::comment_handler::remove_reaction_handler),
+        )
+        .route(
+            routes::API_NOTIFICATIONS,
+            get(crate::handlers::notification_handler::list_notifications_handler),
+        )
+        .route(
+            routes::API_NOTIFICATIONS_UNREAD,
+            get(crate::handlers::notification_handler::unread_count_handler),
+        )
+        .route(
+            routes::API_NOTIFICATIONS_READ_ALL,
+            post(crate::handlers::notification_handler::mark_all_read_handler),
+        )
+        .route(
+            routes::API_NOTIFICATION_READ,
+            post(crate::handlers::notification_handler::mark_read_handler),
+        )
+        .route(
+            routes::API_USER_AVATAR,
+            get(crate::handlers::user_handler::user_avatar_handler),
+        )
+        .route(
+            routes::API_USER_ATHLETE,
+            get(crate::handlers::athlete_handler::get_athlete_profile_handler)
+                .put(crate::handlers::athlete_handler::update_athlete_profile_handler),
+        )
+        .route(
+            routes::API_USER_ATHLETE_HISTORY,
+            get(crate::handlers::athlete_handler::athlete_profile_history_handler),
+        )
+        .route(
+            routes::API_USER_WEIGHTS,
+            post(crate::handlers::athlete_handler::add_weight_handler)
+                .get(crate::handlers::athlete_handler::list_weights_handler),
+        )
+        .route(
+            routes::API_USER_WEIGHT,
+            delete(crate::handlers::athlete_handler::delete_weight_handler),
+        )
         .route(
             routes::API_SPORT_INSERT,
             post(crate::handlers::sport_handler::insert_sport_handler),
@@ -239,6 +721,23 @@ async fn create_production_router(
             routes::API_SPORT_DELETE,
             post(crate::handlers::sport_handler::delete_sport_handler),
         )
+        .route(
+            routes::API_SPORT_SHARES,
+            post(crate::handlers::share_handler::create_share_handler)
+                .get(crate::handlers::share_handler::list_shares_handler),
+        )
+        .route(
+            routes::API_SPORT_SHARE,
+            delete(crate::handlers::share_handler::revoke_share_handler),
+        )
+        .route(
+            routes::API_SHARE,
+            get(crate::handlers::share_handler::shared_sport_handler),
+        )
+        .route(
+            routes::API_SHARE_CARD,
+            get(crate::handlers::share_handler::shared_sport_card_handler),
+        )
         .route(
             routes::API_SPORT_LIST,
             get(crate::handlers::sport_handler::list_sport_handler),
@@ -247,6 +746,168 @@ async fn create_production_router(
             routes::API_SPORT_STATS,
             get(crate::handlers::sport_handler::stats_handler),
         )
+        .route(
+            routes::API_SPORT_METRICS,
+            get(crate::handlers::sport_handler::sport_metrics_handler),
+        )
+        .route(
+            routes::API_SPORT_HEATMAP_HOURLY,
+            get(crate::handlers::sport_handler::heatmap_hourly_handler),
+        )
+        .route(
+            routes::API_SPORT_HEATMAP_CALENDAR,
+            get(crate::handlers::sport_handler::heatmap_calendar_handler),
+        )
+        .route(
+            routes::API_SPORT_REVIEW,
+            get(crate::handlers::sport_handler::year_review_handler),
+        )
+        .route(
+            routes::API_SPORT_REVIEW_CARD,
+            get(crate::handlers::sport_handler::year_review_card_handler),
+        )
+        .route(
+            routes::API_CLUBS,
+            post(crate::handlers::club_handler::create_club_handler)
+                .get(crate::handlers::club_handler::list_clubs_handler),
+        )
+        .route(
+            routes::API_CLUBS_JOIN,
+            post(crate::handlers::club_handler::join_club_handler),
+        )
+        .route(
+            routes::API_CLUBS_INVITES,
+            get(crate::handlers::club_handler::list_club_invites_handler),
+        )
+        .route(
+            routes::API_CLUB,
+            get(crate::handlers::club_handler::get_club_handler)
+                .delete(crate::handlers::club_handler::delete_club_handler),
+        )
+        .route(
+            routes::API_CLUB_JOIN_CODE,
+            post(crate::handlers::club_handler::reset_join_code_handler),
+        )
+        .route(
+            routes::API_CLUB_LEAVE,
+            post(crate::handlers::club_handler::leave_club_handler),
+        )
+        .route(
+            routes::API_CLUB_MEMBERS,
+            get(crate::handlers::club_handler::list_club_members_handler),
+        )
+        .route(
+            routes::API_CLUB_MEMBER,
+            put(crate::handlers::club_handler::set_club_member_role_handler)
+                .delete(crate::handlers::club_handler::remove_club_member_handler),
+        )
+        .route(
+            routes::API_CLUB_INVITES,
+            post(crate::handlers::club_handler::invite_club_member_handler),
+        )
+        .route(
+            routes::API_CLUB_INVITE_ACCEPT,
+            post(crate::handlers::club_handler::accept_club_invite_handler),
+        )
+        .route(
+            routes::API_CLUB_INVITE_DECLINE,
+            post(crate::handlers::club_handler::decline_club_invite_handler),
+        )
+        .route(
+            routes::API_CLUB_CHALLENGES,
+            post(crate::handlers::club_handler::create_challenge_handler)
+                .get(crate::handlers::club_handler::list_challenges_handler),
+        )
+        .route(
+            routes::API_CLUB_CHALLENGE,
+            delete(crate::handlers::club_handler::delete_challenge_handler),
+        )
+        .route(
+            routes::API_CLUB_LEADERBOARD,
+            get(crate::handlers::club_handler::challenge_leaderboard_handler),
+        )
+        .route(
+            routes::API_COACH_GRANTS,
+            post(crate::handlers::coach_handler::create_coach_grant_handler)
+                .get(crate::handlers::coach_handler::list_coach_grants_handler),
+        )
+        .route(
+            routes::API_COACH_GRANT,
+            put(crate::handlers::coach_handler::update_coach_grant_handler)
+                .delete(crate::handlers::coach_handler::revoke_coach_grant_handler),
+        )
+        .route(
+            routes::API_COACH_ATHLETES,
+            get(crate::handlers::coach_handler::list_coach_athletes_handler),
+        )
+        .route(
+            routes::API_COACH_ATHLETE,
+            delete(crate::handlers::coach_handler::leave_coach_grant_handler),
+        )
+        .route(
+            routes::API_COACH_ATHLETE_ACCEPT,
+            post(crate::handlers::coach_handler::accept_coach_grant_handler),
+        )
+        .route(
+            routes::API_ADMIN_USERS,
+            get(crate::handlers::admin_handler::list_users_handler),
+        )
+        .route(
+            routes::API_ADMIN_USER_DISABLE,
+            post(crate::handlers::admin_handler::disable_user_handler),
+        )
+        .route(
+            routes::API_ADMIN_USER_ENABLE,
+            post(crate::handlers::admin_handler::enable_user_handler),
+        )
+        .route(
+            routes::API_ADMIN_USER_PASSWORD_RESET,
+            post(crate::handlers::admin_handler::issue_reset_code_handler),
+        )
+        .route(
+            routes::API_ADMIN_AI_JOBS,
+            get(crate::handlers::admin_handler::list_jobs_handler),
+        )
+        .route(
+            routes::API_ADMIN_AI_JOB,
+            delete(crate::handlers::admin_handler::purge_job_handler),
+        )
+        .route(
+            routes::API_ADMIN_AI_JOB_REQUEUE,
+            post(crate::handlers::admin_handler::requeue_job_handler),
+        )
+        .route(
+            routes::API_ADMIN_AI_QUEUE,
+            get(crate::handlers::admin_handler::queue_stats_handler),
+        )
+        .route(
+            routes::API_ADMIN_AUDIT,
+            get(crate::handlers::admin_handler::query_audit_handler),
+        )
+        .route(
+            routes::API_ADMIN_WEBHOOKS,
+            post(crate::handlers::webhook_handler::admin_create_webhook_handler),
+        )
+        .route(
+            routes::API_WEBHOOKS,
+            get(crate::handlers::webhook_handler::list_webhooks_handler)
+                .post(crate::handlers::webhook_handler::create_webhook_handler),
+        )
+        .route(
+            routes::API_WEBHOOK,
+            delete(crate::handlers::webhook_handler::delete_webhook_handler),
+        )
+        .route(
+            routes::API_WEBHOOK_DELIVERIES,
+            get(crate::handlers::webhook_handler::list_deliveries_handler),
+        )
+        .route(
+            routes::API_WEBHOOK_REDELIVER,
+            post(crate::handlers::webhook_handler::redeliver_handler),
+        )
+        .layer(middleware::from_fn_with_state(app.clone(), refresh_session))
+        .layer(middleware::from_fn_with_state(csrf_guard, csrf_protection))
+        .layer(Extension(trusted_proxies))
         .layer(
             TraceLayer::new_for_http()
                 .make_span_with(DefaultMakeSpan::new().level(Level::INFO))
@@ -1,3 +1,4 @@
+use jsonwebtoken::Algorithm;
 use serde::Deserialize;
 use std::fs;
 use std::path::Path;
@@ -7,6 +8,10 @@ const DEFAULT_SERVER_IP: &str = "127.0.0.1";
 const DEFAULT_SERVER_PORT: u16 = 3000;
 const LOCAL_CONFIG_PATH: &str = "config/app.local.yml";
 const DEFAULT_CONFIG_PATH: &str = "config/app.yml";
+/// 仓库内配置文件使用的占位密钥，部署时必须替换
+pub const DEFAULT_SECURITY_KEY: &str = "change-me-key";
+/// 未配置 `jwt_keys` 时由 `security.key` 生成的 HS256 密钥 id；没有 kid 的旧 token 也按它验证
+pub const DEFAULT_JWT_KID: &str = "default";
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct ServerConfig {
@@ -38,14 +43,143 @@ pub struct AiConfig {
     pub retry_delays_seconds: Vec<u64>,
 }
 
+/// 出站 webhook 投递：失败后按 `retry_base_seconds` 指数退避，最多尝试 `max_attempts` 次
+#[derive(Debug, Clone, Deserialize)]
+pub struct WebhookConfig {
+    #[serde(default = "default_webhook_worker_concurrency")]
+    pub worker_concurrency: usize,
+    #[serde(default = "default_webhook_max_attempts")]
+    pub max_attempts: i32,
+    #[serde(default = "default_webhook_retry_base_seconds")]
+    pub retry_base_seconds: u64,
+    #[serde(default = "default_webhook_retry_max_seconds")]
+    pub retry_max_seconds: u64,
+    /// 单次请求超时
+    #[serde(default = "default_webhook_timeout_seconds")]
+    pub timeout_seconds: u64,
+    /// 允许投递到本机和内网地址，默认拒绝以防服务端请求伪造
+    #[serde(default)]
+    pub allow_private_targets: bool,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct SecurityConfig {
     #[serde(default = "default_security_salt")]
     pub salt: String,
+    /// `jwt_keys` 为空时作为 HS256 JWT 密钥；同时是旧版 AES 密码密文的密钥，
+    /// 仍有账号未升级到 Argon2 时更换它需先把原值写入 `legacy_password_key`
     #[serde(default = "default_security_key")]
     pub key: String,
+    /// 校验旧版 AES 密码密文的密钥，未设置时使用 `key`
+    #[serde(default)]
+    pub legacy_password_key: Option<String>,
     #[serde(default = "default_jwt_ttl_seconds")]
     pub jwt_ttl_seconds: u64,
+    /// JWT 密钥集，为空时只使用由 `key` 生成的 HS256 密钥
+    #[serde(default)]
+    pub jwt_keys: Vec<JwtKeyConfig>,
+    /// 签发新 token 使用的密钥 id，默认取 `jwt_keys` 的第一项
+    #[serde(default)]
+    pub jwt_signing_kid: Option<String>,
+    /// 允许使用占位密钥启动，仅供本地开发
+    #[serde(default)]
+    pub allow_insecure_key: bool,
+    #[serde(default)]
+    pub login_throttle: LoginThrottleConfig,
+    #[serde(default)]
+    pub registration_mode: RegistrationMode,
+    /// `registration_mode: invite` 时可用的邀请码
+    #[serde(default)]
+    pub invite_codes: Vec<String>,
+    /// 启动时设为管理员的用户名
+    #[serde(default)]
+    pub admins: Vec<String>,
+    /// 是否允许用户名密码登录与注册；只使用 OIDC 登录时可关闭
+    #[serde(default = "default_password_login")]
+    pub password_login: bool,
+    /// OpenID Connect 登录，未配置时不启用
+    #[serde(default)]
+    pub oidc: Option<OidcConfig>,
+    #[serde(default)]
+    pub csrf: CsrfConfig,
+    /// 可信反向代理的地址或网段（如 `172.16.0.0/12`）；只有来自这些地址的请求才采用转发头中的客户端 IP
+    #[serde(default)]
+    pub trusted_proxies: Vec<String>,
+}
+
+/// 跨站请求伪造防护，作用于不带 Bearer 令牌的写请求
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CsrfConfig {
+    /// 除本站外允许发起写请求的来源，如 `https://app.example.com`
+    #[serde(default)]
+    pub allowed_origins: Vec<String>,
+    /// 要求已登录的写请求在 `X-CSRF-Token` 头中回传 `slam_csrf` cookie 的值
+    #[serde(default)]
+    pub double_submit: bool,
+}
+
+/// 一个 JWT 密钥。HS256 使用 `secret`；RS256 与 EdDSA 配置私钥时可签发，只配置公钥时仅用于验证
+#[derive(Debug, Clone, Deserialize)]
+pub struct JwtKeyConfig {
+    pub kid: String,
+    /// HS256、RS256 或 EdDSA
+    pub algorithm: Algorithm,
+    #[serde(default)]
+    pub secret: String,
+    /// PEM 私钥文件（RSA 为 PKCS#1 或 PKCS#8，Ed25519 为 PKCS#8）
+    #[serde(default)]
+    pub private_key_file: Option<String>,
+    /// PEM 公钥文件，用于只验证不签发的旧密钥或其他服务的密钥
+    #[serde(default)]
+    pub public_key_file: Option<String>,
+}
+
+/// OpenID Connect 授权码登录（PKCE），身份提供方需支持 discovery
+#[derive(Debug, Clone, Deserialize)]
+pub struct OidcConfig {
+    /// 发行方地址，`{issuer}/.well-known/openid-configuration` 需可访问
+    pub issuer: String,
+    pub client_id: String,
+    /// 机密客户端的密钥，公共客户端留空
+    #[serde(default)]
+    pub client_secret: String,
+    /// 在身份提供方登记的回调地址，指向 `/api/user/oidc/callback`
+    pub redirect_uri: String,
+    #[serde(default = "default_oidc_scopes")]
+    pub scopes: Vec<String>,
+    /// 未关联的身份首次登录时自动创建本地账号，不受 `registration_mode` 限制
+    #[serde(default = "default_oidc_auto_register")]
+    pub auto_register: bool,
+    /// 登录或关联完成后跳转的前端地址
+    #[serde(default = "default_oidc_post_login_redirect")]
+    pub post_login_redirect: String,
+}
+
+/// 注册模式：open 开放注册，invite 需要邀请码，closed 关闭注册
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RegistrationMode {
+    #[default]
+    Open,
+    Invite,
+    Closed,
+}
+
+/// 登录失败限制：账号与 IP 分别计数，超过阈值后按指数退避锁定
+#[derive(Debug, Clone, Deserialize)]
+pub struct LoginThrottleConfig {
+    #[serde(default = "default_max_failures_per_account")]
+    pub max_failures_per_account: u32,
+    #[serde(default = "default_max_failures_per_ip")]
+    pub max_failures_per_ip: u32,
+    /// 首次锁定时长，之后每多失败一次翻倍
+    #[serde(default = "default_lockout_base_seconds")]
+    pub lockout_base_seconds: u64,
+    #[serde(default = "default_lockout_max_seconds")]
+    pub lockout_max_seconds: u64,
+    /// 距上次失败超过该时长后重新计数
+    #[serde(default = "default_failure_window_seconds")]
+    pub failure_window_seconds: u64,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -57,6 +191,8 @@ pub struct AppConfig {
     #[serde(default)]
     pub ai: AiConfig,
     #[serde(default)]
+    pub webhook: WebhookConfig,
+    #[serde(default)]
     pub security: SecurityConfig,
 }
 
@@ -76,11 +212,42 @@ fn default_security_salt() -> String {
     "slam-server-salt".to_string()
 }
 fn default_security_key() -> String {
-    "change-me-key".to_string()
+    DEFAULT_SECURITY_KEY.to_string()
 }
 fn default_jwt_ttl_seconds() -> u64 {
     2592000
 }
+fn default_password_login() -> bool {
+    true
+}
+fn default_oidc_scopes() -> Vec<String> {
+    vec![
+        "openid".to_string(),
+        "profile".to_string(),
+        "email".to_string(),
+    ]
+}
+fn default_oidc_auto_register() -> bool {
+    true
+}
+fn default_oidc_post_login_redirect() -> String {
+    "/".to_string()
+}
+fn default_max_failures_per_account() -> u32 {
+    5
+}
+fn default_max_failures_per_ip() -> u32 {
+    20
+}
+fn default_lockout_base_seconds() -> u64 {
+    60
+}
+fn default_lockout_max_seconds() -> u64 {
+    3600
+}
+fn default_failure_window_seconds() -> u64 {
+    900
+}
 pub fn default_ai_model() -> String {
     "doubao-seed-1-6-251015".to_string()
 }
@@ -96,6 +263,35 @@ fn default_ai_max_attempts() -> i32 {
 fn default_ai_retry_delays_seconds() -> Vec<u64> {
     vec![15, 60]
 }
+fn default_webhook_worker_concurrency() -> usize {
+    1
+}
+fn default_webhook_max_attempts() -> i32 {
+    6
+}
+fn default_webhook_retry_base_seconds() -> u64 {
+    30
+}
+fn default_webhook_retry_max_seconds() -> u64 {
+    3600
+}
+fn default_webhook_timeout_seconds() -> u64 {
+    10
+}
+
+impl SecurityConfig {
+    /// 返回仍在使用占位密钥或空密钥的 HS256 密钥 id，启动时据此拒绝运行
+    pub fn insecure_jwt_key(&self) -> Option<&str> {
+        let weak = |secret: &str| secret.is_empty() || secret == DEFAULT_SECURITY_KEY;
+        if self.jwt_keys.is_empty() {
+            return weak(&self.key).then_some(DEFAULT_JWT_KID);
+        }
+        self.jwt_keys
+            .iter()
+            .find(|k| k.algorithm == Algorithm::HS256 && weak(&k.secret))
+            .map(|k| k.kid.as_str())
+    }
+}
 
 impl AppConfig {
     pub fn new(cfg_path: &str) -> Self {
@@ -148,12 +344,47 @@ impl Default for AiConfig {
         }
     }
 }
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            worker_concurrency: default_webhook_worker_concurrency(),
+            max_attempts: default_webhook_max_attempts(),
+            retry_base_seconds: default_webhook_retry_base_seconds(),
+            retry_max_seconds: default_webhook_retry_max_seconds(),
+            timeout_seconds: default_webhook_timeout_seconds(),
+            allow_private_targets: false,
+        }
+    }
+}
 impl Default for SecurityConfig {
     fn default() -> Self {
         Self {
             salt: default_security_salt(),
             key: default_security_key(),
             jwt_ttl_seconds: default_jwt_ttl_seconds(),
+            jwt_keys: Vec::new(),
+            jwt_signing_kid: None,
+            allow_insecure_key: false,
+            login_throttle: LoginThrottleConfig::default(),
+            registration_mode: RegistrationMode::default(),
+            invite_codes: Vec::new(),
+            admins: Vec::new(),
+            password_login: default_password_login(),
+            oidc: None,
+            csrf: CsrfConfig::default(),
+            trusted_proxies: Vec::new(),
+            legacy_password_key: None,
+        }
+    }
+}
+impl Default for LoginThrottleConfig {
+    fn default() -> Self {
+        Self {
+            max_failures_per_account: default_max_failures_per_account(),
+            max_failures_per_ip: default_max_failures_per_ip(),
+            lockout_base_seconds: default_lockout_base_seconds(),
+            lockout_max_seconds: default_lockout_max_seconds(),
+            failure_window_seconds: default_failure_window_seconds(),
         }
     }
 }
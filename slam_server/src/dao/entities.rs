@@ -14,6 +14,7 @@ pub struct Model {
     pub type_: String,
     pub start_time: i64,
     pub calories: i32,
+    pub calories_estimated: bool,
     pub distance_meter: i32,
     pub duration_second: i32,
     pub heart_rate_avg: i32,
@@ -21,6 +22,7 @@ pub struct Model {
     pub pace_average: String,
     pub extra: String,
     pub tracks: String,
+    pub visibility: String,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -40,6 +42,7 @@ pub mod users {
         pub password: String,
         pub nickname: String,
         pub avatar: String,
+        pub bio: String,
     }
 
     #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -1,12 +1,29 @@
-use crate::model::ai_job::{AiJobAsset, AiJobRecord, AiJobSubmission};
-use crate::model::sport::Sport;
-use crate::model::user::{User, UserInfo};
+use crate::model::access_token::AccessToken;
+use crate::model::ai_job::{AiJobAsset, AiJobEvent, AiJobRecord, AiJobSubmission, AiQueueStats};
+use crate::model::athlete::{AthleteProfile, WeightEntry};
+use crate::model::audit::{AuditEntry, AuditQuery, LoginAttempt};
+use crate::model::club::{Challenge, Club, ClubInvite, ClubMemberRow, ClubRow, LeaderboardRow};
+use crate::model::coach::{CoachGrant, CoachGrantRow};
+use crate::model::comment::{CommentRow, ReactionCount, SportComment, SportOwner};
+use crate::model::notification::{Notification, NotificationRow};
+use crate::model::oidc::{OidcIdentity, OidcLoginState};
+use crate::model::session::Session;
+use crate::model::share::SportShare;
+use crate::model::social::{FeedCursor, FeedRow, Follow, FollowRow};
+use crate::model::sport::{Sport, SportVisibility};
+use crate::model::two_factor::{LoginChallenge, TotpSecret};
+use crate::model::user::{
+    AccountStatus, AdminUserView, AvatarImage, PasswordResetCode, User, UserInfo,
+};
+use crate::model::webhook::{Webhook, WebhookDelivery};
 use async_trait::async_trait;
 
 #[async_trait]
 pub trait SportDao {
-    async fn insert(&self, uid: i32, sport: Sport) -> Result<(), String>;
-    async fn insert_many(&self, uid: i32, sports: Vec<Sport>) -> Result<usize, String>;
+    /// 返回新记录的 id
+    async fn insert(&self, uid: i32, sport: Sport) -> Result<i32, String>;
+    /// 返回新记录的 id，顺序与传入一致
+    async fn insert_many(&self, uid: i32, sports: Vec<Sport>) -> Result<Vec<i32>, String>;
     async fn list(&self, uid: i32, page: i32, size: i32) -> Result<Vec<Sport>, String>;
     async fn list_by_time_range(
         &self,
@@ -39,7 +56,8 @@ pub trait AiJobDao {
         now: i64,
         lease_until: i64,
     ) -> Result<Option<AiJobRecord>, String>;
-    async fn requeue_expired_jobs(&self, now: i64) -> Result<(), String>;
+    /// 返回被重新排队的任务
+    async fn requeue_expired_jobs(&self, now: i64) -> Result<Vec<AiJobRecord>, String>;
     async fn mark_job_ready(&self, id: &str, result_json: &str, now: i64) -> Result<(), String>;
     async fn mark_job_error(
         &self,
@@ -53,12 +71,384 @@ pub trait AiJobDao {
     async fn delete_job(&self, uid: i32, id: &str) -> Result<bool, String>;
     async fn list_assets_for_cleanup(&self, limit: i32) -> Result<Vec<AiJobAsset>, String>;
     async fn mark_asset_deleted(&self, id: &str, now: i64) -> Result<(), String>;
+    /// 不限用户按 id 查询任务，仅供管理接口使用
+    async fn find_job(&self, id: &str) -> Result<Option<AiJobRecord>, String>;
+    async fn list_all_jobs(
+        &self,
+        status: Option<&str>,
+        page: i32,
+        size: i32,
+    ) -> Result<Vec<AiJobRecord>, String>;
+    async fn list_job_ids(&self, uid: i32) -> Result<Vec<String>, String>;
+    async fn queue_stats(&self, now: i64) -> Result<AiQueueStats, String>;
+    async fn create_job_event(&self, event: AiJobEvent) -> Result<i64, String>;
+    /// 按 id 升序返回 after 之后的事件
+    async fn list_job_events(
+        &self,
+        uid: i32,
+        after: i64,
+        limit: i32,
+    ) -> Result<Vec<AiJobEvent>, String>;
+    async fn latest_job_event_id(&self, uid: i32) -> Result<i64, String>;
+    async fn prune_job_events(&self, before: i64) -> Result<u64, String>;
 }
 
 #[async_trait]
 pub trait UserDao {
     async fn insert(&self, user: User) -> Result<i32, String>;
     async fn get_by_id(&self, id: i32) -> Result<Option<UserInfo>, String>;
-    async fn login(&self, name: &str, password: &str) -> Result<Option<User>, String>;
-    async fn set_avatar(&self, uid: i32, base64: String) -> Result<(), String>;
+    async fn get_by_name(&self, name: &str) -> Result<Option<User>, String>;
+    async fn get_account(&self, id: i32) -> Result<Option<User>, String>;
+    async fn update_password(&self, uid: i32, password: &str) -> Result<(), String>;
+    async fn update_profile(&self, uid: i32, nickname: &str, bio: &str) -> Result<(), String>;
+    /// 在一个事务中删除用户及其全部数据，返回被删除的 AI 任务 id，供调用方清理磁盘文件
+    async fn delete_user(&self, uid: i32) -> Result<Vec<String>, String>;
+    /// 替换用户的全部尺寸头像，同时清空旧版 base64 头像
+    async fn set_avatar(&self, uid: i32, images: Vec<AvatarImage>) -> Result<(), String>;
+    /// 取不小于 size 的最小尺寸头像，都比 size 小时取最大的一张
+    async fn get_avatar(&self, uid: i32, size: u32) -> Result<Option<AvatarImage>, String>;
+    /// 仍以 base64 文本保存的旧版头像，返回 (uid, data URL)
+    async fn list_legacy_avatars(&self) -> Result<Vec<(i32, String)>, String>;
+    async fn clear_legacy_avatar(&self, uid: i32) -> Result<(), String>;
+    async fn get_account_status(&self, uid: i32) -> Result<Option<AccountStatus>, String>;
+    /// 设置或清除禁用时间，用户不存在时返回 false
+    async fn set_disabled(&self, uid: i32, disabled_at: Option<i64>) -> Result<bool, String>;
+    /// 把指定用户名的账号设为管理员，返回实际更新的行数
+    async fn promote_admins(&self, names: &[String]) -> Result<u64, String>;
+    /// 按 id 分页列出用户，storage_bytes 只包含头像大小
+    async fn list_users(&self, page: i32, size: i32) -> Result<Vec<AdminUserView>, String>;
+    async fn create_reset_code(&self, code: PasswordResetCode) -> Result<(), String>;
+    /// 核销属于 uid 且未过期、未使用的重置码，成功返回 true
+    async fn consume_reset_code(&self, code_hash: &str, uid: i32, now: i64)
+    -> Result<bool, String>;
+}
+
+#[async_trait]
+pub trait SessionDao {
+    async fn create_session(&self, session: Session) -> Result<(), String>;
+    async fn get_session(&self, id: &str) -> Result<Option<Session>, String>;
+    async fn list_active_sessions(&self, uid: i32, now: i64) -> Result<Vec<Session>, String>;
+    async fn touch_session(
+        &self,
+        id: &str,
+        last_seen_at: i64,
+        expires_at: i64,
+    ) -> Result<(), String>;
+    async fn revoke_session(&self, uid: i32, id: &str, now: i64) -> Result<bool, String>;
+    /// 撤销用户的全部会话，`except` 指定的会话保留
+    async fn revoke_all_sessions(
+        &self,
+        uid: i32,
+        except: Option<&str>,
+        now: i64,
+    ) -> Result<u64, String>;
+}
+
+#[async_trait]
+pub trait AccessTokenDao {
+    async fn create_token(&self, token: AccessToken) -> Result<(), String>;
+    async fn get_token_by_hash(&self, token_hash: &str) -> Result<Option<AccessToken>, String>;
+    async fn list_tokens(&self, uid: i32, now: i64) -> Result<Vec<AccessToken>, String>;
+    async fn touch_token(&self, id: &str, now: i64) -> Result<(), String>;
+    async fn revoke_token(&self, uid: i32, id: &str, now: i64) -> Result<bool, String>;
+    async fn revoke_all_tokens(&self, uid: i32, now: i64) -> Result<u64, String>;
+}
+
+#[async_trait]
+pub trait AthleteDao {
+    async fn create_profile(&self, uid: i32, profile: AthleteProfile) -> Result<i64, String>;
+    /// 返回 at 时刻生效的版本，早于首个版本时返回最早的版本
+    async fn get_profile_at(&self, uid: i32, at: i64) -> Result<Option<AthleteProfile>, String>;
+    async fn list_profiles(&self, uid: i32) -> Result<Vec<AthleteProfile>, String>;
+    async fn add_weight(&self, uid: i32, entry: WeightEntry) -> Result<i64, String>;
+    /// 返回 at 时刻之前最近一次的体重，早于首条记录时返回最早的记录
+    async fn get_weight_at(&self, uid: i32, at: i64) -> Result<Option<WeightEntry>, String>;
+    async fn list_weights(&self, uid: i32, limit: i32) -> Result<Vec<WeightEntry>, String>;
+    async fn delete_weight(&self, uid: i32, id: i64) -> Result<bool, String>;
+}
+
+#[async_trait]
+pub trait AuditDao {
+    async fn append_audit(&self, entry: AuditEntry) -> Result<(), String>;
+    /// 按时间倒序分页查询
+    async fn list_audit(
+        &self,
+        query: &AuditQuery,
+        page: i32,
+        size: i32,
+    ) -> Result<Vec<AuditEntry>, String>;
+}
+
+#[async_trait]
+pub trait LoginAttemptDao {
+    async fn get_login_attempt(&self, key: &str) -> Result<Option<LoginAttempt>, String>;
+    async fn save_login_attempt(&self, attempt: LoginAttempt) -> Result<(), String>;
+    async fn clear_login_attempt(&self, key: &str) -> Result<(), String>;
+}
+
+#[async_trait]
+pub trait TwoFactorDao {
+    async fn get_totp(&self, uid: i32) -> Result<Option<TotpSecret>, String>;
+    /// 保存待验证的新密钥；已启用的密钥不会被覆盖，返回是否写入
+    async fn save_pending_totp(&self, uid: i32, secret: &str, now: i64) -> Result<bool, String>;
+    /// 启用密钥并替换全部恢复码
+    async fn enable_totp(
+        &self,
+        uid: i32,
+        recovery_hashes: &[String],
+        now: i64,
+    ) -> Result<(), String>;
+    async fn replace_recovery_codes(
+        &self,
+        uid: i32,
+        recovery_hashes: &[String],
+        now: i64,
+    ) -> Result<(), String>;
+    async fn delete_totp(&self, uid: i32) -> Result<(), String>;
+    /// 仅当时间步比上次使用的更新时记录，返回 false 表示验证码已被用过
+    async fn advance_totp_step(&self, uid: i32, step: i64) -> Result<bool, String>;
+    async fn consume_recovery_code(
+        &self,
+        uid: i32,
+        code_hash: &str,
+        now: i64,
+    ) -> Result<bool, String>;
+    async fn count_recovery_codes(&self, uid: i32) -> Result<i64, String>;
+    async fn create_challenge(&self, challenge: LoginChallenge) -> Result<(), String>;
+    async fn get_challenge(&self, token_hash: &str) -> Result<Option<LoginChallenge>, String>;
+    async fn add_challenge_attempt(&self, token_hash: &str) -> Result<(), String>;
+    /// 核销挑战令牌，返回 false 表示已被使用
+    async fn consume_challenge(&self, token_hash: &str, now: i64) -> Result<bool, String>;
+}
+
+#[async_trait]
+pub trait OidcDao {
+    async fn create_login_state(&self, state: OidcLoginState) -> Result<(), String>;
+    /// 取出并删除授权状态，已过期或不存在时返回 None
+    async fn take_login_state(
+        &self,
+        state: &str,
+        now: i64,
+    ) -> Result<Option<OidcLoginState>, String>;
+    async fn find_identity(
+        &self,
+        issuer: &str,
+        subject: &str,
+    ) -> Result<Option<OidcIdentity>, String>;
+    async fn create_identity(&self, identity: OidcIdentity) -> Result<i64, String>;
+    async fn touch_identity(&self, id: i64, now: i64) -> Result<(), String>;
+    async fn list_identities(&self, uid: i32) -> Result<Vec<OidcIdentity>, String>;
+    async fn delete_identity(&self, uid: i32, id: i64) -> Result<bool, String>;
+}
+
+#[async_trait]
+pub trait SocialDao {
+    async fn get_follow(
+        &self,
+        follower_uid: i32,
+        followee_uid: i32,
+    ) -> Result<Option<Follow>, String>;
+    async fn create_follow(&self, follow: Follow) -> Result<(), String>;
+    /// 取消关注、拒绝申请或移除粉丝，关系不存在时返回 false
+    async fn delete_follow(&self, follower_uid: i32, followee_uid: i32) -> Result<bool, String>;
+    /// 批准一条待处理的关注申请
+    async fn accept_follow(
+        &self,
+        follower_uid: i32,
+        followee_uid: i32,
+        now: i64,
+    ) -> Result<bool, String>;
+    async fn accept_all_follows(&self, followee_uid: i32, now: i64) -> Result<u64, String>;
+    async fn list_following(&self, uid: i32) -> Result<Vec<FollowRow>, String>;
+    async fn list_followers(
+        &self,
+        uid: i32,
+        status: Option<&str>,
+    ) -> Result<Vec<FollowRow>, String>;
+    /// 已生效的 (粉丝数, 关注数)
+    async fn count_follows(&self, uid: i32) -> Result<(i64, i64), String>;
+    /// 用户不存在时返回 None
+    async fn get_private_account(&self, uid: i32) -> Result<Option<bool>, String>;
+    async fn set_private_account(&self, uid: i32, private_account: bool) -> Result<(), String>;
+    /// 已关注用户中对粉丝可见的运动，按开始时间倒序
+    async fn list_feed(
+        &self,
+        viewer_uid: i32,
+        before: Option<FeedCursor>,
+        limit: i32,
+    ) -> Result<Vec<FeedRow>, String>;
+    /// 某个用户在给定可见范围内的运动，按开始时间倒序
+    async fn list_user_sports(
+        &self,
+        owner_uid: i32,
+        levels: &[SportVisibility],
+        before: Option<FeedCursor>,
+        limit: i32,
+    ) -> Result<Vec<FeedRow>, String>;
+}
+
+#[async_trait]
+pub trait ShareDao {
+    async fn create_share(&self, share: SportShare) -> Result<(), String>;
+    async fn list_shares(&self, uid: i32, sport_id: Option<i32>)
+    -> Result<Vec<SportShare>, String>;
+    async fn get_share_by_hash(&self, token_hash: &str) -> Result<Option<SportShare>, String>;
+    async fn revoke_share(&self, uid: i32, id: &str, now: i64) -> Result<bool, String>;
+    async fn increment_share_views(&self, id: &str) -> Result<(), String>;
+}
+
+#[async_trait]
+pub trait ClubDao {
+    /// 创建俱乐部并把创建者加入为管理员，返回俱乐部 id
+    async fn create_club(&self, club: Club, owner_uid: i32) -> Result<i64, String>;
+    async fn get_club_by_code(&self, join_code: &str) -> Result<Option<Club>, String>;
+    /// 删除俱乐部及其成员、邀请和挑战
+    async fn delete_club(&self, club_id: i64) -> Result<(), String>;
+    async fn set_join_code(&self, club_id: i64, join_code: &str) -> Result<(), String>;
+    async fn list_clubs(&self, uid: i32) -> Result<Vec<ClubRow>, String>;
+    /// 用户所在俱乐部的信息，不是成员时返回 None
+    async fn get_club_row(&self, club_id: i64, uid: i32) -> Result<Option<ClubRow>, String>;
+    /// 两个用户是否同在某个俱乐部
+    async fn shares_club(&self, uid: i32, other_uid: i32) -> Result<bool, String>;
+    async fn add_member(
+        &self,
+        club_id: i64,
+        uid: i32,
+        role: &str,
+        now: i64,
+    ) -> Result<bool, String>;
+    async fn remove_member(&self, club_id: i64, uid: i32) -> Result<bool, String>;
+    async fn set_member_role(&self, club_id: i64, uid: i32, role: &str) -> Result<bool, String>;
+    async fn list_members(&self, club_id: i64) -> Result<Vec<ClubMemberRow>, String>;
+    async fn create_invite(
+        &self,
+        club_id: i64,
+        uid: i32,
+        invited_by: i32,
+        now: i64,
+    ) -> Result<(), String>;
+    async fn delete_invite(&self, club_id: i64, uid: i32) -> Result<bool, String>;
+    async fn list_invites(&self, uid: i32) -> Result<Vec<ClubInvite>, String>;
+    async fn create_challenge(&self, challenge: Challenge) -> Result<i64, String>;
+    async fn list_challenges(&self, club_id: i64) -> Result<Vec<Challenge>, String>;
+    async fn get_challenge(&self, club_id: i64, id: i64) -> Result<Option<Challenge>, String>;
+    async fn delete_challenge(&self, club_id: i64, id: i64) -> Result<bool, String>;
+    /// 按挑战指标统计全部成员在挑战窗口内的成绩，按成绩倒序
+    async fn leaderboard(&self, challenge: &Challenge) -> Result<Vec<LeaderboardRow>, String>;
+}
+
+#[async_trait]
+pub trait CoachDao {
+    async fn get_grant(
+        &self,
+        athlete_uid: i32,
+        coach_uid: i32,
+    ) -> Result<Option<CoachGrant>, String>;
+    async fn create_grant(&self, grant: CoachGrant) -> Result<(), String>;
+    async fn set_grant_scopes(
+        &self,
+        athlete_uid: i32,
+        coach_uid: i32,
+        scopes: &[String],
+    ) -> Result<bool, String>;
+    /// 教练接受待处理的授权
+    async fn accept_grant(
+        &self,
+        athlete_uid: i32,
+        coach_uid: i32,
+        now: i64,
+    ) -> Result<bool, String>;
+    /// 运动员撤销或教练放弃授权，授权不存在时返回 false
+    async fn delete_grant(&self, athlete_uid: i32, coach_uid: i32) -> Result<bool, String>;
+    /// 运动员授出的授权，附带教练信息
+    async fn list_coaches(&self, athlete_uid: i32) -> Result<Vec<CoachGrantRow>, String>;
+    /// 教练收到的授权，附带运动员信息
+    async fn list_athletes(&self, coach_uid: i32) -> Result<Vec<CoachGrantRow>, String>;
+}
+
+#[async_trait]
+pub trait CommentDao {
+    /// 按 id 查询运动记录的主人和可见范围，不区分用户
+    async fn get_sport_owner(&self, sport_id: i32) -> Result<Option<SportOwner>, String>;
+    /// 返回新评论的 id
+    async fn create_comment(&self, comment: SportComment) -> Result<i64, String>;
+    async fn get_comment(&self, sport_id: i32, id: i64) -> Result<Option<SportComment>, String>;
+    async fn update_comment(&self, id: i64, content: &str, now: i64) -> Result<bool, String>;
+    async fn delete_comment(&self, id: i64) -> Result<bool, String>;
+    /// 按发表时间正序
+    async fn list_comments(&self, sport_id: i32) -> Result<Vec<CommentRow>, String>;
+    /// 已经回应过时返回 false
+    async fn add_reaction(
+        &self,
+        sport_id: i32,
+        uid: i32,
+        kind: &str,
+        now: i64,
+    ) -> Result<bool, String>;
+    async fn remove_reaction(&self, sport_id: i32, uid: i32, kind: &str) -> Result<bool, String>;
+    async fn count_reactions(&self, sport_id: i32) -> Result<Vec<ReactionCount>, String>;
+    async fn list_own_reactions(&self, sport_id: i32, uid: i32) -> Result<Vec<String>, String>;
+}
+
+#[async_trait]
+pub trait NotificationDao {
+    async fn create_notification(&self, notification: Notification) -> Result<i64, String>;
+    /// 按 id 倒序，before 为上一页最后一条的 id
+    async fn list_notifications(
+        &self,
+        uid: i32,
+        before: Option<i64>,
+        unread_only: bool,
+        limit: i32,
+    ) -> Result<Vec<NotificationRow>, String>;
+    async fn count_unread(&self, uid: i32) -> Result<i64, String>;
+    /// 标记单条已读，通知不存在或不属于该用户时返回 false
+    async fn mark_read(&self, uid: i32, id: i64, now: i64) -> Result<bool, String>;
+    /// 返回本次标记的条数
+    async fn mark_all_read(&self, uid: i32, now: i64) -> Result<u64, String>;
+}
+
+#[async_trait]
+pub trait WebhookDao {
+    async fn create_webhook(&self, webhook: Webhook) -> Result<(), String>;
+    async fn get_webhook(&self, id: &str) -> Result<Option<Webhook>, String>;
+    async fn list_webhooks(&self, uid: i32) -> Result<Vec<Webhook>, String>;
+    /// 同时删除投递记录
+    async fn delete_webhook(&self, id: &str) -> Result<(), String>;
+    /// 用户自己的 webhook 和全站 webhook
+    async fn list_webhooks_for(&self, subject_uid: i32) -> Result<Vec<Webhook>, String>;
+    async fn create_deliveries(&self, deliveries: Vec<WebhookDelivery>) -> Result<(), String>;
+    async fn get_delivery(
+        &self,
+        webhook_id: &str,
+        id: &str,
+    ) -> Result<Option<WebhookDelivery>, String>;
+    /// 按创建时间倒序
+    async fn list_deliveries(
+        &self,
+        webhook_id: &str,
+        page: i32,
+        size: i32,
+    ) -> Result<Vec<WebhookDelivery>, String>;
+    async fn claim_next_delivery(
+        &self,
+        now: i64,
+        lease_until: i64,
+    ) -> Result<Option<WebhookDelivery>, String>;
+    async fn requeue_expired_deliveries(&self, now: i64) -> Result<(), String>;
+    async fn mark_delivery_succeeded(
+        &self,
+        id: &str,
+        response_status: i32,
+        now: i64,
+    ) -> Result<(), String>;
+    /// retry_at 为空时不再重试
+    async fn mark_delivery_failed(
+        &self,
+        id: &str,
+        response_status: Option<i32>,
+        error: &str,
+        retry_at: Option<i64>,
+        now: i64,
+    ) -> Result<(), String>;
 }
@@ -0,0 +1,118 @@
+use async_trait::async_trait;
+use sea_orm::{ConnectionTrait, DbBackend, Statement};
+
+use super::Repository;
+use crate::dao::idl::AccessTokenDao;
+use crate::model::access_token::AccessToken;
+
+fn token_from_row(row: &sea_orm::QueryResult) -> Result<AccessToken, String> {
+    let scopes: String = row.try_get("", "scopes").map_err(|e| e.to_string())?;
+    Ok(AccessToken {
+        id: row.try_get("", "id").map_err(|e| e.to_string())?,
+        uid: row.try_get("", "uid").map_err(|e| e.to_string())?,
+        name: row.try_get("", "name").map_err(|e| e.to_string())?,
+        token_hash: row.try_get("", "token_hash").map_err(|e| e.to_string())?,
+        token_hint: row.try_get("", "token_hint").map_err(|e| e.to_string())?,
+        scopes: scopes
+            .split(',')
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .collect(),
+        created_at: row.try_get("", "created_at").map_err(|e| e.to_string())?,
+        expires_at: row.try_get("", "expires_at").map_err(|e| e.to_string())?,
+        last_used_at: row.try_get("", "last_used_at").map_err(|e| e.to_string())?,
+        revoked_at: row.try_get("", "revoked_at").map_err(|e| e.to_string())?,
+    })
+}
+
+const TOKEN_COLUMNS: &str = "id, uid, name, token_hash, token_hint, scopes, created_at, expires_at, last_used_at, revoked_at";
+
+#[async_trait]
+impl AccessTokenDao for Repository {
+    async fn create_token(&self, token: AccessToken) -> Result<(), String> {
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "INSERT INTO personal_access_tokens (id, uid, name, token_hash, token_hint, scopes, created_at, expires_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+                vec![
+                    token.id.into(),
+                    token.uid.into(),
+                    token.name.into(),
+                    token.token_hash.into(),
+                    token.token_hint.into(),
+                    token.scopes.join(",").into(),
+                    token.created_at.into(),
+                    token.expires_at.into(),
+                ],
+            ))
+            .await
+            .map_err(|e| format!("创建访问令牌失败: {e}"))?;
+        Ok(())
+    }
+
+    async fn get_token_by_hash(&self, token_hash: &str) -> Result<Option<AccessToken>, String> {
+        let row = self
+            .conn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                format!("SELECT {TOKEN_COLUMNS} FROM personal_access_tokens WHERE token_hash = ?"),
+                vec![token_hash.into()],
+            ))
+            .await
+            .map_err(|e| format!("查询访问令牌失败: {e}"))?;
+        row.as_ref().map(token_from_row).transpose()
+    }
+
+    async fn list_tokens(&self, uid: i32, now: i64) -> Result<Vec<AccessToken>, String> {
+        let rows = self
+            .conn
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                format!(
+                    "SELECT {TOKEN_COLUMNS} FROM personal_access_tokens WHERE uid = ? AND revoked_at IS NULL AND expires_at > ? ORDER BY created_at DESC"
+                ),
+                vec![uid.into(), now.into()],
+            ))
+            .await
+            .map_err(|e| format!("查询访问令牌失败: {e}"))?;
+        rows.iter().map(token_from_row).collect()
+    }
+
+    async fn touch_token(&self, id: &str, now: i64) -> Result<(), String> {
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "UPDATE personal_access_tokens SET last_used_at = ? WHERE id = ?",
+                vec![now.into(), id.into()],
+            ))
+            .await
+            .map_err(|e| format!("更新访问令牌失败: {e}"))?;
+        Ok(())
+    }
+
+    async fn revoke_token(&self, uid: i32, id: &str, now: i64) -> Result<bool, String> {
+        let result = self
+            .conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "UPDATE personal_access_tokens SET revoked_at = ? WHERE uid = ? AND id = ? AND revoked_at IS NULL",
+                vec![now.into(), uid.into(), id.into()],
+            ))
+            .await
+            .map_err(|e| format!("撤销访问令牌失败: {e}"))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn revoke_all_tokens(&self, uid: i32, now: i64) -> Result<u64, String> {
+        let result = self
+            .conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "UPDATE personal_access_tokens SET revoked_at = ? WHERE uid = ? AND revoked_at IS NULL",
+                vec![now.into(), uid.into()],
+            ))
+            .await
+            .map_err(|e| format!("撤销访问令牌失败: {e}"))?;
+        Ok(result.rows_affected())
+    }
+}
@@ -4,7 +4,8 @@ use sea_orm::{ConnectionTrait, DbBackend, Statement, TransactionTrait};
 use super::Repository;
 use crate::dao::idl::AiJobDao;
 use crate::model::ai_job::{
-    AiJobAsset, AiJobRecord, JOB_FAILED, JOB_QUEUED, JOB_READY, JOB_RUNNING,
+    AiJobAsset, AiJobEvent, AiJobRecord, AiQueueStats, JOB_FAILED, JOB_QUEUED, JOB_READY,
+    JOB_RUNNING,
 };
 
 fn job_from_row(row: &sea_orm::QueryResult) -> Result<AiJobRecord, String> {
@@ -32,6 +33,24 @@ fn job_from_row(row: &sea_orm::QueryResult) -> Result<AiJobRecord, String> {
     })
 }
 
+fn event_from_row(row: &sea_orm::QueryResult) -> Result<AiJobEvent, String> {
+    Ok(AiJobEvent {
+        id: row.try_get("", "id").map_err(|e| e.to_string())?,
+        uid: row.try_get("", "uid").map_err(|e| e.to_string())?,
+        job_id: row.try_get("", "job_id").map_err(|e| e.to_string())?,
+        status: row.try_get("", "status").map_err(|e| e.to_string())?,
+        attempts: row.try_get("", "attempts").map_err(|e| e.to_string())?,
+        next_attempt_at: row
+            .try_get("", "next_attempt_at")
+            .map_err(|e| e.to_string())?,
+        error_code: row.try_get("", "error_code").map_err(|e| e.to_string())?,
+        error_message: row
+            .try_get("", "error_message")
+            .map_err(|e| e.to_string())?,
+        created_at: row.try_get("", "created_at").map_err(|e| e.to_string())?,
+    })
+}
+
 fn asset_from_row(row: &sea_orm::QueryResult) -> Result<AiJobAsset, String> {
     Ok(AiJobAsset {
         id: row.try_get("", "id").map_err(|e| e.to_string())?,
@@ -226,22 +245,47 @@ impl AiJobDao for Repository {
         Ok(None)
     }
 
-    async fn requeue_expired_jobs(&self, now: i64) -> Result<(), String> {
-        self.conn
-            .execute(Statement::from_sql_and_values(
+    async fn requeue_expired_jobs(&self, now: i64) -> Result<Vec<AiJobRecord>, String> {
+        let sql = format!("SELECT {JOB_COLUMNS} FROM ai_jobs WHERE status = ? AND lease_until < ?");
+        let rows = self
+            .conn
+            .query_all(Statement::from_sql_and_values(
                 DbBackend::Sqlite,
-                "UPDATE ai_jobs SET status = ?, next_attempt_at = ?, lease_until = NULL, updated_at = ? WHERE status = ? AND lease_until < ?",
-                vec![
-                    JOB_QUEUED.into(),
-                    now.into(),
-                    now.into(),
-                    JOB_RUNNING.into(),
-                    now.into(),
-                ],
+                sql,
+                vec![JOB_RUNNING.into(), now.into()],
             ))
             .await
             .map_err(|e| format!("恢复AI任务失败: {e}"))?;
-        Ok(())
+        let mut requeued = Vec::new();
+        for row in rows {
+            let job = job_from_row(&row)?;
+            // 与领取相同的条件更新，避免多个 worker 重复恢复同一任务
+            let result = self
+                .conn
+                .execute(Statement::from_sql_and_values(
+                    DbBackend::Sqlite,
+                    "UPDATE ai_jobs SET status = ?, next_attempt_at = ?, lease_until = NULL, updated_at = ? WHERE id = ? AND status = ? AND lease_until < ?",
+                    vec![
+                        JOB_QUEUED.into(),
+                        now.into(),
+                        now.into(),
+                        job.id.clone().into(),
+                        JOB_RUNNING.into(),
+                        now.into(),
+                    ],
+                ))
+                .await
+                .map_err(|e| format!("恢复AI任务失败: {e}"))?;
+            if result.rows_affected() == 1 {
+                requeued.push(AiJobRecord {
+                    status: JOB_QUEUED.to_string(),
+                    next_attempt_at: Some(now),
+                    lease_until: None,
+                    ..job
+                });
+            }
+        }
+        Ok(requeued)
     }
 
     async fn mark_job_ready(&self, id: &str, result_json: &str, now: i64) -> Result<(), String> {
@@ -377,4 +421,167 @@ impl AiJobDao for Repository {
             .map_err(|e| format!("更新AI图片清理状态失败: {e}"))?;
         Ok(())
     }
+
+    async fn find_job(&self, id: &str) -> Result<Option<AiJobRecord>, String> {
+        let sql = format!("SELECT {JOB_COLUMNS} FROM ai_jobs WHERE id = ?");
+        let row = self
+            .conn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                sql,
+                vec![id.into()],
+            ))
+            .await
+            .map_err(|e| format!("查询AI任务失败: {e}"))?;
+        row.as_ref().map(job_from_row).transpose()
+    }
+
+    async fn list_all_jobs(
+        &self,
+        status: Option<&str>,
+        page: i32,
+        size: i32,
+    ) -> Result<Vec<AiJobRecord>, String> {
+        let safe_page = page.max(0);
+        let safe_size = size.clamp(1, 100);
+        let (filter, mut values) = match status {
+            Some(status) => ("WHERE status = ?", vec![status.into()]),
+            None => ("", Vec::new()),
+        };
+        values.push(safe_size.into());
+        values.push((safe_page * safe_size).into());
+        let sql = format!(
+            "SELECT {JOB_COLUMNS} FROM ai_jobs {filter} ORDER BY created_at DESC LIMIT ? OFFSET ?"
+        );
+        let rows = self
+            .conn
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                sql,
+                values,
+            ))
+            .await
+            .map_err(|e| format!("查询AI任务失败: {e}"))?;
+        rows.iter().map(job_from_row).collect()
+    }
+
+    async fn list_job_ids(&self, uid: i32) -> Result<Vec<String>, String> {
+        let rows = self
+            .conn
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "SELECT id FROM ai_jobs WHERE uid = ?",
+                vec![uid.into()],
+            ))
+            .await
+            .map_err(|e| format!("查询AI任务失败: {e}"))?;
+        rows.iter()
+            .map(|row| row.try_get("", "id").map_err(|e| e.to_string()))
+            .collect()
+    }
+
+    async fn queue_stats(&self, now: i64) -> Result<AiQueueStats, String> {
+        let row = self
+            .conn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "SELECT \
+                 COALESCE(SUM(status = ?), 0) AS queued, \
+                 COALESCE(SUM(status = ? AND (next_attempt_at IS NULL OR next_attempt_at <= ?)), 0) AS ready_to_run, \
+                 COALESCE(SUM(status = ?), 0) AS running, \
+                 COALESCE(SUM(status = ?), 0) AS failed, \
+                 MIN(CASE WHEN status = ? THEN created_at END) AS oldest_queued_at \
+                 FROM ai_jobs",
+                vec![
+                    JOB_QUEUED.into(),
+                    JOB_QUEUED.into(),
+                    now.into(),
+                    JOB_RUNNING.into(),
+                    JOB_FAILED.into(),
+                    JOB_QUEUED.into(),
+                ],
+            ))
+            .await
+            .map_err(|e| format!("查询AI任务队列失败: {e}"))?
+            .ok_or_else(|| "查询AI任务队列失败".to_string())?;
+        Ok(AiQueueStats {
+            queued: row.try_get("", "queued").map_err(|e| e.to_string())?,
+            ready_to_run: row.try_get("", "ready_to_run").map_err(|e| e.to_string())?,
+            running: row.try_get("", "running").map_err(|e| e.to_string())?,
+            failed: row.try_get("", "failed").map_err(|e| e.to_string())?,
+            oldest_queued_at: row
+                .try_get("", "oldest_queued_at")
+                .map_err(|e| e.to_string())?,
+            worker_concurrency: 0,
+        })
+    }
+
+    async fn create_job_event(&self, event: AiJobEvent) -> Result<i64, String> {
+        let result = self
+            .conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "INSERT INTO ai_job_events (uid, job_id, status, attempts, next_attempt_at, error_code, error_message, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+                vec![
+                    event.uid.into(),
+                    event.job_id.into(),
+                    event.status.into(),
+                    event.attempts.into(),
+                    event.next_attempt_at.into(),
+                    event.error_code.into(),
+                    event.error_message.into(),
+                    event.created_at.into(),
+                ],
+            ))
+            .await
+            .map_err(|e| format!("记录AI任务事件失败: {e}"))?;
+        Ok(result.last_insert_id() as i64)
+    }
+
+    async fn list_job_events(
+        &self,
+        uid: i32,
+        after: i64,
+        limit: i32,
+    ) -> Result<Vec<AiJobEvent>, String> {
+        let rows = self
+            .conn
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "SELECT id, uid, job_id, status, attempts, next_attempt_at, error_code, error_message, created_at FROM ai_job_events WHERE uid = ? AND id > ? ORDER BY id LIMIT ?",
+                vec![uid.into(), after.into(), limit.into()],
+            ))
+            .await
+            .map_err(|e| format!("查询AI任务事件失败: {e}"))?;
+        rows.iter().map(event_from_row).collect()
+    }
+
+    async fn latest_job_event_id(&self, uid: i32) -> Result<i64, String> {
+        let row = self
+            .conn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "SELECT COALESCE(MAX(id), 0) AS id FROM ai_job_events WHERE uid = ?",
+                vec![uid.into()],
+            ))
+            .await
+            .map_err(|e| format!("查询AI任务事件失败: {e}"))?;
+        match row {
+            Some(row) => row.try_get("", "id").map_err(|e| e.to_string()),
+            None => Ok(0),
+        }
+    }
+
+    async fn prune_job_events(&self, before: i64) -> Result<u64, String> {
+        let result = self
+            .conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "DELETE FROM ai_job_events WHERE created_at < ?",
+                vec![before.into()],
+            ))
+            .await
+            .map_err(|e| format!("清理AI任务事件失败: {e}"))?;
+        Ok(result.rows_affected())
+    }
 }
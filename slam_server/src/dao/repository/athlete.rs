@@ -0,0 +1,188 @@
+use async_trait::async_trait;
+use sea_orm::{ConnectionTrait, DbBackend, Statement};
+
+use super::Repository;
+use crate::dao::idl::AthleteDao;
+use crate::model::athlete::{AthleteProfile, WeightEntry};
+
+fn profile_from_row(row: &sea_orm::QueryResult) -> Result<AthleteProfile, String> {
+    Ok(AthleteProfile {
+        id: row.try_get("", "id").map_err(|e| e.to_string())?,
+        valid_from: row.try_get("", "valid_from").map_err(|e| e.to_string())?,
+        birth_date: row.try_get("", "birth_date").map_err(|e| e.to_string())?,
+        sex: row.try_get("", "sex").map_err(|e| e.to_string())?,
+        height_cm: row.try_get("", "height_cm").map_err(|e| e.to_string())?,
+        resting_heart_rate: row
+            .try_get("", "resting_heart_rate")
+            .map_err(|e| e.to_string())?,
+        max_heart_rate: row
+            .try_get("", "max_heart_rate")
+            .map_err(|e| e.to_string())?,
+        unit_system: row.try_get("", "unit_system").map_err(|e| e.to_string())?,
+        created_at: row.try_get("", "created_at").map_err(|e| e.to_string())?,
+    })
+}
+
+fn weight_from_row(row: &sea_orm::QueryResult) -> Result<WeightEntry, String> {
+    Ok(WeightEntry {
+        id: row.try_get("", "id").map_err(|e| e.to_string())?,
+        measured_at: row.try_get("", "measured_at").map_err(|e| e.to_string())?,
+        weight_kg: row.try_get("", "weight_kg").map_err(|e| e.to_string())?,
+    })
+}
+
+const PROFILE_COLUMNS: &str = "id, valid_from, birth_date, sex, height_cm, resting_heart_rate, max_heart_rate, unit_system, created_at";
+const WEIGHT_COLUMNS: &str = "id, measured_at, weight_kg";
+
+impl Repository {
+    async fn query_first<T>(
+        &self,
+        sql: String,
+        values: Vec<sea_orm::Value>,
+        from_row: fn(&sea_orm::QueryResult) -> Result<T, String>,
+        what: &str,
+    ) -> Result<Option<T>, String> {
+        let row = self
+            .conn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                sql,
+                values,
+            ))
+            .await
+            .map_err(|e| format!("查询{what}失败: {e}"))?;
+        row.as_ref().map(from_row).transpose()
+    }
+}
+
+#[async_trait]
+impl AthleteDao for Repository {
+    async fn create_profile(&self, uid: i32, profile: AthleteProfile) -> Result<i64, String> {
+        let result = self
+            .conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "INSERT INTO athlete_profiles (uid, valid_from, birth_date, sex, height_cm, resting_heart_rate, max_heart_rate, unit_system, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                vec![
+                    uid.into(),
+                    profile.valid_from.into(),
+                    profile.birth_date.into(),
+                    profile.sex.into(),
+                    profile.height_cm.into(),
+                    profile.resting_heart_rate.into(),
+                    profile.max_heart_rate.into(),
+                    profile.unit_system.into(),
+                    profile.created_at.into(),
+                ],
+            ))
+            .await
+            .map_err(|e| format!("保存运动员档案失败: {e}"))?;
+        Ok(result.last_insert_id() as i64)
+    }
+
+    async fn get_profile_at(&self, uid: i32, at: i64) -> Result<Option<AthleteProfile>, String> {
+        let current = self
+            .query_first(
+                format!(
+                    "SELECT {PROFILE_COLUMNS} FROM athlete_profiles WHERE uid = ? AND valid_from <= ? ORDER BY valid_from DESC, id DESC LIMIT 1"
+                ),
+                vec![uid.into(), at.into()],
+                profile_from_row,
+                "运动员档案",
+            )
+            .await?;
+        if current.is_some() {
+            return Ok(current);
+        }
+        self.query_first(
+            format!(
+                "SELECT {PROFILE_COLUMNS} FROM athlete_profiles WHERE uid = ? ORDER BY valid_from ASC, id DESC LIMIT 1"
+            ),
+            vec![uid.into()],
+            profile_from_row,
+            "运动员档案",
+        )
+        .await
+    }
+
+    async fn list_profiles(&self, uid: i32) -> Result<Vec<AthleteProfile>, String> {
+        let rows = self
+            .conn
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                format!(
+                    "SELECT {PROFILE_COLUMNS} FROM athlete_profiles WHERE uid = ? ORDER BY valid_from DESC, id DESC"
+                ),
+                vec![uid.into()],
+            ))
+            .await
+            .map_err(|e| format!("查询运动员档案失败: {e}"))?;
+        rows.iter().map(profile_from_row).collect()
+    }
+
+    async fn add_weight(&self, uid: i32, entry: WeightEntry) -> Result<i64, String> {
+        let result = self
+            .conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "INSERT INTO weight_logs (uid, measured_at, weight_kg) VALUES (?, ?, ?)",
+                vec![uid.into(), entry.measured_at.into(), entry.weight_kg.into()],
+            ))
+            .await
+            .map_err(|e| format!("保存体重记录失败: {e}"))?;
+        Ok(result.last_insert_id() as i64)
+    }
+
+    async fn get_weight_at(&self, uid: i32, at: i64) -> Result<Option<WeightEntry>, String> {
+        let current = self
+            .query_first(
+                format!(
+                    "SELECT {WEIGHT_COLUMNS} FROM weight_logs WHERE uid = ? AND measured_at <= ? ORDER BY measured_at DESC, id DESC LIMIT 1"
+                ),
+                vec![uid.into(), at.into()],
+                weight_from_row,
+                "体重记录",
+            )
+            .await?;
+        if current.is_some() {
+            return Ok(current);
+        }
+        self.query_first(
+            format!(
+                "SELECT {WEIGHT_COLUMNS} FROM weight_logs WHERE uid = ? ORDER BY measured_at ASC, id DESC LIMIT 1"
+            ),
+            vec![uid.into()],
+            weight_from_row,
+            "体重记录",
+        )
+        .await
+    }
+
+    async fn list_weights(&self, uid: i32, limit: i32) -> Result<Vec<WeightEntry>, String> {
+        let rows = self
+            .conn
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                format!(
+                    "SELECT {WEIGHT_COLUMNS} FROM weight_logs WHERE uid = ? ORDER BY measured_at DESC, id DESC LIMIT ?"
+                ),
+                vec![uid.into(), limit.into()],
+            ))
+            .await
+            .map_err(|e| format!("查询体重记录失败: {e}"))?;
+        rows.iter().map(weight_from_row).collect()
+    }
+
+    async fn delete_weight(&self, uid: i32, id: i64) -> Result<bool, String> {
+        let result = self
+            .conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "DELETE FROM weight_logs WHERE id = ? AND uid = ?",
+                vec![id.into(), uid.into()],
+            ))
+            .await
+            .map_err(|e| format!("删除体重记录失败: {e}"))?;
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,147 @@
+use async_trait::async_trait;
+use sea_orm::{ConnectionTrait, DbBackend, Statement};
+
+use super::Repository;
+use crate::dao::idl::{AuditDao, LoginAttemptDao};
+use crate::model::audit::{AuditEntry, AuditQuery, LoginAttempt};
+
+#[async_trait]
+impl AuditDao for Repository {
+    async fn append_audit(&self, entry: AuditEntry) -> Result<(), String> {
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "INSERT INTO audit_logs (uid, action, ip, user_agent, detail, created_at) VALUES (?, ?, ?, ?, ?, ?)",
+                vec![
+                    entry.uid.into(),
+                    entry.action.into(),
+                    entry.ip.into(),
+                    entry.user_agent.into(),
+                    entry.detail.into(),
+                    entry.created_at.into(),
+                ],
+            ))
+            .await
+            .map_err(|e| format!("写入审计日志失败: {e}"))?;
+        Ok(())
+    }
+
+    async fn list_audit(
+        &self,
+        query: &AuditQuery,
+        page: i32,
+        size: i32,
+    ) -> Result<Vec<AuditEntry>, String> {
+        let safe_page = page.max(0);
+        let safe_size = size.clamp(1, 200);
+        let mut conditions = Vec::new();
+        let mut values: Vec<sea_orm::Value> = Vec::new();
+        if let Some(uid) = query.uid {
+            conditions.push("uid = ?");
+            values.push(uid.into());
+        }
+        if let Some(action) = &query.action {
+            conditions.push("action = ?");
+            values.push(action.as_str().into());
+        }
+        if let Some(ip) = &query.ip {
+            conditions.push("ip = ?");
+            values.push(ip.as_str().into());
+        }
+        if let Some(since) = query.since {
+            conditions.push("created_at >= ?");
+            values.push(since.into());
+        }
+        if let Some(until) = query.until {
+            conditions.push("created_at < ?");
+            values.push(until.into());
+        }
+        let filter = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+        values.push(safe_size.into());
+        values.push((safe_page * safe_size).into());
+        let rows = self
+            .conn
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                format!(
+                    "SELECT id, uid, action, ip, user_agent, detail, created_at FROM audit_logs {filter} ORDER BY created_at DESC, id DESC LIMIT ? OFFSET ?"
+                ),
+                values,
+            ))
+            .await
+            .map_err(|e| format!("查询审计日志失败: {e}"))?;
+        rows.iter()
+            .map(|row| {
+                Ok(AuditEntry {
+                    id: row.try_get("", "id").map_err(|e| e.to_string())?,
+                    uid: row.try_get("", "uid").map_err(|e| e.to_string())?,
+                    action: row.try_get("", "action").map_err(|e| e.to_string())?,
+                    ip: row.try_get("", "ip").map_err(|e| e.to_string())?,
+                    user_agent: row.try_get("", "user_agent").map_err(|e| e.to_string())?,
+                    detail: row.try_get("", "detail").map_err(|e| e.to_string())?,
+                    created_at: row.try_get("", "created_at").map_err(|e| e.to_string())?,
+                })
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl LoginAttemptDao for Repository {
+    async fn get_login_attempt(&self, key: &str) -> Result<Option<LoginAttempt>, String> {
+        let row = self
+            .conn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "SELECT key, failures, last_failure_at, locked_until FROM login_attempts WHERE key = ?",
+                vec![key.into()],
+            ))
+            .await
+            .map_err(|e| format!("查询登录失败记录失败: {e}"))?;
+        row.map(|row| {
+            Ok(LoginAttempt {
+                key: row.try_get("", "key").map_err(|e| e.to_string())?,
+                failures: row.try_get("", "failures").map_err(|e| e.to_string())?,
+                last_failure_at: row
+                    .try_get("", "last_failure_at")
+                    .map_err(|e| e.to_string())?,
+                locked_until: row.try_get("", "locked_until").map_err(|e| e.to_string())?,
+            })
+        })
+        .transpose()
+    }
+
+    async fn save_login_attempt(&self, attempt: LoginAttempt) -> Result<(), String> {
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "INSERT INTO login_attempts (key, failures, last_failure_at, locked_until) VALUES (?, ?, ?, ?) \
+                 ON CONFLICT(key) DO UPDATE SET failures = excluded.failures, last_failure_at = excluded.last_failure_at, locked_until = excluded.locked_until",
+                vec![
+                    attempt.key.into(),
+                    attempt.failures.into(),
+                    attempt.last_failure_at.into(),
+                    attempt.locked_until.into(),
+                ],
+            ))
+            .await
+            .map_err(|e| format!("保存登录失败记录失败: {e}"))?;
+        Ok(())
+    }
+
+    async fn clear_login_attempt(&self, key: &str) -> Result<(), String> {
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "DELETE FROM login_attempts WHERE key = ?",
+                vec![key.into()],
+            ))
+            .await
+            .map_err(|e| format!("清除登录失败记录失败: {e}"))?;
+        Ok(())
+    }
+}
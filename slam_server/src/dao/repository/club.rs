@@ -0,0 +1,449 @@
+use async_trait::async_trait;
+use sea_orm::{ConnectionTrait, DbBackend, DbErr, Statement, TransactionTrait, Value};
+
+use super::Repository;
+use super::social::AVATAR_VERSION;
+use crate::dao::idl::ClubDao;
+use crate::model::club::{
+    CLUB_OWNER, Challenge, ChallengeMetric, Club, ClubInvite, ClubMemberRow, ClubRow,
+    LeaderboardRow,
+};
+use crate::model::sport::{SportType, SportVisibility};
+
+const CLUB_COLUMNS: &str = "c.id, c.name, c.description, c.join_code, c.created_at";
+const CHALLENGE_COLUMNS: &str =
+    "id, club_id, name, metric, sport_type, start_time, end_time, created_by, created_at";
+
+fn club_from_row(row: &sea_orm::QueryResult) -> Result<Club, String> {
+    Ok(Club {
+        id: row.try_get("", "id").map_err(|e| e.to_string())?,
+        name: row.try_get("", "name").map_err(|e| e.to_string())?,
+        description: row.try_get("", "description").map_err(|e| e.to_string())?,
+        join_code: row.try_get("", "join_code").map_err(|e| e.to_string())?,
+        created_at: row.try_get("", "created_at").map_err(|e| e.to_string())?,
+    })
+}
+
+fn club_row(row: &sea_orm::QueryResult) -> Result<ClubRow, String> {
+    Ok(ClubRow {
+        club: club_from_row(row)?,
+        role: row.try_get("", "role").map_err(|e| e.to_string())?,
+        member_count: row.try_get("", "member_count").map_err(|e| e.to_string())?,
+    })
+}
+
+fn challenge_from_row(row: &sea_orm::QueryResult) -> Result<Challenge, String> {
+    let metric: String = row.try_get("", "metric").map_err(|e| e.to_string())?;
+    let sport_type: Option<String> = row.try_get("", "sport_type").map_err(|e| e.to_string())?;
+    Ok(Challenge {
+        id: row.try_get("", "id").map_err(|e| e.to_string())?,
+        club_id: row.try_get("", "club_id").map_err(|e| e.to_string())?,
+        name: row.try_get("", "name").map_err(|e| e.to_string())?,
+        metric: ChallengeMetric::parse(&metric).ok_or(format!("未知的挑战指标: {metric}"))?,
+        sport_type: sport_type.as_deref().map(SportType::from_str),
+        start_time: row.try_get("", "start_time").map_err(|e| e.to_string())?,
+        end_time: row.try_get("", "end_time").map_err(|e| e.to_string())?,
+        created_by: row.try_get("", "created_by").map_err(|e| e.to_string())?,
+        created_at: row.try_get("", "created_at").map_err(|e| e.to_string())?,
+    })
+}
+
+/// 成员数量的子查询
+const MEMBER_COUNT: &str = "(SELECT COUNT(*) FROM club_members x WHERE x.club_id = c.id)";
+
+#[async_trait]
+impl ClubDao for Repository {
+    async fn create_club(&self, club: Club, owner_uid: i32) -> Result<i64, String> {
+        self.conn
+            .transaction::<_, i64, DbErr>(|txn| {
+                Box::pin(async move {
+                    let result = txn
+                        .execute(Statement::from_sql_and_values(
+                            DbBackend::Sqlite,
+                            "INSERT INTO clubs (name, description, join_code, created_at) \
+                             VALUES (?, ?, ?, ?)",
+                            [
+                                club.name.into(),
+                                club.description.into(),
+                                club.join_code.into(),
+                                club.created_at.into(),
+                            ],
+                        ))
+                        .await?;
+                    let club_id = result.last_insert_id() as i64;
+                    txn.execute(Statement::from_sql_and_values(
+                        DbBackend::Sqlite,
+                        "INSERT INTO club_members (club_id, uid, role, joined_at) VALUES (?, ?, ?, ?)",
+                        [
+                            club_id.into(),
+                            owner_uid.into(),
+                            CLUB_OWNER.into(),
+                            club.created_at.into(),
+                        ],
+                    ))
+                    .await?;
+                    Ok(club_id)
+                })
+            })
+            .await
+            .map_err(|e| format!("创建俱乐部失败: {e}"))
+    }
+
+    async fn get_club_by_code(&self, join_code: &str) -> Result<Option<Club>, String> {
+        let row = self
+            .conn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                format!("SELECT {CLUB_COLUMNS} FROM clubs c WHERE c.join_code = ?"),
+                [join_code.into()],
+            ))
+            .await
+            .map_err(|e| format!("查询俱乐部失败: {e}"))?;
+        row.as_ref().map(club_from_row).transpose()
+    }
+
+    async fn delete_club(&self, club_id: i64) -> Result<(), String> {
+        self.conn
+            .transaction::<_, (), DbErr>(|txn| {
+                Box::pin(async move {
+                    for sql in [
+                        "DELETE FROM club_members WHERE club_id = ?",
+                        "DELETE FROM club_invites WHERE club_id = ?",
+                        "DELETE FROM club_challenges WHERE club_id = ?",
+                        "DELETE FROM clubs WHERE id = ?",
+                    ] {
+                        txn.execute(Statement::from_sql_and_values(
+                            DbBackend::Sqlite,
+                            sql,
+                            [club_id.into()],
+                        ))
+                        .await?;
+                    }
+                    Ok(())
+                })
+            })
+            .await
+            .map_err(|e| format!("删除俱乐部失败: {e}"))
+    }
+
+    async fn set_join_code(&self, club_id: i64, join_code: &str) -> Result<(), String> {
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "UPDATE clubs SET join_code = ? WHERE id = ?",
+                [join_code.into(), club_id.into()],
+            ))
+            .await
+            .map_err(|e| format!("更新加入码失败: {e}"))?;
+        Ok(())
+    }
+
+    async fn list_clubs(&self, uid: i32) -> Result<Vec<ClubRow>, String> {
+        let rows = self
+            .conn
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                format!(
+                    "SELECT {CLUB_COLUMNS}, m.role, {MEMBER_COUNT} AS member_count \
+                     FROM club_members m JOIN clubs c ON c.id = m.club_id \
+                     WHERE m.uid = ? ORDER BY m.joined_at DESC"
+                ),
+                [uid.into()],
+            ))
+            .await
+            .map_err(|e| format!("查询俱乐部失败: {e}"))?;
+        rows.iter().map(club_row).collect()
+    }
+
+    async fn get_club_row(&self, club_id: i64, uid: i32) -> Result<Option<ClubRow>, String> {
+        let row = self
+            .conn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                format!(
+                    "SELECT {CLUB_COLUMNS}, m.role, {MEMBER_COUNT} AS member_count \
+                     FROM club_members m JOIN clubs c ON c.id = m.club_id \
+                     WHERE m.club_id = ? AND m.uid = ?"
+                ),
+                [club_id.into(), uid.into()],
+            ))
+            .await
+            .map_err(|e| format!("查询俱乐部失败: {e}"))?;
+        row.as_ref().map(club_row).transpose()
+    }
+
+    async fn shares_club(&self, uid: i32, other_uid: i32) -> Result<bool, String> {
+        let row = self
+            .conn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "SELECT 1 FROM club_members a JOIN club_members b ON b.club_id = a.club_id \
+                 WHERE a.uid = ? AND b.uid = ? LIMIT 1",
+                [uid.into(), other_uid.into()],
+            ))
+            .await
+            .map_err(|e| format!("查询俱乐部成员失败: {e}"))?;
+        Ok(row.is_some())
+    }
+
+    async fn add_member(
+        &self,
+        club_id: i64,
+        uid: i32,
+        role: &str,
+        now: i64,
+    ) -> Result<bool, String> {
+        let role = role.to_string();
+        self.conn
+            .transaction::<_, bool, DbErr>(|txn| {
+                Box::pin(async move {
+                    let result = txn
+                        .execute(Statement::from_sql_and_values(
+                            DbBackend::Sqlite,
+                            "INSERT OR IGNORE INTO club_members (club_id, uid, role, joined_at) \
+                             VALUES (?, ?, ?, ?)",
+                            [club_id.into(), uid.into(), role.into(), now.into()],
+                        ))
+                        .await?;
+                    txn.execute(Statement::from_sql_and_values(
+                        DbBackend::Sqlite,
+                        "DELETE FROM club_invites WHERE club_id = ? AND uid = ?",
+                        [club_id.into(), uid.into()],
+                    ))
+                    .await?;
+                    Ok(result.rows_affected() > 0)
+                })
+            })
+            .await
+            .map_err(|e| format!("加入俱乐部失败: {e}"))
+    }
+
+    async fn remove_member(&self, club_id: i64, uid: i32) -> Result<bool, String> {
+        let result = self
+            .conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "DELETE FROM club_members WHERE club_id = ? AND uid = ?",
+                [club_id.into(), uid.into()],
+            ))
+            .await
+            .map_err(|e| format!("移除成员失败: {e}"))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn set_member_role(&self, club_id: i64, uid: i32, role: &str) -> Result<bool, String> {
+        let result = self
+            .conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "UPDATE club_members SET role = ? WHERE club_id = ? AND uid = ?",
+                [role.into(), club_id.into(), uid.into()],
+            ))
+            .await
+            .map_err(|e| format!("更新成员角色失败: {e}"))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn list_members(&self, club_id: i64) -> Result<Vec<ClubMemberRow>, String> {
+        let rows = self
+            .conn
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                format!(
+                    "SELECT u.id AS uid, u.nickname, {AVATAR_VERSION} AS avatar_version, m.role, m.joined_at \
+                     FROM club_members m JOIN users u ON u.id = m.uid \
+                     WHERE m.club_id = ? ORDER BY m.joined_at, u.id"
+                ),
+                [club_id.into()],
+            ))
+            .await
+            .map_err(|e| format!("查询成员失败: {e}"))?;
+        rows.iter()
+            .map(|row| {
+                Ok(ClubMemberRow {
+                    uid: row.try_get("", "uid").map_err(|e| e.to_string())?,
+                    nickname: row.try_get("", "nickname").map_err(|e| e.to_string())?,
+                    avatar_version: row
+                        .try_get("", "avatar_version")
+                        .map_err(|e| e.to_string())?,
+                    role: row.try_get("", "role").map_err(|e| e.to_string())?,
+                    joined_at: row.try_get("", "joined_at").map_err(|e| e.to_string())?,
+                })
+            })
+            .collect()
+    }
+
+    async fn create_invite(
+        &self,
+        club_id: i64,
+        uid: i32,
+        invited_by: i32,
+        now: i64,
+    ) -> Result<(), String> {
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "INSERT OR REPLACE INTO club_invites (club_id, uid, invited_by, created_at) \
+                 VALUES (?, ?, ?, ?)",
+                [club_id.into(), uid.into(), invited_by.into(), now.into()],
+            ))
+            .await
+            .map_err(|e| format!("邀请成员失败: {e}"))?;
+        Ok(())
+    }
+
+    async fn delete_invite(&self, club_id: i64, uid: i32) -> Result<bool, String> {
+        let result = self
+            .conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "DELETE FROM club_invites WHERE club_id = ? AND uid = ?",
+                [club_id.into(), uid.into()],
+            ))
+            .await
+            .map_err(|e| format!("删除邀请失败: {e}"))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn list_invites(&self, uid: i32) -> Result<Vec<ClubInvite>, String> {
+        let rows = self
+            .conn
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "SELECT i.club_id, c.name AS club_name, i.invited_by, i.created_at \
+                 FROM club_invites i JOIN clubs c ON c.id = i.club_id \
+                 WHERE i.uid = ? ORDER BY i.created_at DESC",
+                [uid.into()],
+            ))
+            .await
+            .map_err(|e| format!("查询邀请失败: {e}"))?;
+        rows.iter()
+            .map(|row| {
+                Ok(ClubInvite {
+                    club_id: row.try_get("", "club_id").map_err(|e| e.to_string())?,
+                    club_name: row.try_get("", "club_name").map_err(|e| e.to_string())?,
+                    invited_by: row.try_get("", "invited_by").map_err(|e| e.to_string())?,
+                    created_at: row.try_get("", "created_at").map_err(|e| e.to_string())?,
+                })
+            })
+            .collect()
+    }
+
+    async fn create_challenge(&self, challenge: Challenge) -> Result<i64, String> {
+        let result = self
+            .conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "INSERT INTO club_challenges \
+                 (club_id, name, metric, sport_type, start_time, end_time, created_by, created_at) \
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+                [
+                    challenge.club_id.into(),
+                    challenge.name.into(),
+                    challenge.metric.as_str().into(),
+                    challenge.sport_type.map(|t| t.as_str()).into(),
+                    challenge.start_time.into(),
+                    challenge.end_time.into(),
+                    challenge.created_by.into(),
+                    challenge.created_at.into(),
+                ],
+            ))
+            .await
+            .map_err(|e| format!("创建挑战失败: {e}"))?;
+        Ok(result.last_insert_id() as i64)
+    }
+
+    async fn list_challenges(&self, club_id: i64) -> Result<Vec<Challenge>, String> {
+        let rows = self
+            .conn
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                format!(
+                    "SELECT {CHALLENGE_COLUMNS} FROM club_challenges WHERE club_id = ? \
+                     ORDER BY start_time DESC, id DESC"
+                ),
+                [club_id.into()],
+            ))
+            .await
+            .map_err(|e| format!("查询挑战失败: {e}"))?;
+        rows.iter().map(challenge_from_row).collect()
+    }
+
+    async fn get_challenge(&self, club_id: i64, id: i64) -> Result<Option<Challenge>, String> {
+        let row = self
+            .conn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                format!(
+                    "SELECT {CHALLENGE_COLUMNS} FROM club_challenges WHERE club_id = ? AND id = ?"
+                ),
+                [club_id.into(), id.into()],
+            ))
+            .await
+            .map_err(|e| format!("查询挑战失败: {e}"))?;
+        row.as_ref().map(challenge_from_row).transpose()
+    }
+
+    async fn delete_challenge(&self, club_id: i64, id: i64) -> Result<bool, String> {
+        let result = self
+            .conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "DELETE FROM club_challenges WHERE club_id = ? AND id = ?",
+                [club_id.into(), id.into()],
+            ))
+            .await
+            .map_err(|e| format!("删除挑战失败: {e}"))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn leaderboard(&self, challenge: &Challenge) -> Result<Vec<LeaderboardRow>, String> {
+        let value = match challenge.metric {
+            ChallengeMetric::Distance => "COALESCE(SUM(s.distance_meter), 0)",
+            ChallengeMetric::Count => "COUNT(s.id)",
+            ChallengeMetric::Duration => "COALESCE(SUM(s.duration_second), 0)",
+        };
+        let mut values: Vec<Value> = vec![challenge.start_time.into(), challenge.end_time.into()];
+        let type_filter = match challenge.sport_type {
+            Some(t) => {
+                values.push(t.as_str().into());
+                " AND s.type = ?"
+            }
+            None => "",
+        };
+        // 只统计成员愿意让他人看到的运动，私密运动不计入排行
+        values.push(SportVisibility::Followers.as_str().into());
+        values.push(SportVisibility::Public.as_str().into());
+        values.push(challenge.club_id.into());
+        let rows = self
+            .conn
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                format!(
+                    "SELECT u.id AS uid, u.nickname, {AVATAR_VERSION} AS avatar_version, \
+                     {value} AS value, COUNT(s.id) AS sessions \
+                     FROM club_members m JOIN users u ON u.id = m.uid \
+                     LEFT JOIN sports s ON s.uid = m.uid AND s.start_time >= ? AND s.start_time < ?{type_filter} \
+                     AND s.visibility IN (?, ?) \
+                     WHERE m.club_id = ? \
+                     GROUP BY u.id ORDER BY value DESC, u.id"
+                ),
+                values,
+            ))
+            .await
+            .map_err(|e| format!("查询排行榜失败: {e}"))?;
+        rows.iter()
+            .map(|row| {
+                Ok(LeaderboardRow {
+                    uid: row.try_get("", "uid").map_err(|e| e.to_string())?,
+                    nickname: row.try_get("", "nickname").map_err(|e| e.to_string())?,
+                    avatar_version: row
+                        .try_get("", "avatar_version")
+                        .map_err(|e| e.to_string())?,
+                    value: row.try_get("", "value").map_err(|e| e.to_string())?,
+                    sessions: row.try_get("", "sessions").map_err(|e| e.to_string())?,
+                })
+            })
+            .collect()
+    }
+}
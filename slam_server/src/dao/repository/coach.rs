@@ -0,0 +1,176 @@
+use async_trait::async_trait;
+use sea_orm::{ConnectionTrait, DbBackend, Statement};
+
+use super::Repository;
+use super::social::AVATAR_VERSION;
+use crate::dao::idl::CoachDao;
+use crate::model::coach::{CoachGrant, CoachGrantRow, GRANT_ACTIVE, GRANT_PENDING};
+
+fn split_scopes(scopes: String) -> Vec<String> {
+    scopes
+        .split(',')
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn grant_row(row: &sea_orm::QueryResult) -> Result<CoachGrantRow, String> {
+    Ok(CoachGrantRow {
+        uid: row.try_get("", "uid").map_err(|e| e.to_string())?,
+        nickname: row.try_get("", "nickname").map_err(|e| e.to_string())?,
+        avatar_version: row
+            .try_get("", "avatar_version")
+            .map_err(|e| e.to_string())?,
+        scopes: split_scopes(row.try_get("", "scopes").map_err(|e| e.to_string())?),
+        status: row.try_get("", "status").map_err(|e| e.to_string())?,
+        created_at: row.try_get("", "created_at").map_err(|e| e.to_string())?,
+        accepted_at: row.try_get("", "accepted_at").map_err(|e| e.to_string())?,
+    })
+}
+
+impl Repository {
+    /// 列出 by_column 一方的授权，并关联 other_column 一方的用户信息
+    async fn list_grants(
+        &self,
+        by_column: &str,
+        other_column: &str,
+        uid: i32,
+    ) -> Result<Vec<CoachGrantRow>, String> {
+        let rows = self
+            .conn
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                format!(
+                    "SELECT u.id AS uid, u.nickname, {AVATAR_VERSION} AS avatar_version, \
+                     g.scopes, g.status, g.created_at, g.accepted_at \
+                     FROM coach_grants g JOIN users u ON u.id = g.{other_column} \
+                     WHERE g.{by_column} = ? ORDER BY g.created_at DESC"
+                ),
+                [uid.into()],
+            ))
+            .await
+            .map_err(|e| format!("查询教练授权失败: {e}"))?;
+        rows.iter().map(grant_row).collect()
+    }
+}
+
+#[async_trait]
+impl CoachDao for Repository {
+    async fn get_grant(
+        &self,
+        athlete_uid: i32,
+        coach_uid: i32,
+    ) -> Result<Option<CoachGrant>, String> {
+        let row = self
+            .conn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "SELECT scopes, status, created_at, accepted_at FROM coach_grants \
+                 WHERE athlete_uid = ? AND coach_uid = ?",
+                [athlete_uid.into(), coach_uid.into()],
+            ))
+            .await
+            .map_err(|e| format!("查询教练授权失败: {e}"))?;
+        row.map(|row| {
+            Ok(CoachGrant {
+                athlete_uid,
+                coach_uid,
+                scopes: split_scopes(row.try_get("", "scopes").map_err(|e| e.to_string())?),
+                status: row.try_get("", "status").map_err(|e| e.to_string())?,
+                created_at: row.try_get("", "created_at").map_err(|e| e.to_string())?,
+                accepted_at: row.try_get("", "accepted_at").map_err(|e| e.to_string())?,
+            })
+        })
+        .transpose()
+    }
+
+    async fn create_grant(&self, grant: CoachGrant) -> Result<(), String> {
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "INSERT INTO coach_grants (athlete_uid, coach_uid, scopes, status, created_at, accepted_at) \
+                 VALUES (?, ?, ?, ?, ?, ?)",
+                [
+                    grant.athlete_uid.into(),
+                    grant.coach_uid.into(),
+                    grant.scopes.join(",").into(),
+                    grant.status.into(),
+                    grant.created_at.into(),
+                    grant.accepted_at.into(),
+                ],
+            ))
+            .await
+            .map_err(|e| format!("创建教练授权失败: {e}"))?;
+        Ok(())
+    }
+
+    async fn set_grant_scopes(
+        &self,
+        athlete_uid: i32,
+        coach_uid: i32,
+        scopes: &[String],
+    ) -> Result<bool, String> {
+        let result = self
+            .conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "UPDATE coach_grants SET scopes = ? WHERE athlete_uid = ? AND coach_uid = ?",
+                [
+                    scopes.join(",").into(),
+                    athlete_uid.into(),
+                    coach_uid.into(),
+                ],
+            ))
+            .await
+            .map_err(|e| format!("更新教练授权失败: {e}"))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn accept_grant(
+        &self,
+        athlete_uid: i32,
+        coach_uid: i32,
+        now: i64,
+    ) -> Result<bool, String> {
+        let result = self
+            .conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "UPDATE coach_grants SET status = ?, accepted_at = ? \
+                 WHERE athlete_uid = ? AND coach_uid = ? AND status = ?",
+                [
+                    GRANT_ACTIVE.into(),
+                    now.into(),
+                    athlete_uid.into(),
+                    coach_uid.into(),
+                    GRANT_PENDING.into(),
+                ],
+            ))
+            .await
+            .map_err(|e| format!("接受教练授权失败: {e}"))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn delete_grant(&self, athlete_uid: i32, coach_uid: i32) -> Result<bool, String> {
+        let result = self
+            .conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "DELETE FROM coach_grants WHERE athlete_uid = ? AND coach_uid = ?",
+                [athlete_uid.into(), coach_uid.into()],
+            ))
+            .await
+            .map_err(|e| format!("撤销教练授权失败: {e}"))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn list_coaches(&self, athlete_uid: i32) -> Result<Vec<CoachGrantRow>, String> {
+        self.list_grants("athlete_uid", "coach_uid", athlete_uid)
+            .await
+    }
+
+    async fn list_athletes(&self, coach_uid: i32) -> Result<Vec<CoachGrantRow>, String> {
+        self.list_grants("coach_uid", "athlete_uid", coach_uid)
+            .await
+    }
+}
@@ -0,0 +1,196 @@
+use async_trait::async_trait;
+use sea_orm::{ConnectionTrait, DbBackend, Statement};
+
+use super::Repository;
+use super::social::AVATAR_VERSION;
+use crate::dao::idl::CommentDao;
+use crate::model::comment::{CommentRow, ReactionCount, SportComment, SportOwner};
+use crate::model::sport::SportVisibility;
+
+const COMMENT_COLUMNS: &str = "c.id, c.sport_id, c.uid, c.content, c.created_at, c.updated_at";
+
+fn comment_from_row(row: &sea_orm::QueryResult) -> Result<SportComment, String> {
+    Ok(SportComment {
+        id: row.try_get("", "id").map_err(|e| e.to_string())?,
+        sport_id: row.try_get("", "sport_id").map_err(|e| e.to_string())?,
+        uid: row.try_get("", "uid").map_err(|e| e.to_string())?,
+        content: row.try_get("", "content").map_err(|e| e.to_string())?,
+        created_at: row.try_get("", "created_at").map_err(|e| e.to_string())?,
+        updated_at: row.try_get("", "updated_at").map_err(|e| e.to_string())?,
+    })
+}
+
+#[async_trait]
+impl CommentDao for Repository {
+    async fn get_sport_owner(&self, sport_id: i32) -> Result<Option<SportOwner>, String> {
+        let row = self
+            .conn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "SELECT uid, visibility FROM sports WHERE id = ?",
+                [sport_id.into()],
+            ))
+            .await
+            .map_err(|e| format!("查询运动记录失败: {e}"))?;
+        row.map(|row| {
+            let visibility: String = row.try_get("", "visibility").map_err(|e| e.to_string())?;
+            Ok(SportOwner {
+                uid: row.try_get("", "uid").map_err(|e| e.to_string())?,
+                visibility: SportVisibility::parse(&visibility),
+            })
+        })
+        .transpose()
+    }
+
+    async fn create_comment(&self, comment: SportComment) -> Result<i64, String> {
+        let result = self
+            .conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "INSERT INTO sport_comments (sport_id, uid, content, created_at) VALUES (?, ?, ?, ?)",
+                [
+                    comment.sport_id.into(),
+                    comment.uid.into(),
+                    comment.content.into(),
+                    comment.created_at.into(),
+                ],
+            ))
+            .await
+            .map_err(|e| format!("发表评论失败: {e}"))?;
+        Ok(result.last_insert_id() as i64)
+    }
+
+    async fn get_comment(&self, sport_id: i32, id: i64) -> Result<Option<SportComment>, String> {
+        let row = self
+            .conn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                format!("SELECT {COMMENT_COLUMNS} FROM sport_comments c WHERE c.id = ? AND c.sport_id = ?"),
+                [id.into(), sport_id.into()],
+            ))
+            .await
+            .map_err(|e| format!("查询评论失败: {e}"))?;
+        row.as_ref().map(comment_from_row).transpose()
+    }
+
+    async fn update_comment(&self, id: i64, content: &str, now: i64) -> Result<bool, String> {
+        let result = self
+            .conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "UPDATE sport_comments SET content = ?, updated_at = ? WHERE id = ?",
+                [content.into(), now.into(), id.into()],
+            ))
+            .await
+            .map_err(|e| format!("编辑评论失败: {e}"))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn delete_comment(&self, id: i64) -> Result<bool, String> {
+        let result = self
+            .conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "DELETE FROM sport_comments WHERE id = ?",
+                [id.into()],
+            ))
+            .await
+            .map_err(|e| format!("删除评论失败: {e}"))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn list_comments(&self, sport_id: i32) -> Result<Vec<CommentRow>, String> {
+        let rows = self
+            .conn
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                format!(
+                    "SELECT {COMMENT_COLUMNS}, u.nickname, {AVATAR_VERSION} AS avatar_version \
+                     FROM sport_comments c JOIN users u ON u.id = c.uid \
+                     WHERE c.sport_id = ? ORDER BY c.created_at, c.id"
+                ),
+                [sport_id.into()],
+            ))
+            .await
+            .map_err(|e| format!("查询评论失败: {e}"))?;
+        rows.iter()
+            .map(|row| {
+                Ok(CommentRow {
+                    comment: comment_from_row(row)?,
+                    nickname: row.try_get("", "nickname").map_err(|e| e.to_string())?,
+                    avatar_version: row
+                        .try_get("", "avatar_version")
+                        .map_err(|e| e.to_string())?,
+                })
+            })
+            .collect()
+    }
+
+    async fn add_reaction(
+        &self,
+        sport_id: i32,
+        uid: i32,
+        kind: &str,
+        now: i64,
+    ) -> Result<bool, String> {
+        let result = self
+            .conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "INSERT OR IGNORE INTO sport_reactions (sport_id, uid, kind, created_at) VALUES (?, ?, ?, ?)",
+                [sport_id.into(), uid.into(), kind.into(), now.into()],
+            ))
+            .await
+            .map_err(|e| format!("回应失败: {e}"))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn remove_reaction(&self, sport_id: i32, uid: i32, kind: &str) -> Result<bool, String> {
+        let result = self
+            .conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "DELETE FROM sport_reactions WHERE sport_id = ? AND uid = ? AND kind = ?",
+                [sport_id.into(), uid.into(), kind.into()],
+            ))
+            .await
+            .map_err(|e| format!("取消回应失败: {e}"))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn count_reactions(&self, sport_id: i32) -> Result<Vec<ReactionCount>, String> {
+        let rows = self
+            .conn
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "SELECT kind, COUNT(*) AS count FROM sport_reactions \
+                 WHERE sport_id = ? GROUP BY kind ORDER BY kind",
+                [sport_id.into()],
+            ))
+            .await
+            .map_err(|e| format!("查询回应失败: {e}"))?;
+        rows.iter()
+            .map(|row| {
+                Ok(ReactionCount {
+                    kind: row.try_get("", "kind").map_err(|e| e.to_string())?,
+                    count: row.try_get("", "count").map_err(|e| e.to_string())?,
+                })
+            })
+            .collect()
+    }
+
+    async fn list_own_reactions(&self, sport_id: i32, uid: i32) -> Result<Vec<String>, String> {
+        let rows = self
+            .conn
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "SELECT kind FROM sport_reactions WHERE sport_id = ? AND uid = ? ORDER BY kind",
+                [sport_id.into(), uid.into()],
+            ))
+            .await
+            .map_err(|e| format!("查询回应失败: {e}"))?;
+        rows.iter()
+            .map(|row| row.try_get("", "kind").map_err(|e| e.to_string()))
+            .collect()
+    }
+}
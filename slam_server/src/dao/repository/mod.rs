@@ -17,8 +17,21 @@ impl Repository {
     }
 }
 
+mod access_token;
 mod ai_job;
+mod athlete;
+mod audit;
+mod club;
+mod coach;
+mod comment;
 mod compat;
+mod notification;
+mod oidc;
 mod schema;
+mod session;
+mod share;
+mod social;
 mod sport;
+mod two_factor;
 mod user;
+mod webhook;
@@ -0,0 +1,138 @@
+use async_trait::async_trait;
+use sea_orm::{ConnectionTrait, DbBackend, Statement};
+
+use super::Repository;
+use super::social::AVATAR_VERSION;
+use crate::dao::idl::NotificationDao;
+use crate::model::notification::{Notification, NotificationRow};
+
+fn notification_row(row: &sea_orm::QueryResult) -> Result<NotificationRow, String> {
+    Ok(NotificationRow {
+        notification: Notification {
+            id: row.try_get("", "id").map_err(|e| e.to_string())?,
+            uid: row.try_get("", "uid").map_err(|e| e.to_string())?,
+            kind: row.try_get("", "kind").map_err(|e| e.to_string())?,
+            actor_uid: row.try_get("", "actor_uid").map_err(|e| e.to_string())?,
+            sport_id: row.try_get("", "sport_id").map_err(|e| e.to_string())?,
+            detail: row.try_get("", "detail").map_err(|e| e.to_string())?,
+            created_at: row.try_get("", "created_at").map_err(|e| e.to_string())?,
+            read_at: row.try_get("", "read_at").map_err(|e| e.to_string())?,
+        },
+        actor_nickname: row
+            .try_get("", "actor_nickname")
+            .map_err(|e| e.to_string())?,
+        actor_avatar_version: row
+            .try_get("", "actor_avatar_version")
+            .map_err(|e| e.to_string())?,
+    })
+}
+
+#[async_trait]
+impl NotificationDao for Repository {
+    async fn create_notification(&self, notification: Notification) -> Result<i64, String> {
+        let result = self
+            .conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "INSERT INTO notifications (uid, kind, actor_uid, sport_id, detail, created_at) \
+                 VALUES (?, ?, ?, ?, ?, ?)",
+                [
+                    notification.uid.into(),
+                    notification.kind.into(),
+                    notification.actor_uid.into(),
+                    notification.sport_id.into(),
+                    notification.detail.into(),
+                    notification.created_at.into(),
+                ],
+            ))
+            .await
+            .map_err(|e| format!("写入通知失败: {e}"))?;
+        Ok(result.last_insert_id() as i64)
+    }
+
+    async fn list_notifications(
+        &self,
+        uid: i32,
+        before: Option<i64>,
+        unread_only: bool,
+        limit: i32,
+    ) -> Result<Vec<NotificationRow>, String> {
+        let mut sql = format!(
+            "SELECT n.id, n.uid, n.kind, n.actor_uid, n.sport_id, n.detail, n.created_at, n.read_at, \
+             u.nickname AS actor_nickname, {AVATAR_VERSION} AS actor_avatar_version \
+             FROM notifications n LEFT JOIN users u ON u.id = n.actor_uid \
+             WHERE n.uid = ?"
+        );
+        let mut values: Vec<sea_orm::Value> = vec![uid.into()];
+        if let Some(before) = before {
+            sql.push_str(" AND n.id < ?");
+            values.push(before.into());
+        }
+        if unread_only {
+            sql.push_str(" AND n.read_at IS NULL");
+        }
+        sql.push_str(" ORDER BY n.id DESC LIMIT ?");
+        values.push(limit.into());
+        let rows = self
+            .conn
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                sql,
+                values,
+            ))
+            .await
+            .map_err(|e| format!("查询通知失败: {e}"))?;
+        rows.iter().map(notification_row).collect()
+    }
+
+    async fn count_unread(&self, uid: i32) -> Result<i64, String> {
+        let row = self
+            .conn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "SELECT COUNT(*) AS count FROM notifications WHERE uid = ? AND read_at IS NULL",
+                [uid.into()],
+            ))
+            .await
+            .map_err(|e| format!("查询未读通知数量失败: {e}"))?
+            .ok_or_else(|| "查询未读通知数量失败".to_string())?;
+        row.try_get("", "count").map_err(|e| e.to_string())
+    }
+
+    async fn mark_read(&self, uid: i32, id: i64, now: i64) -> Result<bool, String> {
+        let exists = self
+            .conn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "SELECT id FROM notifications WHERE id = ? AND uid = ?",
+                [id.into(), uid.into()],
+            ))
+            .await
+            .map_err(|e| format!("查询通知失败: {e}"))?
+            .is_some();
+        if exists {
+            self.conn
+                .execute(Statement::from_sql_and_values(
+                    DbBackend::Sqlite,
+                    "UPDATE notifications SET read_at = ? WHERE id = ? AND read_at IS NULL",
+                    [now.into(), id.into()],
+                ))
+                .await
+                .map_err(|e| format!("标记通知已读失败: {e}"))?;
+        }
+        Ok(exists)
+    }
+
+    async fn mark_all_read(&self, uid: i32, now: i64) -> Result<u64, String> {
+        let result = self
+            .conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "UPDATE notifications SET read_at = ? WHERE uid = ? AND read_at IS NULL",
+                [now.into(), uid.into()],
+            ))
+            .await
+            .map_err(|e| format!("标记通知已读失败: {e}"))?;
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,170 @@
+use async_trait::async_trait;
+use sea_orm::{ConnectionTrait, DbBackend, Statement};
+
+use super::Repository;
+use crate::dao::idl::OidcDao;
+use crate::model::oidc::{OidcIdentity, OidcLoginState};
+
+fn identity_from_row(row: &sea_orm::QueryResult) -> Result<OidcIdentity, String> {
+    Ok(OidcIdentity {
+        id: row.try_get("", "id").map_err(|e| e.to_string())?,
+        uid: row.try_get("", "uid").map_err(|e| e.to_string())?,
+        issuer: row.try_get("", "issuer").map_err(|e| e.to_string())?,
+        subject: row.try_get("", "subject").map_err(|e| e.to_string())?,
+        email: row.try_get("", "email").map_err(|e| e.to_string())?,
+        created_at: row.try_get("", "created_at").map_err(|e| e.to_string())?,
+        last_login_at: row
+            .try_get("", "last_login_at")
+            .map_err(|e| e.to_string())?,
+    })
+}
+
+const IDENTITY_COLUMNS: &str = "id, uid, issuer, subject, email, created_at, last_login_at";
+
+#[async_trait]
+impl OidcDao for Repository {
+    async fn create_login_state(&self, state: OidcLoginState) -> Result<(), String> {
+        // 顺带清理过期未使用的状态
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "DELETE FROM oidc_login_states WHERE expires_at <= ?",
+                [state.created_at.into()],
+            ))
+            .await
+            .map_err(|e| format!("清理登录状态失败: {e}"))?;
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "INSERT INTO oidc_login_states (state, nonce, code_verifier, link_uid, created_at, expires_at) VALUES (?, ?, ?, ?, ?, ?)",
+                vec![
+                    state.state.into(),
+                    state.nonce.into(),
+                    state.code_verifier.into(),
+                    state.link_uid.into(),
+                    state.created_at.into(),
+                    state.expires_at.into(),
+                ],
+            ))
+            .await
+            .map_err(|e| format!("保存登录状态失败: {e}"))?;
+        Ok(())
+    }
+
+    async fn take_login_state(
+        &self,
+        state: &str,
+        now: i64,
+    ) -> Result<Option<OidcLoginState>, String> {
+        let row = self
+            .conn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "SELECT state, nonce, code_verifier, link_uid, created_at, expires_at FROM oidc_login_states WHERE state = ?",
+                [state.into()],
+            ))
+            .await
+            .map_err(|e| format!("查询登录状态失败: {e}"))?;
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let found = OidcLoginState {
+            state: row.try_get("", "state").map_err(|e| e.to_string())?,
+            nonce: row.try_get("", "nonce").map_err(|e| e.to_string())?,
+            code_verifier: row
+                .try_get("", "code_verifier")
+                .map_err(|e| e.to_string())?,
+            link_uid: row.try_get("", "link_uid").map_err(|e| e.to_string())?,
+            created_at: row.try_get("", "created_at").map_err(|e| e.to_string())?,
+            expires_at: row.try_get("", "expires_at").map_err(|e| e.to_string())?,
+        };
+        // 以删除成功为准，并发回调时只有一个请求能拿到状态
+        let result = self
+            .conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "DELETE FROM oidc_login_states WHERE state = ?",
+                [state.into()],
+            ))
+            .await
+            .map_err(|e| format!("删除登录状态失败: {e}"))?;
+        Ok((result.rows_affected() > 0 && found.expires_at > now).then_some(found))
+    }
+
+    async fn find_identity(
+        &self,
+        issuer: &str,
+        subject: &str,
+    ) -> Result<Option<OidcIdentity>, String> {
+        let row = self
+            .conn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                format!(
+                    "SELECT {IDENTITY_COLUMNS} FROM oidc_identities WHERE issuer = ? AND subject = ?"
+                ),
+                [issuer.into(), subject.into()],
+            ))
+            .await
+            .map_err(|e| format!("查询外部身份失败: {e}"))?;
+        row.as_ref().map(identity_from_row).transpose()
+    }
+
+    async fn create_identity(&self, identity: OidcIdentity) -> Result<i64, String> {
+        let result = self
+            .conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "INSERT INTO oidc_identities (uid, issuer, subject, email, created_at, last_login_at) VALUES (?, ?, ?, ?, ?, ?)",
+                vec![
+                    identity.uid.into(),
+                    identity.issuer.into(),
+                    identity.subject.into(),
+                    identity.email.into(),
+                    identity.created_at.into(),
+                    identity.last_login_at.into(),
+                ],
+            ))
+            .await
+            .map_err(|e| format!("关联外部身份失败: {e}"))?;
+        Ok(result.last_insert_id() as i64)
+    }
+
+    async fn touch_identity(&self, id: i64, now: i64) -> Result<(), String> {
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "UPDATE oidc_identities SET last_login_at = ? WHERE id = ?",
+                [now.into(), id.into()],
+            ))
+            .await
+            .map_err(|e| format!("更新外部身份失败: {e}"))?;
+        Ok(())
+    }
+
+    async fn list_identities(&self, uid: i32) -> Result<Vec<OidcIdentity>, String> {
+        let rows = self
+            .conn
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                format!("SELECT {IDENTITY_COLUMNS} FROM oidc_identities WHERE uid = ? ORDER BY id"),
+                [uid.into()],
+            ))
+            .await
+            .map_err(|e| format!("查询外部身份失败: {e}"))?;
+        rows.iter().map(identity_from_row).collect()
+    }
+
+    async fn delete_identity(&self, uid: i32, id: i64) -> Result<bool, String> {
+        let result = self
+            .conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "DELETE FROM oidc_identities WHERE id = ? AND uid = ?",
+                [id.into(), uid.into()],
+            ))
+            .await
+            .map_err(|e| format!("解除关联失败: {e}"))?;
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -24,6 +24,7 @@ impl Repository {
             type TEXT NOT NULL,
             start_time INTEGER NOT NULL,
             calories INTEGER NOT NULL,
+            calories_estimated INTEGER NOT NULL DEFAULT 0,
             distance_meter INTEGER NOT NULL,
             duration_second INTEGER NOT NULL,
             heart_rate_avg INTEGER NOT NULL,
@@ -31,6 +32,7 @@ impl Repository {
             pace_average TEXT NOT NULL,
             extra TEXT NOT NULL,
             tracks TEXT NOT NULL,
+            visibility TEXT NOT NULL DEFAULT 'private',
             CHECK (json_valid(extra)),
             CHECK (json_valid(tracks))
         );
@@ -41,7 +43,10 @@ impl Repository {
             name TEXT NOT NULL,
             password TEXT NOT NULL,
             nickname TEXT NOT NULL DEFAULT '',
-            avatar TEXT NOT NULL DEFAULT ''
+            avatar TEXT NOT NULL DEFAULT '',
+            bio TEXT NOT NULL DEFAULT '',
+            role TEXT NOT NULL DEFAULT 'user',
+            disabled_at INTEGER
         );
         CREATE UNIQUE INDEX IF NOT EXISTS idx_users_name ON users(name);
         CREATE TABLE IF NOT EXISTS avatars (
@@ -53,6 +58,15 @@ impl Repository {
             updated_at INTEGER NOT NULL DEFAULT (strftime('%s','now'))
         );
         CREATE UNIQUE INDEX IF NOT EXISTS idx_avatars_uid ON avatars(uid);
+        CREATE TABLE IF NOT EXISTS avatar_images (
+            uid INTEGER NOT NULL,
+            size INTEGER NOT NULL,
+            mime TEXT NOT NULL,
+            data BLOB NOT NULL,
+            etag TEXT NOT NULL,
+            updated_at INTEGER NOT NULL,
+            PRIMARY KEY (uid, size)
+        );
 
         CREATE TABLE IF NOT EXISTS ai_jobs (
             id TEXT PRIMARY KEY,
@@ -86,6 +100,275 @@ impl Repository {
             deleted_at INTEGER
         );
         CREATE INDEX IF NOT EXISTS idx_ai_job_assets_job ON ai_job_assets(job_id, position);
+
+        CREATE TABLE IF NOT EXISTS sessions (
+            id TEXT PRIMARY KEY,
+            uid INTEGER NOT NULL,
+            user_agent TEXT NOT NULL DEFAULT '',
+            device TEXT NOT NULL DEFAULT '',
+            ip TEXT NOT NULL DEFAULT '',
+            created_at INTEGER NOT NULL,
+            last_seen_at INTEGER NOT NULL,
+            expires_at INTEGER NOT NULL,
+            revoked_at INTEGER
+        );
+        CREATE INDEX IF NOT EXISTS idx_sessions_uid ON sessions(uid, last_seen_at DESC);
+
+        CREATE TABLE IF NOT EXISTS personal_access_tokens (
+            id TEXT PRIMARY KEY,
+            uid INTEGER NOT NULL,
+            name TEXT NOT NULL,
+            token_hash TEXT NOT NULL,
+            token_hint TEXT NOT NULL,
+            scopes TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            expires_at INTEGER NOT NULL,
+            last_used_at INTEGER,
+            revoked_at INTEGER
+        );
+        CREATE UNIQUE INDEX IF NOT EXISTS idx_pat_hash ON personal_access_tokens(token_hash);
+        CREATE INDEX IF NOT EXISTS idx_pat_uid ON personal_access_tokens(uid, created_at DESC);
+
+        CREATE TABLE IF NOT EXISTS athlete_profiles (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            uid INTEGER NOT NULL,
+            valid_from INTEGER NOT NULL,
+            birth_date TEXT,
+            sex TEXT,
+            height_cm REAL,
+            resting_heart_rate INTEGER,
+            max_heart_rate INTEGER,
+            unit_system TEXT NOT NULL DEFAULT 'metric',
+            created_at INTEGER NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_athlete_profiles_uid ON athlete_profiles(uid, valid_from DESC);
+
+        CREATE TABLE IF NOT EXISTS weight_logs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            uid INTEGER NOT NULL,
+            measured_at INTEGER NOT NULL,
+            weight_kg REAL NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_weight_logs_uid ON weight_logs(uid, measured_at DESC);
+
+        CREATE TABLE IF NOT EXISTS audit_logs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            uid INTEGER,
+            action TEXT NOT NULL,
+            ip TEXT NOT NULL,
+            user_agent TEXT NOT NULL,
+            detail TEXT NOT NULL,
+            created_at INTEGER NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_audit_logs_uid ON audit_logs(uid, created_at DESC);
+
+        CREATE TABLE IF NOT EXISTS login_attempts (
+            key TEXT PRIMARY KEY,
+            failures INTEGER NOT NULL,
+            last_failure_at INTEGER NOT NULL,
+            locked_until INTEGER NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS password_reset_codes (
+            code_hash TEXT PRIMARY KEY,
+            uid INTEGER NOT NULL,
+            created_by INTEGER NOT NULL,
+            created_at INTEGER NOT NULL,
+            expires_at INTEGER NOT NULL,
+            used_at INTEGER
+        );
+        CREATE INDEX IF NOT EXISTS idx_password_reset_codes_uid ON password_reset_codes(uid);
+
+        CREATE TABLE IF NOT EXISTS totp_secrets (
+            uid INTEGER PRIMARY KEY,
+            secret TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            enabled_at INTEGER,
+            last_used_step INTEGER NOT NULL DEFAULT 0
+        );
+
+        CREATE TABLE IF NOT EXISTS totp_recovery_codes (
+            code_hash TEXT PRIMARY KEY,
+            uid INTEGER NOT NULL,
+            created_at INTEGER NOT NULL,
+            used_at INTEGER
+        );
+        CREATE INDEX IF NOT EXISTS idx_totp_recovery_codes_uid ON totp_recovery_codes(uid);
+
+        CREATE TABLE IF NOT EXISTS login_challenges (
+            token_hash TEXT PRIMARY KEY,
+            uid INTEGER NOT NULL,
+            created_at INTEGER NOT NULL,
+            expires_at INTEGER NOT NULL,
+            attempts INTEGER NOT NULL DEFAULT 0,
+            used_at INTEGER
+        );
+        CREATE INDEX IF NOT EXISTS idx_login_challenges_uid ON login_challenges(uid);
+
+        CREATE TABLE IF NOT EXISTS oidc_identities (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            uid INTEGER NOT NULL,
+            issuer TEXT NOT NULL,
+            subject TEXT NOT NULL,
+            email TEXT NOT NULL DEFAULT '',
+            created_at INTEGER NOT NULL,
+            last_login_at INTEGER,
+            UNIQUE(issuer, subject)
+        );
+        CREATE INDEX IF NOT EXISTS idx_oidc_identities_uid ON oidc_identities(uid);
+
+        CREATE TABLE IF NOT EXISTS follows (
+            follower_uid INTEGER NOT NULL,
+            followee_uid INTEGER NOT NULL,
+            status TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            accepted_at INTEGER,
+            PRIMARY KEY (follower_uid, followee_uid)
+        );
+        CREATE INDEX IF NOT EXISTS idx_follows_followee ON follows(followee_uid, status);
+        CREATE INDEX IF NOT EXISTS idx_sports_uid_start_time ON sports(uid, start_time);
+
+        CREATE TABLE IF NOT EXISTS sport_shares (
+            id TEXT PRIMARY KEY,
+            uid INTEGER NOT NULL,
+            sport_id INTEGER NOT NULL,
+            token_hash TEXT NOT NULL UNIQUE,
+            created_at INTEGER NOT NULL,
+            expires_at INTEGER,
+            revoked_at INTEGER,
+            views INTEGER NOT NULL DEFAULT 0
+        );
+        CREATE INDEX IF NOT EXISTS idx_sport_shares_uid ON sport_shares(uid, sport_id);
+
+        CREATE TABLE IF NOT EXISTS clubs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            description TEXT NOT NULL DEFAULT '',
+            join_code TEXT NOT NULL UNIQUE,
+            created_at INTEGER NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS club_members (
+            club_id INTEGER NOT NULL,
+            uid INTEGER NOT NULL,
+            role TEXT NOT NULL,
+            joined_at INTEGER NOT NULL,
+            PRIMARY KEY (club_id, uid)
+        );
+        CREATE INDEX IF NOT EXISTS idx_club_members_uid ON club_members(uid);
+        CREATE TABLE IF NOT EXISTS club_invites (
+            club_id INTEGER NOT NULL,
+            uid INTEGER NOT NULL,
+            invited_by INTEGER NOT NULL,
+            created_at INTEGER NOT NULL,
+            PRIMARY KEY (club_id, uid)
+        );
+        CREATE INDEX IF NOT EXISTS idx_club_invites_uid ON club_invites(uid);
+        CREATE TABLE IF NOT EXISTS club_challenges (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            club_id INTEGER NOT NULL,
+            name TEXT NOT NULL,
+            metric TEXT NOT NULL,
+            sport_type TEXT,
+            start_time INTEGER NOT NULL,
+            end_time INTEGER NOT NULL,
+            created_by INTEGER NOT NULL,
+            created_at INTEGER NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_club_challenges_club ON club_challenges(club_id, start_time DESC);
+
+        CREATE TABLE IF NOT EXISTS coach_grants (
+            athlete_uid INTEGER NOT NULL,
+            coach_uid INTEGER NOT NULL,
+            scopes TEXT NOT NULL,
+            status TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            accepted_at INTEGER,
+            PRIMARY KEY (athlete_uid, coach_uid)
+        );
+        CREATE INDEX IF NOT EXISTS idx_coach_grants_coach ON coach_grants(coach_uid);
+
+        CREATE TABLE IF NOT EXISTS sport_comments (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            sport_id INTEGER NOT NULL,
+            uid INTEGER NOT NULL,
+            content TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER
+        );
+        CREATE INDEX IF NOT EXISTS idx_sport_comments_sport ON sport_comments(sport_id, created_at);
+
+        CREATE TABLE IF NOT EXISTS sport_reactions (
+            sport_id INTEGER NOT NULL,
+            uid INTEGER NOT NULL,
+            kind TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            PRIMARY KEY (sport_id, uid, kind)
+        );
+
+        CREATE TABLE IF NOT EXISTS notifications (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            uid INTEGER NOT NULL,
+            kind TEXT NOT NULL,
+            actor_uid INTEGER,
+            sport_id INTEGER,
+            detail TEXT NOT NULL DEFAULT '{}',
+            created_at INTEGER NOT NULL,
+            read_at INTEGER
+        );
+        CREATE INDEX IF NOT EXISTS idx_notifications_uid ON notifications(uid, id DESC);
+
+        CREATE TABLE IF NOT EXISTS webhooks (
+            id TEXT PRIMARY KEY,
+            uid INTEGER NOT NULL,
+            url TEXT NOT NULL,
+            secret TEXT NOT NULL,
+            events TEXT NOT NULL,
+            all_users INTEGER NOT NULL DEFAULT 0,
+            created_at INTEGER NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_webhooks_uid ON webhooks(uid);
+        CREATE TABLE IF NOT EXISTS webhook_deliveries (
+            id TEXT PRIMARY KEY,
+            webhook_id TEXT NOT NULL,
+            uid INTEGER NOT NULL,
+            subject_uid INTEGER NOT NULL,
+            event TEXT NOT NULL,
+            payload TEXT NOT NULL,
+            status TEXT NOT NULL,
+            attempts INTEGER NOT NULL DEFAULT 0,
+            next_attempt_at INTEGER,
+            lease_until INTEGER,
+            response_status INTEGER,
+            last_error TEXT,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL,
+            delivered_at INTEGER
+        );
+        CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_webhook ON webhook_deliveries(webhook_id, created_at DESC);
+        CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_queue ON webhook_deliveries(status, next_attempt_at, created_at);
+
+        CREATE TABLE IF NOT EXISTS ai_job_events (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            uid INTEGER NOT NULL,
+            job_id TEXT NOT NULL,
+            status TEXT NOT NULL,
+            attempts INTEGER NOT NULL DEFAULT 0,
+            next_attempt_at INTEGER,
+            error_code TEXT,
+            error_message TEXT,
+            created_at INTEGER NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_ai_job_events_uid ON ai_job_events(uid, id);
+        CREATE INDEX IF NOT EXISTS idx_ai_job_events_created ON ai_job_events(created_at);
+
+        CREATE TABLE IF NOT EXISTS oidc_login_states (
+            state TEXT PRIMARY KEY,
+            nonce TEXT NOT NULL,
+            code_verifier TEXT NOT NULL,
+            link_uid INTEGER,
+            created_at INTEGER NOT NULL,
+            expires_at INTEGER NOT NULL
+        );
         "#;
         self.exec_batch(create_sql).await?;
         // 兼容历史列添加
@@ -95,6 +378,46 @@ impl Repository {
         let _ = self
             .exec_batch("ALTER TABLE users ADD COLUMN avatar TEXT NOT NULL DEFAULT '';\n")
             .await;
+        let _ = self
+            .exec_batch(
+                "ALTER TABLE sports ADD COLUMN calories_estimated INTEGER NOT NULL DEFAULT 0;\n",
+            )
+            .await;
+        let _ = self
+            .exec_batch("ALTER TABLE users ADD COLUMN bio TEXT NOT NULL DEFAULT '';\n")
+            .await;
+        let _ = self
+            .exec_batch("ALTER TABLE users ADD COLUMN role TEXT NOT NULL DEFAULT 'user';\n")
+            .await;
+        let _ = self
+            .exec_batch("ALTER TABLE users ADD COLUMN disabled_at INTEGER;\n")
+            .await;
+        let _ = self
+            .exec_batch(
+                "ALTER TABLE sports ADD COLUMN visibility TEXT NOT NULL DEFAULT 'private';\n",
+            )
+            .await;
+        let _ = self
+            .exec_batch(
+                "ALTER TABLE users ADD COLUMN private_account INTEGER NOT NULL DEFAULT 0;\n",
+            )
+            .await;
+        // 审计日志只允许追加；触发器体内含分号，不能走 exec_batch
+        for (event, name) in [
+            ("UPDATE", "audit_logs_no_update"),
+            ("DELETE", "audit_logs_no_delete"),
+        ] {
+            self.conn
+                .execute(Statement::from_string(
+                    DbBackend::Sqlite,
+                    format!(
+                        "CREATE TRIGGER IF NOT EXISTS {name} BEFORE {event} ON audit_logs \
+                         BEGIN SELECT RAISE(ABORT, 'audit_logs is append-only'); END"
+                    ),
+                ))
+                .await
+                .map_err(|e| format!("执行SQL失败: {}", e))?;
+        }
         Ok(())
     }
 
@@ -0,0 +1,123 @@
+use async_trait::async_trait;
+use sea_orm::{ConnectionTrait, DbBackend, Statement};
+
+use super::Repository;
+use crate::dao::idl::SessionDao;
+use crate::model::session::Session;
+
+fn session_from_row(row: &sea_orm::QueryResult) -> Result<Session, String> {
+    Ok(Session {
+        id: row.try_get("", "id").map_err(|e| e.to_string())?,
+        uid: row.try_get("", "uid").map_err(|e| e.to_string())?,
+        user_agent: row.try_get("", "user_agent").map_err(|e| e.to_string())?,
+        device: row.try_get("", "device").map_err(|e| e.to_string())?,
+        ip: row.try_get("", "ip").map_err(|e| e.to_string())?,
+        created_at: row.try_get("", "created_at").map_err(|e| e.to_string())?,
+        last_seen_at: row.try_get("", "last_seen_at").map_err(|e| e.to_string())?,
+        expires_at: row.try_get("", "expires_at").map_err(|e| e.to_string())?,
+        revoked_at: row.try_get("", "revoked_at").map_err(|e| e.to_string())?,
+    })
+}
+
+const SESSION_COLUMNS: &str =
+    "id, uid, user_agent, device, ip, created_at, last_seen_at, expires_at, revoked_at";
+
+#[async_trait]
+impl SessionDao for Repository {
+    async fn create_session(&self, session: Session) -> Result<(), String> {
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "INSERT INTO sessions (id, uid, user_agent, device, ip, created_at, last_seen_at, expires_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+                vec![
+                    session.id.into(),
+                    session.uid.into(),
+                    session.user_agent.into(),
+                    session.device.into(),
+                    session.ip.into(),
+                    session.created_at.into(),
+                    session.last_seen_at.into(),
+                    session.expires_at.into(),
+                ],
+            ))
+            .await
+            .map_err(|e| format!("创建会话失败: {e}"))?;
+        Ok(())
+    }
+
+    async fn get_session(&self, id: &str) -> Result<Option<Session>, String> {
+        let row = self
+            .conn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                format!("SELECT {SESSION_COLUMNS} FROM sessions WHERE id = ?"),
+                vec![id.into()],
+            ))
+            .await
+            .map_err(|e| format!("查询会话失败: {e}"))?;
+        row.as_ref().map(session_from_row).transpose()
+    }
+
+    async fn list_active_sessions(&self, uid: i32, now: i64) -> Result<Vec<Session>, String> {
+        let rows = self
+            .conn
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                format!(
+                    "SELECT {SESSION_COLUMNS} FROM sessions WHERE uid = ? AND revoked_at IS NULL AND expires_at > ? ORDER BY last_seen_at DESC"
+                ),
+                vec![uid.into(), now.into()],
+            ))
+            .await
+            .map_err(|e| format!("查询会话失败: {e}"))?;
+        rows.iter().map(session_from_row).collect()
+    }
+
+    async fn touch_session(
+        &self,
+        id: &str,
+        last_seen_at: i64,
+        expires_at: i64,
+    ) -> Result<(), String> {
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "UPDATE sessions SET last_seen_at = ?, expires_at = MAX(expires_at, ?) WHERE id = ? AND revoked_at IS NULL",
+                vec![last_seen_at.into(), expires_at.into(), id.into()],
+            ))
+            .await
+            .map_err(|e| format!("更新会话失败: {e}"))?;
+        Ok(())
+    }
+
+    async fn revoke_session(&self, uid: i32, id: &str, now: i64) -> Result<bool, String> {
+        let result = self
+            .conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "UPDATE sessions SET revoked_at = ? WHERE uid = ? AND id = ? AND revoked_at IS NULL",
+                vec![now.into(), uid.into(), id.into()],
+            ))
+            .await
+            .map_err(|e| format!("注销会话失败: {e}"))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn revoke_all_sessions(
+        &self,
+        uid: i32,
+        except: Option<&str>,
+        now: i64,
+    ) -> Result<u64, String> {
+        let result = self
+            .conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "UPDATE sessions SET revoked_at = ? WHERE uid = ? AND id <> ? AND revoked_at IS NULL",
+                vec![now.into(), uid.into(), except.unwrap_or("").into()],
+            ))
+            .await
+            .map_err(|e| format!("注销会话失败: {e}"))?;
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,112 @@
+use async_trait::async_trait;
+use sea_orm::{ConnectionTrait, DbBackend, Statement, Value};
+
+use super::Repository;
+use crate::dao::idl::ShareDao;
+use crate::model::share::SportShare;
+
+const SHARE_COLUMNS: &str =
+    "id, uid, sport_id, token_hash, created_at, expires_at, revoked_at, views";
+
+fn share_from_row(row: &sea_orm::QueryResult) -> Result<SportShare, String> {
+    Ok(SportShare {
+        id: row.try_get("", "id").map_err(|e| e.to_string())?,
+        uid: row.try_get("", "uid").map_err(|e| e.to_string())?,
+        sport_id: row.try_get("", "sport_id").map_err(|e| e.to_string())?,
+        token_hash: row.try_get("", "token_hash").map_err(|e| e.to_string())?,
+        created_at: row.try_get("", "created_at").map_err(|e| e.to_string())?,
+        expires_at: row.try_get("", "expires_at").map_err(|e| e.to_string())?,
+        revoked_at: row.try_get("", "revoked_at").map_err(|e| e.to_string())?,
+        views: row.try_get("", "views").map_err(|e| e.to_string())?,
+    })
+}
+
+#[async_trait]
+impl ShareDao for Repository {
+    async fn create_share(&self, share: SportShare) -> Result<(), String> {
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "INSERT INTO sport_shares (id, uid, sport_id, token_hash, created_at, expires_at) \
+                 VALUES (?, ?, ?, ?, ?, ?)",
+                [
+                    share.id.into(),
+                    share.uid.into(),
+                    share.sport_id.into(),
+                    share.token_hash.into(),
+                    share.created_at.into(),
+                    share.expires_at.into(),
+                ],
+            ))
+            .await
+            .map_err(|e| format!("创建分享链接失败: {e}"))?;
+        Ok(())
+    }
+
+    async fn list_shares(
+        &self,
+        uid: i32,
+        sport_id: Option<i32>,
+    ) -> Result<Vec<SportShare>, String> {
+        let mut values: Vec<Value> = vec![uid.into()];
+        let filter = match sport_id {
+            Some(id) => {
+                values.push(id.into());
+                " AND sport_id = ?"
+            }
+            None => "",
+        };
+        let rows = self
+            .conn
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                format!(
+                    "SELECT {SHARE_COLUMNS} FROM sport_shares WHERE uid = ?{filter} \
+                     ORDER BY created_at DESC"
+                ),
+                values,
+            ))
+            .await
+            .map_err(|e| format!("查询分享链接失败: {e}"))?;
+        rows.iter().map(share_from_row).collect()
+    }
+
+    async fn get_share_by_hash(&self, token_hash: &str) -> Result<Option<SportShare>, String> {
+        let row = self
+            .conn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                format!("SELECT {SHARE_COLUMNS} FROM sport_shares WHERE token_hash = ?"),
+                [token_hash.into()],
+            ))
+            .await
+            .map_err(|e| format!("查询分享链接失败: {e}"))?;
+        row.as_ref().map(share_from_row).transpose()
+    }
+
+    async fn revoke_share(&self, uid: i32, id: &str, now: i64) -> Result<bool, String> {
+        let result = self
+            .conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "UPDATE sport_shares SET revoked_at = ? \
+                 WHERE uid = ? AND id = ? AND revoked_at IS NULL",
+                [now.into(), uid.into(), id.into()],
+            ))
+            .await
+            .map_err(|e| format!("撤销分享链接失败: {e}"))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn increment_share_views(&self, id: &str) -> Result<(), String> {
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "UPDATE sport_shares SET views = views + 1 WHERE id = ?",
+                [id.into()],
+            ))
+            .await
+            .map_err(|e| format!("更新浏览次数失败: {e}"))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,321 @@
+use async_trait::async_trait;
+use sea_orm::{ConnectionTrait, DbBackend, FromQueryResult, Statement, Value};
+
+use super::Repository;
+use super::sport::sport_from_model;
+use crate::dao::entities;
+use crate::dao::idl::SocialDao;
+use crate::model::social::{
+    FOLLOW_ACCEPTED, FOLLOW_PENDING, FeedCursor, FeedRow, Follow, FollowRow,
+};
+use crate::model::sport::SportVisibility;
+
+/// 用户头像版本号的子查询，取默认尺寸图片的 ETag
+pub(super) const AVATAR_VERSION: &str =
+    "(SELECT etag FROM avatar_images a WHERE a.uid = u.id ORDER BY a.size DESC LIMIT 1)";
+
+fn follow_row(row: &sea_orm::QueryResult) -> Result<FollowRow, String> {
+    Ok(FollowRow {
+        uid: row.try_get("", "uid").map_err(|e| e.to_string())?,
+        nickname: row.try_get("", "nickname").map_err(|e| e.to_string())?,
+        avatar_version: row
+            .try_get("", "avatar_version")
+            .map_err(|e| e.to_string())?,
+        status: row.try_get("", "status").map_err(|e| e.to_string())?,
+        created_at: row.try_get("", "created_at").map_err(|e| e.to_string())?,
+    })
+}
+
+fn feed_row(row: &sea_orm::QueryResult) -> Result<FeedRow, String> {
+    let model = entities::Model::from_query_result(row, "").map_err(|e| e.to_string())?;
+    Ok(FeedRow {
+        uid: model.uid,
+        nickname: row
+            .try_get("", "author_nickname")
+            .map_err(|e| e.to_string())?,
+        avatar_version: row
+            .try_get("", "author_avatar_version")
+            .map_err(|e| e.to_string())?,
+        sport: sport_from_model(model),
+    })
+}
+
+/// 游标之后（更早）的记录条件
+fn before_clause(before: Option<FeedCursor>, values: &mut Vec<Value>) -> &'static str {
+    match before {
+        Some(c) => {
+            values.extend([c.start_time.into(), c.start_time.into(), c.id.into()]);
+            " AND (s.start_time < ? OR (s.start_time = ? AND s.id < ?))"
+        }
+        None => "",
+    }
+}
+
+#[async_trait]
+impl SocialDao for Repository {
+    async fn get_follow(
+        &self,
+        follower_uid: i32,
+        followee_uid: i32,
+    ) -> Result<Option<Follow>, String> {
+        let row = self
+            .conn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "SELECT status, created_at, accepted_at FROM follows \
+                 WHERE follower_uid = ? AND followee_uid = ?",
+                [follower_uid.into(), followee_uid.into()],
+            ))
+            .await
+            .map_err(|e| format!("查询关注关系失败: {e}"))?;
+        row.map(|row| {
+            Ok(Follow {
+                follower_uid,
+                followee_uid,
+                status: row.try_get("", "status").map_err(|e| e.to_string())?,
+                created_at: row.try_get("", "created_at").map_err(|e| e.to_string())?,
+                accepted_at: row.try_get("", "accepted_at").map_err(|e| e.to_string())?,
+            })
+        })
+        .transpose()
+    }
+
+    async fn create_follow(&self, follow: Follow) -> Result<(), String> {
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "INSERT OR IGNORE INTO follows (follower_uid, followee_uid, status, created_at, accepted_at) \
+                 VALUES (?, ?, ?, ?, ?)",
+                [
+                    follow.follower_uid.into(),
+                    follow.followee_uid.into(),
+                    follow.status.into(),
+                    follow.created_at.into(),
+                    follow.accepted_at.into(),
+                ],
+            ))
+            .await
+            .map_err(|e| format!("关注失败: {e}"))?;
+        Ok(())
+    }
+
+    async fn delete_follow(&self, follower_uid: i32, followee_uid: i32) -> Result<bool, String> {
+        let result = self
+            .conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "DELETE FROM follows WHERE follower_uid = ? AND followee_uid = ?",
+                [follower_uid.into(), followee_uid.into()],
+            ))
+            .await
+            .map_err(|e| format!("取消关注失败: {e}"))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn accept_follow(
+        &self,
+        follower_uid: i32,
+        followee_uid: i32,
+        now: i64,
+    ) -> Result<bool, String> {
+        let result = self
+            .conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "UPDATE follows SET status = ?, accepted_at = ? \
+                 WHERE follower_uid = ? AND followee_uid = ? AND status = ?",
+                [
+                    FOLLOW_ACCEPTED.into(),
+                    now.into(),
+                    follower_uid.into(),
+                    followee_uid.into(),
+                    FOLLOW_PENDING.into(),
+                ],
+            ))
+            .await
+            .map_err(|e| format!("批准关注申请失败: {e}"))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn accept_all_follows(&self, followee_uid: i32, now: i64) -> Result<u64, String> {
+        let result = self
+            .conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "UPDATE follows SET status = ?, accepted_at = ? WHERE followee_uid = ? AND status = ?",
+                [
+                    FOLLOW_ACCEPTED.into(),
+                    now.into(),
+                    followee_uid.into(),
+                    FOLLOW_PENDING.into(),
+                ],
+            ))
+            .await
+            .map_err(|e| format!("批准关注申请失败: {e}"))?;
+        Ok(result.rows_affected())
+    }
+
+    async fn list_following(&self, uid: i32) -> Result<Vec<FollowRow>, String> {
+        let rows = self
+            .conn
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                format!(
+                    "SELECT u.id AS uid, u.nickname, {AVATAR_VERSION} AS avatar_version, f.status, f.created_at \
+                     FROM follows f JOIN users u ON u.id = f.followee_uid \
+                     WHERE f.follower_uid = ? ORDER BY f.created_at DESC"
+                ),
+                [uid.into()],
+            ))
+            .await
+            .map_err(|e| format!("查询关注列表失败: {e}"))?;
+        rows.iter().map(follow_row).collect()
+    }
+
+    async fn list_followers(
+        &self,
+        uid: i32,
+        status: Option<&str>,
+    ) -> Result<Vec<FollowRow>, String> {
+        let mut values: Vec<Value> = vec![uid.into()];
+        let filter = match status {
+            Some(s) => {
+                values.push(s.into());
+                " AND f.status = ?"
+            }
+            None => "",
+        };
+        let rows = self
+            .conn
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                format!(
+                    "SELECT u.id AS uid, u.nickname, {AVATAR_VERSION} AS avatar_version, f.status, f.created_at \
+                     FROM follows f JOIN users u ON u.id = f.follower_uid \
+                     WHERE f.followee_uid = ?{filter} ORDER BY f.created_at DESC"
+                ),
+                values,
+            ))
+            .await
+            .map_err(|e| format!("查询粉丝列表失败: {e}"))?;
+        rows.iter().map(follow_row).collect()
+    }
+
+    async fn count_follows(&self, uid: i32) -> Result<(i64, i64), String> {
+        let row = self
+            .conn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "SELECT \
+                 (SELECT COUNT(*) FROM follows WHERE followee_uid = ? AND status = ?) AS followers, \
+                 (SELECT COUNT(*) FROM follows WHERE follower_uid = ? AND status = ?) AS following",
+                [
+                    uid.into(),
+                    FOLLOW_ACCEPTED.into(),
+                    uid.into(),
+                    FOLLOW_ACCEPTED.into(),
+                ],
+            ))
+            .await
+            .map_err(|e| format!("查询关注数失败: {e}"))?
+            .ok_or("查询关注数失败")?;
+        Ok((
+            row.try_get("", "followers").map_err(|e| e.to_string())?,
+            row.try_get("", "following").map_err(|e| e.to_string())?,
+        ))
+    }
+
+    async fn get_private_account(&self, uid: i32) -> Result<Option<bool>, String> {
+        let row = self
+            .conn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "SELECT private_account FROM users WHERE id = ?",
+                [uid.into()],
+            ))
+            .await
+            .map_err(|e| format!("查询用户失败: {e}"))?;
+        row.map(|row| {
+            row.try_get("", "private_account")
+                .map_err(|e| e.to_string())
+        })
+        .transpose()
+    }
+
+    async fn set_private_account(&self, uid: i32, private_account: bool) -> Result<(), String> {
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "UPDATE users SET private_account = ? WHERE id = ?",
+                [private_account.into(), uid.into()],
+            ))
+            .await
+            .map_err(|e| format!("更新隐私设置失败: {e}"))?;
+        Ok(())
+    }
+
+    async fn list_feed(
+        &self,
+        viewer_uid: i32,
+        before: Option<FeedCursor>,
+        limit: i32,
+    ) -> Result<Vec<FeedRow>, String> {
+        let mut values: Vec<Value> = vec![
+            viewer_uid.into(),
+            FOLLOW_ACCEPTED.into(),
+            SportVisibility::Followers.as_str().into(),
+            SportVisibility::Public.as_str().into(),
+        ];
+        let before = before_clause(before, &mut values);
+        values.push(limit.into());
+        let rows = self
+            .conn
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                format!(
+                    "SELECT s.*, u.nickname AS author_nickname, {AVATAR_VERSION} AS author_avatar_version \
+                     FROM sports s \
+                     JOIN follows f ON f.followee_uid = s.uid AND f.follower_uid = ? AND f.status = ? \
+                     JOIN users u ON u.id = s.uid \
+                     WHERE s.visibility IN (?, ?){before} \
+                     ORDER BY s.start_time DESC, s.id DESC LIMIT ?"
+                ),
+                values,
+            ))
+            .await
+            .map_err(|e| format!("查询动态失败: {e}"))?;
+        rows.iter().map(feed_row).collect()
+    }
+
+    async fn list_user_sports(
+        &self,
+        owner_uid: i32,
+        levels: &[SportVisibility],
+        before: Option<FeedCursor>,
+        limit: i32,
+    ) -> Result<Vec<FeedRow>, String> {
+        if levels.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut values: Vec<Value> = vec![owner_uid.into()];
+        values.extend(levels.iter().map(|l| l.as_str().into()));
+        let placeholders = vec!["?"; levels.len()].join(", ");
+        let before = before_clause(before, &mut values);
+        values.push(limit.into());
+        let rows = self
+            .conn
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                format!(
+                    "SELECT s.*, u.nickname AS author_nickname, {AVATAR_VERSION} AS author_avatar_version \
+                     FROM sports s JOIN users u ON u.id = s.uid \
+                     WHERE s.uid = ? AND s.visibility IN ({placeholders}){before} \
+                     ORDER BY s.start_time DESC, s.id DESC LIMIT ?"
+                ),
+                values,
+            ))
+            .await
+            .map_err(|e| format!("查询运动记录失败: {e}"))?;
+        rows.iter().map(feed_row).collect()
+    }
+}
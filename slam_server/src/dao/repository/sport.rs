@@ -4,7 +4,7 @@ use crate::dao::entities::{self};
 use crate::dao::entities::{DbSportExtra, DbSportTrack};
 use crate::dao::idl::SportDao;
 use crate::model::ai_job::{AiJobSubmission, JOB_READY, JOB_SUBMITTED};
-use crate::model::sport::{Sport, SportExtra, SportType, Track};
+use crate::model::sport::{Sport, SportExtra, SportType, SportVisibility, Track};
 use async_trait::async_trait;
 use sea_orm::{
     ActiveModelTrait, ColumnTrait, ConnectionTrait, DbBackend, EntityTrait, PaginatorTrait,
@@ -13,7 +13,7 @@ use sea_orm::{
 
 #[async_trait]
 impl SportDao for Repository {
-    async fn insert(&self, uid: i32, sport: Sport) -> Result<(), String> {
+    async fn insert(&self, uid: i32, sport: Sport) -> Result<i32, String> {
         let extra_tagged = sport.extra.clone().map(DbSportExtra::from);
         let extra_json =
             serde_json::to_string(&extra_tagged).map_err(|e| format!("extra 序列化失败: {}", e))?;
@@ -31,6 +31,7 @@ impl SportDao for Repository {
         am.type_ = Set(sport.r#type.as_str().to_string());
         am.start_time = Set(sport.start_time);
         am.calories = Set(sport.calories);
+        am.calories_estimated = Set(sport.calories_estimated);
         am.distance_meter = Set(sport.distance_meter);
         am.duration_second = Set(sport.duration_second);
         am.heart_rate_avg = Set(sport.heart_rate_avg);
@@ -38,14 +39,16 @@ impl SportDao for Repository {
         am.pace_average = Set(sport.pace_average);
         am.extra = Set(extra_json);
         am.tracks = Set(tracks_json);
-        am.insert(&self.conn)
+        am.visibility = Set(sport.visibility.as_str().to_string());
+        let model = am
+            .insert(&self.conn)
             .await
             .map_err(|e| format!("插入失败: {}", e))?;
-        Ok(())
+        Ok(model.id)
     }
 
-    async fn insert_many(&self, uid: i32, sports: Vec<Sport>) -> Result<usize, String> {
-        let mut count = 0usize;
+    async fn insert_many(&self, uid: i32, sports: Vec<Sport>) -> Result<Vec<i32>, String> {
+        let mut ids = Vec::new();
         self.conn
             .transaction(|txn| {
                 Box::pin(async move {
@@ -66,6 +69,7 @@ impl SportDao for Repository {
                         am.type_ = Set(sport.r#type.as_str().to_string());
                         am.start_time = Set(sport.start_time);
                         am.calories = Set(sport.calories);
+                        am.calories_estimated = Set(sport.calories_estimated);
                         am.distance_meter = Set(sport.distance_meter);
                         am.duration_second = Set(sport.duration_second);
                         am.heart_rate_avg = Set(sport.heart_rate_avg);
@@ -73,10 +77,10 @@ impl SportDao for Repository {
                         am.pace_average = Set(sport.pace_average);
                         am.extra = Set(extra_json);
                         am.tracks = Set(tracks_json);
-                        am.insert(txn).await.map_err(|e| e)?;
-                        count += 1;
+                        am.visibility = Set(sport.visibility.as_str().to_string());
+                        ids.push(am.insert(txn).await?.id);
                     }
-                    Ok::<_, sea_orm::DbErr>(count)
+                    Ok::<_, sea_orm::DbErr>(ids)
                 })
             })
             .await
@@ -93,26 +97,7 @@ impl SportDao for Repository {
             .fetch_page(safe_page as u64)
             .await
             .map_err(|e| format!("查询失败: {}", e))?;
-        let result = models
-            .into_iter()
-            .map(|m| {
-                let extra: Option<SportExtra> = parse_extra_compat(&m.extra);
-                let tracks: Vec<Track> = parse_tracks_compat(&m.tracks);
-                Sport {
-                    id: m.id,
-                    r#type: SportType::from_str(&m.type_),
-                    start_time: m.start_time,
-                    calories: m.calories,
-                    distance_meter: m.distance_meter,
-                    duration_second: m.duration_second,
-                    heart_rate_avg: m.heart_rate_avg,
-                    heart_rate_max: m.heart_rate_max,
-                    pace_average: m.pace_average,
-                    extra,
-                    tracks,
-                }
-            })
-            .collect();
+        let result = models.into_iter().map(sport_from_model).collect();
         Ok(result)
     }
 
@@ -130,26 +115,7 @@ impl SportDao for Repository {
             .all(&self.conn)
             .await
             .map_err(|e| format!("查询失败: {}", e))?;
-        let result = models
-            .into_iter()
-            .map(|m| {
-                let extra: Option<SportExtra> = parse_extra_compat(&m.extra);
-                let tracks: Vec<Track> = parse_tracks_compat(&m.tracks);
-                Sport {
-                    id: m.id,
-                    r#type: SportType::from_str(&m.type_),
-                    start_time: m.start_time,
-                    calories: m.calories,
-                    distance_meter: m.distance_meter,
-                    duration_second: m.duration_second,
-                    heart_rate_avg: m.heart_rate_avg,
-                    heart_rate_max: m.heart_rate_max,
-                    pace_average: m.pace_average,
-                    extra,
-                    tracks,
-                }
-            })
-            .collect();
+        let result = models.into_iter().map(sport_from_model).collect();
         Ok(result)
     }
 
@@ -182,6 +148,7 @@ impl SportDao for Repository {
         am.type_ = Set(sport.r#type.as_str().to_string());
         am.start_time = Set(sport.start_time);
         am.calories = Set(sport.calories);
+        am.calories_estimated = Set(sport.calories_estimated);
         am.distance_meter = Set(sport.distance_meter);
         am.duration_second = Set(sport.duration_second);
         am.heart_rate_avg = Set(sport.heart_rate_avg);
@@ -189,6 +156,7 @@ impl SportDao for Repository {
         am.pace_average = Set(sport.pace_average);
         am.extra = Set(extra_json);
         am.tracks = Set(tracks_json);
+        am.visibility = Set(sport.visibility.as_str().to_string());
         am.update(&self.conn)
             .await
             .map_err(|e| format!("更新失败: {}", e))?;
@@ -199,13 +167,39 @@ impl SportDao for Repository {
         if id <= 0 {
             return Err("invalid sport id".to_string());
         }
-        let res = entities::Entity::delete_many()
-            .filter(entities::Column::Id.eq(id))
-            .filter(entities::Column::Uid.eq(uid))
-            .exec(&self.conn)
+        // 分享链接、评论和回应与记录本身在同一事务中删除，避免留下孤立数据
+        let deleted = self
+            .conn
+            .transaction(|txn| {
+                Box::pin(async move {
+                    let res = entities::Entity::delete_many()
+                        .filter(entities::Column::Id.eq(id))
+                        .filter(entities::Column::Uid.eq(uid))
+                        .exec(txn)
+                        .await?;
+                    if res.rows_affected == 0 {
+                        return Ok(false);
+                    }
+                    txn.execute(Statement::from_sql_and_values(
+                        DbBackend::Sqlite,
+                        "DELETE FROM sport_shares WHERE uid = ? AND sport_id = ?",
+                        [uid.into(), id.into()],
+                    ))
+                    .await?;
+                    for table in ["sport_comments", "sport_reactions"] {
+                        txn.execute(Statement::from_sql_and_values(
+                            DbBackend::Sqlite,
+                            format!("DELETE FROM {table} WHERE sport_id = ?"),
+                            [id.into()],
+                        ))
+                        .await?;
+                    }
+                    Ok::<_, sea_orm::DbErr>(true)
+                })
+            })
             .await
-            .map_err(|e| format!("删除失败: {}", e))?;
-        if res.rows_affected == 0 {
+            .map_err(|e| format!("删除失败: {e}"))?;
+        if !deleted {
             return Err("记录不存在或无权限".to_string());
         }
         Ok(())
@@ -221,23 +215,7 @@ impl SportDao for Repository {
             .one(&self.conn)
             .await
             .map_err(|e| format!("查询失败: {}", e))?;
-        Ok(model.map(|m| {
-            let extra: Option<SportExtra> = parse_extra_compat(&m.extra);
-            let tracks: Vec<Track> = parse_tracks_compat(&m.tracks);
-            Sport {
-                id: m.id,
-                r#type: SportType::from_str(&m.type_),
-                start_time: m.start_time,
-                calories: m.calories,
-                distance_meter: m.distance_meter,
-                duration_second: m.duration_second,
-                heart_rate_avg: m.heart_rate_avg,
-                heart_rate_max: m.heart_rate_max,
-                pace_average: m.pace_average,
-                extra,
-                tracks,
-            }
-        }))
+        Ok(model.map(sport_from_model))
     }
 
     async fn get_first(&self, uid: i32) -> Result<Option<Sport>, String> {
@@ -247,23 +225,7 @@ impl SportDao for Repository {
             .one(&self.conn)
             .await
             .map_err(|e| format!("查询失败: {}", e))?;
-        Ok(model.map(|m| {
-            let extra: Option<SportExtra> = parse_extra_compat(&m.extra);
-            let tracks: Vec<Track> = parse_tracks_compat(&m.tracks);
-            Sport {
-                id: m.id,
-                r#type: SportType::from_str(&m.type_),
-                start_time: m.start_time,
-                calories: m.calories,
-                distance_meter: m.distance_meter,
-                duration_second: m.duration_second,
-                heart_rate_avg: m.heart_rate_avg,
-                heart_rate_max: m.heart_rate_max,
-                pace_average: m.pace_average,
-                extra,
-                tracks,
-            }
-        }))
+        Ok(model.map(sport_from_model))
     }
 
     async fn insert_from_ai_job(
@@ -318,6 +280,7 @@ impl SportDao for Repository {
                     am.type_ = Set(sport.r#type.as_str().to_string());
                     am.start_time = Set(sport.start_time);
                     am.calories = Set(sport.calories);
+                    am.calories_estimated = Set(sport.calories_estimated);
                     am.distance_meter = Set(sport.distance_meter);
                     am.duration_second = Set(sport.duration_second);
                     am.heart_rate_avg = Set(sport.heart_rate_avg);
@@ -325,6 +288,7 @@ impl SportDao for Repository {
                     am.pace_average = Set(sport.pace_average);
                     am.extra = Set(extra_json);
                     am.tracks = Set(tracks_json);
+                    am.visibility = Set(sport.visibility.as_str().to_string());
                     let inserted = am.insert(txn).await?;
                     let now = chrono::Utc::now().timestamp();
                     let updated = txn
@@ -369,3 +333,23 @@ impl SportDao for Repository {
             .map_err(|e| format!("提交AI识别结果失败: {e}"))
     }
 }
+
+pub(super) fn sport_from_model(m: entities::Model) -> Sport {
+    let extra: Option<SportExtra> = parse_extra_compat(&m.extra);
+    let tracks: Vec<Track> = parse_tracks_compat(&m.tracks);
+    Sport {
+        id: m.id,
+        r#type: SportType::from_str(&m.type_),
+        start_time: m.start_time,
+        calories: m.calories,
+        calories_estimated: m.calories_estimated,
+        distance_meter: m.distance_meter,
+        duration_second: m.duration_second,
+        heart_rate_avg: m.heart_rate_avg,
+        heart_rate_max: m.heart_rate_max,
+        pace_average: m.pace_average,
+        extra,
+        tracks,
+        visibility: SportVisibility::parse(&m.visibility),
+    }
+}
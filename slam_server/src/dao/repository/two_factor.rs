@@ -0,0 +1,240 @@
+use async_trait::async_trait;
+use sea_orm::{
+    ConnectionTrait, DatabaseTransaction, DbBackend, DbErr, Statement, TransactionTrait,
+};
+
+use super::Repository;
+use crate::dao::idl::TwoFactorDao;
+use crate::model::two_factor::{LoginChallenge, TotpSecret};
+
+/// 在事务内删除旧恢复码并写入新的一组
+async fn write_recovery_codes(
+    txn: &DatabaseTransaction,
+    uid: i32,
+    recovery_hashes: &[String],
+    now: i64,
+) -> Result<(), DbErr> {
+    txn.execute(Statement::from_sql_and_values(
+        DbBackend::Sqlite,
+        "DELETE FROM totp_recovery_codes WHERE uid = ?",
+        [uid.into()],
+    ))
+    .await?;
+    for hash in recovery_hashes {
+        txn.execute(Statement::from_sql_and_values(
+            DbBackend::Sqlite,
+            "INSERT INTO totp_recovery_codes (code_hash, uid, created_at) VALUES (?, ?, ?)",
+            [hash.as_str().into(), uid.into(), now.into()],
+        ))
+        .await?;
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl TwoFactorDao for Repository {
+    async fn get_totp(&self, uid: i32) -> Result<Option<TotpSecret>, String> {
+        let row = self
+            .conn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "SELECT uid, secret, created_at, enabled_at, last_used_step FROM totp_secrets WHERE uid = ?",
+                [uid.into()],
+            ))
+            .await
+            .map_err(|e| format!("查询两步验证失败: {e}"))?;
+        row.map(|row| {
+            Ok(TotpSecret {
+                uid: row.try_get("", "uid").map_err(|e| e.to_string())?,
+                secret: row.try_get("", "secret").map_err(|e| e.to_string())?,
+                created_at: row.try_get("", "created_at").map_err(|e| e.to_string())?,
+                enabled_at: row.try_get("", "enabled_at").map_err(|e| e.to_string())?,
+                last_used_step: row
+                    .try_get("", "last_used_step")
+                    .map_err(|e| e.to_string())?,
+            })
+        })
+        .transpose()
+    }
+
+    async fn save_pending_totp(&self, uid: i32, secret: &str, now: i64) -> Result<bool, String> {
+        let result = self
+            .conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "INSERT INTO totp_secrets (uid, secret, created_at) VALUES (?, ?, ?) \
+                 ON CONFLICT(uid) DO UPDATE SET secret = excluded.secret, created_at = excluded.created_at, last_used_step = 0 \
+                 WHERE totp_secrets.enabled_at IS NULL",
+                [uid.into(), secret.into(), now.into()],
+            ))
+            .await
+            .map_err(|e| format!("保存两步验证密钥失败: {e}"))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn enable_totp(
+        &self,
+        uid: i32,
+        recovery_hashes: &[String],
+        now: i64,
+    ) -> Result<(), String> {
+        let recovery_hashes = recovery_hashes.to_vec();
+        self.conn
+            .transaction::<_, (), DbErr>(|txn| {
+                Box::pin(async move {
+                    txn.execute(Statement::from_sql_and_values(
+                        DbBackend::Sqlite,
+                        "UPDATE totp_secrets SET enabled_at = ? WHERE uid = ?",
+                        [now.into(), uid.into()],
+                    ))
+                    .await?;
+                    write_recovery_codes(txn, uid, &recovery_hashes, now).await
+                })
+            })
+            .await
+            .map_err(|e| format!("启用两步验证失败: {e}"))
+    }
+
+    async fn replace_recovery_codes(
+        &self,
+        uid: i32,
+        recovery_hashes: &[String],
+        now: i64,
+    ) -> Result<(), String> {
+        let recovery_hashes = recovery_hashes.to_vec();
+        self.conn
+            .transaction::<_, (), DbErr>(|txn| {
+                Box::pin(async move { write_recovery_codes(txn, uid, &recovery_hashes, now).await })
+            })
+            .await
+            .map_err(|e| format!("生成恢复码失败: {e}"))
+    }
+
+    async fn delete_totp(&self, uid: i32) -> Result<(), String> {
+        for table in ["totp_secrets", "totp_recovery_codes", "login_challenges"] {
+            self.conn
+                .execute(Statement::from_sql_and_values(
+                    DbBackend::Sqlite,
+                    format!("DELETE FROM {table} WHERE uid = ?"),
+                    [uid.into()],
+                ))
+                .await
+                .map_err(|e| format!("关闭两步验证失败: {e}"))?;
+        }
+        Ok(())
+    }
+
+    async fn advance_totp_step(&self, uid: i32, step: i64) -> Result<bool, String> {
+        let result = self
+            .conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "UPDATE totp_secrets SET last_used_step = ? WHERE uid = ? AND last_used_step < ?",
+                [step.into(), uid.into(), step.into()],
+            ))
+            .await
+            .map_err(|e| format!("更新两步验证失败: {e}"))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn consume_recovery_code(
+        &self,
+        uid: i32,
+        code_hash: &str,
+        now: i64,
+    ) -> Result<bool, String> {
+        let result = self
+            .conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "UPDATE totp_recovery_codes SET used_at = ? WHERE code_hash = ? AND uid = ? AND used_at IS NULL",
+                [now.into(), code_hash.into(), uid.into()],
+            ))
+            .await
+            .map_err(|e| format!("核销恢复码失败: {e}"))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn count_recovery_codes(&self, uid: i32) -> Result<i64, String> {
+        let row = self
+            .conn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "SELECT COUNT(*) AS n FROM totp_recovery_codes WHERE uid = ? AND used_at IS NULL",
+                [uid.into()],
+            ))
+            .await
+            .map_err(|e| format!("查询恢复码失败: {e}"))?;
+        match row {
+            Some(row) => row.try_get("", "n").map_err(|e| e.to_string()),
+            None => Ok(0),
+        }
+    }
+
+    async fn create_challenge(&self, challenge: LoginChallenge) -> Result<(), String> {
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "INSERT INTO login_challenges (token_hash, uid, created_at, expires_at, attempts, used_at) VALUES (?, ?, ?, ?, ?, ?)",
+                vec![
+                    challenge.token_hash.into(),
+                    challenge.uid.into(),
+                    challenge.created_at.into(),
+                    challenge.expires_at.into(),
+                    challenge.attempts.into(),
+                    challenge.used_at.into(),
+                ],
+            ))
+            .await
+            .map_err(|e| format!("创建登录挑战失败: {e}"))?;
+        Ok(())
+    }
+
+    async fn get_challenge(&self, token_hash: &str) -> Result<Option<LoginChallenge>, String> {
+        let row = self
+            .conn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "SELECT token_hash, uid, created_at, expires_at, attempts, used_at FROM login_challenges WHERE token_hash = ?",
+                [token_hash.into()],
+            ))
+            .await
+            .map_err(|e| format!("查询登录挑战失败: {e}"))?;
+        row.map(|row| {
+            Ok(LoginChallenge {
+                token_hash: row.try_get("", "token_hash").map_err(|e| e.to_string())?,
+                uid: row.try_get("", "uid").map_err(|e| e.to_string())?,
+                created_at: row.try_get("", "created_at").map_err(|e| e.to_string())?,
+                expires_at: row.try_get("", "expires_at").map_err(|e| e.to_string())?,
+                attempts: row.try_get("", "attempts").map_err(|e| e.to_string())?,
+                used_at: row.try_get("", "used_at").map_err(|e| e.to_string())?,
+            })
+        })
+        .transpose()
+    }
+
+    async fn add_challenge_attempt(&self, token_hash: &str) -> Result<(), String> {
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "UPDATE login_challenges SET attempts = attempts + 1 WHERE token_hash = ?",
+                [token_hash.into()],
+            ))
+            .await
+            .map_err(|e| format!("更新登录挑战失败: {e}"))?;
+        Ok(())
+    }
+
+    async fn consume_challenge(&self, token_hash: &str, now: i64) -> Result<bool, String> {
+        let result = self
+            .conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "UPDATE login_challenges SET used_at = ? WHERE token_hash = ? AND used_at IS NULL",
+                [now.into(), token_hash.into()],
+            ))
+            .await
+            .map_err(|e| format!("核销登录挑战失败: {e}"))?;
+        Ok(result.rows_affected() > 0)
+    }
+}
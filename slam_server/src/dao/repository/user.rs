@@ -1,9 +1,14 @@
 use super::Repository;
-use crate::dao::entities::{avatars, users};
+use crate::dao::entities::users;
 use crate::dao::idl::UserDao;
-use crate::model::user::{User, UserInfo};
+use crate::model::user::{
+    AccountStatus, AdminUserView, AvatarImage, PasswordResetCode, User, UserInfo,
+};
 use async_trait::async_trait;
-use sea_orm::{ColumnTrait, ConnectionTrait, DbBackend, EntityTrait, QueryFilter, Set, Statement};
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DbBackend, DbErr, EntityTrait, QueryFilter, Set, Statement,
+    TransactionTrait,
+};
 
 #[async_trait]
 impl UserDao for Repository {
@@ -28,22 +33,28 @@ impl UserDao for Repository {
         let Some(user) = user else {
             return Ok(None);
         };
-        let avatar = avatars::Entity::find()
-            .filter(avatars::Column::Uid.eq(id))
-            .one(&self.conn)
+        let avatar_version = self
+            .conn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "SELECT etag FROM avatar_images WHERE uid = ? ORDER BY size DESC LIMIT 1",
+                [id.into()],
+            ))
             .await
-            .map_err(|e| format!("查询头像失败: {}", e))?;
-        let avatar_data = avatar.map(|a| a.data).unwrap_or_else(|| user.avatar);
+            .map_err(|e| format!("查询头像失败: {}", e))?
+            .map(|row| row.try_get::<String>("", "etag"))
+            .transpose()
+            .map_err(|e| e.to_string())?;
         Ok(Some(UserInfo {
             nickname: user.nickname,
-            avatar: avatar_data,
+            bio: user.bio,
+            avatar_version,
         }))
     }
 
-    async fn login(&self, name: &str, password: &str) -> Result<Option<User>, String> {
+    async fn get_by_name(&self, name: &str) -> Result<Option<User>, String> {
         let user = users::Entity::find()
             .filter(users::Column::Name.eq(name.to_string()))
-            .filter(users::Column::Password.eq(password.to_string()))
             .one(&self.conn)
             .await
             .map_err(|e| format!("查询用户失败: {}", e))?;
@@ -55,17 +66,381 @@ impl UserDao for Repository {
         }))
     }
 
-    async fn set_avatar(&self, uid: i32, base64: String) -> Result<(), String> {
-        // 使用原生 SQL 实现 SQLite 的 UPSERT 逻辑
-        let sql = format!(
-            "INSERT INTO avatars (uid, data, mime, created_at, updated_at)\n             VALUES ({}, '{}', 'image/jpeg', strftime('%s','now'), strftime('%s','now'))\n             ON CONFLICT(uid) DO UPDATE SET data = excluded.data, mime = excluded.mime, updated_at = strftime('%s','now')",
-            uid,
-            base64.replace("'", "''"),
-        );
+    async fn get_account(&self, id: i32) -> Result<Option<User>, String> {
+        let user = users::Entity::find_by_id(id)
+            .one(&self.conn)
+            .await
+            .map_err(|e| format!("查询用户失败: {}", e))?;
+        Ok(user.map(|u| User {
+            id: u.id,
+            name: u.name,
+            password: u.password,
+            nickname: u.nickname,
+        }))
+    }
+
+    async fn update_profile(&self, uid: i32, nickname: &str, bio: &str) -> Result<(), String> {
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "UPDATE users SET nickname = ?, bio = ? WHERE id = ?",
+                [nickname.into(), bio.into(), uid.into()],
+            ))
+            .await
+            .map_err(|e| format!("更新资料失败: {}", e))?;
+        Ok(())
+    }
+
+    async fn delete_user(&self, uid: i32) -> Result<Vec<String>, String> {
+        self.conn
+            .transaction::<_, Vec<String>, DbErr>(|txn| {
+                Box::pin(async move {
+                    let job_ids = txn
+                        .query_all(Statement::from_sql_and_values(
+                            DbBackend::Sqlite,
+                            "SELECT id FROM ai_jobs WHERE uid = ?",
+                            [uid.into()],
+                        ))
+                        .await?
+                        .iter()
+                        .map(|row| row.try_get::<String>("", "id"))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    // 其他用户收件箱中由该用户触发、或指向该用户运动的通知
+                    txn.execute(Statement::from_sql_and_values(
+                        DbBackend::Sqlite,
+                        "DELETE FROM notifications WHERE actor_uid = ? OR sport_id IN (SELECT id FROM sports WHERE uid = ?)",
+                        [uid.into(), uid.into()],
+                    ))
+                    .await?;
+                    for sql in [
+                        "DELETE FROM sport_comments WHERE sport_id IN (SELECT id FROM sports WHERE uid = ?)",
+                        "DELETE FROM sport_reactions WHERE sport_id IN (SELECT id FROM sports WHERE uid = ?)",
+                    ] {
+                        txn.execute(Statement::from_sql_and_values(
+                            DbBackend::Sqlite,
+                            sql,
+                            [uid.into()],
+                        ))
+                        .await?;
+                    }
+                    for table in [
+                        "ai_job_assets",
+                        "ai_jobs",
+                        "ai_job_events",
+                        "sports",
+                        "avatars",
+                        "avatar_images",
+                        "sessions",
+                        "personal_access_tokens",
+                        "athlete_profiles",
+                        "weight_logs",
+                        "password_reset_codes",
+                        "totp_secrets",
+                        "totp_recovery_codes",
+                        "login_challenges",
+                        "oidc_identities",
+                        "sport_shares",
+                        "club_members",
+                        "club_invites",
+                        "sport_comments",
+                        "sport_reactions",
+                        "notifications",
+                        "webhooks",
+                    ] {
+                        txn.execute(Statement::from_sql_and_values(
+                            DbBackend::Sqlite,
+                            format!("DELETE FROM {table} WHERE uid = ?"),
+                            [uid.into()],
+                        ))
+                        .await?;
+                    }
+                    txn.execute(Statement::from_sql_and_values(
+                        DbBackend::Sqlite,
+                        "DELETE FROM follows WHERE follower_uid = ? OR followee_uid = ?",
+                        [uid.into(), uid.into()],
+                    ))
+                    .await?;
+                    txn.execute(Statement::from_sql_and_values(
+                        DbBackend::Sqlite,
+                        "DELETE FROM coach_grants WHERE athlete_uid = ? OR coach_uid = ?",
+                        [uid.into(), uid.into()],
+                    ))
+                    .await?;
+                    // 包括全站 webhook 里该用户事件的投递记录
+                    txn.execute(Statement::from_sql_and_values(
+                        DbBackend::Sqlite,
+                        "DELETE FROM webhook_deliveries WHERE uid = ? OR subject_uid = ?",
+                        [uid.into(), uid.into()],
+                    ))
+                    .await?;
+                    // 俱乐部失去最后一位管理员时，由最早加入的一名成员接任（同时加入时取 uid 最小者）；
+                    // 没有成员的俱乐部直接删除
+                    txn.execute(Statement::from_string(
+                        DbBackend::Sqlite,
+                        "UPDATE club_members SET role = 'owner' \
+                         WHERE NOT EXISTS (SELECT 1 FROM club_members o \
+                             WHERE o.club_id = club_members.club_id AND o.role = 'owner') \
+                         AND uid = (SELECT f.uid FROM club_members f \
+                             WHERE f.club_id = club_members.club_id \
+                             ORDER BY f.joined_at, f.uid LIMIT 1)",
+                    ))
+                    .await?;
+                    for sql in [
+                        "DELETE FROM club_invites WHERE club_id NOT IN (SELECT club_id FROM club_members)",
+                        "DELETE FROM club_challenges WHERE club_id NOT IN (SELECT club_id FROM club_members)",
+                        "DELETE FROM clubs WHERE id NOT IN (SELECT club_id FROM club_members)",
+                    ] {
+                        txn.execute(Statement::from_string(DbBackend::Sqlite, sql))
+                            .await?;
+                    }
+                    txn.execute(Statement::from_sql_and_values(
+                        DbBackend::Sqlite,
+                        "DELETE FROM users WHERE id = ?",
+                        [uid.into()],
+                    ))
+                    .await?;
+                    Ok(job_ids)
+                })
+            })
+            .await
+            .map_err(|e| format!("删除用户失败: {}", e))
+    }
+
+    async fn update_password(&self, uid: i32, password: &str) -> Result<(), String> {
         self.conn
-            .execute(Statement::from_string(DbBackend::Sqlite, sql))
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "UPDATE users SET password = ? WHERE id = ?",
+                [password.into(), uid.into()],
+            ))
             .await
-            .map_err(|e| format!("更新头像失败: {}", e))?;
+            .map_err(|e| format!("更新密码失败: {}", e))?;
         Ok(())
     }
+
+    async fn set_avatar(&self, uid: i32, images: Vec<AvatarImage>) -> Result<(), String> {
+        self.conn
+            .transaction::<_, (), DbErr>(|txn| {
+                Box::pin(async move {
+                    txn.execute(Statement::from_sql_and_values(
+                        DbBackend::Sqlite,
+                        "DELETE FROM avatar_images WHERE uid = ?",
+                        [uid.into()],
+                    ))
+                    .await?;
+                    for image in images {
+                        txn.execute(Statement::from_sql_and_values(
+                            DbBackend::Sqlite,
+                            "INSERT INTO avatar_images (uid, size, mime, data, etag, updated_at) \
+                             VALUES (?, ?, ?, ?, ?, ?)",
+                            [
+                                uid.into(),
+                                image.size.into(),
+                                image.mime.into(),
+                                image.data.into(),
+                                image.etag.into(),
+                                image.updated_at.into(),
+                            ],
+                        ))
+                        .await?;
+                    }
+                    clear_legacy(txn, uid).await
+                })
+            })
+            .await
+            .map_err(|e| format!("更新头像失败: {}", e))
+    }
+
+    async fn get_avatar(&self, uid: i32, size: u32) -> Result<Option<AvatarImage>, String> {
+        let row = self
+            .conn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "SELECT size, mime, data, etag, updated_at FROM avatar_images WHERE uid = ? \
+                 ORDER BY size < ?, CASE WHEN size < ? THEN -size ELSE size END LIMIT 1",
+                [uid.into(), size.into(), size.into()],
+            ))
+            .await
+            .map_err(|e| format!("查询头像失败: {}", e))?;
+        row.map(|row| {
+            Ok(AvatarImage {
+                size: row.try_get("", "size").map_err(|e| e.to_string())?,
+                mime: row.try_get("", "mime").map_err(|e| e.to_string())?,
+                data: row.try_get("", "data").map_err(|e| e.to_string())?,
+                etag: row.try_get("", "etag").map_err(|e| e.to_string())?,
+                updated_at: row.try_get("", "updated_at").map_err(|e| e.to_string())?,
+            })
+        })
+        .transpose()
+    }
+
+    async fn list_legacy_avatars(&self) -> Result<Vec<(i32, String)>, String> {
+        // avatars 表中的数据比 users.avatar 列新，两处都有时以前者为准
+        let rows = self
+            .conn
+            .query_all(Statement::from_string(
+                DbBackend::Sqlite,
+                "SELECT uid, data FROM avatars WHERE data != '' \
+                 UNION ALL \
+                 SELECT id AS uid, avatar AS data FROM users WHERE avatar != '' \
+                 AND id NOT IN (SELECT uid FROM avatars WHERE data != '')",
+            ))
+            .await
+            .map_err(|e| format!("查询旧版头像失败: {}", e))?;
+        rows.iter()
+            .map(|row| {
+                Ok((
+                    row.try_get("", "uid").map_err(|e| e.to_string())?,
+                    row.try_get("", "data").map_err(|e| e.to_string())?,
+                ))
+            })
+            .collect()
+    }
+
+    async fn clear_legacy_avatar(&self, uid: i32) -> Result<(), String> {
+        clear_legacy(&self.conn, uid)
+            .await
+            .map_err(|e| format!("清理旧版头像失败: {}", e))
+    }
+
+    async fn get_account_status(&self, uid: i32) -> Result<Option<AccountStatus>, String> {
+        let row = self
+            .conn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "SELECT role, disabled_at FROM users WHERE id = ?",
+                [uid.into()],
+            ))
+            .await
+            .map_err(|e| format!("查询用户失败: {}", e))?;
+        row.map(|row| {
+            Ok(AccountStatus {
+                role: row.try_get("", "role").map_err(|e| e.to_string())?,
+                disabled_at: row.try_get("", "disabled_at").map_err(|e| e.to_string())?,
+            })
+        })
+        .transpose()
+    }
+
+    async fn set_disabled(&self, uid: i32, disabled_at: Option<i64>) -> Result<bool, String> {
+        let result = self
+            .conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "UPDATE users SET disabled_at = ? WHERE id = ?",
+                [disabled_at.into(), uid.into()],
+            ))
+            .await
+            .map_err(|e| format!("更新用户状态失败: {}", e))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn promote_admins(&self, names: &[String]) -> Result<u64, String> {
+        let mut updated = 0;
+        for name in names {
+            let result = self
+                .conn
+                .execute(Statement::from_sql_and_values(
+                    DbBackend::Sqlite,
+                    "UPDATE users SET role = 'admin' WHERE name = ? AND role != 'admin'",
+                    [name.as_str().into()],
+                ))
+                .await
+                .map_err(|e| format!("设置管理员失败: {}", e))?;
+            updated += result.rows_affected();
+        }
+        Ok(updated)
+    }
+
+    async fn list_users(&self, page: i32, size: i32) -> Result<Vec<AdminUserView>, String> {
+        let safe_page = page.max(0);
+        let safe_size = size.clamp(1, 100);
+        let rows = self
+            .conn
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "SELECT u.id, u.name, u.nickname, u.role, u.disabled_at, \
+                 (SELECT COUNT(*) FROM sports s WHERE s.uid = u.id) AS sport_count, \
+                 (SELECT COUNT(*) FROM ai_jobs j WHERE j.uid = u.id) AS ai_job_count, \
+                 (SELECT COUNT(*) FROM ai_jobs j WHERE j.uid = u.id AND j.status IN ('queued', 'running')) AS active_ai_job_count, \
+                 COALESCE((SELECT SUM(LENGTH(a.data)) FROM avatar_images a WHERE a.uid = u.id), 0) AS storage_bytes \
+                 FROM users u ORDER BY u.id LIMIT ? OFFSET ?",
+                [safe_size.into(), (safe_page * safe_size).into()],
+            ))
+            .await
+            .map_err(|e| format!("查询用户列表失败: {}", e))?;
+        rows.iter()
+            .map(|row| {
+                Ok(AdminUserView {
+                    id: row.try_get("", "id").map_err(|e| e.to_string())?,
+                    name: row.try_get("", "name").map_err(|e| e.to_string())?,
+                    nickname: row.try_get("", "nickname").map_err(|e| e.to_string())?,
+                    role: row.try_get("", "role").map_err(|e| e.to_string())?,
+                    disabled_at: row.try_get("", "disabled_at").map_err(|e| e.to_string())?,
+                    sport_count: row.try_get("", "sport_count").map_err(|e| e.to_string())?,
+                    ai_job_count: row.try_get("", "ai_job_count").map_err(|e| e.to_string())?,
+                    active_ai_job_count: row
+                        .try_get("", "active_ai_job_count")
+                        .map_err(|e| e.to_string())?,
+                    storage_bytes: row
+                        .try_get("", "storage_bytes")
+                        .map_err(|e| e.to_string())?,
+                })
+            })
+            .collect()
+    }
+
+    async fn create_reset_code(&self, code: PasswordResetCode) -> Result<(), String> {
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "INSERT INTO password_reset_codes (code_hash, uid, created_by, created_at, expires_at, used_at) VALUES (?, ?, ?, ?, ?, ?)",
+                [
+                    code.code_hash.into(),
+                    code.uid.into(),
+                    code.created_by.into(),
+                    code.created_at.into(),
+                    code.expires_at.into(),
+                    code.used_at.into(),
+                ],
+            ))
+            .await
+            .map_err(|e| format!("保存重置码失败: {}", e))?;
+        Ok(())
+    }
+
+    async fn consume_reset_code(
+        &self,
+        code_hash: &str,
+        uid: i32,
+        now: i64,
+    ) -> Result<bool, String> {
+        let result = self
+            .conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "UPDATE password_reset_codes SET used_at = ? WHERE code_hash = ? AND uid = ? AND used_at IS NULL AND expires_at > ?",
+                [now.into(), code_hash.into(), uid.into(), now.into()],
+            ))
+            .await
+            .map_err(|e| format!("核销重置码失败: {}", e))?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+/// 删除旧版 base64 头像：avatars 表中的行和 users.avatar 列
+async fn clear_legacy<C: ConnectionTrait>(conn: &C, uid: i32) -> Result<(), DbErr> {
+    conn.execute(Statement::from_sql_and_values(
+        DbBackend::Sqlite,
+        "DELETE FROM avatars WHERE uid = ?",
+        [uid.into()],
+    ))
+    .await?;
+    conn.execute(Statement::from_sql_and_values(
+        DbBackend::Sqlite,
+        "UPDATE users SET avatar = '' WHERE id = ?",
+        [uid.into()],
+    ))
+    .await?;
+    Ok(())
 }
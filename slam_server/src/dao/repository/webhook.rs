@@ -0,0 +1,349 @@
+use async_trait::async_trait;
+use sea_orm::{ConnectionTrait, DbBackend, Statement, TransactionTrait};
+
+use super::Repository;
+use crate::dao::idl::WebhookDao;
+use crate::model::webhook::{
+    DELIVERY_FAILED, DELIVERY_PENDING, DELIVERY_RUNNING, DELIVERY_SUCCEEDED, Webhook,
+    WebhookDelivery,
+};
+
+const WEBHOOK_COLUMNS: &str = "id, uid, url, secret, events, all_users, created_at";
+const DELIVERY_COLUMNS: &str = "id, webhook_id, uid, subject_uid, event, payload, status, attempts, next_attempt_at, lease_until, response_status, last_error, created_at, updated_at, delivered_at";
+
+fn webhook_from_row(row: &sea_orm::QueryResult) -> Result<Webhook, String> {
+    let events: String = row.try_get("", "events").map_err(|e| e.to_string())?;
+    Ok(Webhook {
+        id: row.try_get("", "id").map_err(|e| e.to_string())?,
+        uid: row.try_get("", "uid").map_err(|e| e.to_string())?,
+        url: row.try_get("", "url").map_err(|e| e.to_string())?,
+        secret: row.try_get("", "secret").map_err(|e| e.to_string())?,
+        events: events
+            .split(',')
+            .filter(|e| !e.is_empty())
+            .map(str::to_string)
+            .collect(),
+        all_users: row.try_get("", "all_users").map_err(|e| e.to_string())?,
+        created_at: row.try_get("", "created_at").map_err(|e| e.to_string())?,
+    })
+}
+
+fn delivery_from_row(row: &sea_orm::QueryResult) -> Result<WebhookDelivery, String> {
+    Ok(WebhookDelivery {
+        id: row.try_get("", "id").map_err(|e| e.to_string())?,
+        webhook_id: row.try_get("", "webhook_id").map_err(|e| e.to_string())?,
+        uid: row.try_get("", "uid").map_err(|e| e.to_string())?,
+        subject_uid: row.try_get("", "subject_uid").map_err(|e| e.to_string())?,
+        event: row.try_get("", "event").map_err(|e| e.to_string())?,
+        payload: row.try_get("", "payload").map_err(|e| e.to_string())?,
+        status: row.try_get("", "status").map_err(|e| e.to_string())?,
+        attempts: row.try_get("", "attempts").map_err(|e| e.to_string())?,
+        next_attempt_at: row
+            .try_get("", "next_attempt_at")
+            .map_err(|e| e.to_string())?,
+        lease_until: row.try_get("", "lease_until").map_err(|e| e.to_string())?,
+        response_status: row
+            .try_get("", "response_status")
+            .map_err(|e| e.to_string())?,
+        last_error: row.try_get("", "last_error").map_err(|e| e.to_string())?,
+        created_at: row.try_get("", "created_at").map_err(|e| e.to_string())?,
+        updated_at: row.try_get("", "updated_at").map_err(|e| e.to_string())?,
+        delivered_at: row.try_get("", "delivered_at").map_err(|e| e.to_string())?,
+    })
+}
+
+#[async_trait]
+impl WebhookDao for Repository {
+    async fn create_webhook(&self, webhook: Webhook) -> Result<(), String> {
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "INSERT INTO webhooks (id, uid, url, secret, events, all_users, created_at) \
+                 VALUES (?, ?, ?, ?, ?, ?, ?)",
+                [
+                    webhook.id.into(),
+                    webhook.uid.into(),
+                    webhook.url.into(),
+                    webhook.secret.into(),
+                    webhook.events.join(",").into(),
+                    webhook.all_users.into(),
+                    webhook.created_at.into(),
+                ],
+            ))
+            .await
+            .map_err(|e| format!("创建webhook失败: {e}"))?;
+        Ok(())
+    }
+
+    async fn get_webhook(&self, id: &str) -> Result<Option<Webhook>, String> {
+        let row = self
+            .conn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                format!("SELECT {WEBHOOK_COLUMNS} FROM webhooks WHERE id = ?"),
+                [id.into()],
+            ))
+            .await
+            .map_err(|e| format!("查询webhook失败: {e}"))?;
+        row.as_ref().map(webhook_from_row).transpose()
+    }
+
+    async fn list_webhooks(&self, uid: i32) -> Result<Vec<Webhook>, String> {
+        let rows = self
+            .conn
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                format!("SELECT {WEBHOOK_COLUMNS} FROM webhooks WHERE uid = ? ORDER BY created_at"),
+                [uid.into()],
+            ))
+            .await
+            .map_err(|e| format!("查询webhook失败: {e}"))?;
+        rows.iter().map(webhook_from_row).collect()
+    }
+
+    async fn delete_webhook(&self, id: &str) -> Result<(), String> {
+        let id = id.to_string();
+        self.conn
+            .transaction(|txn| {
+                Box::pin(async move {
+                    for sql in [
+                        "DELETE FROM webhook_deliveries WHERE webhook_id = ?",
+                        "DELETE FROM webhooks WHERE id = ?",
+                    ] {
+                        txn.execute(Statement::from_sql_and_values(
+                            DbBackend::Sqlite,
+                            sql,
+                            [id.clone().into()],
+                        ))
+                        .await?;
+                    }
+                    Ok::<_, sea_orm::DbErr>(())
+                })
+            })
+            .await
+            .map_err(|e| format!("删除webhook失败: {e}"))
+    }
+
+    async fn list_webhooks_for(&self, subject_uid: i32) -> Result<Vec<Webhook>, String> {
+        let rows = self
+            .conn
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                format!(
+                    "SELECT {WEBHOOK_COLUMNS} FROM webhooks WHERE uid = ? OR all_users = 1 ORDER BY created_at"
+                ),
+                [subject_uid.into()],
+            ))
+            .await
+            .map_err(|e| format!("查询webhook失败: {e}"))?;
+        rows.iter().map(webhook_from_row).collect()
+    }
+
+    async fn create_deliveries(&self, deliveries: Vec<WebhookDelivery>) -> Result<(), String> {
+        self.conn
+            .transaction(|txn| {
+                Box::pin(async move {
+                    for d in deliveries {
+                        txn.execute(Statement::from_sql_and_values(
+                            DbBackend::Sqlite,
+                            format!(
+                                "INSERT INTO webhook_deliveries ({DELIVERY_COLUMNS}) \
+                                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+                            ),
+                            [
+                                d.id.into(),
+                                d.webhook_id.into(),
+                                d.uid.into(),
+                                d.subject_uid.into(),
+                                d.event.into(),
+                                d.payload.into(),
+                                d.status.into(),
+                                d.attempts.into(),
+                                d.next_attempt_at.into(),
+                                d.lease_until.into(),
+                                d.response_status.into(),
+                                d.last_error.into(),
+                                d.created_at.into(),
+                                d.updated_at.into(),
+                                d.delivered_at.into(),
+                            ],
+                        ))
+                        .await?;
+                    }
+                    Ok::<_, sea_orm::DbErr>(())
+                })
+            })
+            .await
+            .map_err(|e| format!("创建webhook投递失败: {e}"))
+    }
+
+    async fn get_delivery(
+        &self,
+        webhook_id: &str,
+        id: &str,
+    ) -> Result<Option<WebhookDelivery>, String> {
+        let row = self
+            .conn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                format!(
+                    "SELECT {DELIVERY_COLUMNS} FROM webhook_deliveries WHERE webhook_id = ? AND id = ?"
+                ),
+                [webhook_id.into(), id.into()],
+            ))
+            .await
+            .map_err(|e| format!("查询webhook投递失败: {e}"))?;
+        row.as_ref().map(delivery_from_row).transpose()
+    }
+
+    async fn list_deliveries(
+        &self,
+        webhook_id: &str,
+        page: i32,
+        size: i32,
+    ) -> Result<Vec<WebhookDelivery>, String> {
+        let size = size.clamp(1, 100);
+        let page = page.max(0);
+        let rows = self
+            .conn
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                format!(
+                    "SELECT {DELIVERY_COLUMNS} FROM webhook_deliveries WHERE webhook_id = ? \
+                     ORDER BY created_at DESC, rowid DESC LIMIT ? OFFSET ?"
+                ),
+                [webhook_id.into(), size.into(), (page * size).into()],
+            ))
+            .await
+            .map_err(|e| format!("查询webhook投递失败: {e}"))?;
+        rows.iter().map(delivery_from_row).collect()
+    }
+
+    async fn claim_next_delivery(
+        &self,
+        now: i64,
+        lease_until: i64,
+    ) -> Result<Option<WebhookDelivery>, String> {
+        for _ in 0..3 {
+            let Some(row) = self
+                .conn
+                .query_one(Statement::from_sql_and_values(
+                    DbBackend::Sqlite,
+                    format!(
+                        "SELECT {DELIVERY_COLUMNS} FROM webhook_deliveries WHERE status = ? \
+                         AND (next_attempt_at IS NULL OR next_attempt_at <= ?) \
+                         ORDER BY created_at, rowid LIMIT 1"
+                    ),
+                    [DELIVERY_PENDING.into(), now.into()],
+                ))
+                .await
+                .map_err(|e| format!("领取webhook投递失败: {e}"))?
+            else {
+                return Ok(None);
+            };
+            let delivery = delivery_from_row(&row)?;
+            let result = self
+                .conn
+                .execute(Statement::from_sql_and_values(
+                    DbBackend::Sqlite,
+                    "UPDATE webhook_deliveries SET status = ?, attempts = attempts + 1, lease_until = ?, \
+                     next_attempt_at = NULL, updated_at = ? WHERE id = ? AND status = ?",
+                    [
+                        DELIVERY_RUNNING.into(),
+                        lease_until.into(),
+                        now.into(),
+                        delivery.id.clone().into(),
+                        DELIVERY_PENDING.into(),
+                    ],
+                ))
+                .await
+                .map_err(|e| format!("领取webhook投递失败: {e}"))?;
+            if result.rows_affected() == 1 {
+                return Ok(Some(WebhookDelivery {
+                    status: DELIVERY_RUNNING.to_string(),
+                    attempts: delivery.attempts + 1,
+                    lease_until: Some(lease_until),
+                    next_attempt_at: None,
+                    updated_at: now,
+                    ..delivery
+                }));
+            }
+        }
+        Ok(None)
+    }
+
+    async fn requeue_expired_deliveries(&self, now: i64) -> Result<(), String> {
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "UPDATE webhook_deliveries SET status = ?, next_attempt_at = ?, lease_until = NULL, updated_at = ? \
+                 WHERE status = ? AND lease_until < ?",
+                [
+                    DELIVERY_PENDING.into(),
+                    now.into(),
+                    now.into(),
+                    DELIVERY_RUNNING.into(),
+                    now.into(),
+                ],
+            ))
+            .await
+            .map_err(|e| format!("恢复webhook投递失败: {e}"))?;
+        Ok(())
+    }
+
+    async fn mark_delivery_succeeded(
+        &self,
+        id: &str,
+        response_status: i32,
+        now: i64,
+    ) -> Result<(), String> {
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "UPDATE webhook_deliveries SET status = ?, response_status = ?, last_error = NULL, \
+                 lease_until = NULL, delivered_at = ?, updated_at = ? WHERE id = ?",
+                [
+                    DELIVERY_SUCCEEDED.into(),
+                    response_status.into(),
+                    now.into(),
+                    now.into(),
+                    id.into(),
+                ],
+            ))
+            .await
+            .map_err(|e| format!("更新webhook投递失败: {e}"))?;
+        Ok(())
+    }
+
+    async fn mark_delivery_failed(
+        &self,
+        id: &str,
+        response_status: Option<i32>,
+        error: &str,
+        retry_at: Option<i64>,
+        now: i64,
+    ) -> Result<(), String> {
+        let status = if retry_at.is_some() {
+            DELIVERY_PENDING
+        } else {
+            DELIVERY_FAILED
+        };
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "UPDATE webhook_deliveries SET status = ?, response_status = ?, last_error = ?, \
+                 next_attempt_at = ?, lease_until = NULL, updated_at = ? WHERE id = ?",
+                [
+                    status.into(),
+                    response_status.into(),
+                    error.into(),
+                    retry_at.into(),
+                    now.into(),
+                    id.into(),
+                ],
+            ))
+            .await
+            .map_err(|e| format!("更新webhook投递失败: {e}"))?;
+        Ok(())
+    }
+}
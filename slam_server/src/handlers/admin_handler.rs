@@ -0,0 +1,272 @@
+use axum::extract::{Path, Query, State};
+use axum::response::IntoResponse;
+use serde::Deserialize;
+use std::sync::Arc;
+
+use super::jwt::AdminContext;
+use super::response::{HandlerResponse, error_response};
+use super::user_handler::UserActionResponse;
+use crate::app::{AppState, routes};
+use crate::model::ai_job::{AdminAiJobView, AiQueueStats};
+use crate::model::audit::{AuditEntry, AuditQuery};
+use crate::model::user::{AdminUserView, IssuedResetCode};
+use crate::service::session_service::ClientMeta;
+
+#[derive(Deserialize)]
+pub struct AdminPageQuery {
+    pub page: Option<i32>,
+    pub size: Option<i32>,
+}
+
+#[derive(Deserialize)]
+pub struct AdminJobQuery {
+    pub status: Option<String>,
+    pub page: Option<i32>,
+    pub size: Option<i32>,
+}
+
+#[derive(Deserialize)]
+pub struct AdminAuditQuery {
+    pub uid: Option<i32>,
+    pub action: Option<String>,
+    pub ip: Option<String>,
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+    pub page: Option<i32>,
+    pub size: Option<i32>,
+}
+
+#[utoipa::path(
+    get,
+    path = routes::API_ADMIN_USERS,
+    params(
+        ("page" = Option<i32>, Query, description = "Page index"),
+        ("size" = Option<i32>, Query, description = "Page size")
+    ),
+    responses(
+        (status = 200, description = "Users with storage and job usage", body = [AdminUserView]),
+        (status = 403, description = "Not an admin", body = String)
+    )
+)]
+pub async fn list_users_handler(
+    State(app): State<Arc<AppState>>,
+    _admin: AdminContext,
+    Query(q): Query<AdminPageQuery>,
+) -> axum::response::Response {
+    let page = q.page.unwrap_or(0);
+    let size = q.size.unwrap_or(20);
+    match app.admin_service.list_users(page, size).await {
+        Ok(v) => HandlerResponse::<Vec<AdminUserView>>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/admin/users/{id}/disable",
+    params(("id" = i32, Path, description = "User id")),
+    responses(
+        (status = 200, description = "Account disabled, sessions and tokens revoked", body = UserActionResponse),
+        (status = 403, description = "Not an admin", body = String),
+        (status = 404, description = "Not found", body = String)
+    )
+)]
+pub async fn disable_user_handler(
+    State(app): State<Arc<AppState>>,
+    AdminContext(admin): AdminContext,
+    client: ClientMeta,
+    Path(id): Path<i32>,
+) -> axum::response::Response {
+    match app
+        .admin_service
+        .set_disabled(admin.uid, id, true, &client)
+        .await
+    {
+        Ok(()) => HandlerResponse::Success(UserActionResponse { success: true }).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/admin/users/{id}/enable",
+    params(("id" = i32, Path, description = "User id")),
+    responses(
+        (status = 200, description = "Account enabled", body = UserActionResponse),
+        (status = 403, description = "Not an admin", body = String),
+        (status = 404, description = "Not found", body = String)
+    )
+)]
+pub async fn enable_user_handler(
+    State(app): State<Arc<AppState>>,
+    AdminContext(admin): AdminContext,
+    client: ClientMeta,
+    Path(id): Path<i32>,
+) -> axum::response::Response {
+    match app
+        .admin_service
+        .set_disabled(admin.uid, id, false, &client)
+        .await
+    {
+        Ok(()) => HandlerResponse::Success(UserActionResponse { success: true }).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/admin/users/{id}/password-reset",
+    params(("id" = i32, Path, description = "User id")),
+    responses(
+        (status = 200, description = "One-time reset code, shown only once", body = IssuedResetCode),
+        (status = 403, description = "Not an admin", body = String),
+        (status = 404, description = "Not found", body = String)
+    )
+)]
+pub async fn issue_reset_code_handler(
+    State(app): State<Arc<AppState>>,
+    AdminContext(admin): AdminContext,
+    client: ClientMeta,
+    Path(id): Path<i32>,
+) -> axum::response::Response {
+    match app
+        .admin_service
+        .issue_reset_code(admin.uid, id, &client)
+        .await
+    {
+        Ok(v) => HandlerResponse::<IssuedResetCode>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = routes::API_ADMIN_AI_JOBS,
+    params(
+        ("status" = Option<String>, Query, description = "queued / running / ready / failed / submitted"),
+        ("page" = Option<i32>, Query, description = "Page index"),
+        ("size" = Option<i32>, Query, description = "Page size")
+    ),
+    responses(
+        (status = 200, description = "AI jobs across all users", body = [AdminAiJobView]),
+        (status = 400, description = "Unknown status", body = String),
+        (status = 403, description = "Not an admin", body = String)
+    )
+)]
+pub async fn list_jobs_handler(
+    State(app): State<Arc<AppState>>,
+    _admin: AdminContext,
+    Query(q): Query<AdminJobQuery>,
+) -> axum::response::Response {
+    let page = q.page.unwrap_or(0);
+    let size = q.size.unwrap_or(20);
+    match app
+        .admin_service
+        .list_jobs(q.status.as_deref(), page, size)
+        .await
+    {
+        Ok(v) => HandlerResponse::<Vec<AdminAiJobView>>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/admin/ai/jobs/{id}/requeue",
+    params(("id" = String, Path, description = "AI job id")),
+    responses(
+        (status = 200, description = "Failed job queued again", body = UserActionResponse),
+        (status = 403, description = "Not an admin", body = String),
+        (status = 404, description = "Not found", body = String),
+        (status = 409, description = "Job is not failed", body = String)
+    )
+)]
+pub async fn requeue_job_handler(
+    State(app): State<Arc<AppState>>,
+    AdminContext(admin): AdminContext,
+    client: ClientMeta,
+    Path(id): Path<String>,
+) -> axum::response::Response {
+    match app.admin_service.requeue_job(admin.uid, &id, &client).await {
+        Ok(()) => HandlerResponse::Success(UserActionResponse { success: true }).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/admin/ai/jobs/{id}",
+    params(("id" = String, Path, description = "AI job id")),
+    responses(
+        (status = 200, description = "Job and its images removed", body = UserActionResponse),
+        (status = 403, description = "Not an admin", body = String),
+        (status = 404, description = "Not found", body = String),
+        (status = 409, description = "Job is running or submitted", body = String)
+    )
+)]
+pub async fn purge_job_handler(
+    State(app): State<Arc<AppState>>,
+    AdminContext(admin): AdminContext,
+    client: ClientMeta,
+    Path(id): Path<String>,
+) -> axum::response::Response {
+    match app.admin_service.purge_job(admin.uid, &id, &client).await {
+        Ok(()) => HandlerResponse::Success(UserActionResponse { success: true }).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = routes::API_ADMIN_AI_QUEUE,
+    responses(
+        (status = 200, description = "Worker queue depth", body = AiQueueStats),
+        (status = 403, description = "Not an admin", body = String)
+    )
+)]
+pub async fn queue_stats_handler(
+    State(app): State<Arc<AppState>>,
+    _admin: AdminContext,
+) -> axum::response::Response {
+    match app.admin_service.queue_stats().await {
+        Ok(v) => HandlerResponse::<AiQueueStats>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = routes::API_ADMIN_AUDIT,
+    params(
+        ("uid" = Option<i32>, Query, description = "User id"),
+        ("action" = Option<String>, Query, description = "Action, e.g. login_failure"),
+        ("ip" = Option<String>, Query, description = "Client IP"),
+        ("since" = Option<i64>, Query, description = "Start timestamp (inclusive)"),
+        ("until" = Option<i64>, Query, description = "End timestamp (exclusive)"),
+        ("page" = Option<i32>, Query, description = "Page index"),
+        ("size" = Option<i32>, Query, description = "Page size")
+    ),
+    responses(
+        (status = 200, description = "Audit entries across users, newest first", body = [AuditEntry]),
+        (status = 403, description = "Not an admin", body = String)
+    )
+)]
+pub async fn query_audit_handler(
+    State(app): State<Arc<AppState>>,
+    _admin: AdminContext,
+    Query(q): Query<AdminAuditQuery>,
+) -> axum::response::Response {
+    let query = AuditQuery {
+        uid: q.uid,
+        action: q.action,
+        ip: q.ip,
+        since: q.since,
+        until: q.until,
+    };
+    let page = q.page.unwrap_or(0);
+    let size = q.size.unwrap_or(50);
+    match app.audit_service.query(&query, page, size).await {
+        Ok(v) => HandlerResponse::<Vec<AuditEntry>>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
@@ -1,14 +1,18 @@
 use std::sync::Arc;
+use std::time::Duration;
 
 use axum::Json;
 use axum::body::Body;
 use axum::extract::{Path, Query, State};
-use axum::http::{HeaderValue, StatusCode, header};
+use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
+use axum::response::sse::{Event, KeepAlive, Sse};
 use axum::response::{IntoResponse, Response};
 use axum_extra::extract::Multipart;
+use futures_util::StreamExt;
 use serde::Deserialize;
 
 use super::jwt::Context;
+use super::response::error_response;
 use crate::app::AppState;
 use crate::service::ai_job_service::JobUpload;
 
@@ -18,6 +22,15 @@ pub struct JobListQuery {
     pub size: Option<i32>,
 }
 
+/// 连接空闲时的心跳间隔，防止代理和移动网络断开长连接
+const EVENT_HEARTBEAT_SECONDS: u64 = 15;
+
+#[derive(Deserialize)]
+pub struct JobEventQuery {
+    /// 无法设置 Last-Event-ID 请求头的客户端可以用该参数续传
+    pub last_event_id: Option<i64>,
+}
+
 #[utoipa::path(
     post,
     path = "/api/ai/jobs",
@@ -97,6 +110,53 @@ pub async fn list_ai_jobs_handler(
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/ai/jobs/events",
+    params(
+        ("Last-Event-ID" = Option<i64>, Header, description = "Resume after this event id"),
+        ("last_event_id" = Option<i64>, Query, description = "Same as the Last-Event-ID header, the header wins")
+    ),
+    responses(
+        (status = 200, description = "Server-Sent Events stream of job status changes, each `job` event carries an AiJobEvent and a heartbeat comment is sent every 15 seconds", body = crate::model::ai_job::AiJobEvent, content_type = "text/event-stream"),
+        (status = 401, description = "Unauthorized")
+    )
+)]
+pub async fn ai_job_events_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    headers: HeaderMap,
+    Query(query): Query<JobEventQuery>,
+) -> Response {
+    let last_event_id = headers
+        .get("last-event-id")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<i64>().ok())
+        .or(query.last_event_id);
+    match app
+        .ai_job_service
+        .event_stream(ctx.uid, last_event_id)
+        .await
+    {
+        Ok(stream) => {
+            let stream = stream.map(|event| {
+                Event::default()
+                    .id(event.id.to_string())
+                    .event("job")
+                    .json_data(&event)
+            });
+            Sse::new(stream)
+                .keep_alive(
+                    KeepAlive::new()
+                        .interval(Duration::from_secs(EVENT_HEARTBEAT_SECONDS))
+                        .text("heartbeat"),
+                )
+                .into_response()
+        }
+        Err(error) => error_response(error.code, error.message),
+    }
+}
+
 #[utoipa::path(
     get,
     path = "/api/ai/jobs/{id}",
@@ -197,8 +257,3 @@ async fn asset_response(app: &AppState, uid: i32, id: &str, thumbnail: bool) ->
         Err(error) => error_response(error.code, error.message),
     }
 }
-
-fn error_response(code: u32, message: String) -> Response {
-    let status = StatusCode::from_u16(code as u16).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
-    (status, Json(serde_json::json!({ "error": message }))).into_response()
-}
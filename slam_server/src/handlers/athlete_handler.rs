@@ -0,0 +1,168 @@
+use axum::extract::{Json, Path, State};
+use axum::response::IntoResponse;
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+use super::jwt::Context;
+use super::response::{HandlerResponse, error_response};
+use super::user_handler::UserActionResponse;
+use crate::app::{AppState, routes};
+use crate::model::athlete::{AthleteProfile, AthleteProfileView, UNIT_METRIC, WeightEntry};
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, ToSchema)]
+pub struct UpdateAthleteProfileRequest {
+    /// 出生日期，格式 YYYY-MM-DD
+    pub birth_date: Option<String>,
+    /// `male` 或 `female`
+    pub sex: Option<String>,
+    pub height_cm: Option<f64>,
+    pub resting_heart_rate: Option<i32>,
+    pub max_heart_rate: Option<i32>,
+    /// `metric`（默认）或 `imperial`
+    pub unit_system: Option<String>,
+    /// 新版本的生效时间（秒级时间戳），默认当前时间
+    pub valid_from: Option<i64>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, ToSchema)]
+pub struct AddWeightRequest {
+    pub weight_kg: f64,
+    /// 测量时间（秒级时间戳），默认当前时间
+    pub measured_at: Option<i64>,
+}
+
+#[utoipa::path(
+    get,
+    path = routes::API_USER_ATHLETE,
+    responses(
+        (status = 200, description = "Current athlete profile", body = AthleteProfileView),
+        (status = 401, description = "Unauthorized", body = String)
+    )
+)]
+pub async fn get_athlete_profile_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+) -> axum::response::Response {
+    match app.athlete_service.current(ctx.uid).await {
+        Ok(v) => HandlerResponse::<AthleteProfileView>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = routes::API_USER_ATHLETE,
+    request_body = UpdateAthleteProfileRequest,
+    responses(
+        (status = 200, description = "New profile version saved", body = AthleteProfile),
+        (status = 400, description = "Invalid parameters", body = String),
+        (status = 401, description = "Unauthorized", body = String)
+    )
+)]
+pub async fn update_athlete_profile_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Json(req): Json<UpdateAthleteProfileRequest>,
+) -> axum::response::Response {
+    let profile = AthleteProfile {
+        id: 0,
+        valid_from: 0,
+        birth_date: req.birth_date,
+        sex: req.sex,
+        height_cm: req.height_cm,
+        resting_heart_rate: req.resting_heart_rate,
+        max_heart_rate: req.max_heart_rate,
+        unit_system: req.unit_system.unwrap_or_else(|| UNIT_METRIC.to_string()),
+        created_at: 0,
+    };
+    match app
+        .athlete_service
+        .update_profile(ctx.uid, profile, req.valid_from)
+        .await
+    {
+        Ok(v) => HandlerResponse::<AthleteProfile>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = routes::API_USER_ATHLETE_HISTORY,
+    responses(
+        (status = 200, description = "All profile versions, newest first", body = Vec<AthleteProfile>),
+        (status = 401, description = "Unauthorized", body = String)
+    )
+)]
+pub async fn athlete_profile_history_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+) -> axum::response::Response {
+    match app.athlete_service.history(ctx.uid).await {
+        Ok(v) => HandlerResponse::<Vec<AthleteProfile>>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = routes::API_USER_WEIGHTS,
+    request_body = AddWeightRequest,
+    responses(
+        (status = 200, description = "Weight recorded", body = WeightEntry),
+        (status = 400, description = "Invalid parameters", body = String),
+        (status = 401, description = "Unauthorized", body = String)
+    )
+)]
+pub async fn add_weight_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Json(req): Json<AddWeightRequest>,
+) -> axum::response::Response {
+    match app
+        .athlete_service
+        .add_weight(ctx.uid, req.weight_kg, req.measured_at)
+        .await
+    {
+        Ok(v) => HandlerResponse::<WeightEntry>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = routes::API_USER_WEIGHTS,
+    responses(
+        (status = 200, description = "Weight log, newest first", body = Vec<WeightEntry>),
+        (status = 401, description = "Unauthorized", body = String)
+    )
+)]
+pub async fn list_weights_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+) -> axum::response::Response {
+    match app.athlete_service.list_weights(ctx.uid).await {
+        Ok(v) => HandlerResponse::<Vec<WeightEntry>>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/user/weights/{id}",
+    params(("id" = i64, Path, description = "Weight entry id")),
+    responses(
+        (status = 200, description = "Weight entry deleted", body = UserActionResponse),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "Not found", body = String)
+    )
+)]
+pub async fn delete_weight_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Path(id): Path<i64>,
+) -> axum::response::Response {
+    match app.athlete_service.delete_weight(ctx.uid, id).await {
+        Ok(()) => HandlerResponse::Success(UserActionResponse { success: true }).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
@@ -0,0 +1,124 @@
+use std::convert::Infallible;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use axum::extract::{ConnectInfo, FromRequestParts};
+use axum::http::request::Parts;
+
+use crate::service::session_service::ClientMeta;
+
+/// 可信反向代理的地址段，以请求扩展的形式注入，供提取客户端 IP 时使用
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies {
+    networks: Vec<(IpAddr, u8)>,
+}
+
+impl TrustedProxies {
+    /// 接受单个地址或 CIDR 网段，无法解析的条目记录告警后忽略
+    pub fn new(entries: &[String]) -> Self {
+        let mut networks = Vec::new();
+        for entry in entries {
+            match parse_network(entry.trim()) {
+                Some(network) => networks.push(network),
+                None => tracing::warn!(entry = %entry, "ignoring invalid trusted proxy entry"),
+            }
+        }
+        Self { networks }
+    }
+
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        let ip = ip.to_canonical();
+        self.networks
+            .iter()
+            .any(|&(network, prefix)| in_network(ip, network, prefix))
+    }
+}
+
+fn parse_network(entry: &str) -> Option<(IpAddr, u8)> {
+    let (addr, prefix) = match entry.split_once('/') {
+        Some((addr, prefix)) => (addr, Some(prefix.parse::<u8>().ok()?)),
+        None => (entry, None),
+    };
+    let addr = addr.parse::<IpAddr>().ok()?.to_canonical();
+    let max = if addr.is_ipv4() { 32 } else { 128 };
+    let prefix = prefix.unwrap_or(max);
+    (prefix <= max).then_some((addr, prefix))
+}
+
+fn in_network(ip: IpAddr, network: IpAddr, prefix: u8) -> bool {
+    match (ip, network) {
+        (IpAddr::V4(ip), IpAddr::V4(network)) => {
+            let mask = u32::MAX.checked_shl(32 - prefix as u32).unwrap_or(0);
+            u32::from(ip) & mask == u32::from(network) & mask
+        }
+        (IpAddr::V6(ip), IpAddr::V6(network)) => {
+            let mask = u128::MAX.checked_shl(128 - prefix as u32).unwrap_or(0);
+            u128::from(ip) & mask == u128::from(network) & mask
+        }
+        _ => false,
+    }
+}
+
+/// 默认取连接的对端地址；对端是可信代理时，取 X-Forwarded-For 中从右往左第一个非可信代理的地址，
+/// 没有该头时取 X-Real-IP。客户端可以随意伪造这些头，因此不信任直连请求携带的转发头
+pub fn client_ip(parts: &Parts) -> String {
+    let Some(peer) = parts
+        .extensions
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip().to_canonical())
+    else {
+        return String::new();
+    };
+    let Some(trusted) = parts
+        .extensions
+        .get::<Arc<TrustedProxies>>()
+        .filter(|trusted| trusted.contains(peer))
+    else {
+        return peer.to_string();
+    };
+    let header = |name: &str| {
+        parts
+            .headers
+            .get_all(name)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .filter_map(|v| v.trim().parse::<IpAddr>().ok())
+            .map(|ip| ip.to_canonical())
+            .collect::<Vec<_>>()
+    };
+    let forwarded = header("x-forwarded-for");
+    if !forwarded.is_empty() {
+        // 整条链都是可信代理时，最左侧的地址就是最初的客户端
+        let ip = forwarded
+            .iter()
+            .rev()
+            .find(|ip| !trusted.contains(**ip))
+            .unwrap_or(&forwarded[0]);
+        return ip.to_string();
+    }
+    header("x-real-ip")
+        .first()
+        .map(|ip| ip.to_string())
+        .unwrap_or_else(|| peer.to_string())
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for ClientMeta {
+    type Rejection = Infallible;
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let user_agent = parts
+            .headers
+            .get(axum::http::header::USER_AGENT)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("")
+            .chars()
+            .take(512)
+            .collect();
+        Ok(ClientMeta {
+            ip: client_ip(parts),
+            user_agent,
+        })
+    }
+}
@@ -0,0 +1,451 @@
+use axum::extract::{Json, Path, State};
+use axum::response::IntoResponse;
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+use super::jwt::Context;
+use super::response::{HandlerResponse, error_response};
+use super::user_handler::UserActionResponse;
+use crate::app::{AppState, routes};
+use crate::model::club::{
+    Challenge, ChallengeMetric, ClubInvite, ClubMemberView, ClubView, Leaderboard,
+};
+use crate::model::sport::SportType;
+use crate::service::club_service::NewChallenge;
+use crate::service::common::ServiceError;
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, ToSchema)]
+pub struct CreateClubRequest {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, ToSchema)]
+pub struct JoinClubRequest {
+    pub code: String,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, ToSchema)]
+pub struct InviteMemberRequest {
+    pub uid: i32,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, ToSchema)]
+pub struct SetMemberRoleRequest {
+    /// owner 或 member
+    pub role: String,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, ToSchema)]
+pub struct CreateChallengeRequest {
+    pub name: String,
+    pub metric: ChallengeMetric,
+    /// 只统计该类型的运动，为空时统计全部
+    pub sport_type: Option<SportType>,
+    pub start_time: i64,
+    pub end_time: i64,
+}
+
+fn action_response(result: Result<(), ServiceError>) -> axum::response::Response {
+    match result {
+        Ok(()) => HandlerResponse::Success(UserActionResponse { success: true }).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+fn club_response(result: Result<ClubView, ServiceError>) -> axum::response::Response {
+    match result {
+        Ok(v) => HandlerResponse::<ClubView>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = routes::API_CLUBS,
+    request_body = CreateClubRequest,
+    responses(
+        (status = 200, description = "Club created with the caller as owner", body = ClubView),
+        (status = 400, description = "Invalid parameters", body = String),
+        (status = 401, description = "Unauthorized", body = String)
+    )
+)]
+pub async fn create_club_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Json(req): Json<CreateClubRequest>,
+) -> axum::response::Response {
+    club_response(
+        app.club_service
+            .create(ctx.uid, &req.name, &req.description)
+            .await,
+    )
+}
+
+#[utoipa::path(
+    get,
+    path = routes::API_CLUBS,
+    responses(
+        (status = 200, description = "Clubs the caller belongs to", body = Vec<ClubView>),
+        (status = 401, description = "Unauthorized", body = String)
+    )
+)]
+pub async fn list_clubs_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+) -> axum::response::Response {
+    match app.club_service.list(ctx.uid).await {
+        Ok(v) => HandlerResponse::<Vec<ClubView>>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = routes::API_CLUBS_JOIN,
+    request_body = JoinClubRequest,
+    responses(
+        (status = 200, description = "Joined the club", body = ClubView),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "Unknown join code", body = String)
+    )
+)]
+pub async fn join_club_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Json(req): Json<JoinClubRequest>,
+) -> axum::response::Response {
+    club_response(app.club_service.join(ctx.uid, &req.code).await)
+}
+
+#[utoipa::path(
+    get,
+    path = routes::API_CLUBS_INVITES,
+    responses(
+        (status = 200, description = "Pending invites for the caller", body = Vec<ClubInvite>),
+        (status = 401, description = "Unauthorized", body = String)
+    )
+)]
+pub async fn list_club_invites_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+) -> axum::response::Response {
+    match app.club_service.invites(ctx.uid).await {
+        Ok(v) => HandlerResponse::<Vec<ClubInvite>>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/clubs/{id}",
+    params(("id" = i64, Path, description = "Club id")),
+    responses(
+        (status = 200, description = "Club details; the join code is only shown to owners", body = ClubView),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "Not found or not a member", body = String)
+    )
+)]
+pub async fn get_club_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Path(id): Path<i64>,
+) -> axum::response::Response {
+    club_response(app.club_service.get(ctx.uid, id).await)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/clubs/{id}",
+    params(("id" = i64, Path, description = "Club id")),
+    responses(
+        (status = 200, description = "Club deleted with its challenges", body = UserActionResponse),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Owners only", body = String),
+        (status = 404, description = "Not found", body = String)
+    )
+)]
+pub async fn delete_club_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Path(id): Path<i64>,
+) -> axum::response::Response {
+    action_response(app.club_service.delete(ctx.uid, id).await)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/clubs/{id}/join-code",
+    params(("id" = i64, Path, description = "Club id")),
+    responses(
+        (status = 200, description = "New join code issued, the old one stops working", body = ClubView),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Owners only", body = String),
+        (status = 404, description = "Not found", body = String)
+    )
+)]
+pub async fn reset_join_code_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Path(id): Path<i64>,
+) -> axum::response::Response {
+    club_response(app.club_service.reset_join_code(ctx.uid, id).await)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/clubs/{id}/leave",
+    params(("id" = i64, Path, description = "Club id")),
+    responses(
+        (status = 200, description = "Left the club; a sole remaining owner deletes it", body = UserActionResponse),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "Not found", body = String),
+        (status = 409, description = "The last owner must hand over first", body = String)
+    )
+)]
+pub async fn leave_club_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Path(id): Path<i64>,
+) -> axum::response::Response {
+    action_response(app.club_service.leave(ctx.uid, id).await)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/clubs/{id}/members",
+    params(("id" = i64, Path, description = "Club id")),
+    responses(
+        (status = 200, description = "Members in joining order", body = Vec<ClubMemberView>),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "Not found", body = String)
+    )
+)]
+pub async fn list_club_members_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Path(id): Path<i64>,
+) -> axum::response::Response {
+    match app.club_service.members(ctx.uid, id).await {
+        Ok(v) => HandlerResponse::<Vec<ClubMemberView>>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/clubs/{id}/members/{uid}",
+    params(
+        ("id" = i64, Path, description = "Club id"),
+        ("uid" = i32, Path, description = "Member id")
+    ),
+    request_body = SetMemberRoleRequest,
+    responses(
+        (status = 200, description = "Role updated", body = UserActionResponse),
+        (status = 400, description = "Invalid role", body = String),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Owners only", body = String),
+        (status = 404, description = "Not found", body = String),
+        (status = 409, description = "A club needs at least one owner", body = String)
+    )
+)]
+pub async fn set_club_member_role_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Path((id, uid)): Path<(i64, i32)>,
+    Json(req): Json<SetMemberRoleRequest>,
+) -> axum::response::Response {
+    action_response(app.club_service.set_role(ctx.uid, id, uid, &req.role).await)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/clubs/{id}/members/{uid}",
+    params(
+        ("id" = i64, Path, description = "Club id"),
+        ("uid" = i32, Path, description = "Member id")
+    ),
+    responses(
+        (status = 200, description = "Member removed", body = UserActionResponse),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Owners only", body = String),
+        (status = 404, description = "Not found", body = String)
+    )
+)]
+pub async fn remove_club_member_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Path((id, uid)): Path<(i64, i32)>,
+) -> axum::response::Response {
+    action_response(app.club_service.remove_member(ctx.uid, id, uid).await)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/clubs/{id}/invites",
+    params(("id" = i64, Path, description = "Club id")),
+    request_body = InviteMemberRequest,
+    responses(
+        (status = 200, description = "Invite sent", body = UserActionResponse),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Owners only", body = String),
+        (status = 404, description = "Club or user not found", body = String),
+        (status = 409, description = "Already a member", body = String)
+    )
+)]
+pub async fn invite_club_member_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Path(id): Path<i64>,
+    Json(req): Json<InviteMemberRequest>,
+) -> axum::response::Response {
+    action_response(app.club_service.invite(ctx.uid, id, req.uid).await)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/clubs/{id}/invites/accept",
+    params(("id" = i64, Path, description = "Club id")),
+    responses(
+        (status = 200, description = "Invite accepted", body = ClubView),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "No pending invite", body = String)
+    )
+)]
+pub async fn accept_club_invite_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Path(id): Path<i64>,
+) -> axum::response::Response {
+    club_response(app.club_service.accept_invite(ctx.uid, id).await)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/clubs/{id}/invites/decline",
+    params(("id" = i64, Path, description = "Club id")),
+    responses(
+        (status = 200, description = "Invite declined", body = UserActionResponse),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "No pending invite", body = String)
+    )
+)]
+pub async fn decline_club_invite_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Path(id): Path<i64>,
+) -> axum::response::Response {
+    action_response(app.club_service.decline_invite(ctx.uid, id).await)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/clubs/{id}/challenges",
+    params(("id" = i64, Path, description = "Club id")),
+    request_body = CreateChallengeRequest,
+    responses(
+        (status = 200, description = "Challenge created", body = Challenge),
+        (status = 400, description = "Invalid parameters", body = String),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Owners only", body = String),
+        (status = 404, description = "Not found", body = String)
+    )
+)]
+pub async fn create_challenge_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Path(id): Path<i64>,
+    Json(req): Json<CreateChallengeRequest>,
+) -> axum::response::Response {
+    let challenge = NewChallenge {
+        name: req.name,
+        metric: req.metric,
+        sport_type: req.sport_type,
+        start_time: req.start_time,
+        end_time: req.end_time,
+    };
+    match app
+        .club_service
+        .create_challenge(ctx.uid, id, challenge)
+        .await
+    {
+        Ok(v) => HandlerResponse::<Challenge>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/clubs/{id}/challenges",
+    params(("id" = i64, Path, description = "Club id")),
+    responses(
+        (status = 200, description = "Challenges, latest first", body = Vec<Challenge>),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "Not found", body = String)
+    )
+)]
+pub async fn list_challenges_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Path(id): Path<i64>,
+) -> axum::response::Response {
+    match app.club_service.challenges(ctx.uid, id).await {
+        Ok(v) => HandlerResponse::<Vec<Challenge>>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/clubs/{id}/challenges/{challenge_id}",
+    params(
+        ("id" = i64, Path, description = "Club id"),
+        ("challenge_id" = i64, Path, description = "Challenge id")
+    ),
+    responses(
+        (status = 200, description = "Challenge deleted", body = UserActionResponse),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Owners only", body = String),
+        (status = 404, description = "Not found", body = String)
+    )
+)]
+pub async fn delete_challenge_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Path((id, challenge_id)): Path<(i64, i64)>,
+) -> axum::response::Response {
+    action_response(
+        app.club_service
+            .delete_challenge(ctx.uid, id, challenge_id)
+            .await,
+    )
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/clubs/{id}/challenges/{challenge_id}/leaderboard",
+    params(
+        ("id" = i64, Path, description = "Club id"),
+        ("challenge_id" = i64, Path, description = "Challenge id")
+    ),
+    responses(
+        (status = 200, description = "Members ranked by the challenge metric within its window", body = Leaderboard),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "Not found", body = String)
+    )
+)]
+pub async fn challenge_leaderboard_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Path((id, challenge_id)): Path<(i64, i64)>,
+) -> axum::response::Response {
+    match app
+        .club_service
+        .leaderboard(ctx.uid, id, challenge_id)
+        .await
+    {
+        Ok(v) => HandlerResponse::<Leaderboard>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
@@ -0,0 +1,227 @@
+use axum::extract::{Json, Path, State};
+use axum::response::IntoResponse;
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+use super::jwt::Context;
+use super::response::{HandlerResponse, error_response};
+use super::user_handler::UserActionResponse;
+use crate::app::{AppState, routes};
+use crate::model::audit::{AUDIT_COACH_GRANT, AUDIT_COACH_REVOKE};
+use crate::model::coach::CoachGrantView;
+use crate::service::session_service::ClientMeta;
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, ToSchema)]
+pub struct CreateCoachGrantRequest {
+    pub coach_uid: i32,
+    /// sports:read、stats:read、comment 中的一个或多个
+    pub scopes: Vec<String>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, ToSchema)]
+pub struct UpdateCoachGrantRequest {
+    pub scopes: Vec<String>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, ToSchema)]
+pub struct CoachGrantScopes {
+    pub scopes: Vec<String>,
+}
+
+#[utoipa::path(
+    post,
+    path = routes::API_COACH_GRANTS,
+    request_body = CreateCoachGrantRequest,
+    responses(
+        (status = 200, description = "Pending grant created, it takes effect once the coach accepts", body = CoachGrantView),
+        (status = 400, description = "Invalid scopes", body = String),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "Coach not found", body = String),
+        (status = 409, description = "Grant already exists", body = String)
+    )
+)]
+pub async fn create_coach_grant_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    client: ClientMeta,
+    Json(req): Json<CreateCoachGrantRequest>,
+) -> axum::response::Response {
+    match app
+        .coach_service
+        .invite(ctx.uid, req.coach_uid, &req.scopes)
+        .await
+    {
+        Ok(v) => {
+            app.audit_service
+                .record(
+                    Some(ctx.uid),
+                    AUDIT_COACH_GRANT,
+                    &client,
+                    serde_json::json!({ "coach_uid": req.coach_uid, "scopes": v.scopes }),
+                )
+                .await;
+            HandlerResponse::<CoachGrantView>::Success(v).into_response()
+        }
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = routes::API_COACH_GRANTS,
+    responses(
+        (status = 200, description = "Coaches the current user has granted access to", body = Vec<CoachGrantView>),
+        (status = 401, description = "Unauthorized", body = String)
+    )
+)]
+pub async fn list_coach_grants_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+) -> axum::response::Response {
+    match app.coach_service.coaches(ctx.uid).await {
+        Ok(v) => HandlerResponse::<Vec<CoachGrantView>>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/coach/grants/{uid}",
+    params(("uid" = i32, Path, description = "Coach user id")),
+    request_body = UpdateCoachGrantRequest,
+    responses(
+        (status = 200, description = "Scopes replaced", body = CoachGrantScopes),
+        (status = 400, description = "Invalid scopes", body = String),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "Not found", body = String)
+    )
+)]
+pub async fn update_coach_grant_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    client: ClientMeta,
+    Path(coach_uid): Path<i32>,
+    Json(req): Json<UpdateCoachGrantRequest>,
+) -> axum::response::Response {
+    match app
+        .coach_service
+        .set_scopes(ctx.uid, coach_uid, &req.scopes)
+        .await
+    {
+        Ok(scopes) => {
+            app.audit_service
+                .record(
+                    Some(ctx.uid),
+                    AUDIT_COACH_GRANT,
+                    &client,
+                    serde_json::json!({ "coach_uid": coach_uid, "scopes": scopes }),
+                )
+                .await;
+            HandlerResponse::Success(CoachGrantScopes { scopes }).into_response()
+        }
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/coach/grants/{uid}",
+    params(("uid" = i32, Path, description = "Coach user id")),
+    responses(
+        (status = 200, description = "Grant revoked, effective immediately", body = UserActionResponse),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "Not found", body = String)
+    )
+)]
+pub async fn revoke_coach_grant_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    client: ClientMeta,
+    Path(coach_uid): Path<i32>,
+) -> axum::response::Response {
+    match app.coach_service.revoke(ctx.uid, coach_uid).await {
+        Ok(()) => {
+            app.audit_service
+                .record(
+                    Some(ctx.uid),
+                    AUDIT_COACH_REVOKE,
+                    &client,
+                    serde_json::json!({ "coach_uid": coach_uid }),
+                )
+                .await;
+            HandlerResponse::Success(UserActionResponse { success: true }).into_response()
+        }
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = routes::API_COACH_ATHLETES,
+    responses(
+        (status = 200, description = "Athletes who granted the current user access, including pending invitations", body = Vec<CoachGrantView>),
+        (status = 401, description = "Unauthorized", body = String)
+    )
+)]
+pub async fn list_coach_athletes_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+) -> axum::response::Response {
+    match app.coach_service.athletes(ctx.uid).await {
+        Ok(v) => HandlerResponse::<Vec<CoachGrantView>>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/coach/athletes/{uid}/accept",
+    params(("uid" = i32, Path, description = "Athlete user id")),
+    responses(
+        (status = 200, description = "Grant accepted", body = UserActionResponse),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "No pending grant", body = String)
+    )
+)]
+pub async fn accept_coach_grant_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Path(athlete_uid): Path<i32>,
+) -> axum::response::Response {
+    match app.coach_service.accept(ctx.uid, athlete_uid).await {
+        Ok(()) => HandlerResponse::Success(UserActionResponse { success: true }).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/coach/athletes/{uid}",
+    params(("uid" = i32, Path, description = "Athlete user id")),
+    responses(
+        (status = 200, description = "Invitation declined or coaching stopped", body = UserActionResponse),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "Not found", body = String)
+    )
+)]
+pub async fn leave_coach_grant_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    client: ClientMeta,
+    Path(athlete_uid): Path<i32>,
+) -> axum::response::Response {
+    match app.coach_service.leave(ctx.uid, athlete_uid).await {
+        Ok(()) => {
+            app.audit_service
+                .record(
+                    Some(ctx.uid),
+                    AUDIT_COACH_REVOKE,
+                    &client,
+                    serde_json::json!({ "athlete_uid": athlete_uid }),
+                )
+                .await;
+            HandlerResponse::Success(UserActionResponse { success: true }).into_response()
+        }
+        Err(e) => error_response(e.code, e.message),
+    }
+}
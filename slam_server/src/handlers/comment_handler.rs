@@ -0,0 +1,197 @@
+use axum::extract::{Json, Path, State};
+use axum::response::IntoResponse;
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+use super::jwt::Context;
+use super::response::{HandlerResponse, error_response};
+use super::user_handler::UserActionResponse;
+use crate::app::AppState;
+use crate::model::comment::{CommentView, ReactionSummary};
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, ToSchema)]
+pub struct CommentRequest {
+    /// 1 到 500 个字符
+    pub content: String,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/social/sports/{id}/comments",
+    params(("id" = i32, Path, description = "Sport id")),
+    responses(
+        (status = 200, description = "Comments in posting order", body = Vec<CommentView>),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "Sport not found or not visible", body = String)
+    )
+)]
+pub async fn list_comments_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Path(sport_id): Path<i32>,
+) -> axum::response::Response {
+    match app.comment_service.list(ctx.uid, sport_id).await {
+        Ok(v) => HandlerResponse::<Vec<CommentView>>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/social/sports/{id}/comments",
+    params(("id" = i32, Path, description = "Sport id")),
+    request_body = CommentRequest,
+    responses(
+        (status = 200, description = "Comment posted, the sport owner is notified", body = CommentView),
+        (status = 400, description = "Empty or too long", body = String),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "Sport not found or not visible", body = String)
+    )
+)]
+pub async fn create_comment_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Path(sport_id): Path<i32>,
+    Json(req): Json<CommentRequest>,
+) -> axum::response::Response {
+    match app
+        .comment_service
+        .create(ctx.uid, sport_id, &req.content)
+        .await
+    {
+        Ok(v) => HandlerResponse::<CommentView>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/social/sports/{id}/comments/{comment_id}",
+    params(
+        ("id" = i32, Path, description = "Sport id"),
+        ("comment_id" = i64, Path, description = "Comment id")
+    ),
+    request_body = CommentRequest,
+    responses(
+        (status = 200, description = "Comment edited", body = UserActionResponse),
+        (status = 400, description = "Empty or too long", body = String),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Not the author", body = String),
+        (status = 404, description = "Not found", body = String)
+    )
+)]
+pub async fn update_comment_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Path((sport_id, comment_id)): Path<(i32, i64)>,
+    Json(req): Json<CommentRequest>,
+) -> axum::response::Response {
+    match app
+        .comment_service
+        .update(ctx.uid, sport_id, comment_id, &req.content)
+        .await
+    {
+        Ok(()) => HandlerResponse::Success(UserActionResponse { success: true }).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/social/sports/{id}/comments/{comment_id}",
+    params(
+        ("id" = i32, Path, description = "Sport id"),
+        ("comment_id" = i64, Path, description = "Comment id")
+    ),
+    responses(
+        (status = 200, description = "Comment deleted by its author or the sport owner", body = UserActionResponse),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Neither the author nor the sport owner", body = String),
+        (status = 404, description = "Not found", body = String)
+    )
+)]
+pub async fn delete_comment_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Path((sport_id, comment_id)): Path<(i32, i64)>,
+) -> axum::response::Response {
+    match app
+        .comment_service
+        .delete(ctx.uid, sport_id, comment_id)
+        .await
+    {
+        Ok(()) => HandlerResponse::Success(UserActionResponse { success: true }).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/social/sports/{id}/reactions",
+    params(("id" = i32, Path, description = "Sport id")),
+    responses(
+        (status = 200, description = "Reaction counts and the current user's reactions", body = ReactionSummary),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "Sport not found or not visible", body = String)
+    )
+)]
+pub async fn list_reactions_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Path(sport_id): Path<i32>,
+) -> axum::response::Response {
+    match app.comment_service.reactions(ctx.uid, sport_id).await {
+        Ok(v) => HandlerResponse::<ReactionSummary>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/social/sports/{id}/reactions/{kind}",
+    params(
+        ("id" = i32, Path, description = "Sport id"),
+        ("kind" = String, Path, description = "kudos, fire or clap")
+    ),
+    responses(
+        (status = 200, description = "Reaction added; repeating it is a no-op", body = ReactionSummary),
+        (status = 400, description = "Unknown reaction", body = String),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "Sport not found or not visible", body = String)
+    )
+)]
+pub async fn add_reaction_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Path((sport_id, kind)): Path<(i32, String)>,
+) -> axum::response::Response {
+    match app.comment_service.react(ctx.uid, sport_id, &kind).await {
+        Ok(v) => HandlerResponse::<ReactionSummary>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/social/sports/{id}/reactions/{kind}",
+    params(
+        ("id" = i32, Path, description = "Sport id"),
+        ("kind" = String, Path, description = "kudos, fire or clap")
+    ),
+    responses(
+        (status = 200, description = "Reaction removed", body = ReactionSummary),
+        (status = 400, description = "Unknown reaction", body = String),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "Sport not found or not visible", body = String)
+    )
+)]
+pub async fn remove_reaction_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Path((sport_id, kind)): Path<(i32, String)>,
+) -> axum::response::Response {
+    match app.comment_service.unreact(ctx.uid, sport_id, &kind).await {
+        Ok(v) => HandlerResponse::<ReactionSummary>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
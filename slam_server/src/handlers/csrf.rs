@@ -0,0 +1,142 @@
+use std::str::FromStr;
+use std::sync::Arc;
+
+use axum::extract::{Request, State};
+use axum::http::header::{AUTHORIZATION, HOST, ORIGIN, REFERER, SET_COOKIE};
+use axum::http::uri::Authority;
+use axum::http::{HeaderMap, HeaderValue, Method};
+use axum::middleware::Next;
+use axum::response::Response;
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use rand::RngCore;
+use reqwest::Url;
+
+use super::jwt::{SESSION_COOKIE, cookie_value};
+use super::response::error_response;
+use crate::config::CsrfConfig;
+
+/// 双重提交令牌的 cookie，前端需能读取，因此不设 HttpOnly
+pub const CSRF_COOKIE: &str = "slam_csrf";
+/// 前端回传令牌的请求头
+pub const CSRF_HEADER: &str = "x-csrf-token";
+
+/// 跨站请求伪造防护：写请求的 Origin/Referer 须为本站或配置允许的来源；
+/// 开启双重提交后，带会话 cookie 的写请求还须在请求头中回传令牌。使用 Bearer 令牌的请求不受影响
+pub struct CsrfGuard {
+    allowed_origins: Vec<String>,
+    double_submit: bool,
+    cookie_max_age: u64,
+}
+
+impl CsrfGuard {
+    pub fn new(config: &CsrfConfig, cookie_max_age: u64) -> Self {
+        let allowed_origins = config
+            .allowed_origins
+            .iter()
+            .filter_map(|o| match Url::parse(o) {
+                Ok(url) => Some(url.origin().ascii_serialization()),
+                Err(e) => {
+                    tracing::warn!(origin = %o, error = %e, "ignoring invalid csrf allowed origin");
+                    None
+                }
+            })
+            .collect();
+        Self {
+            allowed_origins,
+            double_submit: config.double_submit,
+            cookie_max_age,
+        }
+    }
+
+    fn check(&self, headers: &HeaderMap) -> Result<(), &'static str> {
+        // 浏览器发起的跨站写请求总会带 Origin，个别情况下只有 Referer；两者都没有的不是浏览器请求
+        let source = headers.get(ORIGIN).or_else(|| headers.get(REFERER));
+        if let Some(source) = source
+            && !self.source_allowed(source.to_str().unwrap_or(""), headers)
+        {
+            return Err("origin not allowed");
+        }
+        if self.double_submit && cookie_value(headers, SESSION_COOKIE).is_some() {
+            let cookie = cookie_value(headers, CSRF_COOKIE);
+            let header = headers.get(CSRF_HEADER).and_then(|v| v.to_str().ok());
+            match (cookie, header) {
+                (Some(c), Some(h)) if constant_time_eq(c.as_bytes(), h.as_bytes()) => {}
+                _ => return Err("csrf token mismatch"),
+            }
+        }
+        Ok(())
+    }
+
+    fn source_allowed(&self, source: &str, headers: &HeaderMap) -> bool {
+        // `Origin: null` 等无法解析的来源一律拒绝
+        let Ok(url) = Url::parse(source) else {
+            return false;
+        };
+        let origin = url.origin().ascii_serialization();
+        self.allowed_origins.contains(&origin) || same_host(&url, headers)
+    }
+
+    fn issue_cookie(&self) -> Option<HeaderValue> {
+        let mut raw = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut raw);
+        let token = URL_SAFE_NO_PAD.encode(raw);
+        HeaderValue::from_str(&format!(
+            "{CSRF_COOKIE}={token}; Path=/; SameSite=Lax; Max-Age={}",
+            self.cookie_max_age
+        ))
+        .ok()
+    }
+}
+
+/// 来源与请求的 Host 是否一致；反向代理只转发主机名时不比较端口
+fn same_host(url: &Url, headers: &HeaderMap) -> bool {
+    let Some(host) = headers
+        .get(HOST)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| Authority::from_str(v).ok())
+    else {
+        return false;
+    };
+    let Some(source_host) = url.host_str() else {
+        return false;
+    };
+    source_host.eq_ignore_ascii_case(host.host())
+        && host
+            .port_u16()
+            .is_none_or(|port| Some(port) == url.port_or_known_default())
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn is_bearer(headers: &HeaderMap) -> bool {
+    headers
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("Bearer "))
+}
+
+pub async fn csrf_protection(
+    State(guard): State<Arc<CsrfGuard>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let unsafe_method = !matches!(
+        *req.method(),
+        Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE
+    );
+    if unsafe_method
+        && !is_bearer(req.headers())
+        && let Err(reason) = guard.check(req.headers())
+    {
+        tracing::warn!(method = %req.method(), path = %req.uri().path(), reason, "csrf check failed");
+        return error_response(403, "跨站请求校验失败，请刷新页面后重试".to_string());
+    }
+    let issue = guard.double_submit && cookie_value(req.headers(), CSRF_COOKIE).is_none();
+    let mut resp = next.run(req).await;
+    if issue && let Some(cookie) = guard.issue_cookie() {
+        resp.headers_mut().append(SET_COOKIE, cookie);
+    }
+    resp
+}
@@ -1,86 +1,260 @@
 use crate::app::AppState;
+use crate::app::routes;
+use crate::model::audit::AUDIT_COACH_ACCESS;
+use crate::service::session_service::ClientMeta;
 use async_trait::async_trait;
-use axum::extract::FromRequestParts;
-use axum::http::{HeaderMap, StatusCode};
-use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, encode};
+use axum::extract::{FromRequestParts, MatchedPath, Request, State};
+use axum::http::header::{AUTHORIZATION, SET_COOKIE};
+use axum::http::{HeaderMap, HeaderValue, StatusCode};
+use axum::middleware::Next;
+use axum::response::Response;
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{Header, Validation, decode, decode_header, encode};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use super::jwt_keys::JwtKeySet;
+
+pub const SESSION_COOKIE: &str = "slam";
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Claims {
     pub uid: i32,
+    /// 会话 id，对应 sessions 表主键；旧版 token 没有该字段，会被视为无效
+    #[serde(default)]
+    pub jti: String,
     pub iat: usize,
     pub exp: usize,
 }
 
 pub struct Jwt {
     ttl_seconds: u64,
-    secret: String,
+    keys: JwtKeySet,
 }
 
 impl Jwt {
-    pub fn new(ttl_seconds: u64, secret: String) -> Self {
-        Self {
-            ttl_seconds,
-            secret,
-        }
+    pub fn new(ttl_seconds: u64, keys: JwtKeySet) -> Self {
+        Self { ttl_seconds, keys }
     }
 
-    pub fn create_token(&self, uid: i32) -> Result<String, String> {
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .map_err(|e| e.to_string())?
-            .as_secs() as usize;
+    pub fn ttl_seconds(&self) -> u64 {
+        self.ttl_seconds
+    }
+
+    pub fn create_token(&self, uid: i32, jti: &str) -> Result<String, String> {
+        let now = now_seconds()?;
         let exp = now + self.ttl_seconds as usize;
-        let claims = Claims { uid, iat: now, exp };
-        let header = Header::new(Algorithm::HS256);
-        encode(
-            &header,
-            &claims,
-            &EncodingKey::from_secret(self.secret.as_bytes()),
-        )
-        .map_err(|e| e.to_string())
+        let claims = Claims {
+            uid,
+            jti: jti.to_string(),
+            iat: now,
+            exp,
+        };
+        let (key, encoding) = self.keys.signing_key();
+        let mut header = Header::new(key.algorithm);
+        header.kid = Some(key.kid.clone());
+        encode(&header, &claims, encoding).map_err(|e| e.to_string())
     }
 
     pub fn verify_token(&self, token: &str) -> Result<Claims, String> {
-        let mut validation = Validation::new(Algorithm::HS256);
+        let header = decode_header(token).map_err(|e| e.to_string())?;
+        let key = self
+            .keys
+            .find(header.kid.as_deref())
+            .ok_or_else(|| "token签名密钥未知".to_string())?;
+        // 只接受该密钥自己的算法，防止用公钥当 HMAC 密钥伪造
+        let mut validation = Validation::new(key.algorithm);
         validation.validate_exp = false;
-        let data = decode::<Claims>(
-            token,
-            &DecodingKey::from_secret(self.secret.as_bytes()),
-            &validation,
-        )
-        .map_err(|e| e.to_string())?;
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .map_err(|e| e.to_string())?
-            .as_secs() as usize;
-        if data.claims.exp <= now {
+        let data =
+            decode::<Claims>(token, key.decoding(), &validation).map_err(|e| e.to_string())?;
+        if data.claims.exp <= now_seconds()? {
             return Err("token已过期".to_string());
         }
         Ok(data.claims)
     }
 
-    pub fn create_context_from_cookie(&self, headers: &HeaderMap) -> Result<Context, String> {
-        let cookie_header = headers
-            .get("cookie")
-            .and_then(|v| v.to_str().ok())
-            .unwrap_or("");
-        let token = cookie_header
-            .split(';')
-            .map(|s| s.trim())
-            .find_map(|pair| pair.strip_prefix("slam=").map(|v| v.to_string()));
-        match token {
-            Some(t) => self.verify_token(&t).map(|c| Context { uid: c.uid }),
+    pub fn claims_from_cookie(&self, headers: &HeaderMap) -> Result<Claims, String> {
+        match cookie_value(headers, SESSION_COOKIE) {
+            Some(t) => self.verify_token(t),
             None => Err("未登录或token无效".to_string()),
         }
     }
+
+    /// 对外发布的验证公钥
+    pub fn jwks(&self) -> JwkSet {
+        self.keys.jwks()
+    }
+
+    /// 剩余有效期不足一半时需要续期
+    fn needs_refresh(&self, claims: &Claims) -> bool {
+        match now_seconds() {
+            Ok(now) => claims.exp.saturating_sub(now) < self.ttl_seconds as usize / 2,
+            Err(_) => false,
+        }
+    }
+}
+
+/// 读取请求中指定名称的 cookie，值为空时视为不存在
+pub fn cookie_value<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get("cookie")
+        .and_then(|v| v.to_str().ok())?
+        .split(';')
+        .map(|s| s.trim())
+        .find_map(|pair| {
+            pair.strip_prefix(name)
+                .and_then(|rest| rest.strip_prefix('='))
+        })
+        .filter(|t| !t.is_empty())
+}
+
+pub fn session_cookie(token: &str, max_age: u64) -> String {
+    format!("{SESSION_COOKIE}={token}; Path=/; HttpOnly; SameSite=Lax; Max-Age={max_age}")
+}
+
+fn now_seconds() -> Result<usize, String> {
+    Ok(SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| e.to_string())?
+        .as_secs() as usize)
 }
 
 #[derive(Clone)]
 pub struct Context {
     pub uid: i32,
+    /// 当前请求所属的会话 id，使用个人访问令牌时为空
+    pub sid: String,
+    /// 教练代运动员访问时为运动员的 uid，见 [`ACTING_FOR_HEADER`]
+    pub acting_for: Option<i32>,
+}
+
+impl Context {
+    /// 读取数据时使用的 uid：代运动员访问时为运动员，否则为当前用户
+    pub fn data_uid(&self) -> i32 {
+        self.acting_for.unwrap_or(self.uid)
+    }
+}
+
+/// 教练代运动员访问时携带的请求头，值为运动员的 uid
+pub const ACTING_FOR_HEADER: &str = "x-acting-for";
+
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::trim)
+}
+
+/// 个人访问令牌鉴权：令牌需有效，且具备当前路由要求的权限范围
+async fn context_from_bearer(
+    parts: &axum::http::request::Parts,
+    state: &AppState,
+    token: &str,
+) -> Result<Context, (StatusCode, String)> {
+    let token = state
+        .access_token_service
+        .authenticate(token)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::from_u16(e.code as u16).unwrap_or(StatusCode::UNAUTHORIZED),
+                e.message,
+            )
+        })?;
+    let required = parts
+        .extensions
+        .get::<MatchedPath>()
+        .and_then(|p| routes::token_scope(p.as_str()));
+    match required {
+        None => Err((
+            StatusCode::FORBIDDEN,
+            "该接口不支持个人访问令牌".to_string(),
+        )),
+        Some(scope) if !token.scopes.iter().any(|s| s == scope) => Err((
+            StatusCode::FORBIDDEN,
+            format!("访问令牌缺少权限范围: {scope}"),
+        )),
+        Some(_) => Ok(Context {
+            uid: token.uid,
+            sid: String::new(),
+            acting_for: None,
+        }),
+    }
+}
+
+async fn authenticate(
+    parts: &axum::http::request::Parts,
+    state: &AppState,
+) -> Result<Context, (StatusCode, String)> {
+    if let Some(token) = bearer_token(&parts.headers) {
+        return context_from_bearer(parts, state, token).await;
+    }
+    let claims = state
+        .jwt
+        .claims_from_cookie(&parts.headers)
+        .map_err(|e| (StatusCode::UNAUTHORIZED, e))?;
+    match state
+        .session_service
+        .validate(claims.uid, &claims.jti)
+        .await
+    {
+        Ok(_) => Ok(Context {
+            uid: claims.uid,
+            sid: claims.jti,
+            acting_for: None,
+        }),
+        Err(e) => Err((
+            StatusCode::from_u16(e.code as u16).unwrap_or(StatusCode::UNAUTHORIZED),
+            e.message,
+        )),
+    }
+}
+
+/// 代运动员访问：路由需支持代理访问，且运动员授予的有效授权包含该路由所需范围；每次访问都记审计日志
+async fn act_for(
+    parts: &mut axum::http::request::Parts,
+    state: &Arc<AppState>,
+    mut ctx: Context,
+    athlete: i32,
+) -> Result<Context, (StatusCode, String)> {
+    if athlete == ctx.uid {
+        return Ok(ctx);
+    }
+    let path = parts
+        .extensions
+        .get::<MatchedPath>()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_default();
+    let Some(scope) = routes::coach_scope(&path) else {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "该接口不支持代运动员访问".to_string(),
+        ));
+    };
+    state
+        .coach_service
+        .authorize(ctx.uid, athlete, scope)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::from_u16(e.code as u16).unwrap_or(StatusCode::FORBIDDEN),
+                e.message,
+            )
+        })?;
+    let Ok(client) = ClientMeta::from_request_parts(parts, state).await;
+    state
+        .audit_service
+        .record(
+            Some(athlete),
+            AUDIT_COACH_ACCESS,
+            &client,
+            serde_json::json!({ "coach_uid": ctx.uid, "path": path, "scope": scope }),
+        )
+        .await;
+    ctx.acting_for = Some(athlete);
+    Ok(ctx)
 }
 
 #[async_trait]
@@ -90,9 +264,87 @@ impl FromRequestParts<Arc<AppState>> for Context {
         parts: &mut axum::http::request::Parts,
         state: &Arc<AppState>,
     ) -> Result<Self, Self::Rejection> {
-        match state.jwt.create_context_from_cookie(&parts.headers) {
-            Ok(ctx) => Ok(ctx),
-            Err(e) => Err((StatusCode::UNAUTHORIZED, e)),
-        }
+        let ctx = authenticate(parts, state).await?;
+        let Some(value) = parts.headers.get(ACTING_FOR_HEADER) else {
+            return Ok(ctx);
+        };
+        let athlete = value
+            .to_str()
+            .ok()
+            .and_then(|v| v.trim().parse::<i32>().ok())
+            .ok_or_else(|| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    format!("{ACTING_FOR_HEADER} 需为运动员的 uid"),
+                )
+            })?;
+        act_for(parts, state, ctx, athlete).await
+    }
+}
+
+/// 管理接口的登录态：在 [`Context`] 基础上要求当前用户是未禁用的管理员
+#[derive(Clone)]
+pub struct AdminContext(pub Context);
+
+#[async_trait]
+impl FromRequestParts<Arc<AppState>> for AdminContext {
+    type Rejection = (StatusCode, String);
+
+    async fn from_request_parts(
+        parts: &mut axum::http::request::Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        let ctx = Context::from_request_parts(parts, state).await?;
+        state
+            .admin_service
+            .require_admin(ctx.uid)
+            .await
+            .map_err(|e| {
+                (
+                    StatusCode::from_u16(e.code as u16).unwrap_or(StatusCode::FORBIDDEN),
+                    e.message,
+                )
+            })?;
+        Ok(AdminContext(ctx))
+    }
+}
+
+/// 滑动续期中间件：cookie 中的 token 过了半衰期且会话仍有效时，延长会话并下发新 token
+pub async fn refresh_session(
+    State(state): State<Arc<AppState>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let renewed = match state.jwt.claims_from_cookie(req.headers()) {
+        Ok(claims) if state.jwt.needs_refresh(&claims) => renew_token(&state, &claims).await,
+        _ => None,
+    };
+    let mut resp = next.run(req).await;
+    // 登录、注销等接口自己设置了 cookie 时以接口为准
+    let cookie_prefix = format!("{SESSION_COOKIE}=");
+    let handler_set_cookie = resp
+        .headers()
+        .get_all(SET_COOKIE)
+        .iter()
+        .any(|v| v.to_str().is_ok_and(|s| s.starts_with(&cookie_prefix)));
+    if let Some(token) = renewed
+        && !handler_set_cookie
+        && let Ok(val) = HeaderValue::from_str(&session_cookie(&token, state.jwt.ttl_seconds()))
+    {
+        resp.headers_mut().append(SET_COOKIE, val);
+    }
+    resp
+}
+
+async fn renew_token(state: &AppState, claims: &Claims) -> Option<String> {
+    state
+        .session_service
+        .validate(claims.uid, &claims.jti)
+        .await
+        .ok()?;
+    if let Err(e) = state.session_service.extend(&claims.jti).await {
+        tracing::warn!(uid = claims.uid, error = %e, "failed to extend session");
+        return None;
     }
+    state.jwt.create_token(claims.uid, &claims.jti).ok()
 }
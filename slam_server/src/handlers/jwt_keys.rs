@@ -0,0 +1,208 @@
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use jsonwebtoken::jwk::{
+    AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm,
+    OctetKeyPairParameters, OctetKeyPairType, PublicKeyUse, RSAKeyParameters, RSAKeyType,
+};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey};
+use ring::signature::{Ed25519KeyPair, KeyPair};
+use rsa::pkcs1::{DecodeRsaPrivateKey, DecodeRsaPublicKey};
+use rsa::pkcs8::{DecodePrivateKey, DecodePublicKey};
+use rsa::traits::PublicKeyParts;
+use rsa::{RsaPrivateKey, RsaPublicKey};
+
+use crate::config::{DEFAULT_JWT_KID, JwtKeyConfig, SecurityConfig};
+
+/// Ed25519 公钥 SubjectPublicKeyInfo 的固定前缀，后接 32 字节公钥
+const ED25519_SPKI_PREFIX: [u8; 12] = [
+    0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00,
+];
+
+pub struct JwtKey {
+    pub kid: String,
+    pub algorithm: Algorithm,
+    encoding: Option<EncodingKey>,
+    decoding: DecodingKey,
+    /// 非对称密钥的公钥，对外发布在 JWKS 中
+    public_jwk: Option<Jwk>,
+}
+
+impl JwtKey {
+    pub fn decoding(&self) -> &DecodingKey {
+        &self.decoding
+    }
+}
+
+/// 按 kid 区分的 JWT 密钥集：一个用于签发的密钥，其余只用于验证，方便轮换时旧 token 继续有效
+pub struct JwtKeySet {
+    keys: Vec<JwtKey>,
+    signing: usize,
+}
+
+impl JwtKeySet {
+    pub fn from_config(security: &SecurityConfig) -> Result<Self, String> {
+        let configs = if security.jwt_keys.is_empty() {
+            vec![JwtKeyConfig {
+                kid: DEFAULT_JWT_KID.to_string(),
+                algorithm: Algorithm::HS256,
+                secret: security.key.clone(),
+                private_key_file: None,
+                public_key_file: None,
+            }]
+        } else {
+            security.jwt_keys.clone()
+        };
+        let mut keys = Vec::with_capacity(configs.len());
+        for config in &configs {
+            if keys.iter().any(|k: &JwtKey| k.kid == config.kid) {
+                return Err(format!("JWT 密钥 id 重复: {}", config.kid));
+            }
+            keys.push(load_key(config).map_err(|e| format!("JWT 密钥 {}: {e}", config.kid))?);
+        }
+        let signing = match &security.jwt_signing_kid {
+            Some(kid) => keys
+                .iter()
+                .position(|k| &k.kid == kid)
+                .ok_or_else(|| format!("找不到签发用的 JWT 密钥: {kid}"))?,
+            None => 0,
+        };
+        if keys[signing].encoding.is_none() {
+            return Err(format!(
+                "JWT 密钥 {} 只配置了公钥，不能用于签发",
+                keys[signing].kid
+            ));
+        }
+        Ok(Self { keys, signing })
+    }
+
+    /// 签发用的密钥及其 EncodingKey
+    pub fn signing_key(&self) -> (&JwtKey, &EncodingKey) {
+        let key = &self.keys[self.signing];
+        (
+            key,
+            key.encoding
+                .as_ref()
+                .expect("signing key has a private part"),
+        )
+    }
+
+    /// 按 token 头部的 kid 查找验证密钥，没有 kid 的旧 token 使用默认密钥
+    pub fn find(&self, kid: Option<&str>) -> Option<&JwtKey> {
+        let kid = kid.unwrap_or(DEFAULT_JWT_KID);
+        self.keys.iter().find(|k| k.kid == kid)
+    }
+
+    /// 所有非对称密钥的公钥；HS256 密钥不会发布
+    pub fn jwks(&self) -> JwkSet {
+        JwkSet {
+            keys: self
+                .keys
+                .iter()
+                .filter_map(|k| k.public_jwk.clone())
+                .collect(),
+        }
+    }
+}
+
+fn load_key(config: &JwtKeyConfig) -> Result<JwtKey, String> {
+    if config.algorithm == Algorithm::HS256 {
+        if config.secret.is_empty() {
+            return Err("HS256 密钥需要配置 secret".to_string());
+        }
+        let secret = config.secret.as_bytes();
+        return Ok(JwtKey {
+            kid: config.kid.clone(),
+            algorithm: Algorithm::HS256,
+            encoding: Some(EncodingKey::from_secret(secret)),
+            decoding: DecodingKey::from_secret(secret),
+            public_jwk: None,
+        });
+    }
+    let (encoding, public_jwk) = match config.algorithm {
+        Algorithm::RS256 => {
+            let (encoding, public) = match (&config.private_key_file, &config.public_key_file) {
+                (Some(path), _) => {
+                    let pem = read_pem(path)?;
+                    let private = RsaPrivateKey::from_pkcs8_pem(&pem)
+                        .or_else(|_| RsaPrivateKey::from_pkcs1_pem(&pem))
+                        .map_err(|e| format!("RSA 私钥无法解析: {e}"))?;
+                    let encoding = EncodingKey::from_rsa_pem(pem.as_bytes())
+                        .map_err(|e| format!("RSA 私钥无法解析: {e}"))?;
+                    (Some(encoding), private.to_public_key())
+                }
+                (None, Some(path)) => {
+                    let pem = read_pem(path)?;
+                    let public = RsaPublicKey::from_public_key_pem(&pem)
+                        .or_else(|_| RsaPublicKey::from_pkcs1_pem(&pem))
+                        .map_err(|e| format!("RSA 公钥无法解析: {e}"))?;
+                    (None, public)
+                }
+                (None, None) => return Err("RS256 密钥需要配置私钥或公钥文件".to_string()),
+            };
+            let params = AlgorithmParameters::RSA(RSAKeyParameters {
+                key_type: RSAKeyType::RSA,
+                n: URL_SAFE_NO_PAD.encode(public.n().to_bytes_be()),
+                e: URL_SAFE_NO_PAD.encode(public.e().to_bytes_be()),
+            });
+            (encoding, public_jwk(config, KeyAlgorithm::RS256, params))
+        }
+        Algorithm::EdDSA => {
+            let (encoding, public) = match (&config.private_key_file, &config.public_key_file) {
+                (Some(path), _) => {
+                    let der = pem_contents(&read_pem(path)?)?;
+                    let pair = Ed25519KeyPair::from_pkcs8_maybe_unchecked(&der)
+                        .map_err(|e| format!("Ed25519 私钥无法解析: {e}"))?;
+                    (
+                        Some(EncodingKey::from_ed_der(&der)),
+                        pair.public_key().as_ref().to_vec(),
+                    )
+                }
+                (None, Some(path)) => {
+                    let der = pem_contents(&read_pem(path)?)?;
+                    let public = der
+                        .strip_prefix(&ED25519_SPKI_PREFIX)
+                        .filter(|k| k.len() == 32)
+                        .ok_or("Ed25519 公钥无法解析")?;
+                    (None, public.to_vec())
+                }
+                (None, None) => return Err("EdDSA 密钥需要配置私钥或公钥文件".to_string()),
+            };
+            let params = AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+                key_type: OctetKeyPairType::OctetKeyPair,
+                curve: EllipticCurve::Ed25519,
+                x: URL_SAFE_NO_PAD.encode(public),
+            });
+            (encoding, public_jwk(config, KeyAlgorithm::EdDSA, params))
+        }
+        other => return Err(format!("不支持的签名算法: {other:?}")),
+    };
+    let decoding = DecodingKey::from_jwk(&public_jwk).map_err(|e| e.to_string())?;
+    Ok(JwtKey {
+        kid: config.kid.clone(),
+        algorithm: config.algorithm,
+        encoding,
+        decoding,
+        public_jwk: Some(public_jwk),
+    })
+}
+
+fn public_jwk(config: &JwtKeyConfig, algorithm: KeyAlgorithm, params: AlgorithmParameters) -> Jwk {
+    Jwk {
+        common: CommonParameters {
+            public_key_use: Some(PublicKeyUse::Signature),
+            key_algorithm: Some(algorithm),
+            key_id: Some(config.kid.clone()),
+            ..Default::default()
+        },
+        algorithm: params,
+    }
+}
+
+fn read_pem(path: &str) -> Result<String, String> {
+    std::fs::read_to_string(path).map_err(|e| format!("读取 {path} 失败: {e}"))
+}
+
+fn pem_contents(text: &str) -> Result<Vec<u8>, String> {
+    pem::parse(text)
+        .map(|p| p.into_contents())
+        .map_err(|e| format!("PEM 格式无效: {e}"))
+}
@@ -1,14 +1,30 @@
-use axum::extract::Json;
+use axum::extract::{Json, State};
+use jsonwebtoken::jwk::JwkSet;
+use std::sync::Arc;
 
-use crate::app::routes;
+use crate::app::{AppState, routes};
 use serde::Serialize;
 use utoipa::ToSchema;
+pub mod admin_handler;
 pub mod ai_handler;
 pub mod ai_job_handler;
+pub mod athlete_handler;
+pub mod client;
+pub mod club_handler;
+pub mod coach_handler;
+pub mod comment_handler;
+pub mod csrf;
 pub mod jwt;
+pub mod jwt_keys;
+pub mod notification_handler;
+pub mod oidc_handler;
 pub mod response;
+pub mod share_handler;
+pub mod social_handler;
 pub mod sport_handler;
+pub mod two_factor_handler;
 pub mod user_handler;
+pub mod webhook_handler;
 
 // 定义响应数据结构
 #[derive(Serialize, ToSchema)]
@@ -50,6 +66,18 @@ pub async fn get_status() -> Json<ApiResponse> {
     Json(response)
 }
 
+/// 验证 slam token 的公钥（RFC 7517），供其他服务按 kid 选择密钥；HS256 密钥不会出现在这里
+#[utoipa::path(
+    get,
+    path = routes::API_JWKS,
+    responses(
+        (status = 200, description = "JSON Web Key Set of the asymmetric token signing keys")
+    )
+)]
+pub async fn jwks(State(app): State<Arc<AppState>>) -> Json<JwkSet> {
+    Json(app.jwt.jwks())
+}
+
 // 用户相关 handler 移至 user_handler / AI handler 移至 ai_handler
 
 // 用户相关 handler 移至 user_handler 模块
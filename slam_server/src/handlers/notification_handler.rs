@@ -0,0 +1,109 @@
+use axum::extract::{Path, Query, State};
+use axum::response::IntoResponse;
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+use super::jwt::Context;
+use super::response::{HandlerResponse, error_response};
+use super::user_handler::UserActionResponse;
+use crate::app::{AppState, routes};
+use crate::model::notification::{NotificationPage, UnreadCount};
+
+#[derive(Debug, serde::Deserialize)]
+pub struct NotificationQuery {
+    pub cursor: Option<i64>,
+    pub size: Option<i32>,
+    #[serde(default)]
+    pub unread: bool,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, ToSchema)]
+pub struct ReadAllResponse {
+    /// 本次标记为已读的条数
+    pub updated: u64,
+}
+
+#[utoipa::path(
+    get,
+    path = routes::API_NOTIFICATIONS,
+    params(
+        ("cursor" = Option<i64>, Query, description = "next_cursor from the previous page"),
+        ("size" = Option<i32>, Query, description = "Page size, 1 to 50, default 20"),
+        ("unread" = Option<bool>, Query, description = "Only unread notifications")
+    ),
+    responses(
+        (status = 200, description = "Notifications newest first, with the total unread count", body = NotificationPage),
+        (status = 401, description = "Unauthorized", body = String)
+    )
+)]
+pub async fn list_notifications_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Query(q): Query<NotificationQuery>,
+) -> axum::response::Response {
+    match app
+        .notification_service
+        .list(ctx.uid, q.cursor, q.size, q.unread)
+        .await
+    {
+        Ok(v) => HandlerResponse::<NotificationPage>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = routes::API_NOTIFICATIONS_UNREAD,
+    responses(
+        (status = 200, description = "Number of unread notifications", body = UnreadCount),
+        (status = 401, description = "Unauthorized", body = String)
+    )
+)]
+pub async fn unread_count_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+) -> axum::response::Response {
+    match app.notification_service.unread_count(ctx.uid).await {
+        Ok(unread) => HandlerResponse::Success(UnreadCount { unread }).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/notifications/{id}/read",
+    params(("id" = i64, Path, description = "Notification id")),
+    responses(
+        (status = 200, description = "Marked as read; already read notifications keep their read time", body = UserActionResponse),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "Notification not found", body = String)
+    )
+)]
+pub async fn mark_read_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Path(id): Path<i64>,
+) -> axum::response::Response {
+    match app.notification_service.mark_read(ctx.uid, id).await {
+        Ok(()) => HandlerResponse::Success(UserActionResponse { success: true }).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = routes::API_NOTIFICATIONS_READ_ALL,
+    responses(
+        (status = 200, description = "All unread notifications marked as read", body = ReadAllResponse),
+        (status = 401, description = "Unauthorized", body = String)
+    )
+)]
+pub async fn mark_all_read_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+) -> axum::response::Response {
+    match app.notification_service.mark_all_read(ctx.uid).await {
+        Ok(updated) => HandlerResponse::Success(ReadAllResponse { updated }).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
@@ -0,0 +1,216 @@
+use axum::extract::{Path, Query, State};
+use axum::http::header::{LOCATION, SET_COOKIE};
+use axum::http::{HeaderMap, HeaderValue, StatusCode};
+use axum::response::IntoResponse;
+use std::sync::Arc;
+
+use super::jwt::{Context, cookie_value};
+use super::response::{HandlerResponse, error_response};
+use super::user_handler::{UserActionResponse, new_session_cookie};
+use crate::app::{AppState, routes};
+use crate::model::audit::{AUDIT_LOGIN_SUCCESS, AUDIT_OIDC_LINK, AUDIT_OIDC_UNLINK};
+use crate::model::oidc::OidcIdentity;
+use crate::service::session_service::ClientMeta;
+
+/// 保存本次授权 state 的 cookie，回调时与查询参数比对
+const STATE_COOKIE: &str = "slam_oidc";
+
+#[derive(Debug, serde::Deserialize)]
+pub struct CallbackQuery {
+    pub code: Option<String>,
+    pub state: Option<String>,
+    /// 用户拒绝授权等情况下身份提供方返回的错误码
+    pub error: Option<String>,
+}
+
+fn state_cookie(state: &str, max_age: u64) -> String {
+    format!(
+        "{STATE_COOKIE}={state}; Path=/api/user/oidc; HttpOnly; SameSite=Lax; Max-Age={max_age}"
+    )
+}
+
+/// 302 跳转，并附带需要设置的 cookie
+fn redirect(location: &str, cookies: Vec<String>) -> axum::response::Response {
+    let mut resp = StatusCode::FOUND.into_response();
+    match HeaderValue::from_str(location) {
+        Ok(v) => {
+            resp.headers_mut().insert(LOCATION, v);
+        }
+        Err(e) => return error_response(500, format!("跳转地址无效: {e}")),
+    }
+    for cookie in cookies {
+        if let Ok(v) = HeaderValue::from_str(&cookie) {
+            resp.headers_mut().append(SET_COOKIE, v);
+        }
+    }
+    resp
+}
+
+async fn begin(app: &AppState, link_uid: Option<i32>) -> axum::response::Response {
+    match app.oidc_service.begin(link_uid).await {
+        Ok(r) => redirect(&r.authorization_url, vec![state_cookie(&r.state, 600)]),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = routes::API_USER_OIDC_LOGIN,
+    responses(
+        (status = 302, description = "Redirect to the identity provider"),
+        (status = 404, description = "OIDC login is not configured", body = String),
+        (status = 502, description = "Identity provider unavailable", body = String)
+    )
+)]
+pub async fn oidc_login_handler(State(app): State<Arc<AppState>>) -> axum::response::Response {
+    begin(app.as_ref(), None).await
+}
+
+#[utoipa::path(
+    get,
+    path = routes::API_USER_OIDC_LINK,
+    responses(
+        (status = 302, description = "Redirect to the identity provider to link an identity"),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "OIDC login is not configured", body = String)
+    )
+)]
+pub async fn oidc_link_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+) -> axum::response::Response {
+    begin(app.as_ref(), Some(ctx.uid)).await
+}
+
+#[utoipa::path(
+    get,
+    path = routes::API_USER_OIDC_CALLBACK,
+    params(
+        ("code" = Option<String>, Query, description = "Authorization code"),
+        ("state" = Option<String>, Query, description = "State issued by the login endpoint"),
+        ("error" = Option<String>, Query, description = "Error returned by the identity provider")
+    ),
+    responses(
+        (status = 302, description = "Signed in or identity linked; session cookie set on sign-in, or a `two_factor_challenge` fragment to finish at /api/user/login/2fa when two-factor is enabled"),
+        (status = 400, description = "Missing code, or state invalid, expired or not from this browser", body = String),
+        (status = 403, description = "Identity not linked and auto-registration disabled, or account disabled", body = String),
+        (status = 409, description = "Identity already linked to another account", body = String),
+        (status = 502, description = "Token exchange or ID token verification failed", body = String)
+    )
+)]
+pub async fn oidc_callback_handler(
+    State(app): State<Arc<AppState>>,
+    headers: HeaderMap,
+    client: ClientMeta,
+    Query(q): Query<CallbackQuery>,
+) -> axum::response::Response {
+    if let Some(error) = q.error {
+        return error_response(400, format!("身份提供方拒绝了授权: {error}"));
+    }
+    let (Some(code), Some(state)) = (q.code, q.state) else {
+        return error_response(400, "缺少 code 或 state 参数".to_string());
+    };
+    let outcome = match app
+        .oidc_service
+        .finish(&state, cookie_value(&headers, STATE_COOKIE), &code)
+        .await
+    {
+        Ok(v) => v,
+        Err(e) => return error_response(e.code, e.message),
+    };
+    let location = app.oidc_service.post_login_redirect();
+    let clear_state = state_cookie("", 0);
+    if outcome.linked {
+        app.audit_service
+            .record(
+                Some(outcome.uid),
+                AUDIT_OIDC_LINK,
+                &client,
+                serde_json::json!({ "subject": outcome.subject }),
+            )
+            .await;
+        return redirect(location, vec![clear_state]);
+    }
+    // 开启两步验证的账号不直接登录，把挑战令牌放在跳转地址的片段中交给前端完成第二步
+    match app.two_factor_service.is_enabled(outcome.uid).await {
+        Ok(false) => {}
+        Ok(true) => {
+            return match app.two_factor_service.create_challenge(outcome.uid).await {
+                Ok(v) => {
+                    let separator = if location.contains('#') { '&' } else { '#' };
+                    let location =
+                        format!("{location}{separator}two_factor_challenge={}", v.challenge);
+                    redirect(&location, vec![clear_state])
+                }
+                Err(e) => error_response(e.code, e.message),
+            };
+        }
+        Err(e) => return error_response(e.code, e.message),
+    }
+    let session = match new_session_cookie(app.as_ref(), outcome.uid, &client).await {
+        Ok(v) => v,
+        Err(e) => return HandlerResponse::<UserActionResponse>::Error(e).into_response(),
+    };
+    app.audit_service
+        .record(
+            Some(outcome.uid),
+            AUDIT_LOGIN_SUCCESS,
+            &client,
+            serde_json::json!({ "method": "oidc" }),
+        )
+        .await;
+    let mut resp = redirect(location, vec![clear_state]);
+    resp.headers_mut().append(SET_COOKIE, session);
+    resp
+}
+
+#[utoipa::path(
+    get,
+    path = routes::API_USER_OIDC_IDENTITIES,
+    responses(
+        (status = 200, description = "External identities linked to the account", body = Vec<OidcIdentity>),
+        (status = 401, description = "Unauthorized", body = String)
+    )
+)]
+pub async fn list_oidc_identities_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+) -> axum::response::Response {
+    match app.oidc_service.list_identities(ctx.uid).await {
+        Ok(v) => HandlerResponse::<Vec<OidcIdentity>>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/user/oidc/identities/{id}",
+    params(("id" = i64, Path, description = "Identity id")),
+    responses(
+        (status = 200, description = "Identity unlinked", body = UserActionResponse),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "Not found", body = String),
+        (status = 409, description = "Last remaining way to sign in", body = String)
+    )
+)]
+pub async fn unlink_oidc_identity_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    client: ClientMeta,
+    Path(id): Path<i64>,
+) -> axum::response::Response {
+    match app.oidc_service.unlink(ctx.uid, id).await {
+        Ok(()) => {
+            app.audit_service
+                .record(
+                    Some(ctx.uid),
+                    AUDIT_OIDC_UNLINK,
+                    &client,
+                    serde_json::json!({ "identity_id": id }),
+                )
+                .await;
+            HandlerResponse::Success(UserActionResponse { success: true }).into_response()
+        }
+        Err(e) => error_response(e.code, e.message),
+    }
+}
@@ -34,3 +34,9 @@ impl<T: Serialize> IntoResponse for HandlerResponse<T> {
         }
     }
 }
+
+/// 按 ServiceError 的 code 映射 HTTP 状态码，返回 `{ "error": message }`
+pub fn error_response(code: u32, message: String) -> Response {
+    let status = StatusCode::from_u16(code as u16).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
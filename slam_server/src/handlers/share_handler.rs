@@ -0,0 +1,166 @@
+use axum::extract::{Json, Path, Query, State};
+use axum::http::header;
+use axum::response::IntoResponse;
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+use super::jwt::Context;
+use super::response::{HandlerResponse, error_response};
+use super::user_handler::UserActionResponse;
+use crate::app::{AppState, routes};
+use crate::model::audit::{AUDIT_SHARE_CREATE, AUDIT_SHARE_REVOKE};
+use crate::model::share::{CreatedSportShare, SharedSport, SportShareView};
+use crate::service::card_renderer::render_sport_card;
+use crate::service::session_service::ClientMeta;
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, ToSchema)]
+pub struct CreateShareRequest {
+    pub sport_id: i32,
+    /// 有效天数，最长 365；为空时直到撤销前一直有效
+    pub expires_in_days: Option<i64>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct ListSharesQuery {
+    pub sport_id: Option<i32>,
+}
+
+#[utoipa::path(
+    post,
+    path = routes::API_SPORT_SHARES,
+    request_body = CreateShareRequest,
+    responses(
+        (status = 200, description = "Share link created, the token is only returned once", body = CreatedSportShare),
+        (status = 400, description = "Invalid parameters", body = String),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "Sport not found", body = String)
+    )
+)]
+pub async fn create_share_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    client: ClientMeta,
+    Json(req): Json<CreateShareRequest>,
+) -> axum::response::Response {
+    match app
+        .share_service
+        .create(ctx.uid, req.sport_id, req.expires_in_days)
+        .await
+    {
+        Ok(v) => {
+            app.audit_service
+                .record(
+                    Some(ctx.uid),
+                    AUDIT_SHARE_CREATE,
+                    &client,
+                    serde_json::json!({ "share_id": v.info.id, "sport_id": v.info.sport_id }),
+                )
+                .await;
+            HandlerResponse::<CreatedSportShare>::Success(v).into_response()
+        }
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = routes::API_SPORT_SHARES,
+    params(("sport_id" = Option<i32>, Query, description = "Only list links of this sport")),
+    responses(
+        (status = 200, description = "Share links with view counts, including revoked and expired ones", body = Vec<SportShareView>),
+        (status = 401, description = "Unauthorized", body = String)
+    )
+)]
+pub async fn list_shares_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Query(q): Query<ListSharesQuery>,
+) -> axum::response::Response {
+    match app.share_service.list(ctx.uid, q.sport_id).await {
+        Ok(v) => HandlerResponse::<Vec<SportShareView>>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/sport/shares/{id}",
+    params(("id" = String, Path, description = "Share id")),
+    responses(
+        (status = 200, description = "Share link revoked", body = UserActionResponse),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "Not found", body = String)
+    )
+)]
+pub async fn revoke_share_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    client: ClientMeta,
+    Path(id): Path<String>,
+) -> axum::response::Response {
+    match app.share_service.revoke(ctx.uid, &id).await {
+        Ok(()) => {
+            app.audit_service
+                .record(
+                    Some(ctx.uid),
+                    AUDIT_SHARE_REVOKE,
+                    &client,
+                    serde_json::json!({ "share_id": id }),
+                )
+                .await;
+            HandlerResponse::Success(UserActionResponse { success: true }).into_response()
+        }
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/share/{token}",
+    params(("token" = String, Path, description = "Share token")),
+    responses(
+        (status = 200, description = "Read-only sport with tracks, no login required", body = SharedSport),
+        (status = 404, description = "Unknown, revoked or expired link", body = String)
+    )
+)]
+pub async fn shared_sport_handler(
+    State(app): State<Arc<AppState>>,
+    Path(token): Path<String>,
+) -> axum::response::Response {
+    match app.share_service.resolve(&token, true).await {
+        Ok(v) => HandlerResponse::<SharedSport>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/share/{token}/card",
+    params(("token" = String, Path, description = "Share token")),
+    responses(
+        (status = 200, description = "PNG card for link previews, not counted as a view", content_type = "image/png", body = Vec<u8>),
+        (status = 404, description = "Unknown, revoked or expired link", body = String)
+    )
+)]
+pub async fn shared_sport_card_handler(
+    State(app): State<Arc<AppState>>,
+    Path(token): Path<String>,
+) -> axum::response::Response {
+    let png = app
+        .share_service
+        .resolve(&token, false)
+        .await
+        .and_then(|shared| render_sport_card(&shared));
+    match png {
+        // 缓存时间较短，撤销后卡片很快失效
+        Ok(bytes) => (
+            [
+                (header::CONTENT_TYPE, "image/png"),
+                (header::CACHE_CONTROL, "public, max-age=300"),
+            ],
+            bytes,
+        )
+            .into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
@@ -0,0 +1,299 @@
+use axum::extract::{Json, Path, Query, State};
+use axum::response::IntoResponse;
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+use super::jwt::Context;
+use super::response::{HandlerResponse, error_response};
+use super::user_handler::UserActionResponse;
+use crate::app::{AppState, routes};
+use crate::model::social::{FeedPage, FollowView, SocialProfile, SocialSettings};
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, ToSchema)]
+pub struct FollowResponse {
+    /// accepted 表示已关注，pending 表示等待对方批准
+    pub status: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct FeedQuery {
+    pub cursor: Option<String>,
+    pub size: Option<i32>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct FindUserQuery {
+    pub name: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct FollowersQuery {
+    pub status: Option<String>,
+}
+
+fn action_response(
+    result: Result<(), crate::service::common::ServiceError>,
+) -> axum::response::Response {
+    match result {
+        Ok(()) => HandlerResponse::Success(UserActionResponse { success: true }).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = routes::API_SOCIAL_SETTINGS,
+    responses(
+        (status = 200, description = "Privacy settings of the current user", body = SocialSettings),
+        (status = 401, description = "Unauthorized", body = String)
+    )
+)]
+pub async fn get_social_settings_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+) -> axum::response::Response {
+    match app.social_service.settings(ctx.uid).await {
+        Ok(v) => HandlerResponse::<SocialSettings>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = routes::API_SOCIAL_SETTINGS,
+    request_body = SocialSettings,
+    responses(
+        (status = 200, description = "Settings saved; turning private off approves pending follow requests", body = SocialSettings),
+        (status = 401, description = "Unauthorized", body = String)
+    )
+)]
+pub async fn update_social_settings_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Json(req): Json<SocialSettings>,
+) -> axum::response::Response {
+    match app.social_service.update_settings(ctx.uid, req).await {
+        Ok(v) => HandlerResponse::<SocialSettings>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = routes::API_SOCIAL_USERS,
+    params(("name" = String, Query, description = "Exact account name")),
+    responses(
+        (status = 200, description = "Profile of the user with this account name", body = SocialProfile),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "Not found", body = String)
+    )
+)]
+pub async fn find_user_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Query(q): Query<FindUserQuery>,
+) -> axum::response::Response {
+    match app.social_service.find_by_name(ctx.uid, &q.name).await {
+        Ok(v) => HandlerResponse::<SocialProfile>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/social/users/{uid}",
+    params(("uid" = i32, Path, description = "User id")),
+    responses(
+        (status = 200, description = "Profile, follower counts and follow status", body = SocialProfile),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "Not found", body = String)
+    )
+)]
+pub async fn social_profile_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Path(uid): Path<i32>,
+) -> axum::response::Response {
+    match app.social_service.profile(ctx.uid, uid).await {
+        Ok(v) => HandlerResponse::<SocialProfile>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/social/users/{uid}/sports",
+    params(
+        ("uid" = i32, Path, description = "User id"),
+        ("cursor" = Option<String>, Query, description = "next_cursor of the previous page"),
+        ("size" = Option<i32>, Query, description = "Page size, at most 50")
+    ),
+    responses(
+        (status = 200, description = "Sports of the user visible to the caller, newest first", body = FeedPage),
+        (status = 400, description = "Invalid cursor", body = String),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "Not found", body = String)
+    )
+)]
+pub async fn user_sports_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Path(uid): Path<i32>,
+    Query(q): Query<FeedQuery>,
+) -> axum::response::Response {
+    match app
+        .social_service
+        .user_sports(ctx.uid, uid, q.cursor.as_deref(), q.size)
+        .await
+    {
+        Ok(v) => HandlerResponse::<FeedPage>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = routes::API_SOCIAL_FOLLOWING,
+    responses(
+        (status = 200, description = "Users the caller follows or asked to follow", body = Vec<FollowView>),
+        (status = 401, description = "Unauthorized", body = String)
+    )
+)]
+pub async fn list_following_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+) -> axum::response::Response {
+    match app.social_service.following(ctx.uid).await {
+        Ok(v) => HandlerResponse::<Vec<FollowView>>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/social/following/{uid}",
+    params(("uid" = i32, Path, description = "User id")),
+    responses(
+        (status = 200, description = "Followed, or request pending for a private account", body = FollowResponse),
+        (status = 400, description = "Cannot follow yourself", body = String),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "Not found", body = String)
+    )
+)]
+pub async fn follow_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Path(uid): Path<i32>,
+) -> axum::response::Response {
+    match app.social_service.follow(ctx.uid, uid).await {
+        Ok(status) => HandlerResponse::Success(FollowResponse { status }).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/social/following/{uid}",
+    params(("uid" = i32, Path, description = "User id")),
+    responses(
+        (status = 200, description = "Unfollowed or request withdrawn", body = UserActionResponse),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "Not following", body = String)
+    )
+)]
+pub async fn unfollow_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Path(uid): Path<i32>,
+) -> axum::response::Response {
+    action_response(app.social_service.unfollow(ctx.uid, uid).await)
+}
+
+#[utoipa::path(
+    get,
+    path = routes::API_SOCIAL_FOLLOWERS,
+    params(("status" = Option<String>, Query, description = "pending or accepted")),
+    responses(
+        (status = 200, description = "Followers and follow requests", body = Vec<FollowView>),
+        (status = 400, description = "Invalid status", body = String),
+        (status = 401, description = "Unauthorized", body = String)
+    )
+)]
+pub async fn list_followers_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Query(q): Query<FollowersQuery>,
+) -> axum::response::Response {
+    match app
+        .social_service
+        .followers(ctx.uid, q.status.as_deref())
+        .await
+    {
+        Ok(v) => HandlerResponse::<Vec<FollowView>>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/social/followers/{uid}/approve",
+    params(("uid" = i32, Path, description = "Id of the user who asked to follow")),
+    responses(
+        (status = 200, description = "Request approved", body = UserActionResponse),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "No pending request", body = String)
+    )
+)]
+pub async fn approve_follower_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Path(uid): Path<i32>,
+) -> axum::response::Response {
+    action_response(app.social_service.approve(ctx.uid, uid).await)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/social/followers/{uid}",
+    params(("uid" = i32, Path, description = "Follower id")),
+    responses(
+        (status = 200, description = "Request rejected or follower removed", body = UserActionResponse),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "Not a follower", body = String)
+    )
+)]
+pub async fn remove_follower_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Path(uid): Path<i32>,
+) -> axum::response::Response {
+    action_response(app.social_service.remove_follower(ctx.uid, uid).await)
+}
+
+#[utoipa::path(
+    get,
+    path = routes::API_SOCIAL_FEED,
+    params(
+        ("cursor" = Option<String>, Query, description = "next_cursor of the previous page"),
+        ("size" = Option<i32>, Query, description = "Page size, at most 50")
+    ),
+    responses(
+        (status = 200, description = "Recent followers-only and public sports of followed users, newest first", body = FeedPage),
+        (status = 400, description = "Invalid cursor", body = String),
+        (status = 401, description = "Unauthorized", body = String)
+    )
+)]
+pub async fn feed_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Query(q): Query<FeedQuery>,
+) -> axum::response::Response {
+    match app
+        .social_service
+        .feed(ctx.uid, q.cursor.as_deref(), q.size)
+        .await
+    {
+        Ok(v) => HandlerResponse::<FeedPage>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
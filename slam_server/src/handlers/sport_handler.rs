@@ -6,12 +6,17 @@ use std::io::Cursor;
 use std::sync::Arc;
 use utoipa::ToSchema;
 
-use super::response::HandlerResponse;
+use super::response::{HandlerResponse, error_response};
 use crate::app::{AppState, routes};
-use crate::model::sport::Sport;
-use crate::service::sport_service::{StatKind, StatSummary, StatsParam};
+use crate::model::athlete::SportMetrics;
+use crate::model::sport::{Sport, SportType};
+use crate::service::card_renderer::render_year_review_card;
+use crate::service::sport_service::{
+    CalendarHeatmap, HeatmapParam, HourlyHeatmap, StatKind, StatSummary, StatsParam,
+};
+use crate::service::year_review::YearReview;
 use axum::extract::Query;
-use axum::http::StatusCode;
+use axum::http::{StatusCode, header};
 use axum::response::IntoResponse;
 use serde::Deserialize;
 
@@ -114,6 +119,40 @@ pub struct ListQuery {
     pub size: Option<i32>,
 }
 
+#[derive(Deserialize)]
+pub struct MetricsQuery {
+    pub id: i32,
+}
+
+#[utoipa::path(
+    get,
+    path = routes::API_SPORT_METRICS,
+    params(("id" = i32, Query, description = "Sport id")),
+    responses(
+        (status = 200, description = "Heart-rate zones and training load based on the athlete profile valid on the sport's date", body = SportMetrics),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "Not found", body = String)
+    )
+)]
+pub async fn sport_metrics_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Query(q): Query<MetricsQuery>,
+) -> axum::response::Response {
+    let sport = match app.sport_service.get(q.id, &ctx).await {
+        Ok(s) => s,
+        Err(e) => return error_response(e.code, e.message),
+    };
+    match app
+        .athlete_service
+        .sport_metrics(ctx.data_uid(), &sport)
+        .await
+    {
+        Ok(v) => HandlerResponse::<SportMetrics>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
 #[derive(Deserialize)]
 pub struct StatsQuery {
     pub kind: String,
@@ -164,6 +203,163 @@ pub async fn stats_handler(
         Err(e) => HandlerResponse::<StatSummary>::Error(e.message).into_response(),
     }
 }
+#[derive(Deserialize)]
+pub struct HeatmapQuery {
+    pub start: Option<i64>,
+    pub end: Option<i64>,
+    pub year: Option<i32>,
+    pub r#type: Option<String>,
+    pub tz: Option<String>,
+}
+
+impl HeatmapQuery {
+    fn param(&self) -> HeatmapParam {
+        HeatmapParam {
+            start_time: self.start,
+            end_time: self.end,
+            sport_type: self.r#type.as_deref().map(SportType::from_str),
+            tz: self.tz.clone().unwrap_or_default(),
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = routes::API_SPORT_HEATMAP_HOURLY,
+    params(
+        ("start" = Option<i64>, Query, description = "Start timestamp (inclusive)"),
+        ("end" = Option<i64>, Query, description = "End timestamp (inclusive)"),
+        ("type" = Option<String>, Query, description = "Sport type filter"),
+        ("tz" = Option<String>, Query, description = "IANA timezone, defaults to UTC")
+    ),
+    responses(
+        (status = 200, description = "Weekday x hour heatmap", body = HourlyHeatmap),
+        (status = 400, description = "Invalid parameters", body = String),
+        (status = 401, description = "Unauthorized", body = String)
+    )
+)]
+#[axum::debug_handler]
+pub async fn heatmap_hourly_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Query(q): Query<HeatmapQuery>,
+) -> axum::response::Response {
+    match app.sport_service.heatmap_hourly(q.param(), &ctx).await {
+        Ok(v) => HandlerResponse::<HourlyHeatmap>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = routes::API_SPORT_HEATMAP_CALENDAR,
+    params(
+        ("year" = i32, Query, description = "Calendar year"),
+        ("type" = Option<String>, Query, description = "Sport type filter"),
+        ("tz" = Option<String>, Query, description = "IANA timezone, defaults to UTC")
+    ),
+    responses(
+        (status = 200, description = "Daily totals of a year", body = CalendarHeatmap),
+        (status = 400, description = "Invalid parameters", body = String),
+        (status = 401, description = "Unauthorized", body = String)
+    )
+)]
+#[axum::debug_handler]
+pub async fn heatmap_calendar_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Query(q): Query<HeatmapQuery>,
+) -> axum::response::Response {
+    let Some(year) = q.year else {
+        return error_response(400, "invalid year".to_string());
+    };
+    match app
+        .sport_service
+        .heatmap_calendar(year, q.param(), &ctx)
+        .await
+    {
+        Ok(v) => HandlerResponse::<CalendarHeatmap>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ReviewQuery {
+    pub year: Option<i32>,
+    pub tz: Option<String>,
+}
+
+impl ReviewQuery {
+    fn year(&self) -> Option<i32> {
+        self.year.filter(|y| (1970..=9999).contains(y))
+    }
+
+    fn tz(&self) -> &str {
+        self.tz.as_deref().unwrap_or_default()
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = routes::API_SPORT_REVIEW,
+    params(
+        ("year" = i32, Query, description = "Year to review"),
+        ("tz" = Option<String>, Query, description = "IANA timezone, defaults to UTC")
+    ),
+    responses(
+        (status = 200, description = "Year in review report", body = YearReview),
+        (status = 400, description = "Invalid parameters", body = String),
+        (status = 401, description = "Unauthorized", body = String)
+    )
+)]
+#[axum::debug_handler]
+pub async fn year_review_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Query(q): Query<ReviewQuery>,
+) -> axum::response::Response {
+    let Some(year) = q.year() else {
+        return error_response(400, "invalid year".to_string());
+    };
+    match app.sport_service.year_review(year, q.tz(), &ctx).await {
+        Ok(v) => HandlerResponse::<YearReview>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = routes::API_SPORT_REVIEW_CARD,
+    params(
+        ("year" = i32, Query, description = "Year to review"),
+        ("tz" = Option<String>, Query, description = "IANA timezone, defaults to UTC")
+    ),
+    responses(
+        (status = 200, description = "PNG summary card", content_type = "image/png", body = Vec<u8>),
+        (status = 400, description = "Invalid parameters", body = String),
+        (status = 401, description = "Unauthorized", body = String)
+    )
+)]
+#[axum::debug_handler]
+pub async fn year_review_card_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Query(q): Query<ReviewQuery>,
+) -> axum::response::Response {
+    let Some(year) = q.year() else {
+        return error_response(400, "invalid year".to_string());
+    };
+    let png = app
+        .sport_service
+        .year_review(year, q.tz(), &ctx)
+        .await
+        .and_then(|review| render_year_review_card(&review));
+    match png {
+        Ok(bytes) => ([(header::CONTENT_TYPE, "image/png")], bytes).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
 #[utoipa::path(
     post,
     path = routes::API_SPORT_UPDATE,
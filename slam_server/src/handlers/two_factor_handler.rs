@@ -0,0 +1,235 @@
+use axum::extract::{Json, State};
+use axum::response::IntoResponse;
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+use super::jwt::Context;
+use super::response::{HandlerResponse, error_response};
+use super::user_handler::{
+    UserActionResponse, confirm_identity, token_response, too_many_attempts,
+};
+use crate::app::{AppState, routes};
+use crate::model::audit::{
+    AUDIT_LOGIN_SUCCESS, AUDIT_RECOVERY_CODES_REGENERATE, AUDIT_TOTP_DISABLE, AUDIT_TOTP_ENABLE,
+};
+use crate::model::two_factor::{RecoveryCodes, TotpSetup, TwoFactorStatus};
+use crate::service::session_service::ClientMeta;
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, ToSchema)]
+pub struct TwoFactorCodeRequest {
+    /// 认证器 App 中的 6 位验证码
+    pub code: String,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, ToSchema)]
+pub struct DisableTwoFactorRequest {
+    /// 有本地密码的账号必填；没有密码的账号需在最近 10 分钟内通过外部身份重新认证
+    #[serde(default)]
+    pub password: Option<String>,
+    /// 认证器验证码或一个恢复码
+    pub code: String,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, ToSchema)]
+pub struct TwoFactorLoginRequest {
+    /// 登录接口返回的挑战令牌
+    pub challenge: String,
+    /// 认证器验证码或一个恢复码
+    pub code: String,
+}
+
+#[utoipa::path(
+    get,
+    path = routes::API_USER_TWO_FACTOR,
+    responses(
+        (status = 200, description = "Two-factor status", body = TwoFactorStatus),
+        (status = 401, description = "Unauthorized", body = String)
+    )
+)]
+pub async fn two_factor_status_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+) -> axum::response::Response {
+    match app.two_factor_service.status(ctx.uid).await {
+        Ok(v) => HandlerResponse::<TwoFactorStatus>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = routes::API_USER_TWO_FACTOR_SETUP,
+    responses(
+        (status = 200, description = "New TOTP secret, pending confirmation", body = TotpSetup),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 409, description = "Already enabled", body = String)
+    )
+)]
+pub async fn two_factor_setup_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+) -> axum::response::Response {
+    match app.two_factor_service.begin_setup(ctx.uid).await {
+        Ok(v) => HandlerResponse::<TotpSetup>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = routes::API_USER_TWO_FACTOR_ENABLE,
+    request_body = TwoFactorCodeRequest,
+    responses(
+        (status = 200, description = "Enabled; recovery codes are shown only once", body = RecoveryCodes),
+        (status = 400, description = "No pending secret", body = String),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Wrong code", body = String),
+        (status = 409, description = "Already enabled", body = String)
+    )
+)]
+pub async fn two_factor_enable_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    client: ClientMeta,
+    Json(req): Json<TwoFactorCodeRequest>,
+) -> axum::response::Response {
+    match app.two_factor_service.enable(ctx.uid, &req.code).await {
+        Ok(v) => {
+            app.audit_service
+                .record(
+                    Some(ctx.uid),
+                    AUDIT_TOTP_ENABLE,
+                    &client,
+                    serde_json::json!({}),
+                )
+                .await;
+            HandlerResponse::<RecoveryCodes>::Success(v).into_response()
+        }
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = routes::API_USER_TWO_FACTOR_DISABLE,
+    request_body = DisableTwoFactorRequest,
+    responses(
+        (status = 200, description = "Disabled, recovery codes removed", body = UserActionResponse),
+        (status = 400, description = "Not enabled", body = String),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Wrong password or code", body = String)
+    )
+)]
+pub async fn two_factor_disable_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    client: ClientMeta,
+    Json(req): Json<DisableTwoFactorRequest>,
+) -> axum::response::Response {
+    // 验证码留给关闭操作本身校验，同一时间步的验证码只能用一次
+    if let Err(e) = confirm_identity(&app, ctx.uid, req.password, None).await {
+        return error_response(e.code, e.message);
+    }
+    match app.two_factor_service.disable(ctx.uid, &req.code).await {
+        Ok(()) => {
+            app.audit_service
+                .record(
+                    Some(ctx.uid),
+                    AUDIT_TOTP_DISABLE,
+                    &client,
+                    serde_json::json!({}),
+                )
+                .await;
+            HandlerResponse::Success(UserActionResponse { success: true }).into_response()
+        }
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = routes::API_USER_TWO_FACTOR_RECOVERY_CODES,
+    request_body = TwoFactorCodeRequest,
+    responses(
+        (status = 200, description = "New recovery codes; the old ones stop working", body = RecoveryCodes),
+        (status = 400, description = "Not enabled", body = String),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Wrong code", body = String)
+    )
+)]
+pub async fn regenerate_recovery_codes_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    client: ClientMeta,
+    Json(req): Json<TwoFactorCodeRequest>,
+) -> axum::response::Response {
+    match app
+        .two_factor_service
+        .regenerate_recovery_codes(ctx.uid, &req.code)
+        .await
+    {
+        Ok(v) => {
+            app.audit_service
+                .record(
+                    Some(ctx.uid),
+                    AUDIT_RECOVERY_CODES_REGENERATE,
+                    &client,
+                    serde_json::json!({}),
+                )
+                .await;
+            HandlerResponse::<RecoveryCodes>::Success(v).into_response()
+        }
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = routes::API_USER_LOGIN_TWO_FACTOR,
+    request_body = TwoFactorLoginRequest,
+    responses(
+        (status = 200, description = "Second factor accepted, session cookie set", body = UserActionResponse),
+        (status = 401, description = "Wrong code, or challenge expired or used up", body = String),
+        (status = 429, description = "Too many failed attempts, see Retry-After", body = String)
+    )
+)]
+pub async fn login_two_factor_handler(
+    State(app): State<Arc<AppState>>,
+    client: ClientMeta,
+    Json(req): Json<TwoFactorLoginRequest>,
+) -> axum::response::Response {
+    let (_, name) = match app.two_factor_service.pending_login(&req.challenge).await {
+        Ok(v) => v,
+        Err(e) => return error_response(e.code, e.message),
+    };
+    // 验证码错误与密码错误共用账号和 IP 的失败计数
+    if let Some(retry_after) = app.login_throttle.check(&name, &client.ip).await {
+        return too_many_attempts(retry_after);
+    }
+    match app
+        .two_factor_service
+        .complete_login(&req.challenge, &req.code)
+        .await
+    {
+        Ok((uid, method)) => {
+            app.login_throttle.record_success(&name).await;
+            app.audit_service
+                .record(
+                    Some(uid),
+                    AUDIT_LOGIN_SUCCESS,
+                    &client,
+                    serde_json::json!({ "second_factor": method }),
+                )
+                .await;
+            token_response(app.as_ref(), uid, &client).await
+        }
+        Err(e) => {
+            if e.code == 401
+                && let Some(retry_after) = app.login_throttle.record_failure(&name, &client).await
+            {
+                return too_many_attempts(retry_after);
+            }
+            error_response(e.code, e.message)
+        }
+    }
+}
@@ -1,10 +1,26 @@
-use axum::extract::{Json, State};
-use axum::http::{HeaderValue, header::SET_COOKIE};
+use axum::body::Body;
+use axum::extract::{Json, Path, Query, State};
+use axum::http::{
+    HeaderMap, HeaderValue, StatusCode,
+    header::{CACHE_CONTROL, CONTENT_TYPE, ETAG, IF_NONE_MATCH, RETRY_AFTER, SET_COOKIE},
+};
 use axum::response::IntoResponse;
 // no request extractor here for OpenAPI, router closures will decide browser detection
-use super::response::HandlerResponse;
+use super::response::{HandlerResponse, error_response};
 use crate::app::{AppState, routes};
-use crate::handlers::jwt::Context;
+use crate::handlers::jwt::{Context, session_cookie};
+use crate::model::access_token::{AccessTokenView, CreatedAccessToken};
+use crate::model::audit::{
+    AUDIT_ACCOUNT_DELETE, AUDIT_AVATAR_CHANGE, AUDIT_LOGIN_SUCCESS, AUDIT_LOGOUT, AUDIT_LOGOUT_ALL,
+    AUDIT_PASSWORD_CHANGE, AUDIT_PASSWORD_RESET, AUDIT_SESSION_REVOKE, AUDIT_TOKEN_CREATE,
+    AUDIT_TOKEN_REVOKE, AuditEntry,
+};
+use crate::model::session::SessionView;
+use crate::model::two_factor::LoginChallengeView;
+use crate::model::user::AVATAR_SIZES;
+use crate::service::common::ServiceError;
+use crate::service::session_service::ClientMeta;
+use crate::service::user_service::validate_registration;
 use axum_extra::extract::Multipart;
 use std::sync::Arc;
 use utoipa::ToSchema;
@@ -14,6 +30,9 @@ pub struct UserRegisterRequest {
     pub name: String,
     pub password: String,
     pub nickname: String,
+    /// `registration_mode: invite` 时必填
+    #[serde(default)]
+    pub invite_code: Option<String>,
 }
 
 #[derive(Debug, serde::Serialize, serde::Deserialize, ToSchema)]
@@ -21,23 +40,46 @@ pub struct UserActionResponse {
     pub success: bool,
 }
 
-pub fn token_response(app: &AppState, uid: i32) -> axum::response::Response {
-    match app.jwt.create_token(uid) {
-        Ok(token) => {
+/// 为用户创建新会话，返回携带 token 的 Set-Cookie 值
+pub async fn new_session_cookie(
+    app: &AppState,
+    uid: i32,
+    client: &ClientMeta,
+) -> Result<HeaderValue, String> {
+    let session = app
+        .session_service
+        .create(uid, client)
+        .await
+        .map_err(|e| e.message)?;
+    let token = app.jwt.create_token(uid, &session.id)?;
+    HeaderValue::from_str(&session_cookie(&token, app.jwt.ttl_seconds())).map_err(|e| e.to_string())
+}
+
+/// 为用户创建新会话并通过 cookie 下发 token
+pub async fn token_response(
+    app: &AppState,
+    uid: i32,
+    client: &ClientMeta,
+) -> axum::response::Response {
+    match new_session_cookie(app, uid, client).await {
+        Ok(cookie) => {
             let mut resp =
                 HandlerResponse::Success(UserActionResponse { success: true }).into_response();
-            if let Ok(val) = HeaderValue::from_str(&format!(
-                "slam={}; Path=/; HttpOnly; SameSite=Lax; Max-Age=2592000",
-                token
-            )) {
-                resp.headers_mut().append(SET_COOKIE, val);
-            }
+            resp.headers_mut().append(SET_COOKIE, cookie);
             resp
         }
         Err(e) => HandlerResponse::<UserActionResponse>::Error(e).into_response(),
     }
 }
 
+fn clear_cookie_response() -> axum::response::Response {
+    let mut resp = HandlerResponse::Success(UserActionResponse { success: true }).into_response();
+    if let Ok(val) = HeaderValue::from_str(&session_cookie("", 0)) {
+        resp.headers_mut().append(SET_COOKIE, val);
+    }
+    resp
+}
+
 #[utoipa::path(
     post,
     path = routes::API_USER_LOGOUT,
@@ -46,43 +88,442 @@ pub fn token_response(app: &AppState, uid: i32) -> axum::response::Response {
     )
 )]
 pub async fn user_logout_handler(
-    State(_app): State<Arc<AppState>>,
-    _ctx: Context,
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    client: ClientMeta,
 ) -> axum::response::Response {
-    let mut resp = HandlerResponse::Success(UserActionResponse { success: true }).into_response();
-    if let Ok(val) = HeaderValue::from_str("slam=; Path=/; HttpOnly; SameSite=Lax; Max-Age=0") {
-        resp.headers_mut().append(SET_COOKIE, val);
+    if let Err(e) = app.session_service.revoke(ctx.uid, &ctx.sid).await {
+        tracing::warn!(uid = ctx.uid, error = %e.message, "failed to revoke session on logout");
+    }
+    app.audit_service
+        .record(
+            Some(ctx.uid),
+            AUDIT_LOGOUT,
+            &client,
+            serde_json::json!({ "session_id": ctx.sid }),
+        )
+        .await;
+    clear_cookie_response()
+}
+
+#[utoipa::path(
+    post,
+    path = routes::API_USER_LOGOUT_ALL,
+    responses(
+        (status = 200, description = "Revoke every session of the user", body = UserActionResponse),
+        (status = 401, description = "Unauthorized", body = String)
+    )
+)]
+pub async fn user_logout_all_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    client: ClientMeta,
+) -> axum::response::Response {
+    match app.session_service.revoke_all(ctx.uid, None).await {
+        Ok(count) => {
+            tracing::info!(uid = ctx.uid, count, "revoked all sessions");
+            app.audit_service
+                .record(
+                    Some(ctx.uid),
+                    AUDIT_LOGOUT_ALL,
+                    &client,
+                    serde_json::json!({ "revoked": count }),
+                )
+                .await;
+            clear_cookie_response()
+        }
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = routes::API_USER_SESSIONS,
+    responses(
+        (status = 200, description = "Active sessions", body = Vec<SessionView>),
+        (status = 401, description = "Unauthorized", body = String)
+    )
+)]
+pub async fn list_sessions_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+) -> axum::response::Response {
+    match app.session_service.list(ctx.uid, &ctx.sid).await {
+        Ok(v) => HandlerResponse::<Vec<SessionView>>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/user/sessions/{id}",
+    params(("id" = String, Path, description = "Session id")),
+    responses(
+        (status = 200, description = "Session revoked", body = UserActionResponse),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "Not found", body = String)
+    )
+)]
+pub async fn revoke_session_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    client: ClientMeta,
+    Path(id): Path<String>,
+) -> axum::response::Response {
+    if let Err(e) = app.session_service.revoke(ctx.uid, &id).await {
+        return error_response(e.code, e.message);
+    }
+    app.audit_service
+        .record(
+            Some(ctx.uid),
+            AUDIT_SESSION_REVOKE,
+            &client,
+            serde_json::json!({ "session_id": id }),
+        )
+        .await;
+    if id == ctx.sid {
+        clear_cookie_response()
+    } else {
+        HandlerResponse::Success(UserActionResponse { success: true }).into_response()
+    }
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, ToSchema)]
+pub struct ChangePasswordRequest {
+    pub old_password: String,
+    pub new_password: String,
+}
+
+#[utoipa::path(
+    post,
+    path = routes::API_USER_PASSWORD,
+    request_body = ChangePasswordRequest,
+    responses(
+        (status = 200, description = "Password changed, other sessions are revoked", body = UserActionResponse),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Wrong old password", body = String)
+    )
+)]
+pub async fn change_password_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    client: ClientMeta,
+    Json(req): Json<ChangePasswordRequest>,
+) -> axum::response::Response {
+    if let Err(e) = app
+        .user_service
+        .change_password(ctx.uid, req.old_password, req.new_password)
+        .await
+    {
+        return error_response(e.code, e.message);
+    }
+    // 改密后其他设备上的会话全部下线，当前会话保留
+    let keep = Some(ctx.sid.as_str()).filter(|s| !s.is_empty());
+    if let Err(e) = app.session_service.revoke_all(ctx.uid, keep).await {
+        tracing::warn!(uid = ctx.uid, error = %e.message, "failed to revoke sessions after password change");
+    }
+    app.audit_service
+        .record(
+            Some(ctx.uid),
+            AUDIT_PASSWORD_CHANGE,
+            &client,
+            serde_json::json!({}),
+        )
+        .await;
+    HandlerResponse::Success(UserActionResponse { success: true }).into_response()
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, ToSchema)]
+pub struct ResetPasswordRequest {
+    pub name: String,
+    /// 管理员签发的一次性重置码
+    pub code: String,
+    pub new_password: String,
+}
+
+#[utoipa::path(
+    post,
+    path = routes::API_USER_PASSWORD_RESET,
+    request_body = ResetPasswordRequest,
+    responses(
+        (status = 200, description = "Password reset, all sessions are revoked", body = UserActionResponse),
+        (status = 400, description = "Weak password", body = String),
+        (status = 403, description = "Invalid or expired code", body = String)
+    )
+)]
+pub async fn reset_password_handler(
+    State(app): State<Arc<AppState>>,
+    client: ClientMeta,
+    Json(req): Json<ResetPasswordRequest>,
+) -> axum::response::Response {
+    let uid = match app
+        .user_service
+        .reset_password(&req.name, &req.code, req.new_password)
+        .await
+    {
+        Ok(uid) => uid,
+        Err(e) => return error_response(e.code, e.message),
+    };
+    if let Err(e) = app.session_service.revoke_all(uid, None).await {
+        tracing::warn!(uid, error = %e.message, "failed to revoke sessions after password reset");
+    }
+    app.audit_service
+        .record(
+            Some(uid),
+            AUDIT_PASSWORD_RESET,
+            &client,
+            serde_json::json!({}),
+        )
+        .await;
+    HandlerResponse::Success(UserActionResponse { success: true }).into_response()
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, ToSchema)]
+pub struct UpdateProfileRequest {
+    pub nickname: String,
+    #[serde(default)]
+    pub bio: String,
+}
+
+#[utoipa::path(
+    put,
+    path = routes::API_USER_PROFILE,
+    request_body = UpdateProfileRequest,
+    responses(
+        (status = 200, description = "Profile updated", body = UserActionResponse),
+        (status = 400, description = "Invalid parameters", body = String),
+        (status = 401, description = "Unauthorized", body = String)
+    )
+)]
+pub async fn update_profile_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Json(req): Json<UpdateProfileRequest>,
+) -> axum::response::Response {
+    match app
+        .user_service
+        .update_profile(ctx.uid, req.nickname, req.bio)
+        .await
+    {
+        Ok(()) => HandlerResponse::Success(UserActionResponse { success: true }).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, ToSchema)]
+pub struct DeleteAccountRequest {
+    /// 有本地密码的账号必填
+    #[serde(default)]
+    pub password: Option<String>,
+    /// 没有本地密码的账号可用认证器验证码或恢复码代替最近的外部身份重新认证
+    #[serde(default)]
+    pub code: Option<String>,
+}
+
+/// 敏感操作前确认身份：有本地密码的账号校验密码；没有密码的账号（OIDC 自动创建）
+/// 需要提供两步验证码，或在最近几分钟内通过外部身份重新认证（走一遍关联流程）
+pub(crate) async fn confirm_identity(
+    app: &AppState,
+    uid: i32,
+    password: Option<String>,
+    code: Option<&str>,
+) -> Result<(), ServiceError> {
+    if app.user_service.has_password(uid).await? {
+        return app
+            .user_service
+            .verify_password(uid, password.unwrap_or_default())
+            .await;
+    }
+    if let Some(code) = code.filter(|c| !c.trim().is_empty()) {
+        return app.two_factor_service.verify(uid, code).await;
+    }
+    if app.oidc_service.recently_authenticated(uid).await? {
+        return Ok(());
+    }
+    Err(ServiceError {
+        code: 403,
+        message: "请先通过外部身份重新认证后再操作".to_string(),
+    })
+}
+
+#[utoipa::path(
+    delete,
+    path = routes::API_USER_ACCOUNT,
+    request_body = DeleteAccountRequest,
+    responses(
+        (status = 200, description = "Account and all of its data deleted", body = UserActionResponse),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Wrong password or code, or accounts without a password have not re-authenticated with their identity provider in the last 10 minutes", body = String)
+    )
+)]
+pub async fn delete_account_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    client: ClientMeta,
+    Json(req): Json<DeleteAccountRequest>,
+) -> axum::response::Response {
+    if let Err(e) = confirm_identity(&app, ctx.uid, req.password, req.code.as_deref()).await {
+        return error_response(e.code, e.message);
+    }
+    match app.user_service.delete_account(ctx.uid).await {
+        Ok(()) => {
+            // 审计记录不随账号删除，保留删除事件本身
+            app.audit_service
+                .record(
+                    Some(ctx.uid),
+                    AUDIT_ACCOUNT_DELETE,
+                    &client,
+                    serde_json::json!({}),
+                )
+                .await;
+            clear_cookie_response()
+        }
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, ToSchema)]
+pub struct CreateTokenRequest {
+    pub name: String,
+    /// 可选 `sports:read`、`sports:write`、`ai:jobs`
+    pub scopes: Vec<String>,
+    /// 有效天数，默认 90，最长 365
+    pub expires_in_days: Option<i64>,
+}
+
+#[utoipa::path(
+    post,
+    path = routes::API_USER_TOKENS,
+    request_body = CreateTokenRequest,
+    responses(
+        (status = 200, description = "Token created, plaintext is only returned once", body = CreatedAccessToken),
+        (status = 400, description = "Invalid parameters", body = String),
+        (status = 401, description = "Unauthorized", body = String)
+    )
+)]
+pub async fn create_token_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    client: ClientMeta,
+    Json(req): Json<CreateTokenRequest>,
+) -> axum::response::Response {
+    match app
+        .access_token_service
+        .create(ctx.uid, &req.name, &req.scopes, req.expires_in_days)
+        .await
+    {
+        Ok(v) => {
+            app.audit_service
+                .record(
+                    Some(ctx.uid),
+                    AUDIT_TOKEN_CREATE,
+                    &client,
+                    serde_json::json!({
+                        "token_id": v.info.id,
+                        "name": v.info.name,
+                        "scopes": v.info.scopes,
+                    }),
+                )
+                .await;
+            HandlerResponse::<CreatedAccessToken>::Success(v).into_response()
+        }
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = routes::API_USER_TOKENS,
+    responses(
+        (status = 200, description = "Active personal access tokens", body = Vec<AccessTokenView>),
+        (status = 401, description = "Unauthorized", body = String)
+    )
+)]
+pub async fn list_tokens_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+) -> axum::response::Response {
+    match app.access_token_service.list(ctx.uid).await {
+        Ok(v) => HandlerResponse::<Vec<AccessTokenView>>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/user/tokens/{id}",
+    params(("id" = String, Path, description = "Token id")),
+    responses(
+        (status = 200, description = "Token revoked", body = UserActionResponse),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "Not found", body = String)
+    )
+)]
+pub async fn revoke_token_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    client: ClientMeta,
+    Path(id): Path<String>,
+) -> axum::response::Response {
+    match app.access_token_service.revoke(ctx.uid, &id).await {
+        Ok(()) => {
+            app.audit_service
+                .record(
+                    Some(ctx.uid),
+                    AUDIT_TOKEN_REVOKE,
+                    &client,
+                    serde_json::json!({ "token_id": id }),
+                )
+                .await;
+            HandlerResponse::Success(UserActionResponse { success: true }).into_response()
+        }
+        Err(e) => error_response(e.code, e.message),
     }
-    resp
 }
+
 #[utoipa::path(
     post,
     path = routes::API_USER_REGISTER,
     request_body = UserRegisterRequest,
     responses(
         (status = 200, description = "User registered", body = UserActionResponse),
+        (status = 400, description = "Invalid fields", body = String),
+        (status = 403, description = "Registration closed or invalid invite code", body = String),
+        (status = 409, description = "Username taken", body = String),
         (status = 500, description = "Internal error", body = String)
     )
 )]
 pub async fn user_register_handler(
     State(app): State<Arc<AppState>>,
+    client: ClientMeta,
     Json(req): Json<UserRegisterRequest>,
 ) -> axum::response::Response {
+    let fields = validate_registration(&req.name, &req.password, &req.nickname);
+    if !fields.is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "注册信息不合法", "fields": fields })),
+        )
+            .into_response();
+    }
     let user = crate::model::user::User {
         id: 0,
         name: req.name,
         password: req.password,
         nickname: req.nickname,
     };
-    match app.user_service.register(user).await {
-        Ok(uid) => token_response(app.as_ref(), uid),
-        Err(e) => HandlerResponse::<UserActionResponse>::Error(e.message).into_response(),
+    match app.user_service.register(user, req.invite_code).await {
+        Ok(uid) => token_response(app.as_ref(), uid, &client).await,
+        Err(e) => error_response(e.code, e.message),
     }
 }
 
 #[derive(Debug, serde::Serialize, serde::Deserialize, ToSchema)]
 pub struct UserInfoResponse {
+    pub uid: i32,
     pub nickname: String,
+    pub bio: String,
+    /// 头像地址，未上传头像时为空
     pub avatar: String,
 }
 
@@ -100,8 +541,13 @@ pub async fn user_info_handler(
 ) -> axum::response::Response {
     match app.user_service.get_user(ctx.uid).await {
         Ok(u) => HandlerResponse::<UserInfoResponse>::Success(UserInfoResponse {
+            uid: ctx.uid,
             nickname: u.nickname,
-            avatar: u.avatar,
+            bio: u.bio,
+            avatar: u
+                .avatar_version
+                .map(|v| routes::avatar_url(ctx.uid, &v))
+                .unwrap_or_default(),
         })
         .into_response(),
         Err(e) => HandlerResponse::<UserInfoResponse>::Error(e.message).into_response(),
@@ -113,19 +559,64 @@ pub async fn user_info_handler(
     path = routes::API_USER_LOGIN,
     request_body = UserLoginRequest,
     responses(
-        (status = 200, description = "User login", body = UserActionResponse),
+        (status = 200, description = "Logged in with a session cookie, or a challenge to finish at /api/user/login/2fa when two-factor is enabled", body = UserActionResponse),
+        (status = 401, description = "Wrong name or password", body = String),
+        (status = 403, description = "Account disabled", body = String),
+        (status = 429, description = "Too many failed attempts, see Retry-After", body = String),
         (status = 500, description = "Internal error", body = String)
     )
 )]
 pub async fn user_login_handler(
     State(app): State<Arc<AppState>>,
+    client: ClientMeta,
     Json(req): Json<UserLoginRequest>,
 ) -> axum::response::Response {
-    match app.user_service.login(req.name, req.password).await {
-        Ok(uid) => token_response(app.as_ref(), uid),
-        Err(e) => HandlerResponse::<UserActionResponse>::Error(e.message).into_response(),
+    if let Some(retry_after) = app.login_throttle.check(&req.name, &client.ip).await {
+        return too_many_attempts(retry_after);
+    }
+    match app.user_service.login(req.name.clone(), req.password).await {
+        Ok(uid) => {
+            // 开启两步验证的账号先返回挑战令牌，失败计数留到第二步完成后再清零
+            match app.two_factor_service.is_enabled(uid).await {
+                Ok(false) => {}
+                Ok(true) => {
+                    return match app.two_factor_service.create_challenge(uid).await {
+                        Ok(v) => HandlerResponse::<LoginChallengeView>::Success(v).into_response(),
+                        Err(e) => error_response(e.code, e.message),
+                    };
+                }
+                Err(e) => return error_response(e.code, e.message),
+            }
+            app.login_throttle.record_success(&req.name).await;
+            app.audit_service
+                .record(
+                    Some(uid),
+                    AUDIT_LOGIN_SUCCESS,
+                    &client,
+                    serde_json::json!({}),
+                )
+                .await;
+            token_response(app.as_ref(), uid, &client).await
+        }
+        Err(e) => {
+            if e.code == 401
+                && let Some(retry_after) =
+                    app.login_throttle.record_failure(&req.name, &client).await
+            {
+                return too_many_attempts(retry_after);
+            }
+            error_response(e.code, e.message)
+        }
     }
 }
+
+pub(crate) fn too_many_attempts(retry_after: u64) -> axum::response::Response {
+    let mut resp = error_response(429, format!("登录失败次数过多，请在{retry_after}秒后重试"));
+    resp.headers_mut()
+        .insert(RETRY_AFTER, HeaderValue::from(retry_after));
+    resp
+}
+
 #[derive(Debug, serde::Serialize, serde::Deserialize, ToSchema)]
 pub struct UserLoginRequest {
     pub name: String,
@@ -135,6 +626,7 @@ pub struct UserLoginRequest {
 #[derive(Debug, serde::Serialize, serde::Deserialize, ToSchema)]
 pub struct AvatarUploadResponse {
     pub success: bool,
+    /// 新头像的地址
     pub avatar: String,
 }
 
@@ -142,7 +634,8 @@ pub struct AvatarUploadResponse {
     post,
     path = routes::API_USER_AVATAR_UPLOAD,
     responses(
-        (status = 200, description = "Upload avatar", body = AvatarUploadResponse),
+        (status = 200, description = "Upload avatar as a `file` part, or a base64 data URL in an `avatar` field", body = AvatarUploadResponse),
+        (status = 400, description = "Not a decodable image", body = String),
         (status = 401, description = "Unauthorized", body = String),
         (status = 500, description = "Internal error", body = String)
     )
@@ -151,6 +644,7 @@ pub struct AvatarUploadResponse {
 pub async fn user_avatar_upload_handler(
     State(app): State<Arc<AppState>>,
     ctx: Context,
+    client: ClientMeta,
     mut mp: Multipart,
 ) -> axum::response::Response {
     let mut data: Option<Vec<u8>> = None;
@@ -163,37 +657,129 @@ pub async fn user_avatar_upload_handler(
             b64_text = Some(field.text().await.unwrap_or_default());
         }
     }
-    if let Some(bytes) = data {
-        match app.image_service.process_image(bytes) {
-            Ok(resp) => {
-                let b64 = resp.base64_data.into_iter().next().unwrap_or_default();
-                match app.user_service.set_avatar(ctx.uid, b64.clone()).await {
-                    Ok(()) => {
-                        HandlerResponse::<AvatarUploadResponse>::Success(AvatarUploadResponse {
-                            success: true,
-                            avatar: b64,
-                        })
-                        .into_response()
-                    }
-                    Err(e) => {
-                        HandlerResponse::<AvatarUploadResponse>::Error(e.message).into_response()
-                    }
-                }
-            }
-            Err(e) => HandlerResponse::<AvatarUploadResponse>::Error(e.message).into_response(),
-        }
+    let result = if let Some(bytes) = data {
+        app.user_service.set_avatar(ctx.uid, &bytes).await
     } else if let Some(txt) = b64_text {
-        let b64 = txt;
-        match app.user_service.set_avatar(ctx.uid, b64.clone()).await {
-            Ok(()) => HandlerResponse::<AvatarUploadResponse>::Success(AvatarUploadResponse {
+        app.user_service.set_avatar_base64(ctx.uid, &txt).await
+    } else {
+        return error_response(400, "缺少文件或base64参数".to_string());
+    };
+    match result {
+        Ok(version) => {
+            record_avatar_change(&app, ctx.uid, &client).await;
+            HandlerResponse::<AvatarUploadResponse>::Success(AvatarUploadResponse {
                 success: true,
-                avatar: b64,
+                avatar: routes::avatar_url(ctx.uid, &version),
             })
-            .into_response(),
-            Err(e) => HandlerResponse::<AvatarUploadResponse>::Error(e.message).into_response(),
+            .into_response()
         }
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct AvatarQuery {
+    /// 期望的边长（像素），返回不小于它的最小尺寸
+    pub size: Option<u32>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/user/avatar/{uid}",
+    params(
+        ("uid" = i32, Path, description = "User id"),
+        ("size" = Option<u32>, Query, description = "Preferred edge length in pixels, defaults to the largest size"),
+        ("v" = Option<String>, Query, description = "Avatar version from user info, only used to bust caches")
+    ),
+    responses(
+        (status = 200, description = "Avatar image with ETag"),
+        (status = 304, description = "Not modified since the ETag in If-None-Match"),
+        (status = 404, description = "No avatar", body = String)
+    )
+)]
+pub async fn user_avatar_handler(
+    State(app): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(uid): Path<i32>,
+    Query(q): Query<AvatarQuery>,
+) -> axum::response::Response {
+    let size = q.size.unwrap_or(AVATAR_SIZES[AVATAR_SIZES.len() - 1]);
+    let image = match app.user_service.get_avatar(uid, size).await {
+        Ok(v) => v,
+        Err(e) => return error_response(e.code, e.message),
+    };
+    let etag = format!("\"{}\"", image.etag);
+    let not_modified = headers
+        .get(IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| {
+            v.split(',')
+                .map(|t| t.trim().trim_start_matches("W/"))
+                .any(|t| t == etag || t == "*")
+        });
+    let mut resp = if not_modified {
+        StatusCode::NOT_MODIFIED.into_response()
     } else {
-        HandlerResponse::<AvatarUploadResponse>::Error("缺少文件或base64参数".to_string())
-            .into_response()
+        let mut resp = axum::response::Response::new(Body::from(image.data));
+        if let Ok(value) = HeaderValue::from_str(&image.mime) {
+            resp.headers_mut().insert(CONTENT_TYPE, value);
+        }
+        resp
+    };
+    if let Ok(value) = HeaderValue::from_str(&etag) {
+        resp.headers_mut().insert(ETAG, value);
+    }
+    resp.headers_mut().insert(
+        CACHE_CONTROL,
+        HeaderValue::from_static("public, max-age=3600"),
+    );
+    resp
+}
+
+async fn record_avatar_change(app: &AppState, uid: i32, client: &ClientMeta) {
+    app.audit_service
+        .record(
+            Some(uid),
+            AUDIT_AVATAR_CHANGE,
+            client,
+            serde_json::json!({}),
+        )
+        .await;
+}
+
+#[derive(serde::Deserialize)]
+pub struct AuditListQuery {
+    pub action: Option<String>,
+    pub page: Option<i32>,
+    pub size: Option<i32>,
+}
+
+#[utoipa::path(
+    get,
+    path = routes::API_USER_AUDIT,
+    params(
+        ("action" = Option<String>, Query, description = "Only entries with this action"),
+        ("page" = Option<i32>, Query, description = "Page index"),
+        ("size" = Option<i32>, Query, description = "Page size")
+    ),
+    responses(
+        (status = 200, description = "Security events of the current user, newest first", body = [AuditEntry]),
+        (status = 401, description = "Unauthorized", body = String)
+    )
+)]
+pub async fn list_audit_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Query(q): Query<AuditListQuery>,
+) -> axum::response::Response {
+    let page = q.page.unwrap_or(0);
+    let size = q.size.unwrap_or(50);
+    match app
+        .audit_service
+        .list_own(ctx.uid, q.action, page, size)
+        .await
+    {
+        Ok(v) => HandlerResponse::<Vec<AuditEntry>>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
     }
 }
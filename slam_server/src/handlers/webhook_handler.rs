@@ -0,0 +1,208 @@
+use axum::extract::{Json, Path, Query, State};
+use axum::response::IntoResponse;
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+use super::jwt::{AdminContext, Context};
+use super::response::{HandlerResponse, error_response};
+use super::user_handler::UserActionResponse;
+use crate::app::{AppState, routes};
+use crate::model::audit::{AUDIT_WEBHOOK_CREATE, AUDIT_WEBHOOK_DELETE};
+use crate::model::webhook::{CreatedWebhook, WebhookDelivery, WebhookView};
+use crate::service::session_service::ClientMeta;
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, ToSchema)]
+pub struct CreateWebhookRequest {
+    /// http 或 https 地址
+    pub url: String,
+    /// sport.created、sport.updated、sport.deleted、ai_job.ready、ai_job.failed
+    pub events: Vec<String>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct DeliveryListQuery {
+    pub page: Option<i32>,
+    pub size: Option<i32>,
+}
+
+#[utoipa::path(
+    post,
+    path = routes::API_WEBHOOKS,
+    request_body = CreateWebhookRequest,
+    responses(
+        (status = 200, description = "Webhook registered, the signing secret is only returned once", body = CreatedWebhook),
+        (status = 400, description = "Invalid url or events", body = String),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 409, description = "Too many webhooks", body = String)
+    )
+)]
+pub async fn create_webhook_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    client: ClientMeta,
+    Json(req): Json<CreateWebhookRequest>,
+) -> axum::response::Response {
+    create_webhook(&app, ctx.uid, &client, req, false).await
+}
+
+#[utoipa::path(
+    post,
+    path = routes::API_ADMIN_WEBHOOKS,
+    request_body = CreateWebhookRequest,
+    responses(
+        (status = 200, description = "Site-wide webhook registered, it receives events of every user", body = CreatedWebhook),
+        (status = 400, description = "Invalid url or events", body = String),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Not an admin", body = String),
+        (status = 409, description = "Too many webhooks", body = String)
+    )
+)]
+pub async fn admin_create_webhook_handler(
+    State(app): State<Arc<AppState>>,
+    AdminContext(admin): AdminContext,
+    client: ClientMeta,
+    Json(req): Json<CreateWebhookRequest>,
+) -> axum::response::Response {
+    create_webhook(&app, admin.uid, &client, req, true).await
+}
+
+async fn create_webhook(
+    app: &AppState,
+    uid: i32,
+    client: &ClientMeta,
+    req: CreateWebhookRequest,
+    all_users: bool,
+) -> axum::response::Response {
+    match app
+        .webhook_service
+        .create(uid, &req.url, &req.events, all_users)
+        .await
+    {
+        Ok(v) => {
+            app.audit_service
+                .record(
+                    Some(uid),
+                    AUDIT_WEBHOOK_CREATE,
+                    client,
+                    serde_json::json!({
+                        "webhook_id": v.info.id,
+                        "url": v.info.url,
+                        "all_users": all_users,
+                    }),
+                )
+                .await;
+            HandlerResponse::<CreatedWebhook>::Success(v).into_response()
+        }
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = routes::API_WEBHOOKS,
+    responses(
+        (status = 200, description = "Webhooks registered by the current user", body = Vec<WebhookView>),
+        (status = 401, description = "Unauthorized", body = String)
+    )
+)]
+pub async fn list_webhooks_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+) -> axum::response::Response {
+    match app.webhook_service.list(ctx.uid).await {
+        Ok(v) => HandlerResponse::<Vec<WebhookView>>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/webhooks/{id}",
+    params(("id" = String, Path, description = "Webhook id")),
+    responses(
+        (status = 200, description = "Webhook and its delivery log deleted", body = UserActionResponse),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "Webhook not found", body = String)
+    )
+)]
+pub async fn delete_webhook_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    client: ClientMeta,
+    Path(id): Path<String>,
+) -> axum::response::Response {
+    match app.webhook_service.delete(ctx.uid, &id).await {
+        Ok(()) => {
+            app.audit_service
+                .record(
+                    Some(ctx.uid),
+                    AUDIT_WEBHOOK_DELETE,
+                    &client,
+                    serde_json::json!({ "webhook_id": id }),
+                )
+                .await;
+            HandlerResponse::Success(UserActionResponse { success: true }).into_response()
+        }
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/webhooks/{id}/deliveries",
+    params(
+        ("id" = String, Path, description = "Webhook id"),
+        ("page" = Option<i32>, Query, description = "Page index"),
+        ("size" = Option<i32>, Query, description = "Page size")
+    ),
+    responses(
+        (status = 200, description = "Delivery log, newest first", body = Vec<WebhookDelivery>),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "Webhook not found", body = String)
+    )
+)]
+pub async fn list_deliveries_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Path(id): Path<String>,
+    Query(q): Query<DeliveryListQuery>,
+) -> axum::response::Response {
+    let page = q.page.unwrap_or(0);
+    let size = q.size.unwrap_or(20);
+    match app
+        .webhook_service
+        .deliveries(ctx.uid, &id, page, size)
+        .await
+    {
+        Ok(v) => HandlerResponse::<Vec<WebhookDelivery>>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/webhooks/{id}/deliveries/{delivery_id}/redeliver",
+    params(
+        ("id" = String, Path, description = "Webhook id"),
+        ("delivery_id" = String, Path, description = "Delivery to send again")
+    ),
+    responses(
+        (status = 200, description = "A new delivery with the same payload was queued", body = WebhookDelivery),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "Webhook or delivery not found", body = String)
+    )
+)]
+pub async fn redeliver_handler(
+    State(app): State<Arc<AppState>>,
+    ctx: Context,
+    Path((id, delivery_id)): Path<(String, String)>,
+) -> axum::response::Response {
+    match app
+        .webhook_service
+        .redeliver(ctx.uid, &id, &delivery_id)
+        .await
+    {
+        Ok(v) => HandlerResponse::<WebhookDelivery>::Success(v).into_response(),
+        Err(e) => error_response(e.code, e.message),
+    }
+}
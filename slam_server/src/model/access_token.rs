@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+pub const SCOPE_SPORTS_READ: &str = "sports:read";
+pub const SCOPE_SPORTS_WRITE: &str = "sports:write";
+pub const SCOPE_AI_JOBS: &str = "ai:jobs";
+pub const ALL_SCOPES: [&str; 3] = [SCOPE_SPORTS_READ, SCOPE_SPORTS_WRITE, SCOPE_AI_JOBS];
+
+/// 个人访问令牌的明文前缀，便于在日志和密钥扫描中识别
+pub const TOKEN_PREFIX: &str = "slam_pat_";
+
+#[derive(Debug, Clone)]
+pub struct AccessToken {
+    pub id: String,
+    pub uid: i32,
+    pub name: String,
+    /// 明文 token 的 SHA-256，明文只在创建时返回一次
+    pub token_hash: String,
+    /// 明文 token 的前几位，用于列表中辨认
+    pub token_hint: String,
+    pub scopes: Vec<String>,
+    pub created_at: i64,
+    pub expires_at: i64,
+    pub last_used_at: Option<i64>,
+    pub revoked_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AccessTokenView {
+    pub id: String,
+    pub name: String,
+    pub token_hint: String,
+    pub scopes: Vec<String>,
+    pub created_at: i64,
+    pub expires_at: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_used_at: Option<i64>,
+}
+
+impl From<AccessToken> for AccessTokenView {
+    fn from(t: AccessToken) -> Self {
+        Self {
+            id: t.id,
+            name: t.name,
+            token_hint: t.token_hint,
+            scopes: t.scopes,
+            created_at: t.created_at,
+            expires_at: t.expires_at,
+            last_used_at: t.last_used_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CreatedAccessToken {
+    /// 明文 token，仅在创建时返回
+    pub token: String,
+    #[serde(flatten)]
+    pub info: AccessTokenView,
+}
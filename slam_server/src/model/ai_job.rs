@@ -8,6 +8,8 @@ pub const JOB_RUNNING: &str = "running";
 pub const JOB_READY: &str = "ready";
 pub const JOB_FAILED: &str = "failed";
 pub const JOB_SUBMITTED: &str = "submitted";
+/// 仅用于状态事件：任务失败后已安排自动重试，任务本身仍为 queued
+pub const JOB_RETRY_SCHEDULED: &str = "retry_scheduled";
 
 #[derive(Debug, Clone)]
 pub struct AiJobRecord {
@@ -75,3 +77,51 @@ pub struct AiJobSubmission {
     pub sport_id: i32,
     pub asset_paths: Vec<String>,
 }
+
+/// 管理员查看的跨用户任务概要
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AdminAiJobView {
+    pub id: String,
+    pub uid: i32,
+    pub status: String,
+    pub attempts: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_message: Option<String>,
+    pub created_at: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<i64>,
+}
+
+/// AI 任务队列深度
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct AiQueueStats {
+    pub queued: i64,
+    /// 已到重试时间、可以立即领取的排队任务
+    pub ready_to_run: i64,
+    pub running: i64,
+    pub failed: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oldest_queued_at: Option<i64>,
+    pub worker_concurrency: usize,
+}
+
+/// 任务状态变化事件，按 id 递增，供 SSE 推送与断线续传
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AiJobEvent {
+    pub id: i64,
+    #[serde(skip_serializing)]
+    pub uid: i32,
+    pub job_id: String,
+    /// queued、running、ready、failed 或 retry_scheduled
+    pub status: String,
+    pub attempts: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_attempt_at: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_message: Option<String>,
+    pub created_at: i64,
+}
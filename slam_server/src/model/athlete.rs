@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+pub const SEX_MALE: &str = "male";
+pub const SEX_FEMALE: &str = "female";
+pub const UNIT_METRIC: &str = "metric";
+pub const UNIT_IMPERIAL: &str = "imperial";
+
+/// 运动员档案的一个版本，从 valid_from 起生效直到下一个版本
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AthleteProfile {
+    pub id: i64,
+    pub valid_from: i64,
+    /// 出生日期，格式 YYYY-MM-DD
+    pub birth_date: Option<String>,
+    /// `male` 或 `female`
+    pub sex: Option<String>,
+    pub height_cm: Option<f64>,
+    pub resting_heart_rate: Option<i32>,
+    pub max_heart_rate: Option<i32>,
+    /// `metric` 或 `imperial`
+    pub unit_system: String,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct WeightEntry {
+    pub id: i64,
+    pub measured_at: i64,
+    pub weight_kg: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct HeartRateZone {
+    pub zone: i32,
+    pub min_bpm: i32,
+    pub max_bpm: i32,
+}
+
+/// 按运动当天生效的档案计算出的指标
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SportMetrics {
+    pub sport_id: i32,
+    /// 计算所用档案版本的生效时间，没有档案时为空
+    pub profile_valid_from: Option<i64>,
+    pub weight_kg: Option<f64>,
+    pub max_heart_rate: Option<i32>,
+    pub heart_rate_zones: Vec<HeartRateZone>,
+    /// 平均心率所在区间
+    pub heart_rate_avg_zone: Option<i32>,
+    /// Banister TRIMP 训练负荷，需要静息与最大心率
+    pub training_load: Option<f64>,
+}
+
+/// 当前生效的档案、最近体重以及据此推算的心率区间
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AthleteProfileView {
+    pub profile: Option<AthleteProfile>,
+    pub weight_kg: Option<f64>,
+    pub max_heart_rate: Option<i32>,
+    pub heart_rate_zones: Vec<HeartRateZone>,
+}
@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+pub const AUDIT_LOGIN_SUCCESS: &str = "login_success";
+pub const AUDIT_LOGIN_FAILURE: &str = "login_failure";
+pub const AUDIT_LOGIN_LOCKOUT: &str = "login_lockout";
+pub const AUDIT_LOGOUT: &str = "logout";
+pub const AUDIT_LOGOUT_ALL: &str = "logout_all";
+pub const AUDIT_SESSION_REVOKE: &str = "session_revoke";
+pub const AUDIT_TOKEN_CREATE: &str = "token_create";
+pub const AUDIT_TOKEN_REVOKE: &str = "token_revoke";
+pub const AUDIT_PASSWORD_CHANGE: &str = "password_change";
+pub const AUDIT_ACCOUNT_DELETE: &str = "account_delete";
+pub const AUDIT_AVATAR_CHANGE: &str = "avatar_change";
+pub const AUDIT_TOTP_ENABLE: &str = "totp_enable";
+pub const AUDIT_TOTP_DISABLE: &str = "totp_disable";
+pub const AUDIT_RECOVERY_CODES_REGENERATE: &str = "recovery_codes_regenerate";
+pub const AUDIT_OIDC_LINK: &str = "oidc_link";
+pub const AUDIT_OIDC_UNLINK: &str = "oidc_unlink";
+pub const AUDIT_ADMIN_DISABLE_USER: &str = "admin_disable_user";
+pub const AUDIT_ADMIN_ENABLE_USER: &str = "admin_enable_user";
+pub const AUDIT_ADMIN_RESET_PASSWORD: &str = "admin_reset_password";
+pub const AUDIT_ADMIN_REQUEUE_JOB: &str = "admin_requeue_job";
+pub const AUDIT_ADMIN_PURGE_JOB: &str = "admin_purge_job";
+pub const AUDIT_PASSWORD_RESET: &str = "password_reset";
+pub const AUDIT_SHARE_CREATE: &str = "share_create";
+pub const AUDIT_SHARE_REVOKE: &str = "share_revoke";
+pub const AUDIT_COACH_GRANT: &str = "coach_grant";
+pub const AUDIT_COACH_REVOKE: &str = "coach_revoke";
+pub const AUDIT_COACH_ACCESS: &str = "coach_access";
+pub const AUDIT_WEBHOOK_CREATE: &str = "webhook_create";
+pub const AUDIT_WEBHOOK_DELETE: &str = "webhook_delete";
+
+/// 审计日志只追加不修改
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AuditEntry {
+    pub id: i64,
+    /// 关联的用户，无法确定用户时为空
+    pub uid: Option<i32>,
+    pub action: String,
+    pub ip: String,
+    pub user_agent: String,
+    /// 附加信息，JSON 字符串
+    pub detail: String,
+    pub created_at: i64,
+}
+
+/// 审计日志查询条件，字段为空表示不限
+#[derive(Debug, Clone, Default)]
+pub struct AuditQuery {
+    pub uid: Option<i32>,
+    pub action: Option<String>,
+    pub ip: Option<String>,
+    /// 起止时间（秒级时间戳），左闭右开
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+}
+
+/// 某个账号或 IP 的登录失败计数，key 形如 `account:<name>`、`ip:<addr>`
+#[derive(Debug, Clone)]
+pub struct LoginAttempt {
+    pub key: String,
+    pub failures: i64,
+    pub last_failure_at: i64,
+    pub locked_until: i64,
+}
@@ -0,0 +1,193 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::model::social::UserCard;
+use crate::model::sport::SportType;
+
+pub const CLUB_OWNER: &str = "owner";
+pub const CLUB_MEMBER: &str = "member";
+
+#[derive(Debug, Clone)]
+pub struct Club {
+    pub id: i64,
+    pub name: String,
+    pub description: String,
+    /// 加入码，拿到加入码的用户可以直接加入
+    pub join_code: String,
+    pub created_at: i64,
+}
+
+/// 当前用户看到的俱乐部信息
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ClubView {
+    pub id: i64,
+    pub name: String,
+    pub description: String,
+    /// 当前用户在俱乐部中的角色：owner 或 member
+    pub role: String,
+    pub member_count: i64,
+    /// 只有管理员能看到加入码
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub join_code: Option<String>,
+    pub created_at: i64,
+}
+
+/// 俱乐部列表查询的原始行
+#[derive(Debug, Clone)]
+pub struct ClubRow {
+    pub club: Club,
+    pub role: String,
+    pub member_count: i64,
+}
+
+impl From<ClubRow> for ClubView {
+    fn from(row: ClubRow) -> Self {
+        let is_owner = row.role == CLUB_OWNER;
+        Self {
+            id: row.club.id,
+            name: row.club.name,
+            description: row.club.description,
+            role: row.role,
+            member_count: row.member_count,
+            join_code: is_owner.then_some(row.club.join_code),
+            created_at: row.club.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ClubMemberView {
+    #[serde(flatten)]
+    pub user: UserCard,
+    pub role: String,
+    pub joined_at: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ClubMemberRow {
+    pub uid: i32,
+    pub nickname: String,
+    pub avatar_version: Option<String>,
+    pub role: String,
+    pub joined_at: i64,
+}
+
+/// 待处理的俱乐部邀请
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ClubInvite {
+    pub club_id: i64,
+    pub club_name: String,
+    pub invited_by: i32,
+    pub created_at: i64,
+}
+
+/// 挑战的统计指标
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ChallengeMetric {
+    /// 总距离（米）
+    Distance,
+    /// 运动次数
+    Count,
+    /// 总时长（秒）
+    Duration,
+}
+
+impl ChallengeMetric {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChallengeMetric::Distance => "distance",
+            ChallengeMetric::Count => "count",
+            ChallengeMetric::Duration => "duration",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "distance" => Some(ChallengeMetric::Distance),
+            "count" => Some(ChallengeMetric::Count),
+            "duration" => Some(ChallengeMetric::Duration),
+            _ => None,
+        }
+    }
+}
+
+/// 俱乐部内限时挑战，统计窗口为 [start_time, end_time)
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Challenge {
+    pub id: i64,
+    pub club_id: i64,
+    pub name: String,
+    pub metric: ChallengeMetric,
+    /// 只统计该类型的运动，为空时统计全部
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sport_type: Option<SportType>,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub created_by: i32,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct LeaderboardEntry {
+    /// 名次，成绩相同的成员名次相同
+    pub rank: i64,
+    pub user: UserCard,
+    /// 按挑战指标统计的成绩
+    pub value: i64,
+    pub sessions: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Leaderboard {
+    pub challenge: Challenge,
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+/// 排行榜查询的原始行，已按成绩倒序
+#[derive(Debug, Clone)]
+pub struct LeaderboardRow {
+    pub uid: i32,
+    pub nickname: String,
+    pub avatar_version: Option<String>,
+    pub value: i64,
+    pub sessions: i64,
+}
+
+/// 按已排序的成绩计算名次，并列时名次相同、后续名次顺延（1, 1, 3）
+pub fn competition_ranks(values: &[i64]) -> Vec<i64> {
+    let mut ranks = Vec::with_capacity(values.len());
+    for (i, value) in values.iter().enumerate() {
+        let rank = if i > 0 && values[i - 1] == *value {
+            ranks[i - 1]
+        } else {
+            i as i64 + 1
+        };
+        ranks.push(rank);
+    }
+    ranks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ties_share_rank() {
+        assert_eq!(competition_ranks(&[30, 20, 20, 10]), vec![1, 2, 2, 4]);
+        assert_eq!(competition_ranks(&[0, 0]), vec![1, 1]);
+        assert!(competition_ranks(&[]).is_empty());
+    }
+
+    #[test]
+    fn metric_round_trip() {
+        for metric in [
+            ChallengeMetric::Distance,
+            ChallengeMetric::Count,
+            ChallengeMetric::Duration,
+        ] {
+            assert_eq!(ChallengeMetric::parse(metric.as_str()), Some(metric));
+        }
+        assert_eq!(ChallengeMetric::parse("pace"), None);
+    }
+}
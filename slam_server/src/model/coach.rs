@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::model::access_token::SCOPE_SPORTS_READ;
+use crate::model::social::UserCard;
+
+pub const SCOPE_STATS_READ: &str = "stats:read";
+pub const SCOPE_COMMENT: &str = "comment";
+pub const COACH_SCOPES: [&str; 3] = [SCOPE_SPORTS_READ, SCOPE_STATS_READ, SCOPE_COMMENT];
+
+pub const GRANT_PENDING: &str = "pending";
+pub const GRANT_ACTIVE: &str = "active";
+
+/// 运动员授予教练的访问权限，教练接受后生效，运动员可随时撤销
+#[derive(Debug, Clone)]
+pub struct CoachGrant {
+    pub athlete_uid: i32,
+    pub coach_uid: i32,
+    pub scopes: Vec<String>,
+    pub status: String,
+    pub created_at: i64,
+    pub accepted_at: Option<i64>,
+}
+
+impl CoachGrant {
+    pub fn allows(&self, scope: &str) -> bool {
+        self.status == GRANT_ACTIVE && self.scopes.iter().any(|s| s == scope)
+    }
+}
+
+/// 授权列表的一项，user 为对方（运动员视角是教练，教练视角是运动员）
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CoachGrantView {
+    pub user: UserCard,
+    pub scopes: Vec<String>,
+    /// pending 或 active
+    pub status: String,
+    pub created_at: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accepted_at: Option<i64>,
+}
+
+/// 授权列表查询的原始行，uid 等为对方的用户信息
+#[derive(Debug, Clone)]
+pub struct CoachGrantRow {
+    pub uid: i32,
+    pub nickname: String,
+    pub avatar_version: Option<String>,
+    pub scopes: Vec<String>,
+    pub status: String,
+    pub created_at: i64,
+    pub accepted_at: Option<i64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pending_grant_allows_nothing() {
+        let mut grant = CoachGrant {
+            athlete_uid: 1,
+            coach_uid: 2,
+            scopes: vec![SCOPE_SPORTS_READ.to_string()],
+            status: GRANT_PENDING.to_string(),
+            created_at: 0,
+            accepted_at: None,
+        };
+        assert!(!grant.allows(SCOPE_SPORTS_READ));
+        grant.status = GRANT_ACTIVE.to_string();
+        assert!(grant.allows(SCOPE_SPORTS_READ));
+        assert!(!grant.allows(SCOPE_STATS_READ));
+    }
+}
@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::model::social::UserCard;
+use crate::model::sport::SportVisibility;
+
+pub const REACTION_KUDOS: &str = "kudos";
+pub const REACTION_FIRE: &str = "fire";
+pub const REACTION_CLAP: &str = "clap";
+pub const REACTION_KINDS: [&str; 3] = [REACTION_KUDOS, REACTION_FIRE, REACTION_CLAP];
+
+pub const MAX_COMMENT_CHARS: usize = 500;
+
+#[derive(Debug, Clone)]
+pub struct SportComment {
+    pub id: i64,
+    pub sport_id: i32,
+    /// 评论作者
+    pub uid: i32,
+    pub content: String,
+    pub created_at: i64,
+    pub updated_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CommentView {
+    pub id: i64,
+    pub sport_id: i32,
+    pub author: UserCard,
+    pub content: String,
+    pub created_at: i64,
+    /// 最后编辑时间，未编辑过时为空
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<i64>,
+}
+
+/// 评论列表查询的原始行，附带作者信息
+#[derive(Debug, Clone)]
+pub struct CommentRow {
+    pub comment: SportComment,
+    pub nickname: String,
+    pub avatar_version: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ReactionCount {
+    pub kind: String,
+    pub count: i64,
+}
+
+/// 运动记录的回应统计
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ReactionSummary {
+    pub counts: Vec<ReactionCount>,
+    /// 当前用户做出的回应
+    pub mine: Vec<String>,
+}
+
+/// 运动记录的主人和可见范围，用于评论和回应的权限判断
+#[derive(Debug, Clone, Copy)]
+pub struct SportOwner {
+    pub uid: i32,
+    pub visibility: SportVisibility,
+}
@@ -1,4 +1,17 @@
+pub mod access_token;
 pub mod ai_job;
+pub mod athlete;
+pub mod audit;
+pub mod club;
+pub mod coach;
+pub mod comment;
+pub mod notification;
+pub mod oidc;
+pub mod session;
+pub mod share;
+pub mod social;
 pub mod sport;
 pub mod sport_xml;
+pub mod two_factor;
 pub mod user;
+pub mod webhook;
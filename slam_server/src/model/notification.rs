@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::model::social::UserCard;
+
+pub const NOTIFY_COMMENT: &str = "comment";
+pub const NOTIFY_REACTION: &str = "reaction";
+pub const NOTIFY_AI_JOB_READY: &str = "ai_job_ready";
+/// 只在不再重试的最终失败时发送
+pub const NOTIFY_AI_JOB_FAILED: &str = "ai_job_failed";
+/// 新运动刷新了某项个人最佳；某种运动的第一条纪录不发送
+pub const NOTIFY_PERSONAL_RECORD: &str = "personal_record";
+
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub id: i64,
+    /// 接收通知的用户
+    pub uid: i32,
+    pub kind: String,
+    /// 触发通知的用户，系统事件为空
+    pub actor_uid: Option<i32>,
+    pub sport_id: Option<i32>,
+    /// 附加信息，JSON 字符串
+    pub detail: String,
+    pub created_at: i64,
+    pub read_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct NotificationView {
+    pub id: i64,
+    pub kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actor: Option<UserCard>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sport_id: Option<i32>,
+    /// 附加信息，JSON 字符串
+    pub detail: String,
+    pub created_at: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub read_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct NotificationPage {
+    pub items: Vec<NotificationView>,
+    /// 全部未读通知数，不受分页影响
+    pub unread: i64,
+    /// 下一页的游标（本页最后一条通知的 id），没有更多数据时为空
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UnreadCount {
+    pub unread: i64,
+}
+
+/// 通知列表查询的原始行，附带触发者信息；触发者已注销时为空
+#[derive(Debug, Clone)]
+pub struct NotificationRow {
+    pub notification: Notification,
+    pub actor_nickname: Option<String>,
+    pub actor_avatar_version: Option<String>,
+}
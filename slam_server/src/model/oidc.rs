@@ -0,0 +1,28 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// 发起授权时保存的一次性状态，回调时核销
+#[derive(Debug, Clone)]
+pub struct OidcLoginState {
+    pub state: String,
+    pub nonce: String,
+    /// PKCE code_verifier，换取 token 时提交
+    pub code_verifier: String,
+    /// 已登录用户发起关联时的 uid，普通登录为空
+    pub link_uid: Option<i32>,
+    pub created_at: i64,
+    pub expires_at: i64,
+}
+
+/// 关联到本地账号的外部身份，按 (issuer, subject) 唯一
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct OidcIdentity {
+    pub id: i64,
+    #[serde(skip)]
+    pub uid: i32,
+    pub issuer: String,
+    pub subject: String,
+    pub email: String,
+    pub created_at: i64,
+    pub last_login_at: Option<i64>,
+}
@@ -0,0 +1,96 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::model::social::UserCard;
+use crate::model::sport::Sport;
+
+/// 运动记录的公开分享链接，只保存 token 的 SHA-256，明文只在创建时返回一次
+#[derive(Debug, Clone)]
+pub struct SportShare {
+    pub id: String,
+    pub uid: i32,
+    pub sport_id: i32,
+    pub token_hash: String,
+    pub created_at: i64,
+    pub expires_at: Option<i64>,
+    pub revoked_at: Option<i64>,
+    pub views: i64,
+}
+
+impl SportShare {
+    pub fn is_active(&self, now: i64) -> bool {
+        self.revoked_at.is_none() && self.expires_at.is_none_or(|t| t > now)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SportShareView {
+    pub id: String,
+    pub sport_id: i32,
+    pub created_at: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revoked_at: Option<i64>,
+    pub views: i64,
+}
+
+impl From<SportShare> for SportShareView {
+    fn from(s: SportShare) -> Self {
+        Self {
+            id: s.id,
+            sport_id: s.sport_id,
+            created_at: s.created_at,
+            expires_at: s.expires_at,
+            revoked_at: s.revoked_at,
+            views: s.views,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CreatedSportShare {
+    /// 明文 token，仅在创建时返回
+    pub token: String,
+    /// 公开查看地址
+    pub url: String,
+    /// PNG 分享卡片地址
+    pub card_url: String,
+    #[serde(flatten)]
+    pub info: SportShareView,
+}
+
+/// 通过分享链接看到的只读运动记录
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SharedSport {
+    pub user: UserCard,
+    pub sport: Sport,
+    pub views: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<i64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn revoked_or_expired_share_is_inactive() {
+        let mut share = SportShare {
+            id: "s".to_string(),
+            uid: 1,
+            sport_id: 1,
+            token_hash: String::new(),
+            created_at: 0,
+            expires_at: None,
+            revoked_at: None,
+            views: 0,
+        };
+        assert!(share.is_active(100));
+        share.expires_at = Some(100);
+        assert!(!share.is_active(100));
+        assert!(share.is_active(99));
+        share.revoked_at = Some(50);
+        assert!(!share.is_active(60));
+    }
+}
@@ -0,0 +1,163 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::model::sport::{Sport, SportVisibility};
+
+pub const FOLLOW_PENDING: &str = "pending";
+pub const FOLLOW_ACCEPTED: &str = "accepted";
+
+/// 关注关系，私密账号的关注需对方批准后才生效
+#[derive(Debug, Clone)]
+pub struct Follow {
+    pub follower_uid: i32,
+    pub followee_uid: i32,
+    pub status: String,
+    pub created_at: i64,
+    pub accepted_at: Option<i64>,
+}
+
+/// 列表中展示的用户概况
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UserCard {
+    pub uid: i32,
+    pub nickname: String,
+    /// 头像地址，未上传头像时为空
+    pub avatar: String,
+}
+
+/// 关注或粉丝列表的一项
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FollowView {
+    #[serde(flatten)]
+    pub user: UserCard,
+    pub status: String,
+    pub created_at: i64,
+}
+
+/// 列表查询的原始行，avatar_version 由服务层转换为头像地址
+#[derive(Debug, Clone)]
+pub struct FollowRow {
+    pub uid: i32,
+    pub nickname: String,
+    pub avatar_version: Option<String>,
+    pub status: String,
+    pub created_at: i64,
+}
+
+/// 查看者与运动记录主人的关系，决定可见的运动范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Viewer {
+    Owner,
+    Follower,
+    Stranger,
+}
+
+impl Viewer {
+    /// 可见的运动范围；私密账号的公开运动也只对粉丝可见
+    pub fn visible_levels(self, private_account: bool) -> &'static [SportVisibility] {
+        match self {
+            Viewer::Owner => &[
+                SportVisibility::Private,
+                SportVisibility::Followers,
+                SportVisibility::Public,
+            ],
+            Viewer::Follower => &[SportVisibility::Followers, SportVisibility::Public],
+            Viewer::Stranger if private_account => &[],
+            Viewer::Stranger => &[SportVisibility::Public],
+        }
+    }
+}
+
+/// 查看他人主页时的用户信息
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SocialProfile {
+    #[serde(flatten)]
+    pub user: UserCard,
+    pub bio: String,
+    pub private_account: bool,
+    pub followers: i64,
+    pub following: i64,
+    /// 当前用户对该用户的关注状态：none、pending 或 accepted
+    pub follow_status: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SocialSettings {
+    /// 私密账号：关注需要批准，公开运动也只对粉丝可见
+    pub private_account: bool,
+}
+
+/// 动态流中的一条运动记录
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FeedItem {
+    pub user: UserCard,
+    pub sport: Sport,
+}
+
+/// 动态流的原始行
+#[derive(Debug, Clone)]
+pub struct FeedRow {
+    pub uid: i32,
+    pub nickname: String,
+    pub avatar_version: Option<String>,
+    pub sport: Sport,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FeedPage {
+    pub items: Vec<FeedItem>,
+    /// 下一页的游标，没有更多数据时为空
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// 按 (start_time, id) 倒序分页的游标位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeedCursor {
+    pub start_time: i64,
+    pub id: i32,
+}
+
+impl FeedCursor {
+    pub fn encode(&self) -> String {
+        format!("{}_{}", self.start_time, self.id)
+    }
+
+    pub fn decode(s: &str) -> Option<Self> {
+        let (start_time, id) = s.split_once('_')?;
+        Some(Self {
+            start_time: start_time.parse().ok()?,
+            id: id.parse().ok()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursor_round_trip() {
+        let cursor = FeedCursor {
+            start_time: -5,
+            id: 42,
+        };
+        assert_eq!(FeedCursor::decode(&cursor.encode()), Some(cursor));
+        assert_eq!(FeedCursor::decode("abc"), None);
+        assert_eq!(FeedCursor::decode("1_x"), None);
+    }
+
+    #[test]
+    fn private_account_hides_public_sports_from_strangers() {
+        assert!(Viewer::Stranger.visible_levels(true).is_empty());
+        assert_eq!(
+            Viewer::Stranger.visible_levels(false),
+            &[SportVisibility::Public]
+        );
+        assert!(
+            !Viewer::Follower
+                .visible_levels(true)
+                .contains(&SportVisibility::Private)
+        );
+    }
+}
@@ -11,6 +11,8 @@ pub struct Sport {
     pub r#type: SportType,
     pub start_time: i64,
     pub calories: i32,
+    /// calories 为空时由服务端按 MET 估算得到
+    pub calories_estimated: bool,
     pub distance_meter: i32,
     pub duration_second: i32,
     pub heart_rate_avg: i32,
@@ -18,6 +20,8 @@ pub struct Sport {
     pub pace_average: String,
     pub extra: Option<SportExtra>,
     pub tracks: Vec<Track>,
+    /// 可见范围，未指定时仅自己可见
+    pub visibility: SportVisibility,
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema, Default, Clone)]
@@ -112,6 +116,7 @@ impl Sport {
             r#type: data.r#type,
             start_time: ts,
             calories: data.calories,
+            calories_estimated: false,
             distance_meter: data.distance_meter,
             duration_second: data.duration_second,
             heart_rate_avg: data.heart_rate_avg,
@@ -119,6 +124,7 @@ impl Sport {
             pace_average: data.pace_average,
             extra,
             tracks,
+            visibility: SportVisibility::Private,
         })
     }
 }
@@ -231,6 +237,7 @@ mod tests {
             r#type: SportType::Swimming,
             start_time: 1694560000,
             calories: 200,
+            calories_estimated: false,
             distance_meter: 1000,
             duration_second: 600,
             heart_rate_avg: 120,
@@ -266,6 +273,7 @@ mod tests {
                     })),
                 },
             ],
+            visibility: SportVisibility::Private,
         };
 
         let xml = xml_se::to_string(&sport).expect("serialize sport to xml");
@@ -281,6 +289,7 @@ mod tests {
             r#type: SportType::Running,
             start_time: 1694560000,
             calories: 291,
+            calories_estimated: false,
             distance_meter: 4820,
             duration_second: 1872,
             heart_rate_avg: 158,
@@ -300,6 +309,7 @@ mod tests {
                 pace_average: "6'17''".to_string(),
                 extra: None,
             }],
+            visibility: SportVisibility::Private,
         };
         let xml = xml_se::to_string(&sport).expect("serialize running to xml");
         assert!(!xml.is_empty());
@@ -312,6 +322,7 @@ mod tests {
             r#type: SportType::Swimming,
             start_time: 0,
             calories: 0,
+            calories_estimated: false,
             distance_meter: 0,
             duration_second: 0,
             heart_rate_avg: 0,
@@ -342,6 +353,7 @@ mod tests {
                     extra: None,
                 },
             ],
+            visibility: SportVisibility::Private,
         };
         assert!(sport.validate_type_consistency().is_ok());
     }
@@ -353,6 +365,7 @@ mod tests {
             r#type: SportType::Running,
             start_time: 0,
             calories: 0,
+            calories_estimated: false,
             distance_meter: 0,
             duration_second: 0,
             heart_rate_avg: 0,
@@ -372,6 +385,7 @@ mod tests {
                 pace_average: "".to_string(),
                 extra: None,
             }],
+            visibility: SportVisibility::Private,
         };
         assert!(sport.validate_type_consistency().is_ok());
     }
@@ -383,6 +397,7 @@ mod tests {
             r#type: SportType::Swimming,
             start_time: 0,
             calories: 0,
+            calories_estimated: false,
             distance_meter: 0,
             duration_second: 0,
             heart_rate_avg: 0,
@@ -397,6 +412,7 @@ mod tests {
                 pace_max: "6'30''".to_string(),
             })),
             tracks: vec![],
+            visibility: SportVisibility::Private,
         };
         let err = sport
             .validate_type_consistency()
@@ -411,6 +427,7 @@ mod tests {
             r#type: SportType::Swimming,
             start_time: 0,
             calories: 0,
+            calories_estimated: false,
             distance_meter: 0,
             duration_second: 0,
             heart_rate_avg: 0,
@@ -443,6 +460,7 @@ mod tests {
                     })),
                 },
             ],
+            visibility: SportVisibility::Private,
         };
         let err = sport
             .validate_type_consistency()
@@ -457,6 +475,7 @@ mod tests {
             r#type: SportType::Unknown,
             start_time: 0,
             calories: 0,
+            calories_estimated: false,
             distance_meter: 0,
             duration_second: 0,
             heart_rate_avg: 0,
@@ -469,6 +488,7 @@ mod tests {
                 lane_length_meter: None,
             })),
             tracks: vec![],
+            visibility: SportVisibility::Private,
         };
         let err = sport
             .validate_type_consistency()
@@ -491,6 +511,35 @@ mod tests {
     }
 }
 
+/// 运动记录的可见范围
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SportVisibility {
+    #[default]
+    Private,
+    Followers,
+    Public,
+}
+
+impl SportVisibility {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SportVisibility::Private => "private",
+            SportVisibility::Followers => "followers",
+            SportVisibility::Public => "public",
+        }
+    }
+
+    /// 无法识别的值按仅自己可见处理
+    pub fn parse(s: &str) -> Self {
+        match s {
+            "followers" => SportVisibility::Followers,
+            "public" => SportVisibility::Public,
+            _ => SportVisibility::Private,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, ToSchema, Clone, Copy, Default, PartialEq, Eq, Hash)]
 #[serde(rename_all = "PascalCase")]
 pub enum SportType {
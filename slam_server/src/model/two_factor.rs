@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// 用户的 TOTP 密钥；`enabled_at` 为空表示已生成但尚未验证启用
+#[derive(Debug, Clone)]
+pub struct TotpSecret {
+    pub uid: i32,
+    /// Base32 编码的密钥
+    pub secret: String,
+    pub created_at: i64,
+    pub enabled_at: Option<i64>,
+    /// 最近一次验证通过的时间步，同一验证码不能重复使用
+    pub last_used_step: i64,
+}
+
+/// 密码校验通过、等待第二步验证的登录，只保存挑战令牌的哈希
+#[derive(Debug, Clone)]
+pub struct LoginChallenge {
+    pub token_hash: String,
+    pub uid: i32,
+    pub created_at: i64,
+    pub expires_at: i64,
+    pub attempts: i64,
+    pub used_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TwoFactorStatus {
+    pub enabled: bool,
+    /// 尚未使用的恢复码数量
+    pub recovery_codes_remaining: i64,
+}
+
+/// 开始绑定时返回的密钥，需用验证码确认后才生效
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TotpSetup {
+    pub secret: String,
+    pub provisioning_uri: String,
+}
+
+/// 恢复码明文只在生成时返回这一次
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RecoveryCodes {
+    pub recovery_codes: Vec<String>,
+}
+
+/// 开启两步验证的账号登录时不下发 cookie，而是返回挑战令牌
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct LoginChallengeView {
+    pub two_factor_required: bool,
+    pub challenge: String,
+    pub expires_at: i64,
+}
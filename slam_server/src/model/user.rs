@@ -12,5 +12,68 @@ pub struct User {
 #[derive(Debug, Serialize, Deserialize, ToSchema, Default, Clone)]
 pub struct UserInfo {
     pub nickname: String,
-    pub avatar: String,
+    pub bio: String,
+    /// 当前头像的版本（默认尺寸图片的 ETag），未上传头像时为空
+    pub avatar_version: Option<String>,
+}
+
+/// 头像保存的尺寸（正方形边长，像素），最后一档为默认尺寸
+pub const AVATAR_SIZES: [u32; 3] = [64, 128, 256];
+
+/// 某个尺寸的头像图片
+#[derive(Debug, Clone)]
+pub struct AvatarImage {
+    pub size: u32,
+    pub mime: String,
+    pub data: Vec<u8>,
+    pub etag: String,
+    pub updated_at: i64,
+}
+
+/// 只通过外部身份登录、没有本地密码的账号在 password 列保存该值，任何密码都无法匹配
+pub const PASSWORD_UNSET: &str = "!";
+
+pub const ROLE_USER: &str = "user";
+pub const ROLE_ADMIN: &str = "admin";
+
+/// 账号角色与禁用状态
+#[derive(Debug, Clone)]
+pub struct AccountStatus {
+    pub role: String,
+    pub disabled_at: Option<i64>,
+}
+
+/// 管理员查看的用户概况
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone)]
+pub struct AdminUserView {
+    pub id: i32,
+    pub name: String,
+    pub nickname: String,
+    pub role: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disabled_at: Option<i64>,
+    pub sport_count: i64,
+    pub ai_job_count: i64,
+    /// 排队中与识别中的 AI 任务数
+    pub active_ai_job_count: i64,
+    /// 头像与 AI 任务图片占用的字节数
+    pub storage_bytes: i64,
+}
+
+/// 管理员签发的一次性密码重置码，只保存哈希
+#[derive(Debug, Clone)]
+pub struct PasswordResetCode {
+    pub code_hash: String,
+    pub uid: i32,
+    pub created_by: i32,
+    pub created_at: i64,
+    pub expires_at: i64,
+    pub used_at: Option<i64>,
+}
+
+/// 签发重置码的响应，明文只返回这一次
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone)]
+pub struct IssuedResetCode {
+    pub code: String,
+    pub expires_at: i64,
 }
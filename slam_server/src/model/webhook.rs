@@ -0,0 +1,142 @@
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use utoipa::ToSchema;
+
+pub const EVENT_SPORT_CREATED: &str = "sport.created";
+pub const EVENT_SPORT_UPDATED: &str = "sport.updated";
+pub const EVENT_SPORT_DELETED: &str = "sport.deleted";
+pub const EVENT_AI_JOB_READY: &str = "ai_job.ready";
+/// 只在不再重试的最终失败时发送
+pub const EVENT_AI_JOB_FAILED: &str = "ai_job.failed";
+pub const WEBHOOK_EVENTS: [&str; 5] = [
+    EVENT_SPORT_CREATED,
+    EVENT_SPORT_UPDATED,
+    EVENT_SPORT_DELETED,
+    EVENT_AI_JOB_READY,
+    EVENT_AI_JOB_FAILED,
+];
+
+pub const DELIVERY_PENDING: &str = "pending";
+pub const DELIVERY_RUNNING: &str = "running";
+pub const DELIVERY_SUCCEEDED: &str = "succeeded";
+pub const DELIVERY_FAILED: &str = "failed";
+
+/// 请求头：事件名、投递 id、签名时间戳和签名
+pub const HEADER_EVENT: &str = "x-slam-event";
+pub const HEADER_DELIVERY: &str = "x-slam-delivery";
+pub const HEADER_TIMESTAMP: &str = "x-slam-timestamp";
+pub const HEADER_SIGNATURE: &str = "x-slam-signature";
+
+/// 用户注册的出站 webhook；签名密钥需要参与 HMAC 计算，因此明文保存，只在创建时返回
+#[derive(Debug, Clone)]
+pub struct Webhook {
+    pub id: String,
+    pub uid: i32,
+    pub url: String,
+    pub secret: String,
+    pub events: Vec<String>,
+    /// 管理员注册的全站 webhook，接收所有用户的事件
+    pub all_users: bool,
+    pub created_at: i64,
+}
+
+impl Webhook {
+    pub fn subscribes(&self, event: &str) -> bool {
+        self.events.iter().any(|e| e == event)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct WebhookView {
+    pub id: String,
+    pub url: String,
+    pub events: Vec<String>,
+    pub all_users: bool,
+    pub created_at: i64,
+}
+
+impl From<Webhook> for WebhookView {
+    fn from(w: Webhook) -> Self {
+        Self {
+            id: w.id,
+            url: w.url,
+            events: w.events,
+            all_users: w.all_users,
+            created_at: w.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CreatedWebhook {
+    /// 签名密钥，仅在创建时返回
+    pub secret: String,
+    #[serde(flatten)]
+    pub info: WebhookView,
+}
+
+/// 一次投递，按 ai_jobs 的方式排队：领取时加租约，失败后设置下次尝试时间
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct WebhookDelivery {
+    pub id: String,
+    pub webhook_id: String,
+    /// webhook 的所有者
+    #[serde(skip_serializing)]
+    pub uid: i32,
+    /// 事件所属的用户，注销时一并删除相关投递
+    #[serde(skip_serializing)]
+    pub subject_uid: i32,
+    pub event: String,
+    /// 请求体，JSON 字符串
+    pub payload: String,
+    pub status: String,
+    pub attempts: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_attempt_at: Option<i64>,
+    #[serde(skip_serializing)]
+    pub lease_until: Option<i64>,
+    /// 最近一次请求的 HTTP 状态码，连接失败时为空
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_status: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delivered_at: Option<i64>,
+}
+
+/// `sha256=` 加上 HMAC-SHA256(secret, "{timestamp}.{body}") 的十六进制
+pub fn sign_payload(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    let digest: String = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    format!("sha256={digest}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signature_covers_timestamp_and_body() {
+        let body = r#"{"event":"sport.created"}"#;
+        assert_eq!(
+            sign_payload("whsec_test", 1_700_000_000, body),
+            "sha256=d16dda6d77663bdee3168571de008c4372687cfbc4e8678d6ff6c2af548977b2"
+        );
+        assert_ne!(
+            sign_payload("whsec_test", 1_700_000_001, body),
+            sign_payload("whsec_test", 1_700_000_000, body)
+        );
+    }
+}
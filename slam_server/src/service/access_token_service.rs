@@ -0,0 +1,174 @@
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::dao::idl::AccessTokenDao;
+use crate::model::access_token::{
+    ALL_SCOPES, AccessToken, AccessTokenView, CreatedAccessToken, TOKEN_PREFIX,
+};
+use crate::service::common::ServiceError;
+
+const DEFAULT_EXPIRES_IN_DAYS: i64 = 90;
+const MAX_EXPIRES_IN_DAYS: i64 = 365;
+const MAX_TOKENS_PER_USER: usize = 20;
+const TOUCH_INTERVAL_SECONDS: i64 = 60;
+
+pub struct AccessTokenService {
+    dao: Arc<dyn AccessTokenDao + Send + Sync>,
+}
+
+impl AccessTokenService {
+    pub fn new(dao: Arc<dyn AccessTokenDao + Send + Sync>) -> Self {
+        Self { dao }
+    }
+
+    pub async fn create(
+        &self,
+        uid: i32,
+        name: &str,
+        scopes: &[String],
+        expires_in_days: Option<i64>,
+    ) -> Result<CreatedAccessToken, ServiceError> {
+        let name = name.trim();
+        if name.is_empty() || name.chars().count() > 64 {
+            return Err(bad_request("令牌名称不能为空且不超过64个字符"));
+        }
+        let mut normalized: Vec<String> = Vec::new();
+        for scope in scopes {
+            if !ALL_SCOPES.contains(&scope.as_str()) {
+                return Err(bad_request(&format!("未知的权限范围: {scope}")));
+            }
+            if !normalized.contains(scope) {
+                normalized.push(scope.clone());
+            }
+        }
+        if normalized.is_empty() {
+            return Err(bad_request("至少需要一个权限范围"));
+        }
+        let days = expires_in_days.unwrap_or(DEFAULT_EXPIRES_IN_DAYS);
+        if !(1..=MAX_EXPIRES_IN_DAYS).contains(&days) {
+            return Err(bad_request("有效期需在1到365天之间"));
+        }
+        let now = now_timestamp();
+        let active = self
+            .dao
+            .list_tokens(uid, now)
+            .await
+            .map_err(internal_error)?;
+        if active.len() >= MAX_TOKENS_PER_USER {
+            return Err(ServiceError {
+                code: 409,
+                message: "访问令牌数量已达上限".to_string(),
+            });
+        }
+
+        let mut secret = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut secret);
+        let plaintext = format!("{TOKEN_PREFIX}{}", URL_SAFE_NO_PAD.encode(secret));
+        let record = AccessToken {
+            id: Uuid::new_v4().to_string(),
+            uid,
+            name: name.to_string(),
+            token_hash: hash_token(&plaintext),
+            token_hint: plaintext[..TOKEN_PREFIX.len() + 6].to_string(),
+            scopes: normalized,
+            created_at: now,
+            expires_at: now + days * 86400,
+            last_used_at: None,
+            revoked_at: None,
+        };
+        self.dao
+            .create_token(record.clone())
+            .await
+            .map_err(internal_error)?;
+        Ok(CreatedAccessToken {
+            token: plaintext,
+            info: record.into(),
+        })
+    }
+
+    pub async fn list(&self, uid: i32) -> Result<Vec<AccessTokenView>, ServiceError> {
+        let tokens = self
+            .dao
+            .list_tokens(uid, now_timestamp())
+            .await
+            .map_err(internal_error)?;
+        Ok(tokens.into_iter().map(AccessTokenView::from).collect())
+    }
+
+    pub async fn revoke(&self, uid: i32, id: &str) -> Result<(), ServiceError> {
+        if self
+            .dao
+            .revoke_token(uid, id, now_timestamp())
+            .await
+            .map_err(internal_error)?
+        {
+            Ok(())
+        } else {
+            Err(ServiceError {
+                code: 404,
+                message: "访问令牌不存在".to_string(),
+            })
+        }
+    }
+
+    /// 校验明文 token，返回未撤销、未过期的令牌记录
+    pub async fn authenticate(&self, plaintext: &str) -> Result<AccessToken, ServiceError> {
+        let unauthorized = || ServiceError {
+            code: 401,
+            message: "访问令牌无效或已过期".to_string(),
+        };
+        if !plaintext.starts_with(TOKEN_PREFIX) {
+            return Err(unauthorized());
+        }
+        let now = now_timestamp();
+        let token = match self
+            .dao
+            .get_token_by_hash(&hash_token(plaintext))
+            .await
+            .map_err(internal_error)?
+        {
+            Some(t) if t.revoked_at.is_none() && t.expires_at > now => t,
+            _ => return Err(unauthorized()),
+        };
+        if token
+            .last_used_at
+            .is_none_or(|t| now - t >= TOUCH_INTERVAL_SECONDS)
+        {
+            self.dao
+                .touch_token(&token.id, now)
+                .await
+                .map_err(internal_error)?;
+        }
+        Ok(token)
+    }
+}
+
+fn hash_token(plaintext: &str) -> String {
+    Sha256::digest(plaintext.as_bytes())
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+fn bad_request(message: &str) -> ServiceError {
+    ServiceError {
+        code: 400,
+        message: message.to_string(),
+    }
+}
+
+fn now_timestamp() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
+
+fn internal_error(message: String) -> ServiceError {
+    ServiceError { code: 500, message }
+}
@@ -0,0 +1,265 @@
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rand::RngCore;
+
+use crate::dao::idl::{AccessTokenDao, SessionDao, UserDao};
+use crate::model::ai_job::{AdminAiJobView, AiQueueStats};
+use crate::model::audit::{
+    AUDIT_ADMIN_DISABLE_USER, AUDIT_ADMIN_ENABLE_USER, AUDIT_ADMIN_PURGE_JOB,
+    AUDIT_ADMIN_REQUEUE_JOB, AUDIT_ADMIN_RESET_PASSWORD,
+};
+use crate::model::user::{AdminUserView, IssuedResetCode, PasswordResetCode, ROLE_ADMIN};
+use crate::service::ai_job_service::AIJobService;
+use crate::service::audit_service::AuditService;
+use crate::service::common::ServiceError;
+use crate::service::session_service::ClientMeta;
+use crate::service::user_service::hash_reset_code;
+
+/// 重置码有效期
+const RESET_CODE_TTL_SECONDS: i64 = 24 * 3600;
+
+pub struct AdminService {
+    users: Arc<dyn UserDao + Send + Sync>,
+    sessions: Arc<dyn SessionDao + Send + Sync>,
+    tokens: Arc<dyn AccessTokenDao + Send + Sync>,
+    ai_job_service: Arc<AIJobService>,
+    audit: Arc<AuditService>,
+    worker_concurrency: usize,
+}
+
+impl AdminService {
+    pub fn new(
+        users: Arc<dyn UserDao + Send + Sync>,
+        sessions: Arc<dyn SessionDao + Send + Sync>,
+        tokens: Arc<dyn AccessTokenDao + Send + Sync>,
+        ai_job_service: Arc<AIJobService>,
+        audit: Arc<AuditService>,
+        worker_concurrency: usize,
+    ) -> Self {
+        Self {
+            users,
+            sessions,
+            tokens,
+            ai_job_service,
+            audit,
+            worker_concurrency,
+        }
+    }
+
+    /// 启动时把配置中已存在的账号设为管理员；注册时不会提升，
+    /// 否则开放注册或 OIDC 自动注册时任何人都能抢注配置中的用户名成为管理员，
+    /// 账号注册后需重启才会生效
+    pub async fn promote_configured(&self, names: &[String]) {
+        if names.is_empty() {
+            return;
+        }
+        match self.users.promote_admins(names).await {
+            Ok(0) => {}
+            Ok(promoted) => tracing::info!(promoted, "configured admins promoted"),
+            Err(e) => tracing::warn!(error = %e, "failed to promote configured admins"),
+        }
+    }
+
+    /// 当前用户须为未禁用的管理员
+    pub async fn require_admin(&self, uid: i32) -> Result<(), ServiceError> {
+        match self.users.get_account_status(uid).await {
+            Ok(Some(status)) if status.role == ROLE_ADMIN && status.disabled_at.is_none() => Ok(()),
+            Ok(_) => Err(ServiceError {
+                code: 403,
+                message: "需要管理员权限".to_string(),
+            }),
+            Err(e) => Err(internal_error(e)),
+        }
+    }
+
+    pub async fn list_users(
+        &self,
+        page: i32,
+        size: i32,
+    ) -> Result<Vec<AdminUserView>, ServiceError> {
+        let mut users = self
+            .users
+            .list_users(page, size)
+            .await
+            .map_err(internal_error)?;
+        for user in users.iter_mut() {
+            user.storage_bytes += self.ai_job_service.storage_bytes(user.id).await?;
+        }
+        Ok(users)
+    }
+
+    /// 禁用账号会同时撤销其全部会话和访问令牌
+    pub async fn set_disabled(
+        &self,
+        admin_uid: i32,
+        uid: i32,
+        disabled: bool,
+        client: &ClientMeta,
+    ) -> Result<(), ServiceError> {
+        if disabled && uid == admin_uid {
+            return Err(ServiceError {
+                code: 409,
+                message: "不能禁用自己的账号".to_string(),
+            });
+        }
+        let now = now_timestamp();
+        let disabled_at = disabled.then_some(now);
+        if !self
+            .users
+            .set_disabled(uid, disabled_at)
+            .await
+            .map_err(internal_error)?
+        {
+            return Err(user_not_found());
+        }
+        if disabled {
+            self.sessions
+                .revoke_all_sessions(uid, None, now)
+                .await
+                .map_err(internal_error)?;
+            self.tokens
+                .revoke_all_tokens(uid, now)
+                .await
+                .map_err(internal_error)?;
+        }
+        let action = if disabled {
+            AUDIT_ADMIN_DISABLE_USER
+        } else {
+            AUDIT_ADMIN_ENABLE_USER
+        };
+        self.audit
+            .record(
+                Some(admin_uid),
+                action,
+                client,
+                serde_json::json!({ "target_uid": uid }),
+            )
+            .await;
+        tracing::info!(admin_uid, uid, disabled, "account status changed by admin");
+        Ok(())
+    }
+
+    /// 签发一次性重置码，明文只在响应中出现一次，由管理员线下转交用户
+    pub async fn issue_reset_code(
+        &self,
+        admin_uid: i32,
+        uid: i32,
+        client: &ClientMeta,
+    ) -> Result<IssuedResetCode, ServiceError> {
+        if self
+            .users
+            .get_account_status(uid)
+            .await
+            .map_err(internal_error)?
+            .is_none()
+        {
+            return Err(user_not_found());
+        }
+        let mut secret = [0u8; 8];
+        rand::thread_rng().fill_bytes(&mut secret);
+        let hex: String = secret.iter().map(|b| format!("{b:02X}")).collect();
+        let code = format!(
+            "{}-{}-{}-{}",
+            &hex[..4],
+            &hex[4..8],
+            &hex[8..12],
+            &hex[12..]
+        );
+        let now = now_timestamp();
+        let expires_at = now + RESET_CODE_TTL_SECONDS;
+        self.users
+            .create_reset_code(PasswordResetCode {
+                code_hash: hash_reset_code(&code),
+                uid,
+                created_by: admin_uid,
+                created_at: now,
+                expires_at,
+                used_at: None,
+            })
+            .await
+            .map_err(internal_error)?;
+        self.audit
+            .record(
+                Some(admin_uid),
+                AUDIT_ADMIN_RESET_PASSWORD,
+                client,
+                serde_json::json!({ "target_uid": uid, "expires_at": expires_at }),
+            )
+            .await;
+        Ok(IssuedResetCode { code, expires_at })
+    }
+
+    pub async fn list_jobs(
+        &self,
+        status: Option<&str>,
+        page: i32,
+        size: i32,
+    ) -> Result<Vec<AdminAiJobView>, ServiceError> {
+        self.ai_job_service.admin_list(status, page, size).await
+    }
+
+    /// 重新排队任意用户的失败任务
+    pub async fn requeue_job(
+        &self,
+        admin_uid: i32,
+        id: &str,
+        client: &ClientMeta,
+    ) -> Result<(), ServiceError> {
+        let owner = self.ai_job_service.owner_of(id).await?;
+        self.ai_job_service.retry(owner, id).await?;
+        self.audit
+            .record(
+                Some(admin_uid),
+                AUDIT_ADMIN_REQUEUE_JOB,
+                client,
+                serde_json::json!({ "job_id": id, "owner_uid": owner }),
+            )
+            .await;
+        Ok(())
+    }
+
+    /// 删除任意用户的任务及其图片，规则与用户自行删除相同
+    pub async fn purge_job(
+        &self,
+        admin_uid: i32,
+        id: &str,
+        client: &ClientMeta,
+    ) -> Result<(), ServiceError> {
+        let owner = self.ai_job_service.owner_of(id).await?;
+        self.ai_job_service.delete(owner, id).await?;
+        self.audit
+            .record(
+                Some(admin_uid),
+                AUDIT_ADMIN_PURGE_JOB,
+                client,
+                serde_json::json!({ "job_id": id, "owner_uid": owner }),
+            )
+            .await;
+        Ok(())
+    }
+
+    pub async fn queue_stats(&self) -> Result<AiQueueStats, ServiceError> {
+        let mut stats = self.ai_job_service.queue_stats().await?;
+        stats.worker_concurrency = self.worker_concurrency;
+        Ok(stats)
+    }
+}
+
+fn user_not_found() -> ServiceError {
+    ServiceError {
+        code: 404,
+        message: "用户不存在".to_string(),
+    }
+}
+
+fn now_timestamp() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
+
+fn internal_error(message: String) -> ServiceError {
+    ServiceError { code: 500, message }
+}
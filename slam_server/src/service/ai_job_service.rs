@@ -1,19 +1,28 @@
+use std::collections::VecDeque;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicI64, Ordering};
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use tokio::sync::Notify;
+use futures_util::Stream;
+use tokio::sync::{Notify, broadcast};
 use uuid::Uuid;
 
 use crate::dao::idl::AiJobDao;
 use crate::model::ai_job::{
-    AiJobAsset, AiJobRecord, AiJobView, JOB_FAILED, JOB_QUEUED, JOB_RUNNING, JOB_SUBMITTED,
+    AdminAiJobView, AiJobAsset, AiJobEvent, AiJobRecord, AiJobView, AiQueueStats, JOB_FAILED,
+    JOB_QUEUED, JOB_READY, JOB_RETRY_SCHEDULED, JOB_RUNNING, JOB_SUBMITTED,
 };
 use crate::model::sport::Sport;
 use crate::service::common::ServiceError;
 use crate::service::image_service::ImageService;
 
+/// 状态事件保留一天，足够客户端断线后续传
+const EVENT_RETENTION_SECONDS: i64 = 86400;
+const EVENT_PRUNE_INTERVAL_SECONDS: i64 = 60;
+const EVENT_BATCH: i32 = 100;
+
 pub struct JobUpload {
     pub bytes: Vec<u8>,
     pub mime: String,
@@ -24,6 +33,8 @@ pub struct AIJobService {
     image_service: Arc<ImageService>,
     storage_dir: PathBuf,
     notify: Arc<Notify>,
+    events: broadcast::Sender<AiJobEvent>,
+    last_event_prune: AtomicI64,
 }
 
 impl AIJobService {
@@ -38,6 +49,8 @@ impl AIJobService {
             image_service,
             storage_dir: storage_dir.into(),
             notify,
+            events: broadcast::channel(256).0,
+            last_event_prune: AtomicI64::new(0),
         }
     }
 
@@ -136,6 +149,7 @@ impl AIJobService {
             image_count = assets.len(),
             "AI job created"
         );
+        self.record_event(&job, JOB_QUEUED).await;
         self.notify.notify_one();
         Ok(to_view(job, assets))
     }
@@ -155,10 +169,7 @@ impl AIJobService {
             .iter()
             .any(|job| job.status == JOB_QUEUED || job.status == JOB_RUNNING);
         if has_active {
-            self.dao
-                .requeue_expired_jobs(now_timestamp())
-                .await
-                .map_err(internal_error)?;
+            self.recover_expired().await.map_err(internal_error)?;
             self.notify.notify_one();
             jobs = self
                 .dao
@@ -225,6 +236,15 @@ impl AIJobService {
             });
         }
         tracing::info!(job_id = %id, uid, "AI job manually queued for retry");
+        let job = AiJobRecord {
+            status: JOB_QUEUED.to_string(),
+            attempts: 0,
+            next_attempt_at: Some(now),
+            error_code: None,
+            error_message: None,
+            ..job
+        };
+        self.record_event(&job, JOB_QUEUED).await;
         self.notify.notify_one();
         Ok(())
     }
@@ -291,6 +311,97 @@ impl AIJobService {
         Ok(())
     }
 
+    /// 管理接口：跨用户列出任务，可按状态过滤
+    pub async fn admin_list(
+        &self,
+        status: Option<&str>,
+        page: i32,
+        size: i32,
+    ) -> Result<Vec<AdminAiJobView>, ServiceError> {
+        if let Some(status) = status
+            && ![
+                JOB_QUEUED,
+                JOB_RUNNING,
+                JOB_READY,
+                JOB_FAILED,
+                JOB_SUBMITTED,
+            ]
+            .contains(&status)
+        {
+            return Err(ServiceError {
+                code: 400,
+                message: format!("未知的任务状态: {status}"),
+            });
+        }
+        let jobs = self
+            .dao
+            .list_all_jobs(status, page, size)
+            .await
+            .map_err(internal_error)?;
+        Ok(jobs
+            .into_iter()
+            .map(|job| AdminAiJobView {
+                id: job.id,
+                uid: job.uid,
+                status: job.status,
+                attempts: job.attempts,
+                error_code: job.error_code,
+                error_message: job.error_message,
+                created_at: job.created_at,
+                finished_at: job.finished_at,
+            })
+            .collect())
+    }
+
+    /// 管理接口：按 id 查找任务所属用户
+    pub async fn owner_of(&self, id: &str) -> Result<i32, ServiceError> {
+        self.dao
+            .find_job(id)
+            .await
+            .map_err(internal_error)?
+            .map(|job| job.uid)
+            .ok_or_else(|| ServiceError {
+                code: 404,
+                message: "AI任务不存在".to_string(),
+            })
+    }
+
+    pub async fn queue_stats(&self) -> Result<AiQueueStats, ServiceError> {
+        self.dao
+            .queue_stats(now_timestamp())
+            .await
+            .map_err(internal_error)
+    }
+
+    /// 用户全部任务目录在磁盘上占用的字节数
+    pub async fn storage_bytes(&self, uid: i32) -> Result<i64, ServiceError> {
+        let ids = self.dao.list_job_ids(uid).await.map_err(internal_error)?;
+        Ok(ids
+            .iter()
+            .filter(|id| Uuid::parse_str(id).is_ok())
+            .map(|id| dir_size(&self.storage_dir.join(id)))
+            .sum())
+    }
+
+    /// 账号删除后清理任务图片目录，数据库记录由调用方删除
+    pub fn remove_job_files(&self, job_ids: &[String]) {
+        for id in job_ids {
+            if Uuid::parse_str(id).is_err() {
+                continue;
+            }
+            let dir = self.storage_dir.join(id);
+            match fs::remove_dir_all(&dir) {
+                Ok(()) => {}
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
+                Err(error) => tracing::warn!(
+                    job_id = %id,
+                    error = %error,
+                    "AI job files could not be removed"
+                ),
+            }
+        }
+    }
+
     pub async fn read_asset(
         &self,
         uid: i32,
@@ -334,28 +445,134 @@ impl AIJobService {
 
     pub async fn claim(&self, lease_seconds: i64) -> Result<Option<AiJobRecord>, String> {
         let now = now_timestamp();
-        self.dao.claim_next_job(now, now + lease_seconds).await
+        let job = self.dao.claim_next_job(now, now + lease_seconds).await?;
+        if let Some(job) = &job {
+            self.record_event(job, JOB_RUNNING).await;
+        }
+        Ok(job)
     }
 
+    /// 恢复租约过期的任务，并顺带清理过期的状态事件
     pub async fn recover_expired(&self) -> Result<(), String> {
-        self.dao.requeue_expired_jobs(now_timestamp()).await
+        let now = now_timestamp();
+        for job in self.dao.requeue_expired_jobs(now).await? {
+            tracing::warn!(job_id = %job.id, uid = job.uid, "AI job lease expired, requeued");
+            self.record_event(&job, JOB_QUEUED).await;
+        }
+        let last = self.last_event_prune.load(Ordering::Relaxed);
+        if now - last >= EVENT_PRUNE_INTERVAL_SECONDS
+            && self
+                .last_event_prune
+                .compare_exchange(last, now, Ordering::Relaxed, Ordering::Relaxed)
+                .is_ok()
+        {
+            self.dao
+                .prune_job_events(now - EVENT_RETENTION_SECONDS)
+                .await?;
+        }
+        Ok(())
     }
 
-    pub async fn mark_ready(&self, id: &str, sport: &Sport) -> Result<(), String> {
+    pub async fn mark_ready(&self, job: &AiJobRecord, sport: &Sport) -> Result<(), String> {
         let json = serde_json::to_string(sport).map_err(|e| e.to_string())?;
-        self.dao.mark_job_ready(id, &json, now_timestamp()).await
+        self.dao
+            .mark_job_ready(&job.id, &json, now_timestamp())
+            .await?;
+        let job = AiJobRecord {
+            error_code: None,
+            error_message: None,
+            ..job.clone()
+        };
+        self.record_event(&job, JOB_READY).await;
+        Ok(())
     }
 
+    /// retry_at 不为空时任务重新排队，推送 retry_scheduled 事件；已到重试时间的直接唤醒空闲的 worker
     pub async fn mark_error(
         &self,
-        id: &str,
+        job: &AiJobRecord,
         code: &str,
         message: &str,
         retry_at: Option<i64>,
     ) -> Result<(), String> {
         self.dao
-            .mark_job_error(id, code, message, retry_at, now_timestamp())
-            .await
+            .mark_job_error(&job.id, code, message, retry_at, now_timestamp())
+            .await?;
+        let job = AiJobRecord {
+            error_code: Some(code.to_string()),
+            error_message: Some(message.to_string()),
+            next_attempt_at: retry_at,
+            ..job.clone()
+        };
+        let status = if retry_at.is_some() {
+            JOB_RETRY_SCHEDULED
+        } else {
+            JOB_FAILED
+        };
+        self.record_event(&job, status).await;
+        if retry_at.is_some_and(|at| at <= now_timestamp()) {
+            self.notify.notify_one();
+        }
+        Ok(())
+    }
+
+    /// 用户的任务状态事件流：先补发 after 之后的历史事件，再推送新事件；
+    /// after 为空时只推送订阅之后的事件
+    pub async fn event_stream(
+        &self,
+        uid: i32,
+        after: Option<i64>,
+    ) -> Result<impl Stream<Item = AiJobEvent> + Send + 'static, ServiceError> {
+        // 先订阅再查询起点，避免两者之间的事件丢失
+        let receiver = self.events.subscribe();
+        let last_id = match after {
+            Some(after) => after.max(0),
+            None => self
+                .dao
+                .latest_job_event_id(uid)
+                .await
+                .map_err(internal_error)?,
+        };
+        let state = EventStreamState {
+            dao: self.dao.clone(),
+            receiver,
+            uid,
+            last_id,
+            pending: VecDeque::new(),
+            backlog: after.is_some(),
+        };
+        Ok(futures_util::stream::unfold(
+            state,
+            |mut state| async move {
+                let event = state.next().await?;
+                Some((event, state))
+            },
+        ))
+    }
+
+    /// 事件先落库再广播；记录失败只告警，不影响任务本身的状态流转
+    async fn record_event(&self, job: &AiJobRecord, status: &str) {
+        let mut event = AiJobEvent {
+            id: 0,
+            uid: job.uid,
+            job_id: job.id.clone(),
+            status: status.to_string(),
+            attempts: job.attempts,
+            next_attempt_at: job.next_attempt_at,
+            error_code: job.error_code.clone(),
+            error_message: job.error_message.clone(),
+            created_at: now_timestamp(),
+        };
+        match self.dao.create_job_event(event.clone()).await {
+            Ok(id) => {
+                event.id = id;
+                // 没有订阅者时发送失败，忽略即可
+                let _ = self.events.send(event);
+            }
+            Err(error) => {
+                tracing::warn!(job_id = %job.id, status, error = %error, "failed to record AI job event");
+            }
+        }
     }
 
     pub async fn cleanup_submitted_assets(&self) -> Result<(), String> {
@@ -389,6 +606,50 @@ impl AIJobService {
     }
 }
 
+struct EventStreamState {
+    dao: Arc<dyn AiJobDao + Send + Sync>,
+    receiver: broadcast::Receiver<AiJobEvent>,
+    uid: i32,
+    last_id: i64,
+    pending: VecDeque<AiJobEvent>,
+    /// 为 true 时下一次先从数据库补发，而不是等待广播
+    backlog: bool,
+}
+
+impl EventStreamState {
+    /// 广播只作为唤醒信号，事件统一按 id 从数据库读取，
+    /// 这样接收端落后被丢弃的消息也能补齐，且不会重复推送
+    async fn next(&mut self) -> Option<AiJobEvent> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                self.last_id = event.id;
+                return Some(event);
+            }
+            if !self.backlog {
+                match self.receiver.recv().await {
+                    Ok(event) if event.uid != self.uid || event.id <= self.last_id => continue,
+                    Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+            match self
+                .dao
+                .list_job_events(self.uid, self.last_id, EVENT_BATCH)
+                .await
+            {
+                Ok(events) => {
+                    self.backlog = events.len() as i32 >= EVENT_BATCH;
+                    self.pending.extend(events);
+                }
+                Err(error) => {
+                    tracing::warn!(uid = self.uid, error = %error, "failed to load AI job events");
+                    self.backlog = false;
+                }
+            }
+        }
+    }
+}
+
 fn to_view(job: AiJobRecord, assets: Vec<AiJobAsset>) -> AiJobView {
     let result = job
         .result_json
@@ -443,6 +704,20 @@ fn file_error(error: std::io::Error) -> ServiceError {
     }
 }
 
+fn dir_size(dir: &Path) -> i64 {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.metadata() {
+            Ok(meta) if meta.is_dir() => dir_size(&entry.path()),
+            Ok(meta) => meta.len() as i64,
+            Err(_) => 0,
+        })
+        .sum()
+}
+
 fn remove_if_present(path: &str) -> bool {
     match fs::remove_file(path) {
         Ok(()) => true,
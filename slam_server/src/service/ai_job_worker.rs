@@ -2,11 +2,17 @@ use std::fs;
 use std::sync::Arc;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
+use crate::model::notification::{NOTIFY_AI_JOB_FAILED, NOTIFY_AI_JOB_READY};
+use crate::model::webhook::{EVENT_AI_JOB_FAILED, EVENT_AI_JOB_READY};
 use crate::service::ai_job_service::AIJobService;
 use crate::service::ai_service::AIService;
+use crate::service::athlete_service::AthleteService;
 use crate::service::common::ServiceError;
 use crate::service::image_service::ImageService;
+use crate::service::notification_service::NotificationService;
+use crate::service::webhook_service::WebhookService;
 
+#[allow(clippy::too_many_arguments)]
 pub fn start_workers(
     count: usize,
     max_attempts: i32,
@@ -14,6 +20,9 @@ pub fn start_workers(
     jobs: Arc<AIJobService>,
     ai: Arc<AIService>,
     images: Arc<ImageService>,
+    athletes: Arc<AthleteService>,
+    notifications: Arc<NotificationService>,
+    webhooks: Arc<WebhookService>,
 ) {
     let worker_count = count.max(1);
     tracing::info!(
@@ -26,6 +35,9 @@ pub fn start_workers(
         let jobs = jobs.clone();
         let ai = ai.clone();
         let images = images.clone();
+        let athletes = athletes.clone();
+        let notifications = notifications.clone();
+        let webhooks = webhooks.clone();
         let retry_delays_seconds = retry_delays_seconds.clone();
         tokio::spawn(async move {
             worker_loop(
@@ -35,12 +47,16 @@ pub fn start_workers(
                 jobs,
                 ai,
                 images,
+                athletes,
+                notifications,
+                webhooks,
             )
             .await;
         });
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn worker_loop(
     worker_id: usize,
     max_attempts: i32,
@@ -48,6 +64,9 @@ async fn worker_loop(
     jobs: Arc<AIJobService>,
     ai: Arc<AIService>,
     images: Arc<ImageService>,
+    athletes: Arc<AthleteService>,
+    notifications: Arc<NotificationService>,
+    webhooks: Arc<WebhookService>,
 ) {
     let notify = jobs.notify();
     loop {
@@ -73,6 +92,9 @@ async fn worker_loop(
                     &jobs,
                     &ai,
                     &images,
+                    &athletes,
+                    &notifications,
+                    &webhooks,
                     job,
                 )
                 .await
@@ -91,6 +113,7 @@ async fn worker_loop(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn process_job(
     worker_id: usize,
     max_attempts: i32,
@@ -98,6 +121,9 @@ async fn process_job(
     jobs: &AIJobService,
     ai: &AIService,
     images: &ImageService,
+    athletes: &AthleteService,
+    notifications: &NotificationService,
+    webhooks: &WebhookService,
     job: crate::model::ai_job::AiJobRecord,
 ) {
     let started = Instant::now();
@@ -130,8 +156,9 @@ async fn process_job(
     .await;
 
     match result {
-        Ok(sport) => {
-            if let Err(error) = jobs.mark_ready(&job.id, &sport).await {
+        Ok(mut sport) => {
+            athletes.fill_missing_calories(job.uid, &mut sport).await;
+            if let Err(error) = jobs.mark_ready(&job, &sport).await {
                 tracing::error!(worker_id, job_id = %job.id, error = %error, "failed to mark AI job ready");
             } else {
                 tracing::info!(
@@ -142,6 +169,22 @@ async fn process_job(
                     elapsed_ms = started.elapsed().as_millis(),
                     "AI job completed"
                 );
+                notifications
+                    .notify(
+                        job.uid,
+                        NOTIFY_AI_JOB_READY,
+                        None,
+                        None,
+                        serde_json::json!({ "job_id": job.id }),
+                    )
+                    .await;
+                webhooks
+                    .emit(
+                        job.uid,
+                        EVENT_AI_JOB_READY,
+                        serde_json::json!({ "job_id": job.id, "result": sport }),
+                    )
+                    .await;
             }
         }
         Err(error) => {
@@ -172,10 +215,21 @@ async fn process_job(
                 "AI job processing failed"
             );
             if let Err(mark_error) = jobs
-                .mark_error(&job.id, &error.code.to_string(), &error.message, retry_at)
+                .mark_error(&job, &error.code.to_string(), &error.message, retry_at)
                 .await
             {
                 tracing::error!(job_id = %job.id, error = %mark_error, "failed to mark AI job error");
+            } else if retry_at.is_none() {
+                // 还会重试的失败不打扰用户
+                let detail = serde_json::json!({
+                    "job_id": job.id,
+                    "error_code": error.code.to_string(),
+                    "message": error.message,
+                });
+                notifications
+                    .notify(job.uid, NOTIFY_AI_JOB_FAILED, None, None, detail.clone())
+                    .await;
+                webhooks.emit(job.uid, EVENT_AI_JOB_FAILED, detail).await;
             }
         }
     }
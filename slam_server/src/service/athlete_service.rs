@@ -0,0 +1,369 @@
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use chrono::{DateTime, Datelike, NaiveDate};
+
+use crate::dao::idl::AthleteDao;
+use crate::model::athlete::{
+    AthleteProfile, AthleteProfileView, HeartRateZone, SEX_FEMALE, SEX_MALE, SportMetrics,
+    UNIT_IMPERIAL, UNIT_METRIC, WeightEntry,
+};
+use crate::model::sport::Sport;
+use crate::service::calorie::{DEFAULT_WEIGHT_KG, estimate_calories};
+use crate::service::common::ServiceError;
+
+/// 心率区间边界，占心率储备（已知静息心率时）或最大心率的比例
+const ZONE_BOUNDS: [f64; 6] = [0.5, 0.6, 0.7, 0.8, 0.9, 1.0];
+const WEIGHT_LIST_LIMIT: i32 = 365;
+
+pub struct AthleteService {
+    dao: Arc<dyn AthleteDao + Send + Sync>,
+}
+
+/// 某一时刻生效的档案与体重
+#[derive(Debug, Clone, Default)]
+pub struct AthleteSnapshot {
+    pub profile: Option<AthleteProfile>,
+    pub weight_kg: Option<f64>,
+}
+
+impl AthleteSnapshot {
+    /// 档案未填写最大心率时按 Tanaka 公式 208 - 0.7 × 年龄估算
+    pub fn max_heart_rate(&self, at: i64) -> Option<i32> {
+        let profile = self.profile.as_ref()?;
+        if let Some(max) = profile.max_heart_rate {
+            return Some(max);
+        }
+        let age = age_at(profile.birth_date.as_deref()?, at)?;
+        Some((208.0 - 0.7 * age as f64).round() as i32)
+    }
+
+    pub fn resting_heart_rate(&self) -> Option<i32> {
+        self.profile.as_ref()?.resting_heart_rate
+    }
+
+    pub fn heart_rate_zones(&self, at: i64) -> Vec<HeartRateZone> {
+        match self.max_heart_rate(at) {
+            Some(max) => heart_rate_zones(max, self.resting_heart_rate()),
+            None => Vec::new(),
+        }
+    }
+}
+
+impl AthleteService {
+    pub fn new(dao: Arc<dyn AthleteDao + Send + Sync>) -> Self {
+        Self { dao }
+    }
+
+    pub async fn snapshot_at(&self, uid: i32, at: i64) -> Result<AthleteSnapshot, ServiceError> {
+        let profile = self
+            .dao
+            .get_profile_at(uid, at)
+            .await
+            .map_err(internal_error)?;
+        let weight = self
+            .dao
+            .get_weight_at(uid, at)
+            .await
+            .map_err(internal_error)?;
+        Ok(AthleteSnapshot {
+            profile,
+            weight_kg: weight.map(|w| w.weight_kg),
+        })
+    }
+
+    pub async fn current(&self, uid: i32) -> Result<AthleteProfileView, ServiceError> {
+        let now = now_timestamp();
+        let snapshot = self.snapshot_at(uid, now).await?;
+        Ok(AthleteProfileView {
+            max_heart_rate: snapshot.max_heart_rate(now),
+            heart_rate_zones: snapshot.heart_rate_zones(now),
+            weight_kg: snapshot.weight_kg,
+            profile: snapshot.profile,
+        })
+    }
+
+    pub async fn history(&self, uid: i32) -> Result<Vec<AthleteProfile>, ServiceError> {
+        self.dao.list_profiles(uid).await.map_err(internal_error)
+    }
+
+    /// 保存新的档案版本，valid_from 缺省为当前时间；旧版本保留用于历史运动的计算
+    pub async fn update_profile(
+        &self,
+        uid: i32,
+        mut profile: AthleteProfile,
+        valid_from: Option<i64>,
+    ) -> Result<AthleteProfile, ServiceError> {
+        let now = now_timestamp();
+        validate_profile(&profile, now)?;
+        let valid_from = valid_from.unwrap_or(now);
+        if valid_from < 0 {
+            return Err(bad_request("生效时间不能为负数"));
+        }
+        profile.valid_from = valid_from;
+        profile.created_at = now;
+        profile.id = self
+            .dao
+            .create_profile(uid, profile.clone())
+            .await
+            .map_err(internal_error)?;
+        tracing::info!(uid, valid_from, "athlete profile version saved");
+        Ok(profile)
+    }
+
+    pub async fn add_weight(
+        &self,
+        uid: i32,
+        weight_kg: f64,
+        measured_at: Option<i64>,
+    ) -> Result<WeightEntry, ServiceError> {
+        if !(20.0..=400.0).contains(&weight_kg) {
+            return Err(bad_request("体重需在20到400千克之间"));
+        }
+        let now = now_timestamp();
+        let measured_at = measured_at.unwrap_or(now);
+        if measured_at < 0 || measured_at > now + 86400 {
+            return Err(bad_request("测量时间无效"));
+        }
+        let mut entry = WeightEntry {
+            id: 0,
+            measured_at,
+            weight_kg,
+        };
+        entry.id = self
+            .dao
+            .add_weight(uid, entry.clone())
+            .await
+            .map_err(internal_error)?;
+        Ok(entry)
+    }
+
+    pub async fn list_weights(&self, uid: i32) -> Result<Vec<WeightEntry>, ServiceError> {
+        self.dao
+            .list_weights(uid, WEIGHT_LIST_LIMIT)
+            .await
+            .map_err(internal_error)
+    }
+
+    pub async fn delete_weight(&self, uid: i32, id: i64) -> Result<(), ServiceError> {
+        if self
+            .dao
+            .delete_weight(uid, id)
+            .await
+            .map_err(internal_error)?
+        {
+            Ok(())
+        } else {
+            Err(ServiceError {
+                code: 404,
+                message: "体重记录不存在".to_string(),
+            })
+        }
+    }
+
+    /// 缺少卡路里时按运动当天的体重估算并标记为估算值；档案查询失败时退回参考体重
+    pub async fn fill_missing_calories(&self, uid: i32, sport: &mut Sport) {
+        if sport.calories > 0 {
+            return;
+        }
+        let snapshot = match self.snapshot_at(uid, sport.start_time).await {
+            Ok(s) => s,
+            Err(e) => {
+                tracing::warn!(uid, error = %e.message, "athlete snapshot unavailable for calorie estimate");
+                AthleteSnapshot::default()
+            }
+        };
+        let weight = snapshot.weight_kg.unwrap_or(DEFAULT_WEIGHT_KG);
+        if let Some(calories) =
+            estimate_calories(sport, weight, snapshot.max_heart_rate(sport.start_time))
+        {
+            sport.calories = calories;
+            sport.calories_estimated = true;
+        }
+    }
+
+    /// 使用运动当天生效的档案计算心率区间与训练负荷
+    pub async fn sport_metrics(
+        &self,
+        uid: i32,
+        sport: &Sport,
+    ) -> Result<SportMetrics, ServiceError> {
+        let at = sport.start_time;
+        let snapshot = self.snapshot_at(uid, at).await?;
+        let max = snapshot.max_heart_rate(at);
+        let zones = snapshot.heart_rate_zones(at);
+        let training_load = match (max, snapshot.resting_heart_rate()) {
+            (Some(max), Some(resting)) => training_load(
+                sport.duration_second,
+                sport.heart_rate_avg,
+                resting,
+                max,
+                snapshot.profile.as_ref().and_then(|p| p.sex.as_deref()),
+            ),
+            _ => None,
+        };
+        Ok(SportMetrics {
+            sport_id: sport.id,
+            profile_valid_from: snapshot.profile.as_ref().map(|p| p.valid_from),
+            weight_kg: snapshot.weight_kg,
+            max_heart_rate: max,
+            heart_rate_avg_zone: zone_of(&zones, sport.heart_rate_avg),
+            heart_rate_zones: zones,
+            training_load,
+        })
+    }
+}
+
+fn validate_profile(profile: &AthleteProfile, now: i64) -> Result<(), ServiceError> {
+    if let Some(birth_date) = profile.birth_date.as_deref() {
+        let date = NaiveDate::parse_from_str(birth_date, "%Y-%m-%d")
+            .map_err(|_| bad_request("出生日期格式应为 YYYY-MM-DD"))?;
+        let today = DateTime::from_timestamp(now, 0)
+            .map(|dt| dt.date_naive())
+            .unwrap_or_default();
+        if date > today || date.year() < 1900 {
+            return Err(bad_request("出生日期无效"));
+        }
+    }
+    if let Some(sex) = profile.sex.as_deref()
+        && sex != SEX_MALE
+        && sex != SEX_FEMALE
+    {
+        return Err(bad_request("性别只能为 male 或 female"));
+    }
+    if let Some(height) = profile.height_cm
+        && !(50.0..=272.0).contains(&height)
+    {
+        return Err(bad_request("身高需在50到272厘米之间"));
+    }
+    if let Some(resting) = profile.resting_heart_rate
+        && !(25..=120).contains(&resting)
+    {
+        return Err(bad_request("静息心率需在25到120之间"));
+    }
+    if let Some(max) = profile.max_heart_rate
+        && !(100..=230).contains(&max)
+    {
+        return Err(bad_request("最大心率需在100到230之间"));
+    }
+    if let (Some(resting), Some(max)) = (profile.resting_heart_rate, profile.max_heart_rate)
+        && resting >= max
+    {
+        return Err(bad_request("静息心率必须低于最大心率"));
+    }
+    if profile.unit_system != UNIT_METRIC && profile.unit_system != UNIT_IMPERIAL {
+        return Err(bad_request("单位制只能为 metric 或 imperial"));
+    }
+    Ok(())
+}
+
+fn age_at(birth_date: &str, at: i64) -> Option<i32> {
+    let birth = NaiveDate::parse_from_str(birth_date, "%Y-%m-%d").ok()?;
+    let date = DateTime::from_timestamp(at, 0)?.date_naive();
+    let mut age = date.year() - birth.year();
+    if (date.month(), date.day()) < (birth.month(), birth.day()) {
+        age -= 1;
+    }
+    (age >= 0).then_some(age)
+}
+
+/// 五个心率区间；已知静息心率时按 Karvonen 心率储备法划分，否则按最大心率百分比
+pub fn heart_rate_zones(max: i32, resting: Option<i32>) -> Vec<HeartRateZone> {
+    let base = resting.unwrap_or(0) as f64;
+    let range = max as f64 - base;
+    let bpm = |pct: f64| (base + pct * range).round() as i32;
+    ZONE_BOUNDS
+        .windows(2)
+        .enumerate()
+        .map(|(i, w)| HeartRateZone {
+            zone: i as i32 + 1,
+            min_bpm: bpm(w[0]),
+            max_bpm: if i == ZONE_BOUNDS.len() - 2 {
+                max
+            } else {
+                bpm(w[1]) - 1
+            },
+        })
+        .collect()
+}
+
+fn zone_of(zones: &[HeartRateZone], heart_rate: i32) -> Option<i32> {
+    if heart_rate <= 0 {
+        return None;
+    }
+    let first = zones.first()?;
+    if heart_rate < first.min_bpm {
+        return None;
+    }
+    zones
+        .iter()
+        .find(|z| heart_rate <= z.max_bpm)
+        .or(zones.last())
+        .map(|z| z.zone)
+}
+
+/// Banister TRIMP：时长(分钟) × HRr × 0.64 × e^(k × HRr)，男性 k=1.92，女性 k=1.67
+pub fn training_load(
+    duration_second: i32,
+    heart_rate_avg: i32,
+    resting: i32,
+    max: i32,
+    sex: Option<&str>,
+) -> Option<f64> {
+    if duration_second <= 0 || heart_rate_avg <= 0 || max <= resting {
+        return None;
+    }
+    let reserve = ((heart_rate_avg - resting) as f64 / (max - resting) as f64).clamp(0.0, 1.0);
+    let k = if sex == Some(SEX_FEMALE) { 1.67 } else { 1.92 };
+    let minutes = duration_second as f64 / 60.0;
+    let load = minutes * reserve * 0.64 * (k * reserve).exp();
+    Some((load * 10.0).round() / 10.0)
+}
+
+fn bad_request(message: &str) -> ServiceError {
+    ServiceError {
+        code: 400,
+        message: message.to_string(),
+    }
+}
+
+fn now_timestamp() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
+
+fn internal_error(message: String) -> ServiceError {
+    ServiceError { code: 500, message }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zones_use_heart_rate_reserve_when_resting_is_known() {
+        let zones = heart_rate_zones(190, Some(50));
+        assert_eq!(zones.len(), 5);
+        assert_eq!((zones[0].min_bpm, zones[0].max_bpm), (120, 133));
+        assert_eq!((zones[4].min_bpm, zones[4].max_bpm), (176, 190));
+        assert_eq!(zone_of(&zones, 150), Some(3));
+        assert_eq!(zone_of(&zones, 100), None);
+
+        let zones = heart_rate_zones(200, None);
+        assert_eq!(zones[0].min_bpm, 100);
+        assert_eq!(zone_of(&zones, 210), Some(5));
+    }
+
+    #[test]
+    fn training_load_and_age_based_max_heart_rate() {
+        assert_eq!(training_load(3600, 150, 50, 190, Some("male")), Some(108.1));
+        assert!(training_load(3600, 150, 50, 190, Some("female")).unwrap() < 108.1);
+        assert_eq!(training_load(0, 150, 50, 190, None), None);
+
+        // 1990-06-15 出生，2025-06-14 时仍为 34 岁
+        assert_eq!(age_at("1990-06-15", 1_749_859_200), Some(34));
+        assert_eq!(age_at("1990-06-15", 1_749_945_600), Some(35));
+    }
+}
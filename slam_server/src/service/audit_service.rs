@@ -0,0 +1,75 @@
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::dao::idl::AuditDao;
+use crate::model::audit::{AuditEntry, AuditQuery};
+use crate::service::common::ServiceError;
+use crate::service::session_service::ClientMeta;
+
+pub struct AuditService {
+    dao: Arc<dyn AuditDao + Send + Sync>,
+}
+
+impl AuditService {
+    pub fn new(dao: Arc<dyn AuditDao + Send + Sync>) -> Self {
+        Self { dao }
+    }
+
+    /// 写入一条审计日志；写入失败只记录告警，不影响业务请求
+    pub async fn record(
+        &self,
+        uid: Option<i32>,
+        action: &str,
+        client: &ClientMeta,
+        detail: serde_json::Value,
+    ) {
+        let entry = AuditEntry {
+            id: 0,
+            uid,
+            action: action.to_string(),
+            ip: client.ip.clone(),
+            user_agent: client.user_agent.clone(),
+            detail: detail.to_string(),
+            created_at: now_timestamp(),
+        };
+        if let Err(e) = self.dao.append_audit(entry).await {
+            tracing::warn!(action, uid, error = %e, "failed to append audit log");
+        }
+    }
+
+    /// 用户查看自己的审计记录
+    pub async fn list_own(
+        &self,
+        uid: i32,
+        action: Option<String>,
+        page: i32,
+        size: i32,
+    ) -> Result<Vec<AuditEntry>, ServiceError> {
+        let query = AuditQuery {
+            uid: Some(uid),
+            action,
+            ..Default::default()
+        };
+        self.query(&query, page, size).await
+    }
+
+    /// 管理员跨用户查询
+    pub async fn query(
+        &self,
+        query: &AuditQuery,
+        page: i32,
+        size: i32,
+    ) -> Result<Vec<AuditEntry>, ServiceError> {
+        self.dao
+            .list_audit(query, page, size)
+            .await
+            .map_err(|message| ServiceError { code: 500, message })
+    }
+}
+
+fn now_timestamp() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
@@ -0,0 +1,154 @@
+use crate::model::sport::{Sport, SportExtra, SportType};
+
+/// 体重未知时使用的参考体重
+pub const DEFAULT_WEIGHT_KG: f64 = 70.0;
+
+/// 跑步速度下限(km/h)与 MET 对照，取自 Compendium of Physical Activities
+const RUNNING_METS: [(f64, f64); 10] = [
+    (8.0, 8.3),
+    (9.7, 9.8),
+    (10.8, 10.5),
+    (11.3, 11.0),
+    (12.9, 11.8),
+    (13.8, 12.3),
+    (14.5, 12.8),
+    (16.1, 14.5),
+    (17.7, 16.0),
+    (19.3, 19.0),
+];
+const CYCLING_METS: [(f64, f64); 5] = [
+    (16.0, 6.8),
+    (19.3, 8.0),
+    (22.5, 10.0),
+    (25.7, 12.0),
+    (30.6, 15.8),
+];
+/// 游泳每百米配速上限(秒)与 MET 对照，配速越快强度越高
+const SWIMMING_METS: [(f64, f64); 3] = [(100.0, 9.8), (150.0, 8.3), (f64::MAX, 5.8)];
+/// 平均心率占最大心率比例的上限与 MET 对照，用于缺少距离的记录
+const HEART_RATE_METS: [(f64, f64); 5] = [
+    (0.6, 4.0),
+    (0.7, 6.0),
+    (0.8, 8.0),
+    (0.9, 10.0),
+    (f64::MAX, 12.0),
+];
+
+/// 按运动类型与强度估算 MET：优先使用速度/配速，其次心率，最后使用该类型的中等强度
+pub fn estimate_met(sport: &Sport, max_heart_rate: Option<i32>) -> f64 {
+    let hours = sport.duration_second as f64 / 3600.0;
+    let speed_kmh = (sport.distance_meter > 0 && hours > 0.0)
+        .then(|| sport.distance_meter as f64 / 1000.0 / hours);
+    match (sport.r#type, speed_kmh) {
+        (SportType::Running, Some(speed)) if speed >= 6.4 => {
+            met_from_floor(&RUNNING_METS, speed, 6.0)
+        }
+        (SportType::Cycling, Some(speed)) => met_from_floor(&CYCLING_METS, speed, 4.0),
+        (SportType::Swimming, Some(_)) => {
+            if let Some(SportExtra::Swimming(swim)) = &sport.extra
+                && swim.main_stroke == "butterfly"
+            {
+                return 13.8;
+            }
+            let pace_per_100m = sport.duration_second as f64 / sport.distance_meter as f64 * 100.0;
+            met_below_ceiling(&SWIMMING_METS, pace_per_100m)
+        }
+        _ => match max_heart_rate.filter(|max| *max > 0 && sport.heart_rate_avg > 0) {
+            Some(max) => {
+                met_below_ceiling(&HEART_RATE_METS, sport.heart_rate_avg as f64 / max as f64)
+            }
+            None => match sport.r#type {
+                SportType::Running => 8.3,
+                SportType::Cycling => 6.8,
+                SportType::Swimming => 7.0,
+                SportType::Unknown => 5.0,
+            },
+        },
+    }
+}
+
+/// 千卡 = MET × 体重(kg) × 时长(h)；时长缺失时无法估算
+pub fn estimate_calories(
+    sport: &Sport,
+    weight_kg: f64,
+    max_heart_rate: Option<i32>,
+) -> Option<i32> {
+    if sport.duration_second <= 0 {
+        return None;
+    }
+    let hours = sport.duration_second as f64 / 3600.0;
+    Some((estimate_met(sport, max_heart_rate) * weight_kg * hours).round() as i32)
+}
+
+/// 表格按下限升序排列，取 value 达到的最高一档，低于首档时使用 base
+fn met_from_floor(table: &[(f64, f64)], value: f64, base: f64) -> f64 {
+    table
+        .iter()
+        .rev()
+        .find(|(floor, _)| value >= *floor)
+        .map(|(_, met)| *met)
+        .unwrap_or(base)
+}
+
+/// 表格按上限升序排列，取 value 未超过的第一档
+fn met_below_ceiling(table: &[(f64, f64)], value: f64) -> f64 {
+    table
+        .iter()
+        .find(|(threshold, _)| value < *threshold)
+        .or(table.last())
+        .map(|(_, met)| *met)
+        .unwrap_or(5.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::sport::Swimming;
+
+    fn sport(r#type: SportType, distance_meter: i32, duration_second: i32) -> Sport {
+        Sport {
+            r#type,
+            distance_meter,
+            duration_second,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn met_follows_speed_and_pace_tables() {
+        // 10km 用时 1 小时，10 km/h
+        assert_eq!(
+            estimate_met(&sport(SportType::Running, 10000, 3600), None),
+            9.8
+        );
+        assert_eq!(
+            estimate_met(&sport(SportType::Cycling, 25000, 3600), None),
+            10.0
+        );
+        // 每百米 2 分钟
+        assert_eq!(
+            estimate_met(&sport(SportType::Swimming, 1500, 1800), None),
+            8.3
+        );
+        let mut fly = sport(SportType::Swimming, 1500, 1800);
+        fly.extra = Some(SportExtra::Swimming(Swimming::new(
+            "butterfly".into(),
+            0,
+            0,
+        )));
+        assert_eq!(estimate_met(&fly, None), 13.8);
+    }
+
+    #[test]
+    fn heart_rate_is_used_when_distance_is_missing() {
+        let mut s = sport(SportType::Unknown, 0, 1800);
+        assert_eq!(estimate_met(&s, Some(190)), 5.0);
+        s.heart_rate_avg = 150;
+        assert_eq!(estimate_met(&s, Some(190)), 8.0);
+        assert_eq!(estimate_calories(&s, 60.0, Some(190)), Some(240));
+        assert_eq!(
+            estimate_calories(&sport(SportType::Running, 0, 0), 60.0, None),
+            None
+        );
+    }
+}
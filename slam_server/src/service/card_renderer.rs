@@ -0,0 +1,303 @@
+//! 分享卡片渲染模块
+//! 仅依赖 `image` 生成 PNG，文字使用内置 5×7 点阵字体（只支持大写字母、数字和常用符号）
+
+use crate::model::share::SharedSport;
+use crate::service::common::ServiceError;
+use crate::service::year_review::YearReview;
+use chrono::DateTime;
+use image::{ImageOutputFormat, Rgb, RgbImage};
+use std::io::Cursor;
+
+pub const BACKGROUND: Rgb<u8> = Rgb([24, 30, 40]);
+pub const PANEL: Rgb<u8> = Rgb([36, 44, 58]);
+pub const ACCENT: Rgb<u8> = Rgb([255, 122, 69]);
+pub const TEXT: Rgb<u8> = Rgb([236, 240, 245]);
+pub const MUTED: Rgb<u8> = Rgb([140, 150, 165]);
+
+const GLYPH_WIDTH: u32 = 5;
+const GLYPH_HEIGHT: u32 = 7;
+
+/// 简单画布，坐标越界的像素直接忽略
+pub struct Canvas {
+    img: RgbImage,
+}
+
+impl Canvas {
+    pub fn new(width: u32, height: u32, background: Rgb<u8>) -> Self {
+        Self {
+            img: RgbImage::from_pixel(width, height, background),
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.img.width()
+    }
+
+    pub fn fill_rect(&mut self, x: u32, y: u32, w: u32, h: u32, color: Rgb<u8>) {
+        let x_end = (x + w).min(self.img.width());
+        let y_end = (y + h).min(self.img.height());
+        for py in y..y_end {
+            for px in x..x_end {
+                self.img.put_pixel(px, py, color);
+            }
+        }
+    }
+
+    /// 以 `scale` 倍放大绘制文字，小写字母按大写绘制，不支持的字符留空
+    pub fn text(&mut self, x: u32, y: u32, text: &str, scale: u32, color: Rgb<u8>) {
+        let mut cursor = x;
+        for ch in text.chars() {
+            if let Some(rows) = glyph(ch.to_ascii_uppercase()) {
+                for (row, bits) in rows.iter().enumerate() {
+                    for col in 0..GLYPH_WIDTH {
+                        if bits & (1 << (GLYPH_WIDTH - 1 - col)) != 0 {
+                            self.fill_rect(
+                                cursor + col * scale,
+                                y + row as u32 * scale,
+                                scale,
+                                scale,
+                                color,
+                            );
+                        }
+                    }
+                }
+            }
+            cursor += (GLYPH_WIDTH + 1) * scale;
+        }
+    }
+
+    pub fn text_width(text: &str, scale: u32) -> u32 {
+        let n = text.chars().count() as u32;
+        if n == 0 {
+            return 0;
+        }
+        n * (GLYPH_WIDTH + 1) * scale - scale
+    }
+
+    pub fn text_height(scale: u32) -> u32 {
+        GLYPH_HEIGHT * scale
+    }
+
+    pub fn into_png(self) -> Result<Vec<u8>, ServiceError> {
+        let mut buf = Cursor::new(Vec::new());
+        image::DynamicImage::ImageRgb8(self.img)
+            .write_to(&mut buf, ImageOutputFormat::Png)
+            .map_err(|e| ServiceError {
+                code: 500,
+                message: format!("卡片编码失败: {e}"),
+            })?;
+        Ok(buf.into_inner())
+    }
+}
+
+const MONTH_LABELS: [&str; 12] = ["J", "F", "M", "A", "M", "J", "J", "A", "S", "O", "N", "D"];
+const MONTH_NAMES: [&str; 12] = [
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+const WEEKDAY_NAMES: [&str; 7] = ["MON", "TUE", "WED", "THU", "FRI", "SAT", "SUN"];
+
+/// 年度回顾卡片：标题、总计、按月时长柱状图和亮点
+pub fn render_year_review_card(review: &YearReview) -> Result<Vec<u8>, ServiceError> {
+    let mut canvas = Canvas::new(800, 440, BACKGROUND);
+    canvas.fill_rect(0, 0, 800, 8, ACCENT);
+    canvas.text(40, 36, &format!("{} YEAR IN REVIEW", review.year), 4, TEXT);
+
+    let totals = &review.totals;
+    let figures = [
+        ("SESSIONS", totals.count.to_string()),
+        (
+            "HOURS",
+            format!("{:.1}", totals.duration_second as f64 / 3600.0),
+        ),
+        (
+            "KM",
+            format!("{:.1}", totals.distance_meter as f64 / 1000.0),
+        ),
+        ("KCAL", totals.calories.to_string()),
+    ];
+    for (i, (label, value)) in figures.iter().enumerate() {
+        let x = 40 + i as u32 * 185;
+        canvas.fill_rect(x, 90, 170, 80, PANEL);
+        canvas.text(x + 14, 104, value, 4, ACCENT);
+        canvas.text(x + 14, 148, label, 2, MUTED);
+    }
+
+    // 按月时长柱状图
+    let max_duration = review.months.iter().map(|b| b.duration).max().unwrap_or(0);
+    let (chart_top, chart_height) = (200u32, 120u32);
+    for (i, label) in MONTH_LABELS.iter().enumerate() {
+        let x = 40 + i as u32 * 60;
+        let duration = review
+            .months
+            .iter()
+            .find(|b| b.date == i as i32 + 1)
+            .map(|b| b.duration)
+            .unwrap_or(0);
+        let h = if max_duration > 0 {
+            (duration as u64 * chart_height as u64 / max_duration as u64) as u32
+        } else {
+            0
+        };
+        canvas.fill_rect(x, chart_top, 44, chart_height, PANEL);
+        canvas.fill_rect(x, chart_top + chart_height - h, 44, h, ACCENT);
+        canvas.text(x + 17, chart_top + chart_height + 8, label, 2, MUTED);
+    }
+
+    let top_month = review
+        .most_active_month
+        .as_ref()
+        .and_then(|b| MONTH_NAMES.get((b.date - 1) as usize))
+        .unwrap_or(&"-");
+    let top_day = review
+        .most_active_weekday
+        .as_ref()
+        .and_then(|b| WEEKDAY_NAMES.get((b.date - 1) as usize))
+        .unwrap_or(&"-");
+    canvas.text(
+        40,
+        360,
+        &format!(
+            "STREAK {} DAYS   TOP MONTH {}   TOP DAY {}",
+            review.longest_streak_days, top_month, top_day
+        ),
+        2,
+        TEXT,
+    );
+    let mut footer = format!("{} PERSONAL BESTS", review.personal_bests.len());
+    if let Some(prev) = &review.previous_year
+        && prev.duration_second > 0
+    {
+        let change = (totals.duration_second as f64 - prev.duration_second as f64) * 100.0
+            / prev.duration_second as f64;
+        footer.push_str(&format!("   VS {} {:+.0}% TIME", review.year - 1, change));
+    }
+    canvas.text(40, 392, &footer, 2, MUTED);
+    canvas.into_png()
+}
+
+/// 单次运动的分享卡片：类型与日期、主要数据和分段配速柱状图
+pub fn render_sport_card(shared: &SharedSport) -> Result<Vec<u8>, ServiceError> {
+    let sport = &shared.sport;
+    let mut canvas = Canvas::new(800, 420, BACKGROUND);
+    canvas.fill_rect(0, 0, 800, 8, ACCENT);
+    canvas.text(40, 36, sport.r#type.as_str(), 4, TEXT);
+    let date = DateTime::from_timestamp(sport.start_time, 0)
+        .map(|dt| dt.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_default();
+    canvas.text(40, 74, &date, 2, MUTED);
+
+    let figures = [
+        ("KM", format!("{:.2}", sport.distance_meter as f64 / 1000.0)),
+        ("TIME", format_duration(sport.duration_second)),
+        ("PACE", sport.pace_average.clone()),
+        ("KCAL", sport.calories.to_string()),
+    ];
+    for (i, (label, value)) in figures.iter().enumerate() {
+        let x = 40 + i as u32 * 185;
+        canvas.fill_rect(x, 110, 170, 80, PANEL);
+        canvas.text(x + 14, 124, value, 4, ACCENT);
+        canvas.text(x + 14, 168, label, 2, MUTED);
+    }
+
+    // 分段用时柱状图，最多展示前 20 段
+    let splits: Vec<i32> = sport
+        .tracks
+        .iter()
+        .take(20)
+        .map(|t| t.duration_second)
+        .collect();
+    let (chart_top, chart_height) = (220u32, 110u32);
+    let max_split = splits.iter().copied().max().unwrap_or(0);
+    if max_split > 0 {
+        let bar = 720 / splits.len() as u32;
+        for (i, duration) in splits.iter().enumerate() {
+            let x = 40 + i as u32 * bar;
+            let h = ((*duration).max(0) as u64 * chart_height as u64 / max_split as u64) as u32;
+            canvas.fill_rect(x, chart_top, bar - 6, chart_height, PANEL);
+            canvas.fill_rect(x, chart_top + chart_height - h, bar - 6, h, ACCENT);
+        }
+        canvas.text(
+            40,
+            chart_top + chart_height + 10,
+            &format!("{} SPLITS", sport.tracks.len()),
+            2,
+            MUTED,
+        );
+    }
+
+    if sport.heart_rate_avg > 0 {
+        canvas.text(
+            40,
+            380,
+            &format!(
+                "AVG HR {}   MAX HR {}",
+                sport.heart_rate_avg, sport.heart_rate_max
+            ),
+            2,
+            TEXT,
+        );
+    }
+    canvas.into_png()
+}
+
+fn format_duration(seconds: i32) -> String {
+    let seconds = seconds.max(0);
+    let (h, m, s) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if h > 0 {
+        format!("{h}:{m:02}:{s:02}")
+    } else {
+        format!("{m}:{s:02}")
+    }
+}
+
+fn glyph(ch: char) -> Option<[u8; 7]> {
+    let rows = match ch {
+        'A' => [0x0E, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'B' => [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E],
+        'C' => [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E],
+        'D' => [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C],
+        'E' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F],
+        'F' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10],
+        'G' => [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F],
+        'H' => [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'I' => [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        'J' => [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C],
+        'K' => [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11],
+        'L' => [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F],
+        'M' => [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11],
+        'N' => [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11],
+        'O' => [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'P' => [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10],
+        'Q' => [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D],
+        'R' => [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11],
+        'S' => [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E],
+        'T' => [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+        'U' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'V' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04],
+        'W' => [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A],
+        'X' => [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11],
+        'Y' => [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04],
+        'Z' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F],
+        '0' => [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E],
+        '1' => [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        '2' => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F],
+        '3' => [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E],
+        '4' => [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02],
+        '5' => [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E],
+        '6' => [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E],
+        '7' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+        '8' => [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E],
+        '9' => [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C],
+        ':' => [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00],
+        '.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C],
+        ',' => [0x00, 0x00, 0x00, 0x00, 0x0C, 0x04, 0x08],
+        '-' => [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00],
+        '+' => [0x00, 0x04, 0x04, 0x1F, 0x04, 0x04, 0x00],
+        '/' => [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00],
+        '%' => [0x18, 0x19, 0x02, 0x04, 0x08, 0x13, 0x03],
+        '\'' => [0x0C, 0x04, 0x08, 0x00, 0x00, 0x00, 0x00],
+        '"' => [0x0A, 0x0A, 0x0A, 0x00, 0x00, 0x00, 0x00],
+        _ => return None,
+    };
+    Some(rows)
+}
@@ -0,0 +1,419 @@
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rand::Rng;
+
+use crate::dao::idl::{ClubDao, UserDao};
+use crate::model::club::{
+    CLUB_MEMBER, CLUB_OWNER, Challenge, ChallengeMetric, Club, ClubInvite, ClubMemberView, ClubRow,
+    ClubView, Leaderboard, LeaderboardEntry, competition_ranks,
+};
+use crate::model::sport::SportType;
+use crate::service::common::ServiceError;
+use crate::service::social_service::user_card;
+
+const MAX_NAME_CHARS: usize = 40;
+const MAX_DESCRIPTION_CHARS: usize = 200;
+const MAX_CHALLENGE_SECONDS: i64 = 366 * 86400;
+const JOIN_CODE_LEN: usize = 8;
+/// 去掉了容易混淆的 0/O、1/I
+const JOIN_CODE_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+
+/// 创建挑战的参数
+pub struct NewChallenge {
+    pub name: String,
+    pub metric: ChallengeMetric,
+    pub sport_type: Option<SportType>,
+    pub start_time: i64,
+    pub end_time: i64,
+}
+
+pub struct ClubService {
+    dao: Arc<dyn ClubDao + Send + Sync>,
+    users: Arc<dyn UserDao + Send + Sync>,
+}
+
+impl ClubService {
+    pub fn new(dao: Arc<dyn ClubDao + Send + Sync>, users: Arc<dyn UserDao + Send + Sync>) -> Self {
+        Self { dao, users }
+    }
+
+    pub async fn create(
+        &self,
+        uid: i32,
+        name: &str,
+        description: &str,
+    ) -> Result<ClubView, ServiceError> {
+        let name = validate_name(name)?;
+        let description = description.trim();
+        if description.chars().count() > MAX_DESCRIPTION_CHARS {
+            return Err(bad_request("简介不能超过 200 个字符"));
+        }
+        let club_id = self
+            .dao
+            .create_club(
+                Club {
+                    id: 0,
+                    name,
+                    description: description.to_string(),
+                    join_code: generate_join_code(),
+                    created_at: now_timestamp(),
+                },
+                uid,
+            )
+            .await
+            .map_err(internal_error)?;
+        self.get(uid, club_id).await
+    }
+
+    pub async fn list(&self, uid: i32) -> Result<Vec<ClubView>, ServiceError> {
+        let rows = self.dao.list_clubs(uid).await.map_err(internal_error)?;
+        Ok(rows.into_iter().map(ClubView::from).collect())
+    }
+
+    pub async fn get(&self, uid: i32, club_id: i64) -> Result<ClubView, ServiceError> {
+        Ok(self.membership(uid, club_id).await?.into())
+    }
+
+    pub async fn delete(&self, uid: i32, club_id: i64) -> Result<(), ServiceError> {
+        self.require_owner(uid, club_id).await?;
+        self.dao.delete_club(club_id).await.map_err(internal_error)
+    }
+
+    /// 重新生成加入码，旧的加入码随即失效
+    pub async fn reset_join_code(&self, uid: i32, club_id: i64) -> Result<ClubView, ServiceError> {
+        self.require_owner(uid, club_id).await?;
+        self.dao
+            .set_join_code(club_id, &generate_join_code())
+            .await
+            .map_err(internal_error)?;
+        self.get(uid, club_id).await
+    }
+
+    pub async fn join(&self, uid: i32, join_code: &str) -> Result<ClubView, ServiceError> {
+        let club = self
+            .dao
+            .get_club_by_code(&join_code.trim().to_ascii_uppercase())
+            .await
+            .map_err(internal_error)?
+            .ok_or_else(|| not_found("加入码无效"))?;
+        self.dao
+            .add_member(club.id, uid, CLUB_MEMBER, now_timestamp())
+            .await
+            .map_err(internal_error)?;
+        self.get(uid, club.id).await
+    }
+
+    /// 退出俱乐部；最后一位管理员只能在没有其他成员时退出，此时俱乐部随之删除
+    pub async fn leave(&self, uid: i32, club_id: i64) -> Result<(), ServiceError> {
+        let row = self.membership(uid, club_id).await?;
+        if row.role == CLUB_OWNER && self.owner_count(club_id).await? == 1 {
+            if row.member_count > 1 {
+                return Err(conflict("请先指定其他管理员再退出"));
+            }
+            return self.dao.delete_club(club_id).await.map_err(internal_error);
+        }
+        self.dao
+            .remove_member(club_id, uid)
+            .await
+            .map_err(internal_error)?;
+        Ok(())
+    }
+
+    pub async fn members(
+        &self,
+        uid: i32,
+        club_id: i64,
+    ) -> Result<Vec<ClubMemberView>, ServiceError> {
+        self.membership(uid, club_id).await?;
+        let rows = self
+            .dao
+            .list_members(club_id)
+            .await
+            .map_err(internal_error)?;
+        Ok(rows
+            .into_iter()
+            .map(|r| ClubMemberView {
+                user: user_card(r.uid, r.nickname, r.avatar_version),
+                role: r.role,
+                joined_at: r.joined_at,
+            })
+            .collect())
+    }
+
+    /// 设置成员角色，俱乐部至少保留一位管理员
+    pub async fn set_role(
+        &self,
+        uid: i32,
+        club_id: i64,
+        target: i32,
+        role: &str,
+    ) -> Result<(), ServiceError> {
+        if role != CLUB_OWNER && role != CLUB_MEMBER {
+            return Err(bad_request("role 只能是 owner 或 member"));
+        }
+        self.require_owner(uid, club_id).await?;
+        let current = self.membership(target, club_id).await?;
+        if current.role == CLUB_OWNER
+            && role == CLUB_MEMBER
+            && self.owner_count(club_id).await? == 1
+        {
+            return Err(conflict("俱乐部至少需要一位管理员"));
+        }
+        self.dao
+            .set_member_role(club_id, target, role)
+            .await
+            .map_err(internal_error)?;
+        Ok(())
+    }
+
+    pub async fn remove_member(
+        &self,
+        uid: i32,
+        club_id: i64,
+        target: i32,
+    ) -> Result<(), ServiceError> {
+        if uid == target {
+            return Err(bad_request("请使用退出俱乐部"));
+        }
+        self.require_owner(uid, club_id).await?;
+        if self
+            .dao
+            .remove_member(club_id, target)
+            .await
+            .map_err(internal_error)?
+        {
+            Ok(())
+        } else {
+            Err(not_found("该用户不是俱乐部成员"))
+        }
+    }
+
+    pub async fn invite(&self, uid: i32, club_id: i64, target: i32) -> Result<(), ServiceError> {
+        self.require_owner(uid, club_id).await?;
+        self.users
+            .get_by_id(target)
+            .await
+            .map_err(internal_error)?
+            .ok_or_else(|| not_found("用户不存在"))?;
+        if self
+            .dao
+            .get_club_row(club_id, target)
+            .await
+            .map_err(internal_error)?
+            .is_some()
+        {
+            return Err(conflict("该用户已是俱乐部成员"));
+        }
+        self.dao
+            .create_invite(club_id, target, uid, now_timestamp())
+            .await
+            .map_err(internal_error)
+    }
+
+    pub async fn invites(&self, uid: i32) -> Result<Vec<ClubInvite>, ServiceError> {
+        self.dao.list_invites(uid).await.map_err(internal_error)
+    }
+
+    pub async fn accept_invite(&self, uid: i32, club_id: i64) -> Result<ClubView, ServiceError> {
+        let pending = self
+            .dao
+            .list_invites(uid)
+            .await
+            .map_err(internal_error)?
+            .iter()
+            .any(|i| i.club_id == club_id);
+        if !pending {
+            return Err(not_found("邀请不存在"));
+        }
+        self.dao
+            .add_member(club_id, uid, CLUB_MEMBER, now_timestamp())
+            .await
+            .map_err(internal_error)?;
+        self.get(uid, club_id).await
+    }
+
+    pub async fn decline_invite(&self, uid: i32, club_id: i64) -> Result<(), ServiceError> {
+        if self
+            .dao
+            .delete_invite(club_id, uid)
+            .await
+            .map_err(internal_error)?
+        {
+            Ok(())
+        } else {
+            Err(not_found("邀请不存在"))
+        }
+    }
+
+    pub async fn create_challenge(
+        &self,
+        uid: i32,
+        club_id: i64,
+        req: NewChallenge,
+    ) -> Result<Challenge, ServiceError> {
+        self.require_owner(uid, club_id).await?;
+        let name = validate_name(&req.name)?;
+        if req.end_time <= req.start_time {
+            return Err(bad_request("结束时间需晚于开始时间"));
+        }
+        if req.end_time - req.start_time > MAX_CHALLENGE_SECONDS {
+            return Err(bad_request("挑战时长不能超过一年"));
+        }
+        let mut challenge = Challenge {
+            id: 0,
+            club_id,
+            name,
+            metric: req.metric,
+            sport_type: req.sport_type,
+            start_time: req.start_time,
+            end_time: req.end_time,
+            created_by: uid,
+            created_at: now_timestamp(),
+        };
+        challenge.id = self
+            .dao
+            .create_challenge(challenge.clone())
+            .await
+            .map_err(internal_error)?;
+        Ok(challenge)
+    }
+
+    pub async fn challenges(&self, uid: i32, club_id: i64) -> Result<Vec<Challenge>, ServiceError> {
+        self.membership(uid, club_id).await?;
+        self.dao
+            .list_challenges(club_id)
+            .await
+            .map_err(internal_error)
+    }
+
+    pub async fn delete_challenge(
+        &self,
+        uid: i32,
+        club_id: i64,
+        challenge_id: i64,
+    ) -> Result<(), ServiceError> {
+        self.require_owner(uid, club_id).await?;
+        if self
+            .dao
+            .delete_challenge(club_id, challenge_id)
+            .await
+            .map_err(internal_error)?
+        {
+            Ok(())
+        } else {
+            Err(not_found("挑战不存在"))
+        }
+    }
+
+    /// 排行榜每次按成员当前的运动记录实时统计，运动的新增、导入和删除立即生效
+    pub async fn leaderboard(
+        &self,
+        uid: i32,
+        club_id: i64,
+        challenge_id: i64,
+    ) -> Result<Leaderboard, ServiceError> {
+        self.membership(uid, club_id).await?;
+        let challenge = self
+            .dao
+            .get_challenge(club_id, challenge_id)
+            .await
+            .map_err(internal_error)?
+            .ok_or_else(|| not_found("挑战不存在"))?;
+        let rows = self
+            .dao
+            .leaderboard(&challenge)
+            .await
+            .map_err(internal_error)?;
+        let ranks = competition_ranks(&rows.iter().map(|r| r.value).collect::<Vec<_>>());
+        let entries = rows
+            .into_iter()
+            .zip(ranks)
+            .map(|(r, rank)| LeaderboardEntry {
+                rank,
+                user: user_card(r.uid, r.nickname, r.avatar_version),
+                value: r.value,
+                sessions: r.sessions,
+            })
+            .collect();
+        Ok(Leaderboard { challenge, entries })
+    }
+
+    /// 非成员看不到俱乐部，统一返回 404
+    async fn membership(&self, uid: i32, club_id: i64) -> Result<ClubRow, ServiceError> {
+        self.dao
+            .get_club_row(club_id, uid)
+            .await
+            .map_err(internal_error)?
+            .ok_or_else(|| not_found("俱乐部不存在"))
+    }
+
+    async fn require_owner(&self, uid: i32, club_id: i64) -> Result<ClubRow, ServiceError> {
+        let row = self.membership(uid, club_id).await?;
+        if row.role != CLUB_OWNER {
+            return Err(ServiceError {
+                code: 403,
+                message: "需要俱乐部管理员权限".to_string(),
+            });
+        }
+        Ok(row)
+    }
+
+    async fn owner_count(&self, club_id: i64) -> Result<usize, ServiceError> {
+        Ok(self
+            .dao
+            .list_members(club_id)
+            .await
+            .map_err(internal_error)?
+            .iter()
+            .filter(|m| m.role == CLUB_OWNER)
+            .count())
+    }
+}
+
+fn validate_name(name: &str) -> Result<String, ServiceError> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_CHARS {
+        return Err(bad_request("名称不能为空且不超过 40 个字符"));
+    }
+    Ok(name.to_string())
+}
+
+fn generate_join_code() -> String {
+    let mut rng = rand::thread_rng();
+    (0..JOIN_CODE_LEN)
+        .map(|_| JOIN_CODE_ALPHABET[rng.gen_range(0..JOIN_CODE_ALPHABET.len())] as char)
+        .collect()
+}
+
+fn now_timestamp() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn bad_request(message: &str) -> ServiceError {
+    ServiceError {
+        code: 400,
+        message: message.to_string(),
+    }
+}
+
+fn not_found(message: &str) -> ServiceError {
+    ServiceError {
+        code: 404,
+        message: message.to_string(),
+    }
+}
+
+fn conflict(message: &str) -> ServiceError {
+    ServiceError {
+        code: 409,
+        message: message.to_string(),
+    }
+}
+
+fn internal_error(message: String) -> ServiceError {
+    ServiceError { code: 500, message }
+}
@@ -0,0 +1,221 @@
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::dao::idl::{CoachDao, UserDao};
+use crate::model::coach::{COACH_SCOPES, CoachGrant, CoachGrantRow, CoachGrantView, GRANT_PENDING};
+use crate::service::common::ServiceError;
+use crate::service::social_service::user_card;
+
+pub struct CoachService {
+    dao: Arc<dyn CoachDao + Send + Sync>,
+    users: Arc<dyn UserDao + Send + Sync>,
+}
+
+impl CoachService {
+    pub fn new(
+        dao: Arc<dyn CoachDao + Send + Sync>,
+        users: Arc<dyn UserDao + Send + Sync>,
+    ) -> Self {
+        Self { dao, users }
+    }
+
+    /// 运动员邀请教练，教练接受后授权才生效
+    pub async fn invite(
+        &self,
+        athlete: i32,
+        coach: i32,
+        scopes: &[String],
+    ) -> Result<CoachGrantView, ServiceError> {
+        if athlete == coach {
+            return Err(bad_request("不能授权给自己"));
+        }
+        let scopes = normalize_scopes(scopes)?;
+        let user = self
+            .users
+            .get_by_id(coach)
+            .await
+            .map_err(internal_error)?
+            .ok_or_else(|| not_found("用户不存在"))?;
+        if self
+            .dao
+            .get_grant(athlete, coach)
+            .await
+            .map_err(internal_error)?
+            .is_some()
+        {
+            return Err(conflict("已向该用户发出过授权"));
+        }
+        let grant = CoachGrant {
+            athlete_uid: athlete,
+            coach_uid: coach,
+            scopes,
+            status: GRANT_PENDING.to_string(),
+            created_at: now_timestamp(),
+            accepted_at: None,
+        };
+        self.dao
+            .create_grant(grant.clone())
+            .await
+            .map_err(internal_error)?;
+        Ok(CoachGrantView {
+            user: user_card(coach, user.nickname, user.avatar_version),
+            scopes: grant.scopes,
+            status: grant.status,
+            created_at: grant.created_at,
+            accepted_at: None,
+        })
+    }
+
+    /// 运动员调整授权范围，已生效的授权保持生效
+    pub async fn set_scopes(
+        &self,
+        athlete: i32,
+        coach: i32,
+        scopes: &[String],
+    ) -> Result<Vec<String>, ServiceError> {
+        let scopes = normalize_scopes(scopes)?;
+        if self
+            .dao
+            .set_grant_scopes(athlete, coach, &scopes)
+            .await
+            .map_err(internal_error)?
+        {
+            Ok(scopes)
+        } else {
+            Err(not_found("授权不存在"))
+        }
+    }
+
+    /// 运动员撤销授权，立即生效
+    pub async fn revoke(&self, athlete: i32, coach: i32) -> Result<(), ServiceError> {
+        self.delete(athlete, coach).await
+    }
+
+    pub async fn coaches(&self, athlete: i32) -> Result<Vec<CoachGrantView>, ServiceError> {
+        let rows = self
+            .dao
+            .list_coaches(athlete)
+            .await
+            .map_err(internal_error)?;
+        Ok(rows.into_iter().map(grant_view).collect())
+    }
+
+    pub async fn athletes(&self, coach: i32) -> Result<Vec<CoachGrantView>, ServiceError> {
+        let rows = self
+            .dao
+            .list_athletes(coach)
+            .await
+            .map_err(internal_error)?;
+        Ok(rows.into_iter().map(grant_view).collect())
+    }
+
+    pub async fn accept(&self, coach: i32, athlete: i32) -> Result<(), ServiceError> {
+        if self
+            .dao
+            .accept_grant(athlete, coach, now_timestamp())
+            .await
+            .map_err(internal_error)?
+        {
+            Ok(())
+        } else {
+            Err(not_found("没有待接受的授权"))
+        }
+    }
+
+    /// 教练拒绝邀请或不再指导该运动员
+    pub async fn leave(&self, coach: i32, athlete: i32) -> Result<(), ServiceError> {
+        self.delete(athlete, coach).await
+    }
+
+    /// 教练代运动员访问时校验授权，授权需已生效且包含所需范围
+    pub async fn authorize(
+        &self,
+        coach: i32,
+        athlete: i32,
+        scope: &str,
+    ) -> Result<(), ServiceError> {
+        match self
+            .dao
+            .get_grant(athlete, coach)
+            .await
+            .map_err(internal_error)?
+        {
+            Some(grant) if grant.allows(scope) => Ok(()),
+            _ => Err(ServiceError {
+                code: 403,
+                message: format!("未获得该运动员的授权: {scope}"),
+            }),
+        }
+    }
+
+    async fn delete(&self, athlete: i32, coach: i32) -> Result<(), ServiceError> {
+        if self
+            .dao
+            .delete_grant(athlete, coach)
+            .await
+            .map_err(internal_error)?
+        {
+            Ok(())
+        } else {
+            Err(not_found("授权不存在"))
+        }
+    }
+}
+
+fn normalize_scopes(scopes: &[String]) -> Result<Vec<String>, ServiceError> {
+    let mut normalized: Vec<String> = Vec::new();
+    for scope in scopes {
+        if !COACH_SCOPES.contains(&scope.as_str()) {
+            return Err(bad_request(&format!("未知的权限范围: {scope}")));
+        }
+        if !normalized.contains(scope) {
+            normalized.push(scope.clone());
+        }
+    }
+    if normalized.is_empty() {
+        return Err(bad_request("至少需要一个权限范围"));
+    }
+    Ok(normalized)
+}
+
+fn grant_view(row: CoachGrantRow) -> CoachGrantView {
+    CoachGrantView {
+        user: user_card(row.uid, row.nickname, row.avatar_version),
+        scopes: row.scopes,
+        status: row.status,
+        created_at: row.created_at,
+        accepted_at: row.accepted_at,
+    }
+}
+
+fn now_timestamp() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn bad_request(message: &str) -> ServiceError {
+    ServiceError {
+        code: 400,
+        message: message.to_string(),
+    }
+}
+
+fn not_found(message: &str) -> ServiceError {
+    ServiceError {
+        code: 404,
+        message: message.to_string(),
+    }
+}
+
+fn conflict(message: &str) -> ServiceError {
+    ServiceError {
+        code: 409,
+        message: message.to_string(),
+    }
+}
+
+fn internal_error(message: String) -> ServiceError {
+    ServiceError { code: 500, message }
+}
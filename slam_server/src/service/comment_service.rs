@@ -0,0 +1,326 @@
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::dao::idl::{ClubDao, CoachDao, CommentDao, SocialDao, UserDao};
+use crate::model::coach::SCOPE_COMMENT;
+use crate::model::comment::{
+    CommentRow, CommentView, MAX_COMMENT_CHARS, REACTION_KINDS, ReactionSummary, SportComment,
+};
+use crate::model::notification::{NOTIFY_COMMENT, NOTIFY_REACTION};
+use crate::model::social::{FOLLOW_ACCEPTED, Viewer};
+use crate::service::common::ServiceError;
+use crate::service::notification_service::NotificationService;
+use crate::service::social_service::user_card;
+
+/// 通知里附带的评论摘要长度
+const EXCERPT_CHARS: usize = 80;
+
+pub struct CommentService {
+    dao: Arc<dyn CommentDao + Send + Sync>,
+    social: Arc<dyn SocialDao + Send + Sync>,
+    clubs: Arc<dyn ClubDao + Send + Sync>,
+    coaches: Arc<dyn CoachDao + Send + Sync>,
+    users: Arc<dyn UserDao + Send + Sync>,
+    notifications: Arc<NotificationService>,
+}
+
+impl CommentService {
+    pub fn new(
+        dao: Arc<dyn CommentDao + Send + Sync>,
+        social: Arc<dyn SocialDao + Send + Sync>,
+        clubs: Arc<dyn ClubDao + Send + Sync>,
+        coaches: Arc<dyn CoachDao + Send + Sync>,
+        users: Arc<dyn UserDao + Send + Sync>,
+        notifications: Arc<NotificationService>,
+    ) -> Self {
+        Self {
+            dao,
+            social,
+            clubs,
+            coaches,
+            users,
+            notifications,
+        }
+    }
+
+    pub async fn list(&self, viewer: i32, sport_id: i32) -> Result<Vec<CommentView>, ServiceError> {
+        self.visible_sport(viewer, sport_id).await?;
+        let rows = self
+            .dao
+            .list_comments(sport_id)
+            .await
+            .map_err(internal_error)?;
+        Ok(rows.into_iter().map(comment_view).collect())
+    }
+
+    /// 发表评论并通知运动记录的主人
+    pub async fn create(
+        &self,
+        uid: i32,
+        sport_id: i32,
+        content: &str,
+    ) -> Result<CommentView, ServiceError> {
+        let owner = self.visible_sport(uid, sport_id).await?;
+        let content = normalize_content(content)?;
+        let user = self
+            .users
+            .get_by_id(uid)
+            .await
+            .map_err(internal_error)?
+            .ok_or_else(|| not_found("用户不存在"))?;
+        let mut comment = SportComment {
+            id: 0,
+            sport_id,
+            uid,
+            content,
+            created_at: now_timestamp(),
+            updated_at: None,
+        };
+        comment.id = self
+            .dao
+            .create_comment(comment.clone())
+            .await
+            .map_err(internal_error)?;
+        if owner != uid {
+            let excerpt: String = comment.content.chars().take(EXCERPT_CHARS).collect();
+            self.notifications
+                .notify(
+                    owner,
+                    NOTIFY_COMMENT,
+                    Some(uid),
+                    Some(sport_id),
+                    serde_json::json!({ "comment_id": comment.id, "excerpt": excerpt }),
+                )
+                .await;
+        }
+        Ok(comment_view(CommentRow {
+            comment,
+            nickname: user.nickname,
+            avatar_version: user.avatar_version,
+        }))
+    }
+
+    /// 只有作者可以编辑评论
+    pub async fn update(
+        &self,
+        uid: i32,
+        sport_id: i32,
+        comment_id: i64,
+        content: &str,
+    ) -> Result<(), ServiceError> {
+        self.visible_sport(uid, sport_id).await?;
+        let comment = self.comment(sport_id, comment_id).await?;
+        if comment.uid != uid {
+            return Err(forbidden("只能编辑自己的评论"));
+        }
+        let content = normalize_content(content)?;
+        self.dao
+            .update_comment(comment_id, &content, now_timestamp())
+            .await
+            .map_err(internal_error)?;
+        Ok(())
+    }
+
+    /// 作者和运动记录的主人可以删除评论
+    pub async fn delete(
+        &self,
+        uid: i32,
+        sport_id: i32,
+        comment_id: i64,
+    ) -> Result<(), ServiceError> {
+        let owner = self.visible_sport(uid, sport_id).await?;
+        let comment = self.comment(sport_id, comment_id).await?;
+        if comment.uid != uid && owner != uid {
+            return Err(forbidden("只能删除自己的评论或自己运动下的评论"));
+        }
+        self.dao
+            .delete_comment(comment_id)
+            .await
+            .map_err(internal_error)?;
+        Ok(())
+    }
+
+    pub async fn reactions(
+        &self,
+        viewer: i32,
+        sport_id: i32,
+    ) -> Result<ReactionSummary, ServiceError> {
+        self.visible_sport(viewer, sport_id).await?;
+        Ok(ReactionSummary {
+            counts: self
+                .dao
+                .count_reactions(sport_id)
+                .await
+                .map_err(internal_error)?,
+            mine: self
+                .dao
+                .list_own_reactions(sport_id, viewer)
+                .await
+                .map_err(internal_error)?,
+        })
+    }
+
+    /// 回应运动记录，重复回应不会重复计数，也不会重复通知
+    pub async fn react(
+        &self,
+        uid: i32,
+        sport_id: i32,
+        kind: &str,
+    ) -> Result<ReactionSummary, ServiceError> {
+        check_kind(kind)?;
+        let owner = self.visible_sport(uid, sport_id).await?;
+        let added = self
+            .dao
+            .add_reaction(sport_id, uid, kind, now_timestamp())
+            .await
+            .map_err(internal_error)?;
+        if added && owner != uid {
+            self.notifications
+                .notify(
+                    owner,
+                    NOTIFY_REACTION,
+                    Some(uid),
+                    Some(sport_id),
+                    serde_json::json!({ "kind": kind }),
+                )
+                .await;
+        }
+        self.reactions(uid, sport_id).await
+    }
+
+    pub async fn unreact(
+        &self,
+        uid: i32,
+        sport_id: i32,
+        kind: &str,
+    ) -> Result<ReactionSummary, ServiceError> {
+        check_kind(kind)?;
+        self.visible_sport(uid, sport_id).await?;
+        self.dao
+            .remove_reaction(sport_id, uid, kind)
+            .await
+            .map_err(internal_error)?;
+        self.reactions(uid, sport_id).await
+    }
+
+    /// 校验查看者能否看到该运动记录，返回主人的 uid；看不到时与不存在一样返回 404。
+    /// 已关注的粉丝和同一俱乐部的成员按粉丝可见范围判断，持有评论授权的教练可以看到全部运动
+    async fn visible_sport(&self, viewer: i32, sport_id: i32) -> Result<i32, ServiceError> {
+        let gone = || not_found("运动记录不存在");
+        let sport = self
+            .dao
+            .get_sport_owner(sport_id)
+            .await
+            .map_err(internal_error)?
+            .ok_or_else(gone)?;
+        if viewer == sport.uid {
+            return Ok(sport.uid);
+        }
+        if let Some(grant) = self
+            .coaches
+            .get_grant(sport.uid, viewer)
+            .await
+            .map_err(internal_error)?
+            && grant.allows(SCOPE_COMMENT)
+        {
+            return Ok(sport.uid);
+        }
+        let follower = matches!(
+            self.social.get_follow(viewer, sport.uid).await.map_err(internal_error)?,
+            Some(f) if f.status == FOLLOW_ACCEPTED
+        );
+        let relation = if follower
+            || self
+                .clubs
+                .shares_club(viewer, sport.uid)
+                .await
+                .map_err(internal_error)?
+        {
+            Viewer::Follower
+        } else {
+            Viewer::Stranger
+        };
+        let private_account = self
+            .social
+            .get_private_account(sport.uid)
+            .await
+            .map_err(internal_error)?
+            .unwrap_or(true);
+        if relation
+            .visible_levels(private_account)
+            .contains(&sport.visibility)
+        {
+            Ok(sport.uid)
+        } else {
+            Err(gone())
+        }
+    }
+
+    async fn comment(&self, sport_id: i32, comment_id: i64) -> Result<SportComment, ServiceError> {
+        self.dao
+            .get_comment(sport_id, comment_id)
+            .await
+            .map_err(internal_error)?
+            .ok_or_else(|| not_found("评论不存在"))
+    }
+}
+
+fn normalize_content(content: &str) -> Result<String, ServiceError> {
+    let content = content.trim();
+    if content.is_empty() || content.chars().count() > MAX_COMMENT_CHARS {
+        return Err(bad_request("评论内容不能为空且不超过500个字符"));
+    }
+    Ok(content.to_string())
+}
+
+fn check_kind(kind: &str) -> Result<(), ServiceError> {
+    if REACTION_KINDS.contains(&kind) {
+        Ok(())
+    } else {
+        Err(bad_request(&format!("未知的回应类型: {kind}")))
+    }
+}
+
+fn comment_view(row: CommentRow) -> CommentView {
+    let c = row.comment;
+    CommentView {
+        id: c.id,
+        sport_id: c.sport_id,
+        author: user_card(c.uid, row.nickname, row.avatar_version),
+        content: c.content,
+        created_at: c.created_at,
+        updated_at: c.updated_at,
+    }
+}
+
+fn now_timestamp() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn bad_request(message: &str) -> ServiceError {
+    ServiceError {
+        code: 400,
+        message: message.to_string(),
+    }
+}
+
+fn forbidden(message: &str) -> ServiceError {
+    ServiceError {
+        code: 403,
+        message: message.to_string(),
+    }
+}
+
+fn not_found(message: &str) -> ServiceError {
+    ServiceError {
+        code: 404,
+        message: message.to_string(),
+    }
+}
+
+fn internal_error(message: String) -> ServiceError {
+    ServiceError { code: 500, message }
+}
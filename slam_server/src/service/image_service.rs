@@ -108,6 +108,34 @@ impl ImageService {
         Ok(buffer.into_inner())
     }
 
+    /// 把图片居中裁成正方形，按给定边长各生成一张 JPEG 头像
+    pub fn create_avatars(
+        &self,
+        image_data: &[u8],
+        sizes: &[u32],
+    ) -> Result<Vec<(u32, Vec<u8>)>, ServiceError> {
+        let img = image::load_from_memory(image_data).map_err(|e| ServiceError {
+            code: 400,
+            message: format!("无法解码图片: {e}"),
+        })?;
+        // JPEG 不支持透明通道
+        let img = DynamicImage::ImageRgb8(img.to_rgb8());
+        sizes
+            .iter()
+            .map(|&size| {
+                let resized = img.resize_to_fill(size, size, image::imageops::FilterType::Lanczos3);
+                let mut buffer = Cursor::new(Vec::new());
+                resized
+                    .write_to(&mut buffer, ImageOutputFormat::Jpeg(85))
+                    .map_err(|e| ServiceError {
+                        code: 500,
+                        message: format!("无法生成头像: {e}"),
+                    })?;
+                Ok((size, buffer.into_inner()))
+            })
+            .collect()
+    }
+
     /// 等比例压缩图片到长和宽其中之一小于指定阈值
     fn compress_image(&self, img: DynamicImage, threshold: u32) -> DynamicImage {
         let (width, height) = img.dimensions();
@@ -0,0 +1,169 @@
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::LoginThrottleConfig;
+use crate::dao::idl::{LoginAttemptDao, UserDao};
+use crate::model::audit::{AUDIT_LOGIN_FAILURE, AUDIT_LOGIN_LOCKOUT, LoginAttempt};
+use crate::service::audit_service::AuditService;
+use crate::service::session_service::ClientMeta;
+
+/// 登录防爆破：按账号名与来源 IP 分别统计连续失败次数。
+/// 达到阈值后锁定，之后每多失败一次锁定时长翻倍，直到上限。
+pub struct LoginThrottle {
+    dao: Arc<dyn LoginAttemptDao + Send + Sync>,
+    users: Arc<dyn UserDao + Send + Sync>,
+    audit: Arc<AuditService>,
+    config: LoginThrottleConfig,
+}
+
+impl LoginThrottle {
+    pub fn new(
+        dao: Arc<dyn LoginAttemptDao + Send + Sync>,
+        users: Arc<dyn UserDao + Send + Sync>,
+        audit: Arc<AuditService>,
+        config: LoginThrottleConfig,
+    ) -> Self {
+        Self {
+            dao,
+            users,
+            audit,
+            config,
+        }
+    }
+
+    /// 账号或 IP 处于锁定期时返回剩余秒数
+    pub async fn check(&self, name: &str, ip: &str) -> Option<u64> {
+        let now = now_timestamp();
+        let mut retry_after = 0;
+        for key in keys(name, ip) {
+            match self.dao.get_login_attempt(&key).await {
+                Ok(Some(a)) if a.locked_until > now => {
+                    retry_after = retry_after.max((a.locked_until - now) as u64);
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!(error = %e, "failed to read login attempts"),
+            }
+        }
+        (retry_after > 0).then_some(retry_after)
+    }
+
+    /// 记录一次失败并写审计日志；若因此触发锁定，返回锁定秒数
+    pub async fn record_failure(&self, name: &str, client: &ClientMeta) -> Option<u64> {
+        let now = now_timestamp();
+        let uid = match self.users.get_by_name(name).await {
+            Ok(u) => u.map(|u| u.id),
+            Err(_) => None,
+        };
+        self.audit
+            .record(
+                uid,
+                AUDIT_LOGIN_FAILURE,
+                client,
+                serde_json::json!({ "name": name }),
+            )
+            .await;
+        let mut retry_after = 0;
+        for (scope, key) in [("account", account_key(name)), ("ip", ip_key(&client.ip))] {
+            let Some(key) = key else { continue };
+            let threshold = if scope == "account" {
+                self.config.max_failures_per_account
+            } else {
+                self.config.max_failures_per_ip
+            };
+            let previous = match self.dao.get_login_attempt(&key).await {
+                Ok(a) => a,
+                Err(e) => {
+                    tracing::warn!(error = %e, "failed to read login attempts");
+                    None
+                }
+            };
+            let failures = match previous {
+                Some(a) if now - a.last_failure_at <= self.config.failure_window_seconds as i64 => {
+                    a.failures + 1
+                }
+                _ => 1,
+            };
+            let lock_seconds = self.lockout_seconds(failures, threshold);
+            let attempt = LoginAttempt {
+                key,
+                failures,
+                last_failure_at: now,
+                locked_until: if lock_seconds > 0 {
+                    now + lock_seconds as i64
+                } else {
+                    0
+                },
+            };
+            if let Err(e) = self.dao.save_login_attempt(attempt).await {
+                tracing::warn!(error = %e, "failed to save login attempt");
+            }
+            if lock_seconds > 0 {
+                retry_after = retry_after.max(lock_seconds);
+                tracing::warn!(
+                    scope,
+                    name,
+                    ip = %client.ip,
+                    failures,
+                    lock_seconds,
+                    "login locked after repeated failures"
+                );
+                self.audit
+                    .record(
+                        uid,
+                        AUDIT_LOGIN_LOCKOUT,
+                        client,
+                        serde_json::json!({
+                            "scope": scope,
+                            "name": name,
+                            "failures": failures,
+                            "lock_seconds": lock_seconds,
+                        }),
+                    )
+                    .await;
+            }
+        }
+        (retry_after > 0).then_some(retry_after)
+    }
+
+    /// 登录成功后清除账号的失败计数；IP 计数保留，避免攻击者用自有账号重置
+    pub async fn record_success(&self, name: &str) {
+        if let Some(key) = account_key(name)
+            && let Err(e) = self.dao.clear_login_attempt(&key).await
+        {
+            tracing::warn!(error = %e, "failed to clear login attempts");
+        }
+    }
+
+    fn lockout_seconds(&self, failures: i64, threshold: u32) -> u64 {
+        let threshold = threshold.max(1) as i64;
+        if failures < threshold {
+            return 0;
+        }
+        let doublings = (failures - threshold).min(32) as u32;
+        self.config
+            .lockout_base_seconds
+            .saturating_mul(1u64 << doublings)
+            .min(self.config.lockout_max_seconds)
+    }
+}
+
+fn account_key(name: &str) -> Option<String> {
+    let name = name.trim().to_lowercase();
+    (!name.is_empty()).then(|| format!("account:{name}"))
+}
+
+/// 拿不到来源地址时不做 IP 维度的限制，避免所有请求共享同一个计数
+fn ip_key(ip: &str) -> Option<String> {
+    (!ip.is_empty()).then(|| format!("ip:{ip}"))
+}
+
+fn keys(name: &str, ip: &str) -> Vec<String> {
+    account_key(name).into_iter().chain(ip_key(ip)).collect()
+}
+
+fn now_timestamp() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
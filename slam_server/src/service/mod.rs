@@ -1,8 +1,28 @@
+pub mod access_token_service;
+pub mod admin_service;
 pub mod ai_job_service;
 pub mod ai_job_worker;
 pub mod ai_service;
+pub mod athlete_service;
+pub mod audit_service;
+pub mod calorie;
+pub mod card_renderer;
+pub mod club_service;
+pub mod coach_service;
+pub mod comment_service;
 pub mod common;
 pub mod image_service;
 pub mod llm;
+pub mod login_throttle;
+pub mod notification_service;
+pub mod oidc_service;
+pub mod session_service;
+pub mod share_service;
+pub mod social_service;
 pub mod sport_service;
+pub mod totp;
+pub mod two_factor_service;
 pub mod user_service;
+pub mod webhook_service;
+pub mod webhook_worker;
+pub mod year_review;
@@ -0,0 +1,130 @@
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::dao::idl::NotificationDao;
+use crate::model::notification::{
+    Notification, NotificationPage, NotificationRow, NotificationView,
+};
+use crate::service::common::ServiceError;
+use crate::service::social_service::user_card;
+
+const DEFAULT_PAGE_SIZE: i32 = 20;
+const MAX_PAGE_SIZE: i32 = 50;
+
+pub struct NotificationService {
+    dao: Arc<dyn NotificationDao + Send + Sync>,
+}
+
+impl NotificationService {
+    pub fn new(dao: Arc<dyn NotificationDao + Send + Sync>) -> Self {
+        Self { dao }
+    }
+
+    /// 给用户发一条通知；写入失败只记录告警，不影响触发通知的业务
+    pub async fn notify(
+        &self,
+        uid: i32,
+        kind: &str,
+        actor_uid: Option<i32>,
+        sport_id: Option<i32>,
+        detail: serde_json::Value,
+    ) {
+        let notification = Notification {
+            id: 0,
+            uid,
+            kind: kind.to_string(),
+            actor_uid,
+            sport_id,
+            detail: detail.to_string(),
+            created_at: now_timestamp(),
+            read_at: None,
+        };
+        if let Err(e) = self.dao.create_notification(notification).await {
+            tracing::warn!(uid, kind, error = %e, "failed to create notification");
+        }
+    }
+
+    /// 按时间倒序分页，cursor 为上一页返回的 next_cursor
+    pub async fn list(
+        &self,
+        uid: i32,
+        cursor: Option<i64>,
+        size: Option<i32>,
+        unread_only: bool,
+    ) -> Result<NotificationPage, ServiceError> {
+        let size = size.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+        let mut rows = self
+            .dao
+            .list_notifications(uid, cursor, unread_only, size + 1)
+            .await
+            .map_err(internal_error)?;
+        let has_more = rows.len() > size as usize;
+        rows.truncate(size as usize);
+        let next_cursor = has_more
+            .then(|| rows.last())
+            .flatten()
+            .map(|r| r.notification.id);
+        Ok(NotificationPage {
+            items: rows.into_iter().map(notification_view).collect(),
+            unread: self.unread_count(uid).await?,
+            next_cursor,
+        })
+    }
+
+    pub async fn unread_count(&self, uid: i32) -> Result<i64, ServiceError> {
+        self.dao.count_unread(uid).await.map_err(internal_error)
+    }
+
+    /// 已读的通知保持最初的已读时间
+    pub async fn mark_read(&self, uid: i32, id: i64) -> Result<(), ServiceError> {
+        let found = self
+            .dao
+            .mark_read(uid, id, now_timestamp())
+            .await
+            .map_err(internal_error)?;
+        if found {
+            Ok(())
+        } else {
+            Err(ServiceError {
+                code: 404,
+                message: "通知不存在".to_string(),
+            })
+        }
+    }
+
+    /// 返回本次标记为已读的条数
+    pub async fn mark_all_read(&self, uid: i32) -> Result<u64, ServiceError> {
+        self.dao
+            .mark_all_read(uid, now_timestamp())
+            .await
+            .map_err(internal_error)
+    }
+}
+
+fn notification_view(row: NotificationRow) -> NotificationView {
+    let n = row.notification;
+    let actor = match (n.actor_uid, row.actor_nickname) {
+        (Some(uid), Some(nickname)) => Some(user_card(uid, nickname, row.actor_avatar_version)),
+        _ => None,
+    };
+    NotificationView {
+        id: n.id,
+        kind: n.kind,
+        actor,
+        sport_id: n.sport_id,
+        detail: n.detail,
+        created_at: n.created_at,
+        read_at: n.read_at,
+    }
+}
+
+fn now_timestamp() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn internal_error(message: String) -> ServiceError {
+    ServiceError { code: 500, message }
+}
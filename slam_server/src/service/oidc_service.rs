@@ -0,0 +1,567 @@
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use jsonwebtoken::jwk::{Jwk, JwkSet};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, decode, decode_header};
+use rand::RngCore;
+use reqwest::{Client, Url};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use tokio::sync::RwLock;
+
+use crate::config::{OidcConfig, SecurityConfig};
+use crate::dao::idl::{OidcDao, UserDao};
+use crate::model::oidc::{OidcIdentity, OidcLoginState};
+use crate::model::user::{PASSWORD_UNSET, User};
+use crate::service::common::ServiceError;
+
+/// 从发起授权到回调的最长时间
+const LOGIN_STATE_TTL_SECONDS: i64 = 600;
+/// 没有本地密码的账号执行敏感操作前，外部身份认证的有效时间
+const REAUTH_WINDOW_SECONDS: i64 = 600;
+
+/// discovery 文档中用到的字段
+#[derive(Debug, Clone, Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct IdTokenClaims {
+    sub: String,
+    #[serde(default)]
+    nonce: Option<String>,
+    #[serde(default)]
+    email: Option<String>,
+    #[serde(default)]
+    preferred_username: Option<String>,
+    #[serde(default)]
+    name: Option<String>,
+}
+
+/// 发起授权的跳转地址与需要写入浏览器 cookie 的 state
+pub struct OidcRedirect {
+    pub authorization_url: String,
+    pub state: String,
+}
+
+/// 回调处理结果
+pub struct OidcOutcome {
+    pub uid: i32,
+    /// 本次为已登录用户关联身份，而不是登录
+    pub linked: bool,
+    pub subject: String,
+}
+
+/// OpenID Connect 授权码登录（PKCE），外部身份按 (issuer, subject) 关联到本地账号
+pub struct OidcService {
+    config: Option<OidcConfig>,
+    security: SecurityConfig,
+    dao: Arc<dyn OidcDao + Send + Sync>,
+    users: Arc<dyn UserDao + Send + Sync>,
+    http: Client,
+    metadata: RwLock<Option<ProviderMetadata>>,
+    jwks: RwLock<Option<JwkSet>>,
+}
+
+impl OidcService {
+    pub fn new(
+        security: SecurityConfig,
+        dao: Arc<dyn OidcDao + Send + Sync>,
+        users: Arc<dyn UserDao + Send + Sync>,
+    ) -> Self {
+        let http = Client::builder()
+            .connect_timeout(Duration::from_secs(10))
+            .timeout(Duration::from_secs(30))
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .expect("build reqwest client");
+        Self {
+            config: security.oidc.clone(),
+            security,
+            dao,
+            users,
+            http,
+            metadata: RwLock::new(None),
+            jwks: RwLock::new(None),
+        }
+    }
+
+    fn config(&self) -> Result<&OidcConfig, ServiceError> {
+        self.config.as_ref().ok_or_else(|| ServiceError {
+            code: 404,
+            message: "未启用 OIDC 登录".to_string(),
+        })
+    }
+
+    /// 登录或关联完成后浏览器跳转的地址
+    pub fn post_login_redirect(&self) -> &str {
+        self.config
+            .as_ref()
+            .map_or("/", |c| c.post_login_redirect.as_str())
+    }
+
+    /// 生成 state、nonce 与 PKCE 参数，返回身份提供方的授权地址
+    pub async fn begin(&self, link_uid: Option<i32>) -> Result<OidcRedirect, ServiceError> {
+        let config = self.config()?;
+        let metadata = self.metadata().await?;
+        let state = random_token();
+        let nonce = random_token();
+        let code_verifier = random_token();
+        let code_challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()));
+        let now = now_timestamp();
+        self.dao
+            .create_login_state(OidcLoginState {
+                state: state.clone(),
+                nonce: nonce.clone(),
+                code_verifier,
+                link_uid,
+                created_at: now,
+                expires_at: now + LOGIN_STATE_TTL_SECONDS,
+            })
+            .await
+            .map_err(internal_error)?;
+        let url = Url::parse_with_params(
+            &metadata.authorization_endpoint,
+            &[
+                ("response_type", "code"),
+                ("client_id", config.client_id.as_str()),
+                ("redirect_uri", config.redirect_uri.as_str()),
+                ("scope", config.scopes.join(" ").as_str()),
+                ("state", state.as_str()),
+                ("nonce", nonce.as_str()),
+                ("code_challenge", code_challenge.as_str()),
+                ("code_challenge_method", "S256"),
+            ],
+        )
+        .map_err(|e| internal_error(format!("授权地址无效: {e}")))?;
+        Ok(OidcRedirect {
+            authorization_url: url.to_string(),
+            state,
+        })
+    }
+
+    /// 处理回调：核对 state、用授权码换取 ID Token 并校验，再登录或关联本地账号
+    pub async fn finish(
+        &self,
+        state: &str,
+        browser_state: Option<&str>,
+        code: &str,
+    ) -> Result<OidcOutcome, ServiceError> {
+        let config = self.config()?;
+        // state 须与发起授权的浏览器 cookie 一致，防止登录 CSRF
+        if browser_state != Some(state) {
+            return Err(invalid_state());
+        }
+        let login_state = self
+            .dao
+            .take_login_state(state, now_timestamp())
+            .await
+            .map_err(internal_error)?
+            .ok_or_else(invalid_state)?;
+        let metadata = self.metadata().await?;
+        let id_token = self
+            .exchange_code(config, &metadata, code, &login_state.code_verifier)
+            .await?;
+        let claims = self.verify_id_token(config, &metadata, &id_token).await?;
+        if claims.nonce.as_deref() != Some(login_state.nonce.as_str()) {
+            return Err(ServiceError {
+                code: 400,
+                message: "ID Token nonce 不匹配".to_string(),
+            });
+        }
+        let now = now_timestamp();
+        let existing = self
+            .dao
+            .find_identity(&metadata.issuer, &claims.sub)
+            .await
+            .map_err(internal_error)?;
+
+        if let Some(uid) = login_state.link_uid {
+            match existing {
+                Some(identity) if identity.uid != uid => {
+                    return Err(ServiceError {
+                        code: 409,
+                        message: "该外部身份已关联其他账号".to_string(),
+                    });
+                }
+                // 已关联的身份再走一遍授权，视为重新认证
+                Some(identity) => {
+                    self.dao
+                        .touch_identity(identity.id, now)
+                        .await
+                        .map_err(internal_error)?;
+                }
+                None => {
+                    self.create_identity(uid, &metadata.issuer, &claims, now)
+                        .await?;
+                    tracing::info!(uid, issuer = %metadata.issuer, "oidc identity linked");
+                }
+            }
+            return Ok(OidcOutcome {
+                uid,
+                linked: true,
+                subject: claims.sub,
+            });
+        }
+
+        let uid = match existing {
+            Some(identity) => {
+                self.dao
+                    .touch_identity(identity.id, now)
+                    .await
+                    .map_err(internal_error)?;
+                identity.uid
+            }
+            None if config.auto_register => {
+                let uid = self.provision_user(&claims).await?;
+                self.create_identity(uid, &metadata.issuer, &claims, now)
+                    .await?;
+                uid
+            }
+            None => {
+                return Err(ServiceError {
+                    code: 403,
+                    message: "该外部身份尚未关联本地账号，请先登录后在账号设置中关联".to_string(),
+                });
+            }
+        };
+        let disabled = self
+            .users
+            .get_account_status(uid)
+            .await
+            .map_err(internal_error)?
+            .is_none_or(|status| status.disabled_at.is_some());
+        if disabled {
+            return Err(ServiceError {
+                code: 403,
+                message: "账号已被禁用，请联系管理员".to_string(),
+            });
+        }
+        Ok(OidcOutcome {
+            uid,
+            linked: false,
+            subject: claims.sub,
+        })
+    }
+
+    /// 最近 REAUTH_WINDOW_SECONDS 内是否通过任一外部身份登录或重新认证过
+    pub async fn recently_authenticated(&self, uid: i32) -> Result<bool, ServiceError> {
+        let since = now_timestamp() - REAUTH_WINDOW_SECONDS;
+        let identities = self
+            .dao
+            .list_identities(uid)
+            .await
+            .map_err(internal_error)?;
+        Ok(identities
+            .iter()
+            .any(|i| i.last_login_at.is_some_and(|at| at >= since)))
+    }
+
+    pub async fn list_identities(&self, uid: i32) -> Result<Vec<OidcIdentity>, ServiceError> {
+        self.dao.list_identities(uid).await.map_err(internal_error)
+    }
+
+    /// 解除关联；账号没有其他登录方式时拒绝，避免把自己锁在门外
+    pub async fn unlink(&self, uid: i32, id: i64) -> Result<(), ServiceError> {
+        let identities = self
+            .dao
+            .list_identities(uid)
+            .await
+            .map_err(internal_error)?;
+        if !identities.iter().any(|i| i.id == id) {
+            return Err(ServiceError {
+                code: 404,
+                message: "外部身份不存在".to_string(),
+            });
+        }
+        if identities.len() == 1 {
+            let has_password = self
+                .users
+                .get_account(uid)
+                .await
+                .map_err(internal_error)?
+                .is_some_and(|u| u.password != PASSWORD_UNSET);
+            if !has_password || !self.security.password_login {
+                return Err(ServiceError {
+                    code: 409,
+                    message: "这是账号唯一的登录方式，不能解除关联".to_string(),
+                });
+            }
+        }
+        self.dao
+            .delete_identity(uid, id)
+            .await
+            .map_err(internal_error)?;
+        Ok(())
+    }
+
+    async fn create_identity(
+        &self,
+        uid: i32,
+        issuer: &str,
+        claims: &IdTokenClaims,
+        now: i64,
+    ) -> Result<(), ServiceError> {
+        match self
+            .dao
+            .create_identity(OidcIdentity {
+                id: 0,
+                uid,
+                issuer: issuer.to_string(),
+                subject: claims.sub.clone(),
+                email: claims.email.clone().unwrap_or_default(),
+                created_at: now,
+                last_login_at: Some(now),
+            })
+            .await
+        {
+            Ok(_) => Ok(()),
+            // 并发回调同一身份时由唯一索引兜底
+            Err(e) if e.contains("UNIQUE constraint failed") => Err(ServiceError {
+                code: 409,
+                message: "该外部身份已关联其他账号".to_string(),
+            }),
+            Err(e) => Err(internal_error(e)),
+        }
+    }
+
+    /// 为首次登录的外部身份创建本地账号，用户名取自 preferred_username 或邮箱，重名时追加序号
+    async fn provision_user(&self, claims: &IdTokenClaims) -> Result<i32, ServiceError> {
+        let base = username_base(claims);
+        let nickname: String = claims
+            .name
+            .as_deref()
+            .map(str::trim)
+            .filter(|n| !n.is_empty())
+            .unwrap_or(&base)
+            .chars()
+            .take(32)
+            .collect();
+        for attempt in 1..=20 {
+            let name = if attempt == 1 {
+                base.clone()
+            } else {
+                format!("{}-{attempt}", &base[..base.len().min(28)])
+            };
+            if self
+                .users
+                .get_by_name(&name)
+                .await
+                .map_err(internal_error)?
+                .is_some()
+            {
+                continue;
+            }
+            let user = User {
+                id: 0,
+                name: name.clone(),
+                password: PASSWORD_UNSET.to_string(),
+                nickname: nickname.clone(),
+            };
+            match self.users.insert(user).await {
+                Ok(uid) => {
+                    tracing::info!(uid, name = %name, "user registered via oidc");
+                    return Ok(uid);
+                }
+                Err(e) if e.contains("UNIQUE constraint failed") => continue,
+                Err(e) => return Err(internal_error(e)),
+            }
+        }
+        Err(ServiceError {
+            code: 409,
+            message: "无法为该外部身份分配用户名".to_string(),
+        })
+    }
+
+    async fn metadata(&self) -> Result<ProviderMetadata, ServiceError> {
+        if let Some(m) = self.metadata.read().await.as_ref() {
+            return Ok(m.clone());
+        }
+        let config = self.config()?;
+        let issuer = config.issuer.trim_end_matches('/');
+        let url = format!("{issuer}/.well-known/openid-configuration");
+        let metadata: ProviderMetadata = self.get_json(&url).await?;
+        if metadata.issuer.trim_end_matches('/') != issuer {
+            return Err(provider_error("discovery 文档中的 issuer 与配置不一致"));
+        }
+        *self.metadata.write().await = Some(metadata.clone());
+        Ok(metadata)
+    }
+
+    async fn exchange_code(
+        &self,
+        config: &OidcConfig,
+        metadata: &ProviderMetadata,
+        code: &str,
+        code_verifier: &str,
+    ) -> Result<String, ServiceError> {
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", config.redirect_uri.as_str()),
+            ("client_id", config.client_id.as_str()),
+            ("code_verifier", code_verifier),
+        ];
+        if !config.client_secret.is_empty() {
+            form.push(("client_secret", config.client_secret.as_str()));
+        }
+        let resp = self
+            .http
+            .post(&metadata.token_endpoint)
+            .form(&form)
+            .send()
+            .await
+            .map_err(|e| provider_error(&format!("请求 token 接口失败: {e}")))?;
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let body = resp.text().await.unwrap_or_default();
+            tracing::warn!(%status, body = %body, "oidc token exchange rejected");
+            return Err(provider_error("授权码无效或已过期"));
+        }
+        let token: TokenResponse = resp
+            .json()
+            .await
+            .map_err(|e| provider_error(&format!("token 响应无法解析: {e}")))?;
+        Ok(token.id_token)
+    }
+
+    /// 按 JWKS 校验签名、issuer、audience 与有效期；找不到 kid 时刷新一次 JWKS 以应对密钥轮换
+    async fn verify_id_token(
+        &self,
+        config: &OidcConfig,
+        metadata: &ProviderMetadata,
+        id_token: &str,
+    ) -> Result<IdTokenClaims, ServiceError> {
+        let header =
+            decode_header(id_token).map_err(|e| provider_error(&format!("ID Token 无效: {e}")))?;
+        if matches!(
+            header.alg,
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+        ) {
+            return Err(provider_error("不支持对称签名的 ID Token"));
+        }
+        let kid = header.kid.as_deref();
+        let key = match self.find_jwk(&metadata.jwks_uri, kid, false).await? {
+            Some(key) => key,
+            None => self
+                .find_jwk(&metadata.jwks_uri, kid, true)
+                .await?
+                .ok_or_else(|| provider_error("找不到 ID Token 的签名密钥"))?,
+        };
+        let key = DecodingKey::from_jwk(&key)
+            .map_err(|e| provider_error(&format!("签名密钥无效: {e}")))?;
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[metadata.issuer.as_str()]);
+        validation.set_audience(&[config.client_id.as_str()]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+        decode::<IdTokenClaims>(id_token, &key, &validation)
+            .map(|data| data.claims)
+            .map_err(|e| provider_error(&format!("ID Token 校验失败: {e}")))
+    }
+
+    async fn find_jwk(
+        &self,
+        jwks_uri: &str,
+        kid: Option<&str>,
+        refresh: bool,
+    ) -> Result<Option<Jwk>, ServiceError> {
+        let cached = if refresh {
+            None
+        } else {
+            self.jwks.read().await.clone()
+        };
+        let jwks = match cached {
+            Some(jwks) => jwks,
+            None => {
+                let jwks: JwkSet = self.get_json(jwks_uri).await?;
+                *self.jwks.write().await = Some(jwks.clone());
+                jwks
+            }
+        };
+        Ok(match kid {
+            Some(kid) => jwks.find(kid).cloned(),
+            None if jwks.keys.len() == 1 => jwks.keys.first().cloned(),
+            None => None,
+        })
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T, ServiceError> {
+        let resp = self
+            .http
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| provider_error(&format!("请求 {url} 失败: {e}")))?;
+        if !resp.status().is_success() {
+            return Err(provider_error(&format!(
+                "请求 {url} 返回 {}",
+                resp.status()
+            )));
+        }
+        resp.json()
+            .await
+            .map_err(|e| provider_error(&format!("{url} 响应无法解析: {e}")))
+    }
+}
+
+/// 生成符合本地用户名规则的候选名：3-32 位字母、数字或 `_` `.` `-`，以字母或数字开头
+fn username_base(claims: &IdTokenClaims) -> String {
+    let raw = claims
+        .preferred_username
+        .as_deref()
+        .or_else(|| claims.email.as_deref().and_then(|e| e.split('@').next()))
+        .unwrap_or("");
+    let cleaned: String = raw
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+        .skip_while(|c| !c.is_ascii_alphanumeric())
+        .take(32)
+        .collect();
+    if cleaned.len() >= 3 {
+        cleaned
+    } else {
+        format!("user-{:08x}", rand::thread_rng().next_u32())
+    }
+}
+
+fn random_token() -> String {
+    let mut raw = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut raw);
+    URL_SAFE_NO_PAD.encode(raw)
+}
+
+fn invalid_state() -> ServiceError {
+    ServiceError {
+        code: 400,
+        message: "登录状态无效或已过期，请重新发起登录".to_string(),
+    }
+}
+
+fn provider_error(message: &str) -> ServiceError {
+    tracing::warn!(error = %message, "oidc provider error");
+    ServiceError {
+        code: 502,
+        message: message.to_string(),
+    }
+}
+
+fn now_timestamp() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
+
+fn internal_error(message: String) -> ServiceError {
+    ServiceError { code: 500, message }
+}
@@ -0,0 +1,173 @@
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use uuid::Uuid;
+
+use crate::dao::idl::SessionDao;
+use crate::model::session::{Session, SessionView};
+use crate::service::common::ServiceError;
+
+/// 同一会话的最后活跃时间最多每分钟写一次库
+const TOUCH_INTERVAL_SECONDS: i64 = 60;
+
+pub struct SessionService {
+    dao: Arc<dyn SessionDao + Send + Sync>,
+    ttl_seconds: i64,
+}
+
+/// 创建会话时记录的客户端信息
+#[derive(Debug, Clone, Default)]
+pub struct ClientMeta {
+    pub ip: String,
+    pub user_agent: String,
+}
+
+impl SessionService {
+    pub fn new(dao: Arc<dyn SessionDao + Send + Sync>, ttl_seconds: u64) -> Self {
+        Self {
+            dao,
+            ttl_seconds: ttl_seconds as i64,
+        }
+    }
+
+    pub async fn create(&self, uid: i32, client: &ClientMeta) -> Result<Session, ServiceError> {
+        let now = now_timestamp();
+        let session = Session {
+            id: Uuid::new_v4().to_string(),
+            uid,
+            user_agent: client.user_agent.clone(),
+            device: describe_device(&client.user_agent),
+            ip: client.ip.clone(),
+            created_at: now,
+            last_seen_at: now,
+            expires_at: now + self.ttl_seconds,
+            revoked_at: None,
+        };
+        self.dao
+            .create_session(session.clone())
+            .await
+            .map_err(internal_error)?;
+        Ok(session)
+    }
+
+    /// 校验 token 对应的会话仍然有效，并顺带刷新最后活跃时间
+    pub async fn validate(&self, uid: i32, id: &str) -> Result<Session, ServiceError> {
+        let now = now_timestamp();
+        let session = match self.dao.get_session(id).await.map_err(internal_error)? {
+            Some(s) if s.uid == uid && s.revoked_at.is_none() && s.expires_at > now => s,
+            _ => {
+                return Err(ServiceError {
+                    code: 401,
+                    message: "会话已失效，请重新登录".to_string(),
+                });
+            }
+        };
+        if now - session.last_seen_at >= TOUCH_INTERVAL_SECONDS {
+            self.dao
+                .touch_session(id, now, session.expires_at)
+                .await
+                .map_err(internal_error)?;
+        }
+        Ok(session)
+    }
+
+    /// 滑动续期：把会话有效期延长到 now + ttl，返回新的过期时间
+    pub async fn extend(&self, id: &str) -> Result<i64, ServiceError> {
+        let now = now_timestamp();
+        let expires_at = now + self.ttl_seconds;
+        self.dao
+            .touch_session(id, now, expires_at)
+            .await
+            .map_err(internal_error)?;
+        Ok(expires_at)
+    }
+
+    pub async fn list(&self, uid: i32, current: &str) -> Result<Vec<SessionView>, ServiceError> {
+        let sessions = self
+            .dao
+            .list_active_sessions(uid, now_timestamp())
+            .await
+            .map_err(internal_error)?;
+        Ok(sessions
+            .into_iter()
+            .map(|s| SessionView {
+                current: s.id == current,
+                id: s.id,
+                device: s.device,
+                user_agent: s.user_agent,
+                ip: s.ip,
+                created_at: s.created_at,
+                last_seen_at: s.last_seen_at,
+                expires_at: s.expires_at,
+            })
+            .collect())
+    }
+
+    pub async fn revoke(&self, uid: i32, id: &str) -> Result<(), ServiceError> {
+        if self
+            .dao
+            .revoke_session(uid, id, now_timestamp())
+            .await
+            .map_err(internal_error)?
+        {
+            Ok(())
+        } else {
+            Err(ServiceError {
+                code: 404,
+                message: "会话不存在".to_string(),
+            })
+        }
+    }
+
+    pub async fn revoke_all(&self, uid: i32, except: Option<&str>) -> Result<u64, ServiceError> {
+        self.dao
+            .revoke_all_sessions(uid, except, now_timestamp())
+            .await
+            .map_err(internal_error)
+    }
+}
+
+/// 从 User-Agent 粗略识别浏览器与系统，例如 `Chrome on macOS`
+pub fn describe_device(user_agent: &str) -> String {
+    let os = [
+        ("iPhone", "iOS"),
+        ("iPad", "iPadOS"),
+        ("Android", "Android"),
+        ("Windows", "Windows"),
+        ("Mac OS X", "macOS"),
+        ("Macintosh", "macOS"),
+        ("Linux", "Linux"),
+    ]
+    .iter()
+    .find(|(needle, _)| user_agent.contains(needle))
+    .map(|(_, name)| *name);
+    // 顺序有意义：Edge/Opera 的 UA 同时包含 Chrome，Chrome 的 UA 同时包含 Safari
+    let browser = [
+        ("Edg/", "Edge"),
+        ("OPR/", "Opera"),
+        ("Firefox/", "Firefox"),
+        ("Chrome/", "Chrome"),
+        ("Safari/", "Safari"),
+        ("curl/", "curl"),
+    ]
+    .iter()
+    .find(|(needle, _)| user_agent.contains(needle))
+    .map(|(_, name)| *name);
+    match (browser, os) {
+        (Some(b), Some(o)) => format!("{b} on {o}"),
+        (Some(b), None) => b.to_string(),
+        (None, Some(o)) => o.to_string(),
+        (None, None) => "Unknown".to_string(),
+    }
+}
+
+fn now_timestamp() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
+
+fn internal_error(message: String) -> ServiceError {
+    ServiceError { code: 500, message }
+}
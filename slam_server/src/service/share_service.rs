@@ -0,0 +1,176 @@
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::app::routes;
+use crate::dao::idl::{ShareDao, SportDao, UserDao};
+use crate::model::share::{CreatedSportShare, SharedSport, SportShare, SportShareView};
+use crate::service::common::ServiceError;
+use crate::service::social_service::user_card;
+
+const MAX_EXPIRES_IN_DAYS: i64 = 365;
+
+pub struct ShareService {
+    dao: Arc<dyn ShareDao + Send + Sync>,
+    sports: Arc<dyn SportDao + Send + Sync>,
+    users: Arc<dyn UserDao + Send + Sync>,
+}
+
+impl ShareService {
+    pub fn new(
+        dao: Arc<dyn ShareDao + Send + Sync>,
+        sports: Arc<dyn SportDao + Send + Sync>,
+        users: Arc<dyn UserDao + Send + Sync>,
+    ) -> Self {
+        Self { dao, sports, users }
+    }
+
+    /// 为自己的运动记录创建分享链接，expires_in_days 为空时长期有效
+    pub async fn create(
+        &self,
+        uid: i32,
+        sport_id: i32,
+        expires_in_days: Option<i64>,
+    ) -> Result<CreatedSportShare, ServiceError> {
+        if let Some(days) = expires_in_days
+            && !(1..=MAX_EXPIRES_IN_DAYS).contains(&days)
+        {
+            return Err(bad_request("有效期需在 1 到 365 天之间"));
+        }
+        self.sports
+            .get_by_id(uid, sport_id)
+            .await
+            .map_err(internal_error)?
+            .ok_or_else(|| not_found("运动记录不存在"))?;
+        let now = now_timestamp();
+        let mut secret = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut secret);
+        let token = URL_SAFE_NO_PAD.encode(secret);
+        let share = SportShare {
+            id: Uuid::new_v4().to_string(),
+            uid,
+            sport_id,
+            token_hash: hash_token(&token),
+            created_at: now,
+            expires_at: expires_in_days.map(|days| now + days * 86400),
+            revoked_at: None,
+            views: 0,
+        };
+        self.dao
+            .create_share(share.clone())
+            .await
+            .map_err(internal_error)?;
+        Ok(CreatedSportShare {
+            url: routes::share_url(&token),
+            card_url: routes::share_card_url(&token),
+            token,
+            info: share.into(),
+        })
+    }
+
+    pub async fn list(
+        &self,
+        uid: i32,
+        sport_id: Option<i32>,
+    ) -> Result<Vec<SportShareView>, ServiceError> {
+        let shares = self
+            .dao
+            .list_shares(uid, sport_id)
+            .await
+            .map_err(internal_error)?;
+        Ok(shares.into_iter().map(SportShareView::from).collect())
+    }
+
+    pub async fn revoke(&self, uid: i32, id: &str) -> Result<(), ServiceError> {
+        if self
+            .dao
+            .revoke_share(uid, id, now_timestamp())
+            .await
+            .map_err(internal_error)?
+        {
+            Ok(())
+        } else {
+            Err(not_found("分享链接不存在"))
+        }
+    }
+
+    /// 通过分享链接查看运动记录，无需登录；count_view 为 true 时计入浏览次数
+    pub async fn resolve(
+        &self,
+        token: &str,
+        count_view: bool,
+    ) -> Result<SharedSport, ServiceError> {
+        let gone = || not_found("分享链接不存在或已失效");
+        let share = match self
+            .dao
+            .get_share_by_hash(&hash_token(token))
+            .await
+            .map_err(internal_error)?
+        {
+            Some(s) if s.is_active(now_timestamp()) => s,
+            _ => return Err(gone()),
+        };
+        let sport = self
+            .sports
+            .get_by_id(share.uid, share.sport_id)
+            .await
+            .map_err(internal_error)?
+            .ok_or_else(gone)?;
+        let user = self
+            .users
+            .get_by_id(share.uid)
+            .await
+            .map_err(internal_error)?
+            .ok_or_else(gone)?;
+        let mut views = share.views;
+        if count_view {
+            self.dao
+                .increment_share_views(&share.id)
+                .await
+                .map_err(internal_error)?;
+            views += 1;
+        }
+        Ok(SharedSport {
+            user: user_card(share.uid, user.nickname, user.avatar_version),
+            sport,
+            views,
+            expires_at: share.expires_at,
+        })
+    }
+}
+
+fn hash_token(plaintext: &str) -> String {
+    Sha256::digest(plaintext.as_bytes())
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+fn now_timestamp() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn bad_request(message: &str) -> ServiceError {
+    ServiceError {
+        code: 400,
+        message: message.to_string(),
+    }
+}
+
+fn not_found(message: &str) -> ServiceError {
+    ServiceError {
+        code: 404,
+        message: message.to_string(),
+    }
+}
+
+fn internal_error(message: String) -> ServiceError {
+    ServiceError { code: 500, message }
+}
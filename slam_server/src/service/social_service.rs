@@ -0,0 +1,340 @@
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::app::routes;
+use crate::dao::idl::{SocialDao, UserDao};
+use crate::model::social::{
+    FOLLOW_ACCEPTED, FOLLOW_PENDING, FeedCursor, FeedItem, FeedPage, FeedRow, Follow, FollowRow,
+    FollowView, SocialProfile, SocialSettings, UserCard, Viewer,
+};
+use crate::service::common::ServiceError;
+
+const DEFAULT_PAGE_SIZE: i32 = 20;
+const MAX_PAGE_SIZE: i32 = 50;
+
+pub struct SocialService {
+    dao: Arc<dyn SocialDao + Send + Sync>,
+    users: Arc<dyn UserDao + Send + Sync>,
+}
+
+impl SocialService {
+    pub fn new(
+        dao: Arc<dyn SocialDao + Send + Sync>,
+        users: Arc<dyn UserDao + Send + Sync>,
+    ) -> Self {
+        Self { dao, users }
+    }
+
+    /// 关注用户，对方是私密账号时生成待批准的申请；返回关注状态
+    pub async fn follow(&self, uid: i32, target: i32) -> Result<String, ServiceError> {
+        if uid == target {
+            return Err(bad_request("不能关注自己"));
+        }
+        let private_account = self.private_account(target).await?;
+        if let Some(existing) = self
+            .dao
+            .get_follow(uid, target)
+            .await
+            .map_err(internal_error)?
+        {
+            return Ok(existing.status);
+        }
+        let now = now_timestamp();
+        let (status, accepted_at) = if private_account {
+            (FOLLOW_PENDING, None)
+        } else {
+            (FOLLOW_ACCEPTED, Some(now))
+        };
+        self.dao
+            .create_follow(Follow {
+                follower_uid: uid,
+                followee_uid: target,
+                status: status.to_string(),
+                created_at: now,
+                accepted_at,
+            })
+            .await
+            .map_err(internal_error)?;
+        Ok(status.to_string())
+    }
+
+    /// 取消关注，也用于撤回尚未批准的申请
+    pub async fn unfollow(&self, uid: i32, target: i32) -> Result<(), ServiceError> {
+        if self
+            .dao
+            .delete_follow(uid, target)
+            .await
+            .map_err(internal_error)?
+        {
+            Ok(())
+        } else {
+            Err(not_found("未关注该用户"))
+        }
+    }
+
+    pub async fn approve(&self, uid: i32, follower: i32) -> Result<(), ServiceError> {
+        if self
+            .dao
+            .accept_follow(follower, uid, now_timestamp())
+            .await
+            .map_err(internal_error)?
+        {
+            Ok(())
+        } else {
+            Err(not_found("关注申请不存在"))
+        }
+    }
+
+    /// 拒绝关注申请或移除粉丝
+    pub async fn remove_follower(&self, uid: i32, follower: i32) -> Result<(), ServiceError> {
+        if self
+            .dao
+            .delete_follow(follower, uid)
+            .await
+            .map_err(internal_error)?
+        {
+            Ok(())
+        } else {
+            Err(not_found("该用户没有关注你"))
+        }
+    }
+
+    pub async fn following(&self, uid: i32) -> Result<Vec<FollowView>, ServiceError> {
+        let rows = self.dao.list_following(uid).await.map_err(internal_error)?;
+        Ok(rows.into_iter().map(follow_view).collect())
+    }
+
+    pub async fn followers(
+        &self,
+        uid: i32,
+        status: Option<&str>,
+    ) -> Result<Vec<FollowView>, ServiceError> {
+        if let Some(s) = status
+            && s != FOLLOW_PENDING
+            && s != FOLLOW_ACCEPTED
+        {
+            return Err(bad_request("status 只能是 pending 或 accepted"));
+        }
+        let rows = self
+            .dao
+            .list_followers(uid, status)
+            .await
+            .map_err(internal_error)?;
+        Ok(rows.into_iter().map(follow_view).collect())
+    }
+
+    pub async fn settings(&self, uid: i32) -> Result<SocialSettings, ServiceError> {
+        Ok(SocialSettings {
+            private_account: self.private_account(uid).await?,
+        })
+    }
+
+    /// 改为公开账号时，所有待处理的申请自动通过
+    pub async fn update_settings(
+        &self,
+        uid: i32,
+        settings: SocialSettings,
+    ) -> Result<SocialSettings, ServiceError> {
+        self.dao
+            .set_private_account(uid, settings.private_account)
+            .await
+            .map_err(internal_error)?;
+        if !settings.private_account {
+            self.dao
+                .accept_all_follows(uid, now_timestamp())
+                .await
+                .map_err(internal_error)?;
+        }
+        Ok(settings)
+    }
+
+    pub async fn profile(&self, viewer: i32, uid: i32) -> Result<SocialProfile, ServiceError> {
+        let user = self
+            .users
+            .get_by_id(uid)
+            .await
+            .map_err(internal_error)?
+            .ok_or_else(|| not_found("用户不存在"))?;
+        let private_account = self.private_account(uid).await?;
+        let (followers, following) = self.dao.count_follows(uid).await.map_err(internal_error)?;
+        let follow_status = if viewer == uid {
+            "none".to_string()
+        } else {
+            self.dao
+                .get_follow(viewer, uid)
+                .await
+                .map_err(internal_error)?
+                .map(|f| f.status)
+                .unwrap_or_else(|| "none".to_string())
+        };
+        Ok(SocialProfile {
+            user: user_card(uid, user.nickname, user.avatar_version),
+            bio: user.bio,
+            private_account,
+            followers,
+            following,
+            follow_status,
+        })
+    }
+
+    /// 按用户名精确查找，便于通过账号名关注
+    pub async fn find_by_name(
+        &self,
+        viewer: i32,
+        name: &str,
+    ) -> Result<SocialProfile, ServiceError> {
+        let user = self
+            .users
+            .get_by_name(name.trim())
+            .await
+            .map_err(internal_error)?
+            .ok_or_else(|| not_found("用户不存在"))?;
+        self.profile(viewer, user.id).await
+    }
+
+    /// 已关注用户最近的运动，游标为上一页返回的 next_cursor
+    pub async fn feed(
+        &self,
+        uid: i32,
+        cursor: Option<&str>,
+        size: Option<i32>,
+    ) -> Result<FeedPage, ServiceError> {
+        let before = parse_cursor(cursor)?;
+        let size = page_size(size);
+        let rows = self
+            .dao
+            .list_feed(uid, before, size + 1)
+            .await
+            .map_err(internal_error)?;
+        Ok(feed_page(rows, size))
+    }
+
+    /// 查看某个用户的运动，按双方关系过滤可见范围
+    pub async fn user_sports(
+        &self,
+        viewer: i32,
+        owner: i32,
+        cursor: Option<&str>,
+        size: Option<i32>,
+    ) -> Result<FeedPage, ServiceError> {
+        let before = parse_cursor(cursor)?;
+        let size = page_size(size);
+        let private_account = self.private_account(owner).await?;
+        let relation = self.viewer(viewer, owner).await?;
+        let rows = self
+            .dao
+            .list_user_sports(
+                owner,
+                relation.visible_levels(private_account),
+                before,
+                size + 1,
+            )
+            .await
+            .map_err(internal_error)?;
+        Ok(feed_page(rows, size))
+    }
+
+    /// 查看者与运动记录主人的关系
+    pub async fn viewer(&self, viewer: i32, owner: i32) -> Result<Viewer, ServiceError> {
+        if viewer == owner {
+            return Ok(Viewer::Owner);
+        }
+        let follow = self
+            .dao
+            .get_follow(viewer, owner)
+            .await
+            .map_err(internal_error)?;
+        Ok(match follow {
+            Some(f) if f.status == FOLLOW_ACCEPTED => Viewer::Follower,
+            _ => Viewer::Stranger,
+        })
+    }
+
+    async fn private_account(&self, uid: i32) -> Result<bool, ServiceError> {
+        self.dao
+            .get_private_account(uid)
+            .await
+            .map_err(internal_error)?
+            .ok_or_else(|| not_found("用户不存在"))
+    }
+}
+
+/// 列表和分享页中的用户概况，头像版本转换为访问地址
+pub(crate) fn user_card(uid: i32, nickname: String, avatar_version: Option<String>) -> UserCard {
+    UserCard {
+        uid,
+        nickname,
+        avatar: avatar_version
+            .map(|v| routes::avatar_url(uid, &v))
+            .unwrap_or_default(),
+    }
+}
+
+fn follow_view(row: FollowRow) -> FollowView {
+    FollowView {
+        user: user_card(row.uid, row.nickname, row.avatar_version),
+        status: row.status,
+        created_at: row.created_at,
+    }
+}
+
+/// 多查一条用于判断是否还有下一页
+fn feed_page(mut rows: Vec<FeedRow>, size: i32) -> FeedPage {
+    let has_more = rows.len() > size as usize;
+    rows.truncate(size as usize);
+    let next_cursor = has_more.then(|| rows.last()).flatten().map(|r| {
+        FeedCursor {
+            start_time: r.sport.start_time,
+            id: r.sport.id,
+        }
+        .encode()
+    });
+    FeedPage {
+        items: rows
+            .into_iter()
+            .map(|r| FeedItem {
+                user: user_card(r.uid, r.nickname, r.avatar_version),
+                sport: r.sport,
+            })
+            .collect(),
+        next_cursor,
+    }
+}
+
+fn parse_cursor(cursor: Option<&str>) -> Result<Option<FeedCursor>, ServiceError> {
+    match cursor.filter(|c| !c.is_empty()) {
+        Some(c) => FeedCursor::decode(c)
+            .map(Some)
+            .ok_or_else(|| bad_request("cursor 无效")),
+        None => Ok(None),
+    }
+}
+
+fn page_size(size: Option<i32>) -> i32 {
+    size.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE)
+}
+
+fn now_timestamp() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn bad_request(message: &str) -> ServiceError {
+    ServiceError {
+        code: 400,
+        message: message.to_string(),
+    }
+}
+
+fn not_found(message: &str) -> ServiceError {
+    ServiceError {
+        code: 404,
+        message: message.to_string(),
+    }
+}
+
+fn internal_error(message: String) -> ServiceError {
+    ServiceError { code: 500, message }
+}
@@ -1,4 +1,5 @@
-use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Utc, Weekday};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Timelike, Utc, Weekday};
+use chrono_tz::Tz;
 use ctx_marco::inject_ctx;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -7,14 +8,23 @@ use utoipa::ToSchema;
 use crate::dao::cache::ResultCache;
 use crate::dao::idl::SportDao;
 use crate::handlers::jwt::Context;
-use crate::model::sport::{Sport, SportExtra, SportType};
+use crate::model::notification::NOTIFY_PERSONAL_RECORD;
+use crate::model::sport::{Sport, SportExtra, SportType, SportVisibility};
+use crate::model::webhook::{EVENT_SPORT_CREATED, EVENT_SPORT_DELETED, EVENT_SPORT_UPDATED};
 use crate::service::ai_job_service::AIJobService;
+use crate::service::athlete_service::AthleteService;
 use crate::service::common::ServiceError;
+use crate::service::notification_service::NotificationService;
+use crate::service::webhook_service::WebhookService;
+use crate::service::year_review::{YearReview, build_year_review, personal_bests};
 
 pub struct SportService {
     dao: Arc<dyn SportDao + Send + Sync>,
     cache_total: Arc<dyn ResultCache<StatSummary, i32> + Send + Sync>,
     cache_year: Arc<dyn ResultCache<StatSummary, String> + Send + Sync>,
+    athlete: Arc<AthleteService>,
+    notifications: Arc<NotificationService>,
+    webhooks: Arc<WebhookService>,
 }
 
 impl SportService {
@@ -22,19 +32,29 @@ impl SportService {
         dao: Arc<dyn SportDao + Send + Sync>,
         cache_total: Arc<dyn ResultCache<StatSummary, i32> + Send + Sync>,
         cache_year: Arc<dyn ResultCache<StatSummary, String> + Send + Sync>,
+        athlete: Arc<AthleteService>,
+        notifications: Arc<NotificationService>,
+        webhooks: Arc<WebhookService>,
     ) -> Self {
         Self {
             dao,
             cache_total,
             cache_year,
+            athlete,
+            notifications,
+            webhooks,
         }
     }
 
     #[inject_ctx]
-    pub async fn insert(&self, sport: Sport) -> Result<(), ServiceError> {
+    pub async fn insert(&self, mut sport: Sport) -> Result<(), ServiceError> {
+        self.athlete
+            .fill_missing_calories(ctx.uid, &mut sport)
+            .await;
         let y = DateTime::from_timestamp(sport.start_time, 0).map(|dt| dt.year());
-        self.dao
-            .insert(ctx.uid, sport)
+        sport.id = self
+            .dao
+            .insert(ctx.uid, sport.clone())
             .await
             .map_err(|e| ServiceError {
                 code: 500,
@@ -45,20 +65,25 @@ impl SportService {
             let key = format!("{}@{}", ctx.uid, year);
             self.cache_year.invalidate(key).await;
         }
+        self.notify_personal_records(ctx.uid, &[sport.id]).await;
+        self.emit(ctx.uid, EVENT_SPORT_CREATED, &sport).await;
         Ok(())
     }
 
     #[inject_ctx]
     pub async fn insert_with_ai_job(
         &self,
-        sport: Sport,
+        mut sport: Sport,
         ai_job_id: Option<String>,
     ) -> Result<i32, ServiceError> {
+        self.athlete
+            .fill_missing_calories(ctx.uid, &mut sport)
+            .await;
         let y = DateTime::from_timestamp(sport.start_time, 0).map(|dt| dt.year());
         let sport_id = if let Some(job_id) = ai_job_id {
             let submission = self
                 .dao
-                .insert_from_ai_job(ctx.uid, sport, &job_id)
+                .insert_from_ai_job(ctx.uid, sport.clone(), &job_id)
                 .await
                 .map_err(|e| ServiceError {
                     code: if e.contains("不存在") {
@@ -82,13 +107,12 @@ impl SportService {
             submission.sport_id
         } else {
             self.dao
-                .insert(ctx.uid, sport)
+                .insert(ctx.uid, sport.clone())
                 .await
                 .map_err(|e| ServiceError {
                     code: 500,
                     message: e,
-                })?;
-            0
+                })?
         };
         self.cache_total.invalidate(ctx.uid).await;
         if let Some(year) = y {
@@ -96,13 +120,16 @@ impl SportService {
                 .invalidate(format!("{}@{}", ctx.uid, year))
                 .await;
         }
+        sport.id = sport_id;
+        self.notify_personal_records(ctx.uid, &[sport_id]).await;
+        self.emit(ctx.uid, EVENT_SPORT_CREATED, &sport).await;
         Ok(sport_id)
     }
 
     #[inject_ctx]
     pub async fn list(&self, page: i32, size: i32) -> Result<Vec<Sport>, ServiceError> {
         self.dao
-            .list(ctx.uid, page, size)
+            .list(ctx.data_uid(), page, size)
             .await
             .map_err(|e| ServiceError {
                 code: 500,
@@ -110,7 +137,25 @@ impl SportService {
             })
     }
     #[inject_ctx]
-    pub async fn update(&self, sport: Sport) -> Result<(), ServiceError> {
+    pub async fn get(&self, id: i32) -> Result<Sport, ServiceError> {
+        match self.dao.get_by_id(ctx.data_uid(), id).await {
+            Ok(Some(s)) => Ok(s),
+            Ok(None) => Err(ServiceError {
+                code: 404,
+                message: "运动记录不存在".to_string(),
+            }),
+            Err(e) => Err(ServiceError {
+                code: 500,
+                message: e,
+            }),
+        }
+    }
+
+    #[inject_ctx]
+    pub async fn update(&self, mut sport: Sport) -> Result<(), ServiceError> {
+        self.athlete
+            .fill_missing_calories(ctx.uid, &mut sport)
+            .await;
         let old = self
             .dao
             .get_by_id(ctx.uid, sport.id)
@@ -121,13 +166,14 @@ impl SportService {
             })?;
         let ny = DateTime::from_timestamp(sport.start_time, 0).map(|dt| dt.year());
         self.dao
-            .update(ctx.uid, sport)
+            .update(ctx.uid, sport.clone())
             .await
             .map_err(|e| ServiceError {
                 code: 500,
                 message: e,
             })?;
         self.cache_total.invalidate(ctx.uid).await;
+        let existed = old.is_some();
         if let Some(o) = old {
             let oy = DateTime::from_timestamp(o.start_time, 0).map(|dt| dt.year());
             if let Some(oyr) = oy {
@@ -139,6 +185,9 @@ impl SportService {
             let key = format!("{}@{}", ctx.uid, nyr);
             self.cache_year.invalidate(key).await;
         }
+        if existed {
+            self.emit(ctx.uid, EVENT_SPORT_UPDATED, &sport).await;
+        }
         Ok(())
     }
 
@@ -149,7 +198,7 @@ impl SportService {
         reader: csv::Reader<R>,
     ) -> Result<usize, ServiceError> {
         let mut r = reader;
-        let sports = parse_sports_from_csv(&vendor, &mut r);
+        let mut sports = parse_sports_from_csv(&vendor, &mut r);
         if sports.is_empty() {
             return Err(ServiceError {
                 code: 400,
@@ -165,15 +214,18 @@ impl SportService {
                 });
             }
         }
+        for s in sports.iter_mut() {
+            self.athlete.fill_missing_calories(ctx.uid, s).await;
+        }
         let mut years: std::collections::HashSet<i32> = std::collections::HashSet::new();
         for s in &sports {
             if let Some(y) = DateTime::from_timestamp(s.start_time, 0).map(|dt| dt.year()) {
                 years.insert(y);
             }
         }
-        let inserted = self
+        let ids = self
             .dao
-            .insert_many(ctx.uid, sports)
+            .insert_many(ctx.uid, sports.clone())
             .await
             .map_err(|e| ServiceError {
                 code: 500,
@@ -184,7 +236,12 @@ impl SportService {
             let key = format!("{}@{}", ctx.uid, y);
             self.cache_year.invalidate(key).await;
         }
-        Ok(inserted)
+        self.notify_personal_records(ctx.uid, &ids).await;
+        for (mut sport, id) in sports.into_iter().zip(ids.iter().copied()) {
+            sport.id = id;
+            self.emit(ctx.uid, EVENT_SPORT_CREATED, &sport).await;
+        }
+        Ok(ids.len())
     }
 
     #[inject_ctx]
@@ -210,6 +267,13 @@ impl SportService {
                 let key = format!("{}@{}", ctx.uid, y);
                 self.cache_year.invalidate(key).await;
             }
+            self.webhooks
+                .emit(
+                    ctx.uid,
+                    EVENT_SPORT_DELETED,
+                    serde_json::json!({ "id": id }),
+                )
+                .await;
         }
         Ok(())
     }
@@ -217,12 +281,12 @@ impl SportService {
     #[inject_ctx]
     pub async fn stats(&self, spec: StatsParam) -> Result<StatSummary, ServiceError> {
         if let StatKind::Total = spec.kind {
-            if let Some(cached) = self.cache_total.get(ctx.uid).await {
+            if let Some(cached) = self.cache_total.get(ctx.data_uid()).await {
                 return Ok(cached);
             }
         }
         if let StatKind::Year = spec.kind {
-            let key = format!("{}@{}", ctx.uid, spec.year);
+            let key = format!("{}@{}", ctx.data_uid(), spec.year);
             if let Some(cached) = self.cache_year.get(key.clone()).await {
                 return Ok(cached);
             }
@@ -270,7 +334,7 @@ impl SportService {
         };
         let sports = self
             .dao
-            .list_by_time_range(ctx.uid, start_time, end_time)
+            .list_by_time_range(ctx.data_uid(), start_time, end_time)
             .await
             .map_err(|e| ServiceError {
                 code: 500,
@@ -281,13 +345,13 @@ impl SportService {
         let total_duration_second: i32 = sports.iter().map(|s| s.duration_second).sum();
         let total_distance_meter: i32 = sports.iter().map(|s| s.distance_meter).sum();
         let buckets = match spec.kind {
-            StatKind::Year => group_by_month(sports.clone()),
+            StatKind::Year => group_by_month(sports.clone(), Tz::UTC),
             StatKind::Month => group_by_month_day(sports.clone()),
-            StatKind::Week => group_by_week_day(sports.clone()),
+            StatKind::Week => group_by_week_day(sports.clone(), Tz::UTC),
             StatKind::Total => Vec::new(),
         };
         let earliest_year = match spec.kind {
-            StatKind::Year => match self.dao.get_first(ctx.uid).await {
+            StatKind::Year => match self.dao.get_first(ctx.data_uid()).await {
                 Ok(Some(first)) => {
                     DateTime::from_timestamp(first.start_time, 0).map(|dt| dt.year())
                 }
@@ -312,10 +376,10 @@ impl SportService {
             earliest_year,
         };
         if let StatKind::Total = spec.kind {
-            self.cache_total.set(ctx.uid, summary.clone()).await;
+            self.cache_total.set(ctx.data_uid(), summary.clone()).await;
         }
         if let StatKind::Year = spec.kind {
-            let key = format!("{}@{}", ctx.uid, spec.year);
+            let key = format!("{}@{}", ctx.data_uid(), spec.year);
             self.cache_year.set(key.clone(), summary.clone()).await;
         }
         Ok(summary)
@@ -335,7 +399,180 @@ impl SportService {
                 code: 500,
                 message: e,
             })?;
-        Ok(group_by_month(items))
+        Ok(group_by_month(items, Tz::UTC))
+    }
+
+    /// 年度回顾：按用户时区划分年份、月份、星期和自然日，并与上一年对比
+    #[inject_ctx]
+    pub async fn year_review(&self, year: i32, tz: &str) -> Result<YearReview, ServiceError> {
+        let tz = parse_tz(tz)?;
+        let current = self.year_summary(year, tz, ctx).await?;
+        let previous = match current.earliest_year {
+            Some(earliest) if earliest < year => Some(self.year_summary(year - 1, tz, ctx).await?),
+            _ => None,
+        };
+        let (_, year_end) = local_year_range(tz, year)?;
+        let history = self
+            .dao
+            .list_by_time_range(ctx.data_uid(), 0, year_end)
+            .await
+            .map_err(|e| ServiceError {
+                code: 500,
+                message: e,
+            })?;
+        Ok(build_year_review(
+            year,
+            tz,
+            &current,
+            previous.as_ref(),
+            &history,
+        ))
+    }
+
+    /// 本地时区的年度统计；UTC 直接复用带缓存的年度统计
+    #[inject_ctx]
+    async fn year_summary(&self, year: i32, tz: Tz) -> Result<StatSummary, ServiceError> {
+        if tz == Tz::UTC {
+            let spec = StatsParam {
+                kind: StatKind::Year,
+                year,
+                month: None,
+                week: None,
+            };
+            return self.stats(spec, ctx).await;
+        }
+        let (start_time, end_time) = local_year_range(tz, year)?;
+        let sports = self
+            .dao
+            .list_by_time_range(ctx.data_uid(), start_time, end_time)
+            .await
+            .map_err(|e| ServiceError {
+                code: 500,
+                message: e,
+            })?;
+        let earliest_year = match self.dao.get_first(ctx.data_uid()).await {
+            Ok(Some(first)) => {
+                DateTime::from_timestamp(first.start_time, 0).map(|dt| dt.with_timezone(&tz).year())
+            }
+            _ => None,
+        };
+        Ok(StatSummary {
+            buckets: group_by_month(sports.clone(), tz),
+            type_buckets: group_by_type(sports.clone()),
+            total_count: sports.len() as i32,
+            total_calories: sports.iter().map(|s| s.calories).sum(),
+            total_duration_second: sports.iter().map(|s| s.duration_second).sum(),
+            total_distance_meter: sports.iter().map(|s| s.distance_meter).sum(),
+            sports,
+            earliest_year,
+        })
+    }
+
+    /// 按用户时区统计 星期×小时 的 7×24 热力矩阵
+    #[inject_ctx]
+    pub async fn heatmap_hourly(&self, spec: HeatmapParam) -> Result<HourlyHeatmap, ServiceError> {
+        let tz = parse_tz(&spec.tz)?;
+        let start_time = spec.start_time.unwrap_or(0);
+        let end_time = spec.end_time.unwrap_or(i64::MAX);
+        if start_time > end_time {
+            return Err(ServiceError {
+                code: 400,
+                message: "invalid time range".to_string(),
+            });
+        }
+        let sports = self
+            .dao
+            .list_by_time_range(ctx.data_uid(), start_time, end_time)
+            .await
+            .map_err(|e| ServiceError {
+                code: 500,
+                message: e,
+            })?;
+        let sports = filter_by_type(sports, spec.sport_type);
+        Ok(HourlyHeatmap {
+            tz: tz.name().to_string(),
+            cells: group_by_week_day_hour(sports, tz),
+        })
+    }
+
+    /// 按用户时区统计某一年每天的运动量（类似 GitHub 贡献图）
+    #[inject_ctx]
+    pub async fn heatmap_calendar(
+        &self,
+        year: i32,
+        spec: HeatmapParam,
+    ) -> Result<CalendarHeatmap, ServiceError> {
+        let tz = parse_tz(&spec.tz)?;
+        let (start_time, end_time) = local_year_range(tz, year)?;
+        let sports = self
+            .dao
+            .list_by_time_range(ctx.data_uid(), start_time, end_time)
+            .await
+            .map_err(|e| ServiceError {
+                code: 500,
+                message: e,
+            })?;
+        let sports = filter_by_type(sports, spec.sport_type);
+        Ok(CalendarHeatmap {
+            year,
+            tz: tz.name().to_string(),
+            days: group_by_calendar_day(sports, tz, year),
+        })
+    }
+
+    /// 新增的运动刷新个人最佳时通知本人，与新增之前保持的纪录比较；
+    /// 查询失败只记录告警，不影响新增运动
+    async fn notify_personal_records(&self, uid: i32, new_ids: &[i32]) {
+        let history = match self.dao.list_by_time_range(uid, 0, i64::MAX).await {
+            Ok(v) => v,
+            Err(e) => {
+                tracing::warn!(uid, error = %e, "failed to load sports for personal records");
+                return;
+            }
+        };
+        let old: Vec<Sport> = history
+            .iter()
+            .filter(|s| !new_ids.contains(&s.id))
+            .cloned()
+            .collect();
+        if old.len() == history.len() {
+            return;
+        }
+        let previous = personal_bests(&old);
+        for best in personal_bests(&history)
+            .into_iter()
+            .filter(|b| new_ids.contains(&b.sport.id))
+        {
+            let Some(before) = previous
+                .iter()
+                .find(|p| p.r#type == best.r#type && p.metric == best.metric)
+            else {
+                continue;
+            };
+            self.notifications
+                .notify(
+                    uid,
+                    NOTIFY_PERSONAL_RECORD,
+                    None,
+                    Some(best.sport.id),
+                    serde_json::json!({
+                        "type": best.r#type,
+                        "metric": best.metric,
+                        "value": best.value,
+                        "previous_value": before.value,
+                    }),
+                )
+                .await;
+        }
+    }
+
+    async fn emit(&self, uid: i32, event: &str, sport: &Sport) {
+        match serde_json::to_value(sport) {
+            Ok(data) => self.webhooks.emit(uid, event, data).await,
+            Err(e) => {
+                tracing::warn!(uid, event, error = %e, "failed to serialize sport for webhook")
+            }
+        }
     }
 }
 
@@ -375,23 +612,170 @@ pub struct StatSummary {
     pub earliest_year: Option<i32>,
 }
 
-fn group_by_month(items: Vec<Sport>) -> Vec<StatBucket> {
-    group_by_key(items, |dt| dt.month())
+#[derive(Debug, Clone)]
+pub struct HeatmapParam {
+    pub start_time: Option<i64>,
+    pub end_time: Option<i64>,
+    pub sport_type: Option<SportType>,
+    /// IANA 时区名，例如 `Asia/Shanghai`
+    pub tz: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone, Default)]
+pub struct HeatCell {
+    pub count: i32,
+    pub duration: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone)]
+pub struct HourlyHeatmap {
+    pub tz: String,
+    /// 7 行（周一到周日）× 24 列（0-23 点）
+    pub cells: Vec<Vec<HeatCell>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone)]
+pub struct CalendarDay {
+    /// 本地日期，格式 `YYYY-MM-DD`
+    pub date: String,
+    pub count: i32,
+    pub duration: i32,
+    pub calories: i32,
+    pub distance_meter: i32,
+    /// 0 表示无运动，1-4 按当年有运动日的时长四分位划分
+    pub level: u8,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone)]
+pub struct CalendarHeatmap {
+    pub year: i32,
+    pub tz: String,
+    pub days: Vec<CalendarDay>,
+}
+
+fn parse_tz(name: &str) -> Result<Tz, ServiceError> {
+    if name.trim().is_empty() {
+        return Ok(Tz::UTC);
+    }
+    name.trim().parse::<Tz>().map_err(|_| ServiceError {
+        code: 400,
+        message: format!("invalid tz: {}", name),
+    })
+}
+
+fn local_midnight(tz: Tz, date: NaiveDate) -> Option<i64> {
+    tz.from_local_datetime(&date.and_hms_opt(0, 0, 0)?)
+        .earliest()
+        .map(|dt| dt.timestamp())
+}
+
+/// 本地时区某一年的起止时间戳（含两端）
+fn local_year_range(tz: Tz, year: i32) -> Result<(i64, i64), ServiceError> {
+    let invalid_year = || ServiceError {
+        code: 400,
+        message: "invalid year".to_string(),
+    };
+    let first_day = NaiveDate::from_ymd_opt(year, 1, 1).ok_or_else(invalid_year)?;
+    let next_year = NaiveDate::from_ymd_opt(year + 1, 1, 1).ok_or_else(invalid_year)?;
+    let start_time = local_midnight(tz, first_day).ok_or_else(invalid_year)?;
+    let end_time = local_midnight(tz, next_year).ok_or_else(invalid_year)? - 1;
+    Ok((start_time, end_time))
+}
+
+fn filter_by_type(items: Vec<Sport>, sport_type: Option<SportType>) -> Vec<Sport> {
+    match sport_type {
+        Some(t) => items.into_iter().filter(|s| s.r#type == t).collect(),
+        None => items,
+    }
+}
+
+fn group_by_week_day_hour(items: Vec<Sport>, tz: Tz) -> Vec<Vec<HeatCell>> {
+    let mut cells = vec![vec![HeatCell::default(); 24]; 7];
+    for sport in items.into_iter() {
+        let Some(dt) = DateTime::from_timestamp(sport.start_time, 0) else {
+            continue;
+        };
+        let local = dt.with_timezone(&tz);
+        let cell =
+            &mut cells[local.weekday().num_days_from_monday() as usize][local.hour() as usize];
+        cell.count += 1;
+        cell.duration += sport.duration_second;
+    }
+    cells
+}
+
+fn group_by_calendar_day(items: Vec<Sport>, tz: Tz, year: i32) -> Vec<CalendarDay> {
+    let mut acc: std::collections::HashMap<NaiveDate, CalendarDay> =
+        std::collections::HashMap::new();
+    for sport in items.into_iter() {
+        let Some(dt) = DateTime::from_timestamp(sport.start_time, 0) else {
+            continue;
+        };
+        let date = dt.with_timezone(&tz).date_naive();
+        let entry = acc.entry(date).or_insert(CalendarDay {
+            date: date.format("%Y-%m-%d").to_string(),
+            count: 0,
+            duration: 0,
+            calories: 0,
+            distance_meter: 0,
+            level: 0,
+        });
+        entry.count += 1;
+        entry.duration += sport.duration_second;
+        entry.calories += sport.calories;
+        entry.distance_meter += sport.distance_meter;
+    }
+    let mut active: Vec<i32> = acc.values().map(|d| d.duration.max(1)).collect();
+    active.sort_unstable();
+    let quartile =
+        |q: usize| active.get((active.len() * q / 4).min(active.len().saturating_sub(1)));
+    let thresholds = [quartile(1), quartile(2), quartile(3)];
+
+    let mut days = Vec::with_capacity(366);
+    let mut date = NaiveDate::from_ymd_opt(year, 1, 1).expect("valid year");
+    while date.year() == year {
+        let day = match acc.remove(&date) {
+            Some(mut d) => {
+                let duration = d.duration.max(1);
+                d.level = 1 + thresholds
+                    .iter()
+                    .filter(|t| t.is_some_and(|t| duration > *t))
+                    .count() as u8;
+                d
+            }
+            None => CalendarDay {
+                date: date.format("%Y-%m-%d").to_string(),
+                count: 0,
+                duration: 0,
+                calories: 0,
+                distance_meter: 0,
+                level: 0,
+            },
+        };
+        days.push(day);
+        date = date.succ_opt().expect("date overflow");
+    }
+    days
+}
+
+fn group_by_month(items: Vec<Sport>, tz: Tz) -> Vec<StatBucket> {
+    group_by_key(items, tz, |dt| dt.month())
 }
 
 fn group_by_month_day(items: Vec<Sport>) -> Vec<StatBucket> {
-    group_by_key(items, |dt| dt.day())
+    group_by_key(items, Tz::UTC, |dt| dt.day())
 }
 
-fn group_by_week_day(items: Vec<Sport>) -> Vec<StatBucket> {
-    group_by_key(items, |dt| dt.weekday().num_days_from_monday() + 1)
+pub(crate) fn group_by_week_day(items: Vec<Sport>, tz: Tz) -> Vec<StatBucket> {
+    group_by_key(items, tz, |dt| dt.weekday().num_days_from_monday() + 1)
 }
 
-fn group_by_key(items: Vec<Sport>, key: impl Fn(&DateTime<Utc>) -> u32) -> Vec<StatBucket> {
+/// 按 `tz` 的本地时间取分组键
+fn group_by_key(items: Vec<Sport>, tz: Tz, key: impl Fn(&DateTime<Tz>) -> u32) -> Vec<StatBucket> {
     let mut acc: std::collections::HashMap<u32, StatBucket> = std::collections::HashMap::new();
     for sport in items.into_iter() {
         let dt = DateTime::from_timestamp(sport.start_time, 0).expect("invalid timestamp");
-        let k = key(&dt);
+        let k = key(&dt.with_timezone(&tz));
         let entry = acc.entry(k).or_insert(StatBucket {
             date: k as i32,
             duration: 0,
@@ -519,6 +903,7 @@ impl VendorFileParser for XiaomiParser {
                 r#type: SportType::Swimming,
                 start_time,
                 calories,
+                calories_estimated: false,
                 distance_meter,
                 duration_second,
                 heart_rate_avg: 0,
@@ -531,6 +916,7 @@ impl VendorFileParser for XiaomiParser {
                     lane_length_meter: None,
                 })),
                 tracks: vec![],
+                visibility: SportVisibility::Private,
             };
             res.push(sport);
         }
@@ -0,0 +1,348 @@
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+use crate::dao::idl::{TwoFactorDao, UserDao};
+use crate::model::two_factor::{
+    LoginChallenge, LoginChallengeView, RecoveryCodes, TotpSecret, TotpSetup, TwoFactorStatus,
+};
+use crate::service::common::ServiceError;
+use crate::service::totp;
+use crate::service::user_service::hash_reset_code;
+
+/// 挑战令牌有效期，需在此时间内完成第二步验证
+const CHALLENGE_TTL_SECONDS: i64 = 300;
+/// 同一挑战令牌最多尝试的验证码次数
+const MAX_CHALLENGE_ATTEMPTS: i64 = 5;
+const RECOVERY_CODE_COUNT: usize = 10;
+const SECRET_BYTES: usize = 20;
+
+pub const METHOD_TOTP: &str = "totp";
+pub const METHOD_RECOVERY_CODE: &str = "recovery_code";
+
+/// TOTP 两步验证：绑定、恢复码与两步登录
+pub struct TwoFactorService {
+    dao: Arc<dyn TwoFactorDao + Send + Sync>,
+    users: Arc<dyn UserDao + Send + Sync>,
+}
+
+impl TwoFactorService {
+    pub fn new(
+        dao: Arc<dyn TwoFactorDao + Send + Sync>,
+        users: Arc<dyn UserDao + Send + Sync>,
+    ) -> Self {
+        Self { dao, users }
+    }
+
+    pub async fn status(&self, uid: i32) -> Result<TwoFactorStatus, ServiceError> {
+        let enabled = self.enabled_secret(uid).await?.is_some();
+        let recovery_codes_remaining = if enabled {
+            self.dao
+                .count_recovery_codes(uid)
+                .await
+                .map_err(internal_error)?
+        } else {
+            0
+        };
+        Ok(TwoFactorStatus {
+            enabled,
+            recovery_codes_remaining,
+        })
+    }
+
+    pub async fn is_enabled(&self, uid: i32) -> Result<bool, ServiceError> {
+        Ok(self.enabled_secret(uid).await?.is_some())
+    }
+
+    /// 生成新密钥，验证通过前不生效；重复调用会替换尚未启用的密钥
+    pub async fn begin_setup(&self, uid: i32) -> Result<TotpSetup, ServiceError> {
+        let account = match self.users.get_account(uid).await.map_err(internal_error)? {
+            Some(u) => u,
+            None => {
+                return Err(ServiceError {
+                    code: 404,
+                    message: "用户不存在".to_string(),
+                });
+            }
+        };
+        let mut raw = [0u8; SECRET_BYTES];
+        rand::thread_rng().fill_bytes(&mut raw);
+        let secret = totp::base32_encode(&raw);
+        if !self
+            .dao
+            .save_pending_totp(uid, &secret, now_timestamp())
+            .await
+            .map_err(internal_error)?
+        {
+            return Err(already_enabled());
+        }
+        Ok(TotpSetup {
+            provisioning_uri: totp::provisioning_uri(&account.name, &secret),
+            secret,
+        })
+    }
+
+    /// 用认证器中的验证码确认绑定，返回一组新的恢复码
+    pub async fn enable(&self, uid: i32, code: &str) -> Result<RecoveryCodes, ServiceError> {
+        let secret = match self.dao.get_totp(uid).await.map_err(internal_error)? {
+            Some(s) if s.enabled_at.is_some() => return Err(already_enabled()),
+            Some(s) => s,
+            None => {
+                return Err(ServiceError {
+                    code: 400,
+                    message: "请先生成两步验证密钥".to_string(),
+                });
+            }
+        };
+        if !self.check_totp(&secret, code).await? {
+            return Err(wrong_code(403));
+        }
+        let (codes, hashes) = generate_recovery_codes();
+        self.dao
+            .enable_totp(uid, &hashes, now_timestamp())
+            .await
+            .map_err(internal_error)?;
+        tracing::info!(uid, "two-factor authentication enabled");
+        Ok(RecoveryCodes {
+            recovery_codes: codes,
+        })
+    }
+
+    /// 关闭两步验证，需要验证码或恢复码
+    pub async fn disable(&self, uid: i32, code: &str) -> Result<(), ServiceError> {
+        let secret = self.require_enabled(uid).await?;
+        if self.check_second_factor(&secret, code).await?.is_none() {
+            return Err(wrong_code(403));
+        }
+        self.dao.delete_totp(uid).await.map_err(internal_error)?;
+        tracing::info!(uid, "two-factor authentication disabled");
+        Ok(())
+    }
+
+    /// 用验证码或恢复码确认敏感操作，供没有本地密码的账号使用
+    pub async fn verify(&self, uid: i32, code: &str) -> Result<(), ServiceError> {
+        let secret = self.require_enabled(uid).await?;
+        if self.check_second_factor(&secret, code).await?.is_none() {
+            return Err(wrong_code(403));
+        }
+        Ok(())
+    }
+
+    /// 作废旧恢复码并生成新的一组，只接受认证器验证码
+    pub async fn regenerate_recovery_codes(
+        &self,
+        uid: i32,
+        code: &str,
+    ) -> Result<RecoveryCodes, ServiceError> {
+        let secret = self.require_enabled(uid).await?;
+        if !self.check_totp(&secret, code).await? {
+            return Err(wrong_code(403));
+        }
+        let (codes, hashes) = generate_recovery_codes();
+        self.dao
+            .replace_recovery_codes(uid, &hashes, now_timestamp())
+            .await
+            .map_err(internal_error)?;
+        Ok(RecoveryCodes {
+            recovery_codes: codes,
+        })
+    }
+
+    /// 密码校验通过后签发挑战令牌，明文只返回这一次
+    pub async fn create_challenge(&self, uid: i32) -> Result<LoginChallengeView, ServiceError> {
+        let mut raw = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut raw);
+        let token = URL_SAFE_NO_PAD.encode(raw);
+        let now = now_timestamp();
+        let expires_at = now + CHALLENGE_TTL_SECONDS;
+        self.dao
+            .create_challenge(LoginChallenge {
+                token_hash: hash_challenge(&token),
+                uid,
+                created_at: now,
+                expires_at,
+                attempts: 0,
+                used_at: None,
+            })
+            .await
+            .map_err(internal_error)?;
+        Ok(LoginChallengeView {
+            two_factor_required: true,
+            challenge: token,
+            expires_at,
+        })
+    }
+
+    /// 查出挑战令牌对应的账号 (uid, 用户名)，供调用方做登录限流
+    pub async fn pending_login(&self, token: &str) -> Result<(i32, String), ServiceError> {
+        let challenge = self.active_challenge(token).await?;
+        match self
+            .users
+            .get_account(challenge.uid)
+            .await
+            .map_err(internal_error)?
+        {
+            Some(u) => Ok((u.id, u.name)),
+            None => Err(challenge_expired()),
+        }
+    }
+
+    /// 完成第二步验证，返回 uid 与使用的验证方式
+    pub async fn complete_login(
+        &self,
+        token: &str,
+        code: &str,
+    ) -> Result<(i32, &'static str), ServiceError> {
+        let challenge = self.active_challenge(token).await?;
+        let secret = match self.enabled_secret(challenge.uid).await? {
+            Some(s) => s,
+            // 挑战签发后两步验证被关闭，要求重新登录
+            None => return Err(challenge_expired()),
+        };
+        let Some(method) = self.check_second_factor(&secret, code).await? else {
+            self.dao
+                .add_challenge_attempt(&challenge.token_hash)
+                .await
+                .map_err(internal_error)?;
+            return Err(wrong_code(401));
+        };
+        if !self
+            .dao
+            .consume_challenge(&challenge.token_hash, now_timestamp())
+            .await
+            .map_err(internal_error)?
+        {
+            return Err(challenge_expired());
+        }
+        Ok((challenge.uid, method))
+    }
+
+    async fn active_challenge(&self, token: &str) -> Result<LoginChallenge, ServiceError> {
+        match self
+            .dao
+            .get_challenge(&hash_challenge(token))
+            .await
+            .map_err(internal_error)?
+        {
+            Some(c)
+                if c.used_at.is_none()
+                    && c.expires_at > now_timestamp()
+                    && c.attempts < MAX_CHALLENGE_ATTEMPTS =>
+            {
+                Ok(c)
+            }
+            _ => Err(challenge_expired()),
+        }
+    }
+
+    async fn enabled_secret(&self, uid: i32) -> Result<Option<TotpSecret>, ServiceError> {
+        Ok(self
+            .dao
+            .get_totp(uid)
+            .await
+            .map_err(internal_error)?
+            .filter(|s| s.enabled_at.is_some()))
+    }
+
+    async fn require_enabled(&self, uid: i32) -> Result<TotpSecret, ServiceError> {
+        self.enabled_secret(uid).await?.ok_or_else(|| ServiceError {
+            code: 400,
+            message: "两步验证未开启".to_string(),
+        })
+    }
+
+    /// 校验认证器验证码；同一时间步只能使用一次
+    async fn check_totp(&self, secret: &TotpSecret, code: &str) -> Result<bool, ServiceError> {
+        let Some(raw) = totp::base32_decode(&secret.secret) else {
+            return Err(internal_error("两步验证密钥损坏".to_string()));
+        };
+        let Some(step) = totp::verify(&raw, code, now_timestamp() as u64) else {
+            return Ok(false);
+        };
+        self.dao
+            .advance_totp_step(secret.uid, step as i64)
+            .await
+            .map_err(internal_error)
+    }
+
+    /// 先按认证器验证码校验，不是 6 位数字时再按恢复码核销
+    async fn check_second_factor(
+        &self,
+        secret: &TotpSecret,
+        code: &str,
+    ) -> Result<Option<&'static str>, ServiceError> {
+        if self.check_totp(secret, code).await? {
+            return Ok(Some(METHOD_TOTP));
+        }
+        let code = code.trim();
+        if code.len() == totp::DIGITS as usize && code.bytes().all(|b| b.is_ascii_digit()) {
+            return Ok(None);
+        }
+        let consumed = self
+            .dao
+            .consume_recovery_code(secret.uid, &hash_reset_code(code), now_timestamp())
+            .await
+            .map_err(internal_error)?;
+        if consumed {
+            tracing::info!(uid = secret.uid, "recovery code used");
+        }
+        Ok(consumed.then_some(METHOD_RECOVERY_CODE))
+    }
+}
+
+/// 生成恢复码明文与对应哈希，格式 XXXXX-XXXXX；哈希规则与重置码相同，忽略大小写与分隔符
+fn generate_recovery_codes() -> (Vec<String>, Vec<String>) {
+    let mut rng = rand::thread_rng();
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let mut raw = [0u8; 5];
+            rng.fill_bytes(&mut raw);
+            let hex: String = raw.iter().map(|b| format!("{b:02X}")).collect();
+            let code = format!("{}-{}", &hex[..5], &hex[5..]);
+            let hash = hash_reset_code(&code);
+            (code, hash)
+        })
+        .unzip()
+}
+
+fn hash_challenge(token: &str) -> String {
+    Sha256::digest(token.as_bytes())
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+fn already_enabled() -> ServiceError {
+    ServiceError {
+        code: 409,
+        message: "两步验证已开启".to_string(),
+    }
+}
+
+fn wrong_code(code: u32) -> ServiceError {
+    ServiceError {
+        code,
+        message: "验证码错误".to_string(),
+    }
+}
+
+fn challenge_expired() -> ServiceError {
+    ServiceError {
+        code: 401,
+        message: "验证已过期，请重新登录".to_string(),
+    }
+}
+
+fn now_timestamp() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
+
+fn internal_error(message: String) -> ServiceError {
+    ServiceError { code: 500, message }
+}
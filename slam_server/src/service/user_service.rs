@@ -1,27 +1,53 @@
-use crate::config::SecurityConfig;
+use crate::config::{RegistrationMode, SecurityConfig};
 use crate::dao::idl::UserDao;
-use crate::model::user::{User, UserInfo};
+use crate::model::user::{AVATAR_SIZES, AvatarImage, PASSWORD_UNSET, User, UserInfo};
+use crate::service::ai_job_service::AIJobService;
 use crate::service::common::ServiceError;
+use crate::service::image_service::ImageService;
 use aes::Aes256;
+use argon2::Argon2;
+use argon2::password_hash::{
+    PasswordHash, PasswordHasher, PasswordVerifier, SaltString, rand_core::OsRng,
+};
 use base64::{Engine, engine::general_purpose::STANDARD as BASE64_ENGINE};
 use cbc::Encryptor;
 use cipher::{BlockEncryptMut, KeyIvInit, block_padding::Pkcs7};
 use sha2::{Digest, Sha256};
-use std::sync::Arc;
+use std::collections::BTreeMap;
+use std::sync::{Arc, LazyLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+use subtle::ConstantTimeEq;
 
 pub struct UserService {
     dao: Arc<dyn UserDao + Send + Sync>,
     security: SecurityConfig,
+    ai_job_service: Arc<AIJobService>,
+    image_service: Arc<ImageService>,
 }
 
 impl UserService {
-    pub fn new(dao: Arc<dyn UserDao + Send + Sync>, security: SecurityConfig) -> Self {
-        Self { dao, security }
+    pub fn new(
+        dao: Arc<dyn UserDao + Send + Sync>,
+        security: SecurityConfig,
+        ai_job_service: Arc<AIJobService>,
+        image_service: Arc<ImageService>,
+    ) -> Self {
+        Self {
+            dao,
+            security,
+            ai_job_service,
+            image_service,
+        }
     }
 
     fn derive_key_iv(&self) -> ([u8; 32], [u8; 16]) {
+        let legacy_key = self
+            .security
+            .legacy_password_key
+            .as_deref()
+            .unwrap_or(&self.security.key);
         let mut h1 = Sha256::new();
-        h1.update(self.security.key.as_bytes());
+        h1.update(legacy_key.as_bytes());
         let key_bytes = h1.finalize();
         let mut h2 = Sha256::new();
         h2.update(format!("iv:{}", self.security.salt).as_bytes());
@@ -33,7 +59,8 @@ impl UserService {
         (key, iv)
     }
 
-    fn encrypt_password(&self, pwd: &str) -> String {
+    /// 旧版密码存储格式（AES-256-CBC，固定密钥与 IV），仅用于校验尚未迁移的账号
+    fn legacy_encrypt_password(&self, pwd: &str) -> String {
         let (key, iv) = self.derive_key_iv();
         let enc = Encryptor::<Aes256>::new(&key.into(), &iv.into());
         let mut buf = pwd.as_bytes().to_vec();
@@ -46,12 +73,54 @@ impl UserService {
         BASE64_ENGINE.encode(ct)
     }
 
-    pub async fn register(&self, mut user: User) -> Result<i32, ServiceError> {
-        let encrypted = self.encrypt_password(&user.password);
-        user.password = encrypted;
+    /// 注册新用户；调用方需先通过 [`validate_registration`] 校验字段
+    pub async fn register(
+        &self,
+        mut user: User,
+        invite_code: Option<String>,
+    ) -> Result<i32, ServiceError> {
+        if !self.security.password_login {
+            return Err(password_login_disabled());
+        }
+        match self.security.registration_mode {
+            RegistrationMode::Open => {}
+            RegistrationMode::Closed => {
+                return Err(ServiceError {
+                    code: 403,
+                    message: "当前实例未开放注册".to_string(),
+                });
+            }
+            RegistrationMode::Invite => {
+                let code = invite_code.unwrap_or_default();
+                let code = code.trim();
+                if code.is_empty() || !self.security.invite_codes.iter().any(|c| c == code) {
+                    return Err(ServiceError {
+                        code: 403,
+                        message: "邀请码无效".to_string(),
+                    });
+                }
+            }
+        }
+        match self.dao.get_by_name(&user.name).await {
+            Ok(Some(_)) => return Err(name_taken()),
+            Ok(None) => {}
+            Err(e) => {
+                return Err(ServiceError {
+                    code: 500,
+                    message: e,
+                });
+            }
+        }
+        user.password = hash_password(user.password).await?;
+        user.nickname = user.nickname.trim().to_string();
         user.id = 0;
         match self.dao.insert(user.clone()).await {
-            Ok(uid) => Ok(uid),
+            Ok(uid) => {
+                tracing::info!(uid, name = %user.name, "user registered");
+                Ok(uid)
+            }
+            // 并发注册同名账号时由唯一索引兜底
+            Err(e) if e.contains("UNIQUE constraint failed") => Err(name_taken()),
             Err(e) => Err(ServiceError {
                 code: 500,
                 message: e,
@@ -60,12 +129,120 @@ impl UserService {
     }
 
     pub async fn login(&self, name: String, password: String) -> Result<i32, ServiceError> {
-        let encrypted = self.encrypt_password(&password);
-        match self.dao.login(&name, &encrypted).await {
-            Ok(Some(u)) => Ok(u.id),
+        if !self.security.password_login {
+            return Err(password_login_disabled());
+        }
+        let user = match self.dao.get_by_name(&name).await {
+            Ok(Some(u)) => u,
+            Ok(None) => {
+                // 用户不存在时同样跑一次 Argon2，避免通过响应时间探测用户名
+                verify_dummy_password(password).await;
+                return Err(invalid_credentials());
+            }
+            Err(e) => {
+                return Err(ServiceError {
+                    code: 500,
+                    message: e,
+                });
+            }
+        };
+        if !self.check_password(&user, password).await? {
+            return Err(invalid_credentials());
+        }
+        let disabled = self
+            .dao
+            .get_account_status(user.id)
+            .await
+            .map_err(|e| ServiceError {
+                code: 500,
+                message: e,
+            })?
+            .is_some_and(|status| status.disabled_at.is_some());
+        if disabled {
+            return Err(ServiceError {
+                code: 403,
+                message: "账号已被禁用，请联系管理员".to_string(),
+            });
+        }
+        Ok(user.id)
+    }
+
+    /// 使用管理员签发的一次性重置码设置新密码，返回用户 id 供调用方撤销会话
+    pub async fn reset_password(
+        &self,
+        name: &str,
+        code: &str,
+        new_password: String,
+    ) -> Result<i32, ServiceError> {
+        let invalid_code = || ServiceError {
+            code: 403,
+            message: "重置码无效或已过期".to_string(),
+        };
+        let user = match self.dao.get_by_name(name).await {
+            Ok(Some(u)) => u,
+            Ok(None) => return Err(invalid_code()),
+            Err(e) => {
+                return Err(ServiceError {
+                    code: 500,
+                    message: e,
+                });
+            }
+        };
+        if let Some(message) = check_password_strength(&user.name, &new_password) {
+            return Err(ServiceError { code: 400, message });
+        }
+        let consumed = self
+            .dao
+            .consume_reset_code(&hash_reset_code(code), user.id, now_timestamp())
+            .await
+            .map_err(|e| ServiceError {
+                code: 500,
+                message: e,
+            })?;
+        if !consumed {
+            return Err(invalid_code());
+        }
+        let hashed = hash_password(new_password).await?;
+        self.dao
+            .update_password(user.id, &hashed)
+            .await
+            .map_err(|e| ServiceError {
+                code: 500,
+                message: e,
+            })?;
+        tracing::info!(uid = user.id, "password reset with one-time code");
+        Ok(user.id)
+    }
+
+    /// 校验密码；旧版 AES 密文校验通过后升级为 Argon2id 哈希
+    async fn check_password(&self, user: &User, password: String) -> Result<bool, ServiceError> {
+        if user.password == PASSWORD_UNSET {
+            verify_dummy_password(password).await;
+            return Ok(false);
+        }
+        if is_argon2_hash(&user.password) {
+            return verify_password(password, user.password.clone()).await;
+        }
+        let legacy = self.legacy_encrypt_password(&password);
+        if !bool::from(legacy.as_bytes().ct_eq(user.password.as_bytes())) {
+            return Ok(false);
+        }
+        let hashed = hash_password(password).await?;
+        match self.dao.update_password(user.id, &hashed).await {
+            Ok(()) => tracing::info!(uid = user.id, "legacy password upgraded to argon2id"),
+            Err(e) => {
+                tracing::warn!(uid = user.id, error = %e, "failed to upgrade legacy password")
+            }
+        }
+        Ok(true)
+    }
+
+    async fn get_account(&self, uid: i32) -> Result<User, ServiceError> {
+        match self.dao.get_account(uid).await {
+            Ok(Some(u)) => Ok(u),
             Ok(None) => Err(ServiceError {
-                code: 401,
-                message: "用户名或密码错误".to_string(),
+                code: 404,
+                message: "用户不存在".to_string(),
             }),
             Err(e) => Err(ServiceError {
                 code: 500,
@@ -74,6 +251,87 @@ impl UserService {
         }
     }
 
+    pub async fn change_password(
+        &self,
+        uid: i32,
+        old_password: String,
+        new_password: String,
+    ) -> Result<(), ServiceError> {
+        let user = self.get_account(uid).await?;
+        if let Some(message) = check_password_strength(&user.name, &new_password) {
+            return Err(ServiceError { code: 400, message });
+        }
+        if !self.check_password(&user, old_password).await? {
+            return Err(ServiceError {
+                code: 403,
+                message: "原密码错误".to_string(),
+            });
+        }
+        let hashed = hash_password(new_password).await?;
+        self.dao
+            .update_password(uid, &hashed)
+            .await
+            .map_err(|e| ServiceError {
+                code: 500,
+                message: e,
+            })?;
+        tracing::info!(uid, "password changed");
+        Ok(())
+    }
+
+    pub async fn update_profile(
+        &self,
+        uid: i32,
+        nickname: String,
+        bio: String,
+    ) -> Result<(), ServiceError> {
+        let nickname = nickname.trim();
+        if let Some(message) = check_nickname(nickname) {
+            return Err(ServiceError { code: 400, message });
+        }
+        if bio.chars().count() > 200 {
+            return Err(ServiceError {
+                code: 400,
+                message: "简介不超过200个字符".to_string(),
+            });
+        }
+        self.dao
+            .update_profile(uid, nickname, bio.trim())
+            .await
+            .map_err(|e| ServiceError {
+                code: 500,
+                message: e,
+            })
+    }
+
+    /// 敏感操作前再次确认密码
+    pub async fn verify_password(&self, uid: i32, password: String) -> Result<(), ServiceError> {
+        let user = self.get_account(uid).await?;
+        if !self.check_password(&user, password).await? {
+            return Err(ServiceError {
+                code: 403,
+                message: "密码错误".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// 通过 OIDC 自动创建的账号没有本地密码，敏感操作改用其他方式确认身份
+    pub async fn has_password(&self, uid: i32) -> Result<bool, ServiceError> {
+        Ok(self.get_account(uid).await?.password != PASSWORD_UNSET)
+    }
+
+    /// 删除账号及其全部数据，调用方需先确认身份
+    pub async fn delete_account(&self, uid: i32) -> Result<(), ServiceError> {
+        let job_ids = self.dao.delete_user(uid).await.map_err(|e| ServiceError {
+            code: 500,
+            message: e,
+        })?;
+        self.ai_job_service.remove_job_files(&job_ids);
+        tracing::info!(uid, job_count = job_ids.len(), "account deleted");
+        Ok(())
+    }
+
     pub async fn get_user(&self, id: i32) -> Result<UserInfo, ServiceError> {
         match self.dao.get_by_id(id).await {
             Ok(Some(u)) => Ok(u),
@@ -88,13 +346,266 @@ impl UserService {
         }
     }
 
-    pub async fn set_avatar(&self, uid: i32, base64: String) -> Result<(), ServiceError> {
-        match self.dao.set_avatar(uid, base64).await {
-            Ok(()) => Ok(()),
+    /// 生成各尺寸头像并保存，返回新头像的版本号
+    pub async fn set_avatar(&self, uid: i32, image_data: &[u8]) -> Result<String, ServiceError> {
+        let now = now_timestamp();
+        let images: Vec<AvatarImage> = self
+            .image_service
+            .create_avatars(image_data, &AVATAR_SIZES)?
+            .into_iter()
+            .map(|(size, data)| AvatarImage {
+                size,
+                mime: "image/jpeg".to_string(),
+                etag: content_etag(&data),
+                data,
+                updated_at: now,
+            })
+            .collect();
+        let version = images.last().map(|i| i.etag.clone()).unwrap_or_default();
+        self.dao
+            .set_avatar(uid, images)
+            .await
+            .map_err(|e| ServiceError {
+                code: 500,
+                message: e,
+            })?;
+        Ok(version)
+    }
+
+    /// 上传 data URL 或纯 base64 文本形式的头像
+    pub async fn set_avatar_base64(&self, uid: i32, text: &str) -> Result<String, ServiceError> {
+        let bytes = decode_data_url(text).ok_or_else(|| ServiceError {
+            code: 400,
+            message: "头像不是有效的base64图片".to_string(),
+        })?;
+        self.set_avatar(uid, &bytes).await
+    }
+
+    pub async fn get_avatar(&self, uid: i32, size: u32) -> Result<AvatarImage, ServiceError> {
+        match self.dao.get_avatar(uid, size).await {
+            Ok(Some(image)) => Ok(image),
+            Ok(None) => Err(ServiceError {
+                code: 404,
+                message: "头像不存在".to_string(),
+            }),
             Err(e) => Err(ServiceError {
                 code: 500,
                 message: e,
             }),
         }
     }
+
+    /// 把旧版以 base64 文本保存的头像转换为各尺寸图片；无法解码的直接丢弃
+    pub async fn migrate_legacy_avatars(&self) {
+        let legacy = match self.dao.list_legacy_avatars().await {
+            Ok(v) => v,
+            Err(e) => {
+                tracing::error!(error = %e, "failed to list legacy avatars");
+                return;
+            }
+        };
+        let mut migrated = 0;
+        for (uid, text) in legacy {
+            match self.set_avatar_base64(uid, &text).await {
+                Ok(_) => migrated += 1,
+                Err(e) => {
+                    tracing::warn!(uid, error = %e.message, "dropping undecodable legacy avatar");
+                    if let Err(e) = self.dao.clear_legacy_avatar(uid).await {
+                        tracing::error!(uid, error = %e, "failed to clear legacy avatar");
+                    }
+                }
+            }
+        }
+        if migrated > 0 {
+            tracing::info!(count = migrated, "migrated legacy avatars");
+        }
+    }
+}
+
+/// 解析 `data:image/...;base64,` 前缀的 data URL，也接受不带前缀的 base64
+fn decode_data_url(text: &str) -> Option<Vec<u8>> {
+    let text = text.trim();
+    let payload = match text.strip_prefix("data:") {
+        Some(rest) => rest.split_once(";base64,")?.1,
+        None => text,
+    };
+    BASE64_ENGINE.decode(payload).ok()
+}
+
+/// 按图片内容生成 ETag，内容不变时保持不变
+fn content_etag(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .take(8)
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+fn invalid_credentials() -> ServiceError {
+    ServiceError {
+        code: 401,
+        message: "用户名或密码错误".to_string(),
+    }
+}
+
+/// 重置码哈希，忽略大小写与分隔符，方便用户手动输入
+pub fn hash_reset_code(code: &str) -> String {
+    let normalized: String = code
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    Sha256::digest(normalized.as_bytes())
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+fn password_login_disabled() -> ServiceError {
+    ServiceError {
+        code: 403,
+        message: "本实例已关闭用户名密码登录，请使用统一身份认证登录".to_string(),
+    }
+}
+
+fn name_taken() -> ServiceError {
+    ServiceError {
+        code: 409,
+        message: "用户名已被占用".to_string(),
+    }
+}
+
+/// 校验注册字段，返回 字段名 -> 错误信息；为空表示全部通过
+pub fn validate_registration(
+    name: &str,
+    password: &str,
+    nickname: &str,
+) -> BTreeMap<&'static str, String> {
+    let mut errors = BTreeMap::new();
+    if let Some(message) = check_username(name) {
+        errors.insert("name", message);
+    }
+    if let Some(message) = check_password_strength(name, password) {
+        errors.insert("password", message);
+    }
+    if let Some(message) = check_nickname(nickname.trim()) {
+        errors.insert("nickname", message);
+    }
+    errors
+}
+
+/// 用户名：3-32 位字母、数字或 `_` `.` `-`，须以字母或数字开头
+fn check_username(name: &str) -> Option<String> {
+    let len = name.chars().count();
+    if !(3..=32).contains(&len) {
+        return Some("用户名长度需为3-32个字符".to_string());
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+    {
+        return Some("用户名只能包含字母、数字、下划线、点和连字符".to_string());
+    }
+    if !name.starts_with(|c: char| c.is_ascii_alphanumeric()) {
+        return Some("用户名须以字母或数字开头".to_string());
+    }
+    None
+}
+
+/// 密码：8-128 位，至少包含字母、数字、符号中的两类，且不能与用户名相同
+fn check_password_strength(name: &str, password: &str) -> Option<String> {
+    let len = password.chars().count();
+    if !(8..=128).contains(&len) {
+        return Some("密码长度需为8-128个字符".to_string());
+    }
+    let has_letter = password.chars().any(|c| c.is_alphabetic());
+    let has_digit = password.chars().any(|c| c.is_ascii_digit());
+    let has_other = password.chars().any(|c| !c.is_alphanumeric());
+    if [has_letter, has_digit, has_other]
+        .iter()
+        .filter(|b| **b)
+        .count()
+        < 2
+    {
+        return Some("密码需至少包含字母、数字、符号中的两类".to_string());
+    }
+    if password.eq_ignore_ascii_case(name) {
+        return Some("密码不能与用户名相同".to_string());
+    }
+    None
+}
+
+fn check_nickname(nickname: &str) -> Option<String> {
+    if nickname.is_empty() || nickname.chars().count() > 32 {
+        return Some("昵称不能为空且不超过32个字符".to_string());
+    }
+    None
+}
+
+fn now_timestamp() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
+
+fn is_argon2_hash(stored: &str) -> bool {
+    stored.starts_with("$argon2")
+}
+
+/// Argon2id 默认参数，盐和参数都写入 PHC 字符串；哈希计算较重，放到阻塞线程池执行
+async fn hash_password(password: String) -> Result<String, ServiceError> {
+    tokio::task::spawn_blocking(move || {
+        let salt = SaltString::generate(&mut OsRng);
+        Argon2::default()
+            .hash_password(password.as_bytes(), &salt)
+            .map(|h| h.to_string())
+    })
+    .await
+    .map_err(|e| ServiceError {
+        code: 500,
+        message: format!("密码哈希失败: {e}"),
+    })?
+    .map_err(|e| ServiceError {
+        code: 500,
+        message: format!("密码哈希失败: {e}"),
+    })
+}
+
+/// 与真实哈希参数相同的占位哈希，只用于拉平不存在账号的校验耗时
+static DUMMY_PASSWORD_HASH: LazyLock<String> = LazyLock::new(|| {
+    let salt = SaltString::encode_b64(b"slam-dummy-salt").expect("valid dummy salt");
+    Argon2::default()
+        .hash_password(b"slam-dummy-password", &salt)
+        .expect("hash dummy password")
+        .to_string()
+});
+
+async fn verify_dummy_password(password: String) {
+    let _ = tokio::task::spawn_blocking(move || {
+        if let Ok(parsed) = PasswordHash::new(&DUMMY_PASSWORD_HASH) {
+            let _ = Argon2::default().verify_password(password.as_bytes(), &parsed);
+        }
+    })
+    .await;
+}
+
+async fn verify_password(password: String, stored: String) -> Result<bool, ServiceError> {
+    tokio::task::spawn_blocking(move || {
+        let parsed = PasswordHash::new(&stored)?;
+        match Argon2::default().verify_password(password.as_bytes(), &parsed) {
+            Ok(()) => Ok(true),
+            Err(argon2::password_hash::Error::Password) => Ok(false),
+            Err(e) => Err(e),
+        }
+    })
+    .await
+    .map_err(|e| ServiceError {
+        code: 500,
+        message: format!("密码校验失败: {e}"),
+    })?
+    .map_err(|e| ServiceError {
+        code: 500,
+        message: format!("密码校验失败: {e}"),
+    })
 }
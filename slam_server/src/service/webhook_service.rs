@@ -0,0 +1,343 @@
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rand::RngCore;
+use tokio::sync::Notify;
+use uuid::Uuid;
+
+use crate::dao::idl::WebhookDao;
+use crate::model::webhook::{
+    CreatedWebhook, DELIVERY_PENDING, WEBHOOK_EVENTS, Webhook, WebhookDelivery, WebhookView,
+};
+use crate::service::common::ServiceError;
+
+const MAX_WEBHOOKS_PER_USER: usize = 10;
+const MAX_URL_LEN: usize = 2048;
+const SECRET_PREFIX: &str = "whsec_";
+pub(crate) const PRIVATE_TARGET_ERROR: &str = "webhook地址不能指向本机或内网地址";
+
+pub struct WebhookService {
+    dao: Arc<dyn WebhookDao + Send + Sync>,
+    notify: Arc<Notify>,
+    allow_private_targets: bool,
+}
+
+impl WebhookService {
+    pub fn new(dao: Arc<dyn WebhookDao + Send + Sync>, allow_private_targets: bool) -> Self {
+        Self {
+            dao,
+            notify: Arc::new(Notify::new()),
+            allow_private_targets,
+        }
+    }
+
+    pub fn notify(&self) -> Arc<Notify> {
+        self.notify.clone()
+    }
+
+    /// 注册 webhook；all_users 只允许管理员使用，由调用方校验
+    pub async fn create(
+        &self,
+        uid: i32,
+        url: &str,
+        events: &[String],
+        all_users: bool,
+    ) -> Result<CreatedWebhook, ServiceError> {
+        let url = normalize_url(url)?;
+        self.check_target(&url).await.map_err(|e| bad_request(&e))?;
+        let events = normalize_events(events)?;
+        let existing = self.dao.list_webhooks(uid).await.map_err(internal_error)?;
+        if existing.len() >= MAX_WEBHOOKS_PER_USER {
+            return Err(ServiceError {
+                code: 409,
+                message: "webhook数量已达上限".to_string(),
+            });
+        }
+        let mut secret = [0u8; 24];
+        rand::thread_rng().fill_bytes(&mut secret);
+        let secret: String = secret.iter().map(|b| format!("{b:02x}")).collect();
+        let webhook = Webhook {
+            id: Uuid::new_v4().to_string(),
+            uid,
+            url,
+            secret: format!("{SECRET_PREFIX}{secret}"),
+            events,
+            all_users,
+            created_at: now_timestamp(),
+        };
+        self.dao
+            .create_webhook(webhook.clone())
+            .await
+            .map_err(internal_error)?;
+        Ok(CreatedWebhook {
+            secret: webhook.secret.clone(),
+            info: webhook.into(),
+        })
+    }
+
+    pub async fn list(&self, uid: i32) -> Result<Vec<WebhookView>, ServiceError> {
+        let webhooks = self.dao.list_webhooks(uid).await.map_err(internal_error)?;
+        Ok(webhooks.into_iter().map(WebhookView::from).collect())
+    }
+
+    pub async fn delete(&self, uid: i32, id: &str) -> Result<(), ServiceError> {
+        self.own_webhook(uid, id).await?;
+        self.dao.delete_webhook(id).await.map_err(internal_error)
+    }
+
+    /// 投递记录，按创建时间倒序
+    pub async fn deliveries(
+        &self,
+        uid: i32,
+        id: &str,
+        page: i32,
+        size: i32,
+    ) -> Result<Vec<WebhookDelivery>, ServiceError> {
+        self.own_webhook(uid, id).await?;
+        self.dao
+            .list_deliveries(id, page, size)
+            .await
+            .map_err(internal_error)
+    }
+
+    /// 以原请求体重新排队一次投递，原记录保持不变；请求体中的事件 id 不变，接收方可据此去重
+    pub async fn redeliver(
+        &self,
+        uid: i32,
+        id: &str,
+        delivery_id: &str,
+    ) -> Result<WebhookDelivery, ServiceError> {
+        self.own_webhook(uid, id).await?;
+        let original = self
+            .dao
+            .get_delivery(id, delivery_id)
+            .await
+            .map_err(internal_error)?
+            .ok_or_else(|| not_found("投递记录不存在"))?;
+        let delivery = pending_delivery(
+            id,
+            original.uid,
+            original.subject_uid,
+            &original.event,
+            original.payload,
+            now_timestamp(),
+        );
+        self.dao
+            .create_deliveries(vec![delivery.clone()])
+            .await
+            .map_err(internal_error)?;
+        self.notify.notify_one();
+        Ok(delivery)
+    }
+
+    /// 为订阅了该事件的 webhook 排队投递；失败只记录告警，不影响触发事件的业务
+    pub async fn emit(&self, subject_uid: i32, event: &str, data: serde_json::Value) {
+        let webhooks = match self.dao.list_webhooks_for(subject_uid).await {
+            Ok(webhooks) => webhooks,
+            Err(e) => {
+                tracing::warn!(uid = subject_uid, event, error = %e, "failed to load webhooks");
+                return;
+            }
+        };
+        let now = now_timestamp();
+        let payload = serde_json::json!({
+            "id": Uuid::new_v4().to_string(),
+            "event": event,
+            "created_at": now,
+            "uid": subject_uid,
+            "data": data,
+        })
+        .to_string();
+        let deliveries: Vec<WebhookDelivery> = webhooks
+            .iter()
+            .filter(|w| w.subscribes(event))
+            .map(|w| pending_delivery(&w.id, w.uid, subject_uid, event, payload.clone(), now))
+            .collect();
+        if deliveries.is_empty() {
+            return;
+        }
+        if let Err(e) = self.dao.create_deliveries(deliveries).await {
+            tracing::warn!(uid = subject_uid, event, error = %e, "failed to queue webhook deliveries");
+            return;
+        }
+        self.notify.notify_one();
+    }
+
+    pub async fn claim(&self, lease_seconds: i64) -> Result<Option<WebhookDelivery>, String> {
+        let now = now_timestamp();
+        self.dao.claim_next_delivery(now, now + lease_seconds).await
+    }
+
+    pub async fn recover_expired(&self) -> Result<(), String> {
+        self.dao.requeue_expired_deliveries(now_timestamp()).await
+    }
+
+    pub async fn webhook(&self, id: &str) -> Result<Option<Webhook>, String> {
+        self.dao.get_webhook(id).await
+    }
+
+    pub async fn mark_succeeded(&self, id: &str, response_status: i32) -> Result<(), String> {
+        self.dao
+            .mark_delivery_succeeded(id, response_status, now_timestamp())
+            .await
+    }
+
+    pub async fn mark_failed(
+        &self,
+        id: &str,
+        response_status: Option<i32>,
+        error: &str,
+        retry_at: Option<i64>,
+    ) -> Result<(), String> {
+        self.dao
+            .mark_delivery_failed(id, response_status, error, retry_at, now_timestamp())
+            .await
+    }
+
+    /// 解析地址中的主机，拒绝指向本机、内网、链路本地等非公网地址的目标，
+    /// 避免借 webhook 访问服务端所在网络；注册时和每次投递前都会检查
+    pub async fn check_target(&self, url: &str) -> Result<(), String> {
+        if self.allow_private_targets {
+            return Ok(());
+        }
+        let parsed = reqwest::Url::parse(url).map_err(|_| "webhook地址无效".to_string())?;
+        let host = parsed
+            .host_str()
+            .ok_or_else(|| "webhook地址无效".to_string())?;
+        let addrs: Vec<IpAddr> = match host.trim_matches(['[', ']']).parse::<IpAddr>() {
+            Ok(ip) => vec![ip],
+            Err(_) => {
+                let port = parsed.port_or_known_default().unwrap_or(80);
+                tokio::net::lookup_host((host, port))
+                    .await
+                    .map_err(|_| "webhook地址无法解析".to_string())?
+                    .map(|addr| addr.ip())
+                    .collect()
+            }
+        };
+        if addrs.is_empty() {
+            return Err("webhook地址无法解析".to_string());
+        }
+        if !addrs.into_iter().all(is_public_ip) {
+            return Err(PRIVATE_TARGET_ERROR.to_string());
+        }
+        Ok(())
+    }
+
+    async fn own_webhook(&self, uid: i32, id: &str) -> Result<Webhook, ServiceError> {
+        self.dao
+            .get_webhook(id)
+            .await
+            .map_err(internal_error)?
+            .filter(|w| w.uid == uid)
+            .ok_or_else(|| not_found("webhook不存在"))
+    }
+}
+
+fn pending_delivery(
+    webhook_id: &str,
+    uid: i32,
+    subject_uid: i32,
+    event: &str,
+    payload: String,
+    now: i64,
+) -> WebhookDelivery {
+    WebhookDelivery {
+        id: Uuid::new_v4().to_string(),
+        webhook_id: webhook_id.to_string(),
+        uid,
+        subject_uid,
+        event: event.to_string(),
+        payload,
+        status: DELIVERY_PENDING.to_string(),
+        attempts: 0,
+        next_attempt_at: None,
+        lease_until: None,
+        response_status: None,
+        last_error: None,
+        created_at: now,
+        updated_at: now,
+        delivered_at: None,
+    }
+}
+
+fn normalize_url(url: &str) -> Result<String, ServiceError> {
+    let url = url.trim();
+    let parsed = reqwest::Url::parse(url).map_err(|_| bad_request("webhook地址无效"))?;
+    if url.len() > MAX_URL_LEN
+        || !matches!(parsed.scheme(), "http" | "https")
+        || parsed.host_str().is_none()
+    {
+        return Err(bad_request("webhook地址需为 http 或 https 地址"));
+    }
+    Ok(url.to_string())
+}
+
+/// 回环、私有、链路本地、运营商级 NAT、组播等地址都不是公网地址
+pub(crate) fn is_public_ip(ip: IpAddr) -> bool {
+    match ip.to_canonical() {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_unspecified()
+                || ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || ip.is_documentation()
+                || a == 0
+                || (a == 100 && (64..128).contains(&b)))
+        }
+        IpAddr::V6(ip) => {
+            let first = ip.segments()[0];
+            !(ip.is_unspecified()
+                || ip.is_loopback()
+                || ip.is_multicast()
+                || first & 0xfe00 == 0xfc00
+                || first & 0xffc0 == 0xfe80)
+        }
+    }
+}
+
+fn normalize_events(events: &[String]) -> Result<Vec<String>, ServiceError> {
+    let mut normalized: Vec<String> = Vec::new();
+    for event in events {
+        let event = event.trim();
+        if !WEBHOOK_EVENTS.contains(&event) {
+            return Err(bad_request(&format!("未知的事件类型: {event}")));
+        }
+        if !normalized.iter().any(|e| e == event) {
+            normalized.push(event.to_string());
+        }
+    }
+    if normalized.is_empty() {
+        return Err(bad_request("至少订阅一种事件"));
+    }
+    Ok(normalized)
+}
+
+fn now_timestamp() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn bad_request(message: &str) -> ServiceError {
+    ServiceError {
+        code: 400,
+        message: message.to_string(),
+    }
+}
+
+fn not_found(message: &str) -> ServiceError {
+    ServiceError {
+        code: 404,
+        message: message.to_string(),
+    }
+}
+
+fn internal_error(message: String) -> ServiceError {
+    ServiceError { code: 500, message }
+}
@@ -0,0 +1,218 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+
+use crate::config::WebhookConfig;
+use crate::model::webhook::{
+    HEADER_DELIVERY, HEADER_EVENT, HEADER_SIGNATURE, HEADER_TIMESTAMP, WebhookDelivery,
+    sign_payload,
+};
+use crate::service::webhook_service::{PRIVATE_TARGET_ERROR, WebhookService, is_public_ip};
+
+/// 租约在请求超时之外留出的余量
+const LEASE_MARGIN_SECONDS: u64 = 30;
+
+/// 只返回公网地址的解析器，防止域名在投递前检查之后被重新解析到内网地址
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|addr| is_public_ip(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(PRIVATE_TARGET_ERROR.into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+pub fn start_webhook_workers(config: WebhookConfig, webhooks: Arc<WebhookService>) {
+    let worker_count = config.worker_concurrency.max(1);
+    tracing::info!(
+        worker_count,
+        max_attempts = config.max_attempts.max(1),
+        retry_base_seconds = config.retry_base_seconds,
+        "starting webhook workers"
+    );
+    let mut builder = reqwest::Client::builder()
+        .timeout(Duration::from_secs(config.timeout_seconds.max(1)))
+        .redirect(reqwest::redirect::Policy::none())
+        .user_agent(concat!("slam-server-webhook/", env!("CARGO_PKG_VERSION")));
+    if !config.allow_private_targets {
+        builder = builder.dns_resolver(Arc::new(PublicResolver));
+    }
+    let client = match builder.build() {
+        Ok(client) => client,
+        Err(error) => {
+            tracing::error!(error = %error, "failed to build webhook client");
+            return;
+        }
+    };
+    for worker_id in 0..worker_count {
+        let config = config.clone();
+        let webhooks = webhooks.clone();
+        let client = client.clone();
+        tokio::spawn(async move {
+            worker_loop(worker_id, config, webhooks, client).await;
+        });
+    }
+}
+
+async fn worker_loop(
+    worker_id: usize,
+    config: WebhookConfig,
+    webhooks: Arc<WebhookService>,
+    client: reqwest::Client,
+) {
+    let notify = webhooks.notify();
+    let lease_seconds = (config.timeout_seconds.max(1) + LEASE_MARGIN_SECONDS) as i64;
+    loop {
+        if let Err(error) = webhooks.recover_expired().await {
+            tracing::error!(error = %error, "failed to recover expired webhook deliveries");
+        }
+        match webhooks.claim(lease_seconds).await {
+            Ok(Some(delivery)) => {
+                deliver(worker_id, &config, &webhooks, &client, delivery).await;
+            }
+            Ok(None) => {
+                tokio::select! {
+                    _ = notify.notified() => {},
+                    _ = tokio::time::sleep(Duration::from_secs(2)) => {},
+                }
+            }
+            Err(error) => {
+                tracing::error!(error = %error, "failed to claim webhook delivery");
+                tokio::time::sleep(Duration::from_secs(2)).await;
+            }
+        }
+    }
+}
+
+async fn deliver(
+    worker_id: usize,
+    config: &WebhookConfig,
+    webhooks: &WebhookService,
+    client: &reqwest::Client,
+    delivery: WebhookDelivery,
+) {
+    let started = Instant::now();
+    let webhook = match webhooks.webhook(&delivery.webhook_id).await {
+        Ok(Some(webhook)) => webhook,
+        Ok(None) => {
+            mark_failed(webhooks, &delivery, None, "webhook已删除", None).await;
+            return;
+        }
+        Err(error) => {
+            let retry_at = retry_at(config, delivery.attempts);
+            mark_failed(webhooks, &delivery, None, &error, retry_at).await;
+            return;
+        }
+    };
+    // 地址可能在注册后改为解析到内网，或是检查上线前注册的，投递前再检查一次
+    if let Err(error) = webhooks.check_target(&webhook.url).await {
+        let retry_at = retry_at(config, delivery.attempts);
+        tracing::warn!(
+            worker_id,
+            delivery_id = %delivery.id,
+            webhook_id = %delivery.webhook_id,
+            error = %error,
+            "webhook target rejected"
+        );
+        mark_failed(webhooks, &delivery, None, &error, retry_at).await;
+        return;
+    }
+    let timestamp = now_timestamp();
+    let result = client
+        .post(&webhook.url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(HEADER_EVENT, &delivery.event)
+        .header(HEADER_DELIVERY, &delivery.id)
+        .header(HEADER_TIMESTAMP, timestamp.to_string())
+        .header(
+            HEADER_SIGNATURE,
+            sign_payload(&webhook.secret, timestamp, &delivery.payload),
+        )
+        .body(delivery.payload.clone())
+        .send()
+        .await;
+    let (response_status, error) = match result {
+        Ok(response) if response.status().is_success() => {
+            let status = response.status().as_u16() as i32;
+            if let Err(error) = webhooks.mark_succeeded(&delivery.id, status).await {
+                tracing::error!(delivery_id = %delivery.id, error = %error, "failed to mark webhook delivery succeeded");
+            } else {
+                tracing::info!(
+                    worker_id,
+                    delivery_id = %delivery.id,
+                    webhook_id = %delivery.webhook_id,
+                    event = %delivery.event,
+                    attempt = delivery.attempts,
+                    status,
+                    elapsed_ms = started.elapsed().as_millis(),
+                    "webhook delivered"
+                );
+            }
+            return;
+        }
+        Ok(response) => (
+            Some(response.status().as_u16() as i32),
+            format!("HTTP {}", response.status()),
+        ),
+        Err(error) => (None, error.to_string()),
+    };
+    let retry_at = retry_at(config, delivery.attempts);
+    tracing::warn!(
+        worker_id,
+        delivery_id = %delivery.id,
+        webhook_id = %delivery.webhook_id,
+        event = %delivery.event,
+        attempt = delivery.attempts,
+        response_status,
+        error = %error,
+        retry_at,
+        elapsed_ms = started.elapsed().as_millis(),
+        "webhook delivery failed"
+    );
+    mark_failed(webhooks, &delivery, response_status, &error, retry_at).await;
+}
+
+async fn mark_failed(
+    webhooks: &WebhookService,
+    delivery: &WebhookDelivery,
+    response_status: Option<i32>,
+    error: &str,
+    retry_at: Option<i64>,
+) {
+    if let Err(mark_error) = webhooks
+        .mark_failed(&delivery.id, response_status, error, retry_at)
+        .await
+    {
+        tracing::error!(delivery_id = %delivery.id, error = %mark_error, "failed to mark webhook delivery error");
+    }
+}
+
+/// 第 n 次失败后等待 base * 2^(n-1) 秒，不超过上限；用完尝试次数后不再重试
+fn retry_at(config: &WebhookConfig, attempts: i32) -> Option<i64> {
+    if attempts >= config.max_attempts.max(1) {
+        return None;
+    }
+    let exponent = (attempts - 1).clamp(0, 30) as u32;
+    let delay = config
+        .retry_base_seconds
+        .saturating_mul(1u64 << exponent)
+        .min(config.retry_max_seconds);
+    Some(now_timestamp() + delay as i64)
+}
+
+fn now_timestamp() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
@@ -0,0 +1,225 @@
+//! 年度回顾报告
+//! 在 `StatKind::Year` 已聚合的数据之上计算分类型汇总、最长/最快运动、连续运动天数、个人最佳等
+
+use chrono::{DateTime, Datelike, NaiveDate};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::model::sport::{Sport, SportType};
+use crate::service::sport_service::{StatBucket, StatSummary, TypeBucket, group_by_week_day};
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone)]
+pub struct ReviewSession {
+    pub id: i32,
+    pub r#type: SportType,
+    pub start_time: i64,
+    pub distance_meter: i32,
+    pub duration_second: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone, Default)]
+pub struct YearTotals {
+    pub count: i32,
+    pub duration_second: i32,
+    pub distance_meter: i32,
+    pub calories: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone)]
+pub struct PersonalBest {
+    pub r#type: SportType,
+    /// `longest_distance`（米）、`longest_duration`（秒）或 `fastest_pace`（秒/公里）
+    pub metric: String,
+    pub value: i32,
+    /// 打破的上一个纪录，首次产生纪录时为空
+    pub previous_value: Option<i32>,
+    pub sport: ReviewSession,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone)]
+pub struct YearReview {
+    pub year: i32,
+    pub totals: YearTotals,
+    pub type_totals: Vec<TypeBucket>,
+    pub months: Vec<StatBucket>,
+    pub longest_session: Option<ReviewSession>,
+    /// 每种运动类型中平均配速最快的一次
+    pub fastest_sessions: Vec<ReviewSession>,
+    pub most_active_month: Option<StatBucket>,
+    /// 1 表示周一，7 表示周日
+    pub most_active_weekday: Option<StatBucket>,
+    pub active_days: i32,
+    pub longest_streak_days: i32,
+    pub personal_bests: Vec<PersonalBest>,
+    pub previous_year: Option<YearTotals>,
+    pub earliest_year: Option<i32>,
+}
+
+pub const METRIC_LONGEST_DISTANCE: &str = "longest_distance";
+pub const METRIC_LONGEST_DURATION: &str = "longest_duration";
+pub const METRIC_FASTEST_PACE: &str = "fastest_pace";
+
+/// `current` 为当年的年度统计，`previous` 为上一年的年度统计（没有数据时为空），
+/// `history` 为截至当年年底的全部运动，用于判定个人最佳；星期、自然日和年份按 `tz` 的本地时间划分
+pub fn build_year_review(
+    year: i32,
+    tz: Tz,
+    current: &StatSummary,
+    previous: Option<&StatSummary>,
+    history: &[Sport],
+) -> YearReview {
+    let sports = &current.sports;
+    let longest_session = sports
+        .iter()
+        .max_by_key(|s| (s.duration_second, -s.start_time))
+        .map(to_session);
+    let mut fastest_sessions: Vec<ReviewSession> = Vec::new();
+    for s in sports.iter().filter(|s| pace_second_per_km(s).is_some()) {
+        match fastest_sessions.iter_mut().find(|f| f.r#type == s.r#type) {
+            Some(f) if pace_second_per_km(s) < pace_of(f) => *f = to_session(s),
+            Some(_) => {}
+            None => fastest_sessions.push(to_session(s)),
+        }
+    }
+    fastest_sessions.sort_by_key(|s| s.r#type.as_str());
+
+    let most_active = |buckets: Vec<StatBucket>| {
+        buckets
+            .into_iter()
+            .max_by_key(|b| (b.count, b.duration, -b.date))
+    };
+    let mut days: Vec<NaiveDate> = sports
+        .iter()
+        .filter_map(|s| DateTime::from_timestamp(s.start_time, 0))
+        .map(|dt| dt.with_timezone(&tz))
+        .filter(|dt| dt.year() == year)
+        .map(|dt| dt.date_naive())
+        .collect();
+    days.sort_unstable();
+    days.dedup();
+
+    YearReview {
+        year,
+        totals: totals_of(current),
+        type_totals: current.type_buckets.clone(),
+        months: current.buckets.clone(),
+        longest_session,
+        fastest_sessions,
+        most_active_month: most_active(current.buckets.clone()),
+        most_active_weekday: most_active(group_by_week_day(sports.clone(), tz)),
+        active_days: days.len() as i32,
+        longest_streak_days: longest_streak(&days),
+        personal_bests: personal_bests(history)
+            .into_iter()
+            .filter(|pb| {
+                DateTime::from_timestamp(pb.sport.start_time, 0)
+                    .map(|dt| dt.with_timezone(&tz).year())
+                    == Some(year)
+            })
+            .collect(),
+        previous_year: previous.map(totals_of),
+        earliest_year: current.earliest_year,
+    }
+}
+
+/// 按时间顺序回放运动记录，返回每种运动类型每项指标当前保持的纪录
+pub fn personal_bests(history: &[Sport]) -> Vec<PersonalBest> {
+    let mut ordered: Vec<&Sport> = history.iter().collect();
+    ordered.sort_by_key(|s| (s.start_time, s.id));
+    let mut bests: Vec<PersonalBest> = Vec::new();
+    for s in ordered {
+        let candidates = [
+            (
+                METRIC_LONGEST_DISTANCE,
+                (s.distance_meter > 0).then_some(s.distance_meter),
+            ),
+            (
+                METRIC_LONGEST_DURATION,
+                (s.duration_second > 0).then_some(s.duration_second),
+            ),
+            (METRIC_FASTEST_PACE, pace_second_per_km(s)),
+        ];
+        for (metric, value) in candidates {
+            let Some(value) = value else { continue };
+            let lower_is_better = metric == METRIC_FASTEST_PACE;
+            match bests
+                .iter_mut()
+                .find(|b| b.r#type == s.r#type && b.metric == metric)
+            {
+                Some(b) => {
+                    let improved = if lower_is_better {
+                        value < b.value
+                    } else {
+                        value > b.value
+                    };
+                    if improved {
+                        b.previous_value = Some(b.value);
+                        b.value = value;
+                        b.sport = to_session(s);
+                    }
+                }
+                None => bests.push(PersonalBest {
+                    r#type: s.r#type,
+                    metric: metric.to_string(),
+                    value,
+                    previous_value: None,
+                    sport: to_session(s),
+                }),
+            }
+        }
+    }
+    bests.sort_by(|a, b| (a.r#type.as_str(), &a.metric).cmp(&(b.r#type.as_str(), &b.metric)));
+    bests
+}
+
+fn totals_of(summary: &StatSummary) -> YearTotals {
+    YearTotals {
+        count: summary.total_count,
+        duration_second: summary.total_duration_second,
+        distance_meter: summary.total_distance_meter,
+        calories: summary.total_calories,
+    }
+}
+
+fn to_session(s: &Sport) -> ReviewSession {
+    ReviewSession {
+        id: s.id,
+        r#type: s.r#type,
+        start_time: s.start_time,
+        distance_meter: s.distance_meter,
+        duration_second: s.duration_second,
+    }
+}
+
+/// 距离过短的记录不参与配速比较，避免误录入的数据成为纪录
+fn pace_second_per_km(s: &Sport) -> Option<i32> {
+    let min_distance = match s.r#type {
+        SportType::Swimming => 100,
+        _ => 1000,
+    };
+    if s.distance_meter < min_distance || s.duration_second <= 0 {
+        return None;
+    }
+    Some((s.duration_second as i64 * 1000 / s.distance_meter as i64) as i32)
+}
+
+fn pace_of(s: &ReviewSession) -> Option<i32> {
+    (s.distance_meter > 0)
+        .then(|| (s.duration_second as i64 * 1000 / s.distance_meter as i64) as i32)
+}
+
+fn longest_streak(sorted_days: &[NaiveDate]) -> i32 {
+    let mut best = 0;
+    let mut current = 0;
+    let mut prev: Option<NaiveDate> = None;
+    for day in sorted_days {
+        current = match prev {
+            Some(p) if p.succ_opt() == Some(*day) => current + 1,
+            _ => 1,
+        };
+        best = best.max(current);
+        prev = Some(*day);
+    }
+    best
+}
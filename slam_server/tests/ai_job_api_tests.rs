@@ -5,10 +5,11 @@ use std::time::Duration;
 use async_trait::async_trait;
 use axum::body::{Body, to_bytes};
 use axum::http::{Request, StatusCode, header};
+use futures_util::StreamExt;
 use reqwest::multipart;
 use slam_server::app::{self, AppConfig, routes};
 use slam_server::dao::Repository;
-use slam_server::dao::idl::AiJobDao;
+use slam_server::dao::idl::{AiJobDao, SportDao, UserDao};
 use slam_server::model::ai_job::{
     AiJobRecord, JOB_FAILED, JOB_QUEUED, JOB_READY, JOB_RUNNING, JOB_SUBMITTED,
 };
@@ -750,6 +751,253 @@ async fn retryable_failure_runs_automatically_until_ready() {
     assert_eq!(ready["attempts"], 2);
 }
 
+async fn call(
+    app: &mut axum::Router,
+    method: &str,
+    uri: &str,
+    cookie: &str,
+) -> (StatusCode, serde_json::Value) {
+    let request = Request::builder()
+        .uri(uri)
+        .method(method)
+        .header("cookie", cookie)
+        .body(Body::empty())
+        .unwrap();
+    response_json(app.call(request).await.unwrap()).await
+}
+
+/// 通知在任务状态更新之后写入，轮询到期望的未读数为止
+async fn wait_for_unread(app: &mut axum::Router, cookie: &str, expected: i64) {
+    for _ in 0..100 {
+        let (status, body) = call(app, "GET", routes::API_NOTIFICATIONS_UNREAD, cookie).await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        if body["unread"] == expected {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    panic!("unread notifications did not reach {expected}");
+}
+
+#[tokio::test]
+async fn finished_jobs_notify_owner_and_inbox_tracks_read_state() {
+    let temp = TempDir::new().unwrap();
+    let mock = Arc::new(MockLlm::new(vec![
+        Err(LLMError::TimeoutError("mock timeout".to_string())),
+        Ok(SAMPLE_XML_SWIMMING.to_string()),
+        Err(LLMError::LLMAuthenticationError(
+            "鉴权失败: API Key 无效".to_string(),
+        )),
+    ]));
+    let mut app = app::create_app_with_llm(isolated_config(&temp, 3), mock).await;
+    let cookie = register(&mut app, "ai_job_inbox").await;
+    let other_cookie = register(&mut app, "ai_job_inbox_other").await;
+
+    // 自动重试前的失败不通知，只有最终结果通知
+    let ready = create_job(&mut app, &cookie).await;
+    let ready_id = ready["id"].as_str().unwrap();
+    wait_for_status(&mut app, &cookie, ready_id, JOB_READY).await;
+    wait_for_unread(&mut app, &cookie, 1).await;
+    let failed = create_job(&mut app, &cookie).await;
+    let failed_id = failed["id"].as_str().unwrap();
+    wait_for_status(&mut app, &cookie, failed_id, JOB_FAILED).await;
+    wait_for_unread(&mut app, &cookie, 2).await;
+
+    let (status, page) = call(&mut app, "GET", routes::API_NOTIFICATIONS, &cookie).await;
+    assert_eq!(status, StatusCode::OK, "{page}");
+    assert_eq!(page["unread"], 2);
+    assert!(page["next_cursor"].is_null());
+    let items = page["items"].as_array().unwrap();
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0]["kind"], "ai_job_failed");
+    assert!(items[0]["actor"].is_null());
+    let detail: serde_json::Value =
+        serde_json::from_str(items[0]["detail"].as_str().unwrap()).unwrap();
+    assert_eq!(detail["job_id"], failed_id);
+    assert_eq!(detail["error_code"], "502");
+    assert_eq!(items[1]["kind"], "ai_job_ready");
+    let detail: serde_json::Value =
+        serde_json::from_str(items[1]["detail"].as_str().unwrap()).unwrap();
+    assert_eq!(detail["job_id"], ready_id);
+
+    // 游标分页
+    let uri = format!("{}?size=1", routes::API_NOTIFICATIONS);
+    let (_, first) = call(&mut app, "GET", &uri, &cookie).await;
+    assert_eq!(first["items"][0]["id"], items[0]["id"]);
+    let cursor = first["next_cursor"].as_i64().unwrap();
+    let uri = format!("{}?size=1&cursor={cursor}", routes::API_NOTIFICATIONS);
+    let (_, second) = call(&mut app, "GET", &uri, &cookie).await;
+    assert_eq!(second["items"][0]["id"], items[1]["id"]);
+    assert!(second["next_cursor"].is_null());
+
+    // 标记单条已读，别人的通知不可见
+    let read_uri = routes::API_NOTIFICATION_READ.replace(":id", &cursor.to_string());
+    let (status, _) = call(&mut app, "POST", &read_uri, &other_cookie).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = call(&mut app, "POST", &read_uri, &cookie).await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, other) = call(&mut app, "GET", routes::API_NOTIFICATIONS, &other_cookie).await;
+    assert_eq!(other["items"], serde_json::json!([]));
+    assert_eq!(other["unread"], 0);
+
+    let uri = format!("{}?unread=true", routes::API_NOTIFICATIONS);
+    let (_, unread) = call(&mut app, "GET", &uri, &cookie).await;
+    assert_eq!(unread["unread"], 1);
+    assert_eq!(unread["items"].as_array().unwrap().len(), 1);
+    assert_eq!(unread["items"][0]["kind"], "ai_job_ready");
+
+    let (status, body) = call(
+        &mut app,
+        "POST",
+        routes::API_NOTIFICATIONS_READ_ALL,
+        &cookie,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["updated"], 1);
+    wait_for_unread(&mut app, &cookie, 0).await;
+    let (_, page) = call(&mut app, "GET", routes::API_NOTIFICATIONS, &cookie).await;
+    assert!(
+        page["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|n| n["read_at"].is_i64())
+    );
+}
+
+/// 打开任务事件流，last_event_id 通过 Last-Event-ID 请求头传递
+async fn open_events(
+    app: &mut axum::Router,
+    cookie: &str,
+    last_event_id: Option<i64>,
+) -> (StatusCode, axum::body::BodyDataStream) {
+    let mut request = Request::builder()
+        .uri(routes::API_AI_JOB_EVENTS)
+        .header("cookie", cookie);
+    if let Some(id) = last_event_id {
+        request = request.header("last-event-id", id.to_string());
+    }
+    let response = app
+        .call(request.body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    (response.status(), response.into_body().into_data_stream())
+}
+
+/// 期待中的事件最长等待时间，只在事件缺失时才会等满，留足并行测试占满机器时的余量
+const EVENT_WAIT: Duration = Duration::from_secs(30);
+
+/// 读取下一个带数据的 SSE 事件，返回事件 id 与数据；超时返回 None
+async fn next_event(
+    stream: &mut axum::body::BodyDataStream,
+    buffer: &mut String,
+    wait: Duration,
+) -> Option<(i64, serde_json::Value)> {
+    loop {
+        while let Some(end) = buffer.find("\n\n") {
+            let frame: String = buffer.drain(..end + 2).collect();
+            let mut id = None;
+            let mut data = None;
+            for line in frame.lines() {
+                if let Some(value) = line.strip_prefix("id:") {
+                    id = value.trim().parse().ok();
+                } else if let Some(value) = line.strip_prefix("data:") {
+                    data = serde_json::from_str(value.trim()).ok();
+                }
+            }
+            if let (Some(id), Some(data)) = (id, data) {
+                return Some((id, data));
+            }
+        }
+        let chunk = tokio::time::timeout(wait, stream.next()).await.ok()??;
+        buffer.push_str(std::str::from_utf8(&chunk.unwrap()).unwrap());
+    }
+}
+
+#[tokio::test]
+async fn job_event_stream_pushes_transitions_and_resumes_from_last_event_id() {
+    let temp = TempDir::new().unwrap();
+    let mock = Arc::new(MockLlm::new(vec![
+        Err(LLMError::TimeoutError("mock timeout".to_string())),
+        Ok(SAMPLE_XML_SWIMMING.to_string()),
+    ]));
+    let mut app = app::create_app_with_llm(isolated_config(&temp, 3), mock).await;
+    let cookie = register(&mut app, "ai_job_events").await;
+    let other_cookie = register(&mut app, "ai_job_events_other").await;
+
+    let request = Request::builder()
+        .uri(routes::API_AI_JOB_EVENTS)
+        .body(Body::empty())
+        .unwrap();
+    let response = app.call(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let (status, mut stream) = open_events(&mut app, &cookie, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let created = create_job(&mut app, &cookie).await;
+    let id = created["id"].as_str().unwrap();
+
+    // 排队、识别、失败后安排重试、再次识别、完成
+    let mut buffer = String::new();
+    let mut events = Vec::new();
+    while events.len() < 5 {
+        let event = next_event(&mut stream, &mut buffer, EVENT_WAIT)
+            .await
+            .expect("job event");
+        events.push(event);
+    }
+    let statuses: Vec<&str> = events
+        .iter()
+        .map(|(_, data)| data["status"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        statuses,
+        vec!["queued", "running", "retry_scheduled", "running", "ready"]
+    );
+    assert!(events.iter().all(|(_, data)| data["job_id"] == id));
+    assert!(events.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    assert_eq!(events[2].1["attempts"], 1);
+    assert_eq!(events[2].1["error_code"], "504");
+    assert!(events[2].1["next_attempt_at"].is_i64());
+    assert_eq!(events[4].1["attempts"], 2);
+    assert!(events[4].1.get("error_code").is_none());
+    assert!(events[4].1.get("uid").is_none());
+
+    // 断线后带上最后收到的事件 id 重连，只补发之后的事件
+    let (status, mut resumed) = open_events(&mut app, &cookie, Some(events[1].0)).await;
+    assert_eq!(status, StatusCode::OK);
+    let mut buffer = String::new();
+    for expected in &events[2..] {
+        let event = next_event(&mut resumed, &mut buffer, EVENT_WAIT)
+            .await
+            .expect("replayed event");
+        assert_eq!(&event, expected);
+    }
+    assert!(
+        next_event(&mut resumed, &mut buffer, Duration::from_millis(200))
+            .await
+            .is_none()
+    );
+
+    // 查询参数同样可以续传，别人的事件不会推送
+    let uri = format!("{}?last_event_id=0", routes::API_AI_JOB_EVENTS);
+    let request = Request::builder()
+        .uri(uri)
+        .header("cookie", &other_cookie)
+        .body(Body::empty())
+        .unwrap();
+    let response = app.call(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let mut other = response.into_body().into_data_stream();
+    let mut buffer = String::new();
+    assert!(
+        next_event(&mut other, &mut buffer, Duration::from_millis(200))
+            .await
+            .is_none()
+    );
+}
+
 #[tokio::test]
 async fn ai_job_creation_requires_authentication_and_valid_image() {
     let temp = TempDir::new().unwrap();
@@ -976,3 +1224,123 @@ async fn authentication_failure_is_not_retried_automatically() {
     assert_eq!(failed["attempts"], 1);
     assert!(failed["next_attempt_at"].is_null());
 }
+
+#[tokio::test]
+async fn account_deletion_removes_rows_and_job_files() {
+    let temp = TempDir::new().unwrap();
+    let config = isolated_config(&temp, 1);
+    let mock = Arc::new(MockLlm::new(vec![Ok(SAMPLE_XML_SWIMMING.to_string())]));
+    let mut app = app::create_app_with_llm(config.clone(), mock).await;
+    let cookie = register(&mut app, "leaving_owner").await;
+    let repository = Repository::new(&config.db.path).await.unwrap();
+
+    let created = create_job(&mut app, &cookie).await;
+    let job_id = created["id"].as_str().unwrap();
+    let ready = wait_for_status(&mut app, &cookie, job_id, JOB_READY).await;
+    let request = Request::builder()
+        .uri(routes::API_SPORT_INSERT)
+        .method("POST")
+        .header("content-type", "application/json")
+        .header("cookie", &cookie)
+        .body(Body::from(ready["result"].to_string()))
+        .unwrap();
+    assert_eq!(app.call(request).await.unwrap().status(), StatusCode::OK);
+    let job_dir = temp.path().join("ai-jobs").join(job_id);
+    assert!(job_dir.exists());
+    assert_eq!(repository.list(1, 0, 10).await.unwrap().len(), 1);
+
+    let delete_account = |password: &str| {
+        Request::builder()
+            .uri(routes::API_USER_ACCOUNT)
+            .method("DELETE")
+            .header("content-type", "application/json")
+            .header("cookie", &cookie)
+            .body(Body::from(
+                serde_json::json!({ "password": password }).to_string(),
+            ))
+            .unwrap()
+    };
+    let response = app.call(delete_account("wrong")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert!(job_dir.exists());
+
+    let response = app.call(delete_account("p@ssw0rd")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(!job_dir.exists());
+    assert!(repository.get_account(1).await.unwrap().is_none());
+    assert!(repository.get_job(1, job_id).await.unwrap().is_none());
+    assert!(repository.list(1, 0, 10).await.unwrap().is_empty());
+    let (status, _) = list_jobs(&mut app, &cookie, "").await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+async fn admin_call(
+    app: &mut axum::Router,
+    method: &str,
+    uri: &str,
+    cookie: &str,
+) -> (StatusCode, serde_json::Value) {
+    let request = Request::builder()
+        .uri(uri)
+        .method(method)
+        .header("cookie", cookie)
+        .body(Body::empty())
+        .unwrap();
+    response_json(app.call(request).await.unwrap()).await
+}
+
+#[tokio::test]
+async fn admin_can_requeue_and_purge_jobs_across_users() {
+    let temp = TempDir::new().unwrap();
+    let mock = Arc::new(MockLlm::new(vec![
+        Err(LLMError::APIFailure("upstream down".to_string())),
+        Ok(SAMPLE_XML_SWIMMING.to_string()),
+    ]));
+    let mut config = isolated_config(&temp, 1);
+    config.security.admins = vec!["ops_admin".to_string()];
+    let mut app = app::create_app_with_llm(config.clone(), mock.clone()).await;
+    let admin = register(&mut app, "ops_admin").await;
+    // 管理员在启动时按已有账号提升
+    let mut app = app::create_app_with_llm(config, mock).await;
+    let owner = register(&mut app, "ops_owner").await;
+    let created = create_job(&mut app, &owner).await;
+    let id = created["id"].as_str().unwrap().to_string();
+    wait_for_status(&mut app, &owner, &id, "failed").await;
+
+    let (status, _) = admin_call(&mut app, "GET", routes::API_ADMIN_AI_QUEUE, &owner).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, stats) = admin_call(&mut app, "GET", routes::API_ADMIN_AI_QUEUE, &admin).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(stats["failed"], 1);
+    assert_eq!(stats["queued"], 0);
+    assert_eq!(stats["worker_concurrency"], 1);
+
+    let uri = format!("{}?status=failed", routes::API_ADMIN_AI_JOBS);
+    let (status, jobs) = admin_call(&mut app, "GET", &uri, &admin).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(jobs.as_array().unwrap().len(), 1);
+    assert_eq!(jobs[0]["id"], id.as_str());
+    let uri = format!("{}?status=bogus", routes::API_ADMIN_AI_JOBS);
+    let (status, _) = admin_call(&mut app, "GET", &uri, &admin).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, users) = admin_call(&mut app, "GET", routes::API_ADMIN_USERS, &admin).await;
+    assert_eq!(status, StatusCode::OK);
+    let owner_row = &users.as_array().unwrap()[1];
+    assert_eq!(owner_row["ai_job_count"], 1);
+    assert!(owner_row["storage_bytes"].as_i64().unwrap() > 0);
+
+    let requeue = routes::API_ADMIN_AI_JOB_REQUEUE.replace(":id", &id);
+    let (status, body) = admin_call(&mut app, "POST", &requeue, &admin).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    wait_for_status(&mut app, &owner, &id, "ready").await;
+    let (status, _) = admin_call(&mut app, "POST", &requeue, &admin).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let purge = routes::API_ADMIN_AI_JOB.replace(":id", &id);
+    let (status, _) = admin_call(&mut app, "DELETE", &purge, &admin).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(!temp.path().join("ai-jobs").join(&id).exists());
+    let (status, _) = admin_call(&mut app, "DELETE", &purge, &admin).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
@@ -871,64 +871,471 @@ async fn test_sport_stats_total() {
 }
 
 #[tokio::test]
-async fn test_user_avatar_upload_and_get() {
+async fn test_sport_heatmap_hourly_and_calendar_use_timezone() {
     let mut app = app::create_app(AppConfig::default()).await;
     let cookie_header =
-        register_and_get_cookie(&mut app, "test_avatar", "AvatarUser", "p@ssw0rd").await;
+        register_and_get_cookie(&mut app, "test_heatmap", "HeatmapUser", "p@ssw0rd").await;
 
-    let base64 = "data:image/jpeg;base64,ZmFrZQ==";
-    let form = multipart::Form::new().text("avatar", base64);
-    let boundary = form.boundary().to_string();
-    let stream = form.into_stream();
-    let body = Body::from_stream(stream);
+    // 2025-11-17 23:30 UTC 为上海时间 2025-11-18（周二）07:30
+    let swim_ts = Utc
+        .with_ymd_and_hms(2025, 11, 17, 23, 30, 0)
+        .unwrap()
+        .timestamp();
+    let run_ts = Utc
+        .with_ymd_and_hms(2025, 11, 20, 10, 0, 0)
+        .unwrap()
+        .timestamp();
+    for (ty, ts, duration) in [("Swimming", swim_ts, 600), ("Running", run_ts, 1800)] {
+        let body = serde_json::json!({
+            "type": ty,
+            "start_time": ts,
+            "calories": 100,
+            "distance_meter": 1000,
+            "duration_second": duration,
+            "heart_rate_avg": 120,
+            "heart_rate_max": 140,
+            "pace_average": ""
+        });
+        let req = Request::builder()
+            .uri(routes::API_SPORT_INSERT)
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .header("Cookie", cookie_header.clone())
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let (status, _) = print_response("运动插入", app.call(req).await.unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+    }
 
-    let upload_req = Request::builder()
-        .uri(routes::API_USER_AVATAR_UPLOAD)
-        .method("POST")
-        .header(
-            "Content-Type",
-            format!("multipart/form-data; boundary={}", boundary),
-        )
+    let hourly_req = Request::builder()
+        .uri(format!(
+            "{}?start={}&end={}&type=Swimming&tz=Asia/Shanghai",
+            routes::API_SPORT_HEATMAP_HOURLY,
+            swim_ts - 86400,
+            run_ts + 86400
+        ))
+        .method("GET")
         .header("Cookie", cookie_header.clone())
-        .body(body)
+        .body(Body::empty())
         .unwrap();
-    let upload_resp = app.call(upload_req).await.unwrap();
-    let (upload_status, upload_bytes) = print_response("头像上传", upload_resp).await;
-    assert_eq!(upload_status, StatusCode::OK);
-    let upload_json: serde_json::Value = serde_json::from_slice(&upload_bytes).unwrap();
-    assert!(upload_json.get("success").unwrap().as_bool().unwrap());
+    let (status, bytes) = print_response("小时热力图", app.call(hourly_req).await.unwrap()).await;
+    assert_eq!(status, StatusCode::OK);
+    let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    let cells = json.get("cells").unwrap().as_array().unwrap();
+    assert_eq!(cells.len(), 7);
+    assert!(cells.iter().all(|row| row.as_array().unwrap().len() == 24));
+    assert_eq!(cells[1][7]["count"].as_i64().unwrap(), 1);
+    assert_eq!(cells[1][7]["duration"].as_i64().unwrap(), 600);
+    let total: i64 = cells
+        .iter()
+        .flat_map(|row| row.as_array().unwrap())
+        .map(|c| c["count"].as_i64().unwrap())
+        .sum();
+    assert_eq!(total, 1);
+
+    let calendar_req = Request::builder()
+        .uri(format!(
+            "{}?year=2025&tz=Asia/Shanghai",
+            routes::API_SPORT_HEATMAP_CALENDAR
+        ))
+        .method("GET")
+        .header("Cookie", cookie_header.clone())
+        .body(Body::empty())
+        .unwrap();
+    let (status, bytes) = print_response("日历热力图", app.call(calendar_req).await.unwrap()).await;
+    assert_eq!(status, StatusCode::OK);
+    let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    let days = json.get("days").unwrap().as_array().unwrap();
+    assert_eq!(days.len(), 365);
+    let day = |date: &str| days.iter().find(|d| d["date"] == date).unwrap().clone();
+    assert_eq!(day("2025-11-17")["count"].as_i64().unwrap(), 0);
+    assert_eq!(day("2025-11-18")["count"].as_i64().unwrap(), 1);
+    assert_eq!(day("2025-11-20")["duration"].as_i64().unwrap(), 1800);
     assert!(
-        upload_json
-            .get("avatar")
-            .unwrap()
-            .as_str()
-            .unwrap()
-            .starts_with("data:image/jpeg;base64,")
+        day("2025-11-20")["level"].as_u64().unwrap() > day("2025-11-18")["level"].as_u64().unwrap()
     );
 
-    let get_req = Request::builder()
-        .uri(routes::API_USER_INFO)
+    let invalid_req = Request::builder()
+        .uri(format!(
+            "{}?year=2025&tz=Mars/Olympus",
+            routes::API_SPORT_HEATMAP_CALENDAR
+        ))
         .method("GET")
         .header("Cookie", cookie_header.clone())
         .body(Body::empty())
         .unwrap();
-    let get_resp = app.call(get_req).await.unwrap();
-    let (get_status, get_bytes) = print_response("头像获取", get_resp).await;
-    assert_eq!(get_status, StatusCode::OK);
-    let get_json: serde_json::Value = serde_json::from_slice(&get_bytes).unwrap();
-    assert_eq!(
-        get_json.get("nickname").unwrap().as_str().unwrap(),
-        "AvatarUser"
+    let (status, _) = print_response("无效时区", app.call(invalid_req).await.unwrap()).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_sport_year_review_and_card() {
+    let mut app = app::create_app(AppConfig::default()).await;
+    let cookie_header =
+        register_and_get_cookie(&mut app, "test_review", "ReviewUser", "p@ssw0rd").await;
+
+    let ts = |y, m, d| Utc.with_ymd_and_hms(y, m, d, 8, 0, 0).unwrap().timestamp();
+    let sports = [
+        ("Running", ts(2024, 5, 1), 5000, 1800),
+        ("Running", ts(2025, 3, 1), 10000, 3600),
+        ("Running", ts(2025, 3, 2), 5000, 1400),
+        ("Swimming", ts(2025, 6, 7), 1500, 2400),
+    ];
+    for (ty, start, distance, duration) in sports {
+        let body = serde_json::json!({
+            "type": ty,
+            "start_time": start,
+            "calories": 300,
+            "distance_meter": distance,
+            "duration_second": duration,
+            "heart_rate_avg": 130,
+            "heart_rate_max": 160,
+            "pace_average": ""
+        });
+        let req = Request::builder()
+            .uri(routes::API_SPORT_INSERT)
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .header("Cookie", cookie_header.clone())
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let (status, _) = print_response("运动插入", app.call(req).await.unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let review_req = Request::builder()
+        .uri(format!("{}?year=2025", routes::API_SPORT_REVIEW))
+        .method("GET")
+        .header("Cookie", cookie_header.clone())
+        .body(Body::empty())
+        .unwrap();
+    let (status, bytes) = print_response("年度回顾", app.call(review_req).await.unwrap()).await;
+    assert_eq!(status, StatusCode::OK);
+    let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(json["totals"]["count"].as_i64().unwrap(), 3);
+    assert_eq!(json["previous_year"]["count"].as_i64().unwrap(), 1);
+    assert_eq!(json["longest_session"]["duration_second"], 3600);
+    assert_eq!(json["active_days"], 3);
+    assert_eq!(json["longest_streak_days"], 2);
+    assert_eq!(json["most_active_month"]["date"], 3);
+    assert_eq!(json["fastest_sessions"].as_array().unwrap().len(), 2);
+    let pbs = json["personal_bests"].as_array().unwrap();
+    let pb = |ty: &str, metric: &str| {
+        pbs.iter()
+            .find(|p| p["type"] == ty && p["metric"] == metric)
+            .cloned()
+    };
+    let distance = pb("Running", "longest_distance").unwrap();
+    assert_eq!(distance["value"], 10000);
+    assert_eq!(distance["previous_value"], 5000);
+    let pace = pb("Running", "fastest_pace").unwrap();
+    assert_eq!(pace["value"], 280);
+    assert_eq!(pace["previous_value"], 360);
+    assert!(pb("Swimming", "longest_distance").is_some());
+
+    let card_req = Request::builder()
+        .uri(format!("{}?year=2025", routes::API_SPORT_REVIEW_CARD))
+        .method("GET")
+        .header("Cookie", cookie_header.clone())
+        .body(Body::empty())
+        .unwrap();
+    let resp = app.call(card_req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers()["content-type"], "image/png");
+    let png = to_bytes(resp.into_body(), 1024 * 1024).await.unwrap();
+    assert!(png.starts_with(b"\x89PNG"));
+
+    let invalid_req = Request::builder()
+        .uri(routes::API_SPORT_REVIEW)
+        .method("GET")
+        .header("Cookie", cookie_header.clone())
+        .body(Body::empty())
+        .unwrap();
+    let (status, _) =
+        print_response("年度回顾缺少年份", app.call(invalid_req).await.unwrap()).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_sport_year_review_uses_local_time() {
+    let mut app = app::create_app(AppConfig::default()).await;
+    let cookie_header =
+        register_and_get_cookie(&mut app, "test_review_tz", "ReviewTzUser", "p@ssw0rd").await;
+
+    // 北京时间 2025-01-01 05:00、2025-03-02（周日）20:00、2025-03-03（周一）06:00
+    let ts = |y, m, d, h| Utc.with_ymd_and_hms(y, m, d, h, 0, 0).unwrap().timestamp();
+    let sports = [
+        (ts(2024, 12, 31, 21), 1800),
+        (ts(2025, 3, 2, 12), 1800),
+        (ts(2025, 3, 2, 22), 3600),
+    ];
+    for (start, duration) in sports {
+        let body = serde_json::json!({
+            "type": "Running",
+            "start_time": start,
+            "calories": 300,
+            "distance_meter": 5000,
+            "duration_second": duration,
+            "heart_rate_avg": 130,
+            "heart_rate_max": 160,
+            "pace_average": ""
+        });
+        let req = Request::builder()
+            .uri(routes::API_SPORT_INSERT)
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .header("Cookie", cookie_header.clone())
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let (status, _) = print_response("运动插入", app.call(req).await.unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let review = |query: &str| {
+        Request::builder()
+            .uri(format!("{}?{}", routes::API_SPORT_REVIEW, query))
+            .method("GET")
+            .header("Cookie", cookie_header.clone())
+            .body(Body::empty())
+            .unwrap()
+    };
+    let (status, bytes) = print_response(
+        "本地时区年度回顾",
+        app.call(review("year=2025&tz=Asia/Shanghai"))
+            .await
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(json["totals"]["count"], 3);
+    assert!(json["previous_year"].is_null());
+    assert_eq!(json["earliest_year"], 2025);
+    assert_eq!(json["active_days"], 3);
+    assert_eq!(json["longest_streak_days"], 2);
+    assert_eq!(json["most_active_month"]["date"], 3);
+    assert_eq!(json["most_active_weekday"]["date"], 1);
+    let months: Vec<i64> = json["months"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|b| b["date"].as_i64().unwrap())
+        .collect();
+    assert_eq!(months, vec![1, 3]);
+
+    // 同样的数据按 UTC 划分：元旦的运动属于上一年，两次三月的运动在同一天
+    let (status, bytes) =
+        print_response("UTC 年度回顾", app.call(review("year=2025")).await.unwrap()).await;
+    assert_eq!(status, StatusCode::OK);
+    let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(json["totals"]["count"], 2);
+    assert_eq!(json["previous_year"]["count"], 1);
+    assert_eq!(json["active_days"], 1);
+    assert_eq!(json["longest_streak_days"], 1);
+    assert_eq!(json["most_active_weekday"]["date"], 7);
+
+    let (status, _) = print_response(
+        "年度回顾无效时区",
+        app.call(review("year=2025&tz=Mars/Olympus")).await.unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_athlete_profile_versions_drive_sport_metrics() {
+    let mut app = app::create_app(AppConfig::default()).await;
+    let cookie_header =
+        register_and_get_cookie(&mut app, "test_athlete", "AthleteUser", "p@ssw0rd").await;
+    let ts = |y, m, d| Utc.with_ymd_and_hms(y, m, d, 8, 0, 0).unwrap().timestamp();
+    let send = |method: &str, uri: String, body: serde_json::Value| {
+        Request::builder()
+            .uri(uri)
+            .method(method)
+            .header("Content-Type", "application/json")
+            .header("Cookie", cookie_header.clone())
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    };
+
+    let invalid = send(
+        "PUT",
+        routes::API_USER_ATHLETE.to_string(),
+        serde_json::json!({ "sex": "unknown" }),
     );
-    assert!(
-        get_json
-            .get("avatar")
+    let (status, _) = print_response("档案参数错误", app.call(invalid).await.unwrap()).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let profiles = [
+        serde_json::json!({
+            "birth_date": "1990-06-15",
+            "sex": "male",
+            "resting_heart_rate": 60,
+            "max_heart_rate": 190,
+            "valid_from": ts(2024, 1, 1)
+        }),
+        serde_json::json!({
+            "birth_date": "1990-06-15",
+            "sex": "male",
+            "height_cm": 178.0,
+            "resting_heart_rate": 50,
+            "max_heart_rate": 190,
+            "unit_system": "imperial",
+            "valid_from": ts(2025, 1, 1)
+        }),
+    ];
+    for body in profiles {
+        let req = send("PUT", routes::API_USER_ATHLETE.to_string(), body);
+        let (status, _) = print_response("更新档案", app.call(req).await.unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+    for (weight, at) in [(72.0, ts(2024, 6, 1)), (70.0, ts(2025, 2, 1))] {
+        let body = serde_json::json!({ "weight_kg": weight, "measured_at": at });
+        let req = send("POST", routes::API_USER_WEIGHTS.to_string(), body);
+        let (status, _) = print_response("记录体重", app.call(req).await.unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+    for start in [ts(2024, 5, 1), ts(2025, 3, 1)] {
+        let body = serde_json::json!({
+            "type": "Running",
+            "start_time": start,
+            "calories": 600,
+            "distance_meter": 10000,
+            "duration_second": 3600,
+            "heart_rate_avg": 150,
+            "heart_rate_max": 175,
+            "pace_average": ""
+        });
+        let req = send("POST", routes::API_SPORT_INSERT.to_string(), body);
+        let (status, _) = print_response("运动插入", app.call(req).await.unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let req = send(
+        "GET",
+        format!("{}?page=0&size=10", routes::API_SPORT_LIST),
+        serde_json::Value::Null,
+    );
+    let (_, bytes) = print_response("运动列表", app.call(req).await.unwrap()).await;
+    let sports: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    let id_at = |start: i64| {
+        sports
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|s| s["start_time"] == start)
+            .unwrap()["id"]
+            .as_i64()
             .unwrap()
-            .as_str()
+    };
+    let mut metrics_of = async |id: i64| {
+        let req = send(
+            "GET",
+            format!("{}?id={id}", routes::API_SPORT_METRICS),
+            serde_json::Value::Null,
+        );
+        let (status, bytes) = print_response("运动指标", app.call(req).await.unwrap()).await;
+        (
+            status,
+            serde_json::from_slice::<serde_json::Value>(&bytes).unwrap_or_default(),
+        )
+    };
+
+    let (status, recent) = metrics_of(id_at(ts(2025, 3, 1))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(recent["profile_valid_from"], ts(2025, 1, 1));
+    assert_eq!(recent["weight_kg"], 70.0);
+    assert_eq!(recent["heart_rate_zones"][0]["min_bpm"], 120);
+    assert_eq!(recent["heart_rate_avg_zone"], 3);
+    assert_eq!(recent["training_load"], 108.1);
+    // 早于首条体重记录的运动使用最早的体重
+    let (_, older) = metrics_of(id_at(ts(2024, 5, 1))).await;
+    assert_eq!(older["profile_valid_from"], ts(2024, 1, 1));
+    assert_eq!(older["weight_kg"], 72.0);
+    assert_eq!(older["heart_rate_zones"][0]["min_bpm"], 125);
+    let (status, _) = metrics_of(0).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let req = send(
+        "GET",
+        routes::API_USER_ATHLETE.to_string(),
+        serde_json::Value::Null,
+    );
+    let (_, bytes) = print_response("当前档案", app.call(req).await.unwrap()).await;
+    let current: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(current["profile"]["unit_system"], "imperial");
+    assert_eq!(current["weight_kg"], 70.0);
+    assert_eq!(current["heart_rate_zones"].as_array().unwrap().len(), 5);
+    let req = send(
+        "GET",
+        routes::API_USER_ATHLETE_HISTORY.to_string(),
+        serde_json::Value::Null,
+    );
+    let (_, bytes) = print_response("档案历史", app.call(req).await.unwrap()).await;
+    let history: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(history.as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_missing_calories_are_estimated_from_weight() {
+    let mut app = app::create_app(AppConfig::default()).await;
+    let cookie_header =
+        register_and_get_cookie(&mut app, "test_calorie", "CalorieUser", "p@ssw0rd").await;
+    let send = |uri: &str, body: serde_json::Value| {
+        Request::builder()
+            .uri(uri)
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .header("Cookie", cookie_header.clone())
+            .body(Body::from(body.to_string()))
             .unwrap()
-            .starts_with("data:image/jpeg;base64,")
+    };
+    let req = send(
+        routes::API_USER_WEIGHTS,
+        serde_json::json!({ "weight_kg": 60.0, "measured_at": 1_700_000_000 }),
     );
+    assert_eq!(app.call(req).await.unwrap().status(), StatusCode::OK);
+    for (start, calories) in [(1_735_700_000, 0), (1_735_800_000, 700)] {
+        let body = serde_json::json!({
+            "type": "Running",
+            "start_time": start,
+            "calories": calories,
+            "distance_meter": 10000,
+            "duration_second": 3600,
+            "heart_rate_avg": 0,
+            "heart_rate_max": 0,
+            "pace_average": ""
+        });
+        let req = send(routes::API_SPORT_INSERT, body);
+        let (status, _) = print_response("运动插入", app.call(req).await.unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let req = Request::builder()
+        .uri(format!("{}?page=0&size=10", routes::API_SPORT_LIST))
+        .method("GET")
+        .header("Cookie", cookie_header.clone())
+        .body(Body::empty())
+        .unwrap();
+    let (_, bytes) = print_response("运动列表", app.call(req).await.unwrap()).await;
+    let sports: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    let by_start = |start: i64| {
+        sports
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|s| s["start_time"] == start)
+            .cloned()
+            .unwrap()
+    };
+    // 10 km/h 对应 9.8 MET，60kg 跑 1 小时约 588 千卡
+    let estimated = by_start(1_735_700_000);
+    assert_eq!(estimated["calories"], 588);
+    assert_eq!(estimated["calories_estimated"], true);
+    let measured = by_start(1_735_800_000);
+    assert_eq!(measured["calories"], 700);
+    assert_eq!(measured["calories_estimated"], false);
 }
+
 struct TestMockLLM {
     result: RwLock<Result<String, slam_server::service::llm::LLMError>>,
 }
@@ -957,6 +1364,162 @@ impl LlmTrait for TestMockLLM {
     }
 }
 
+fn avatar_data_url() -> String {
+    use base64::Engine;
+    let image = std::fs::read("tests/test.jpg").unwrap();
+    format!(
+        "data:image/jpeg;base64,{}",
+        base64::engine::general_purpose::STANDARD.encode(image)
+    )
+}
+
+async fn upload_avatar_text(app: &mut axum::Router, cookie: &str, text: &str) -> Response<Body> {
+    let form = multipart::Form::new().text("avatar", text.to_string());
+    let boundary = form.boundary().to_string();
+    let req = Request::builder()
+        .uri(routes::API_USER_AVATAR_UPLOAD)
+        .method("POST")
+        .header(
+            "Content-Type",
+            format!("multipart/form-data; boundary={}", boundary),
+        )
+        .header("Cookie", cookie)
+        .body(Body::from_stream(form.into_stream()))
+        .unwrap();
+    app.call(req).await.unwrap()
+}
+
+async fn user_info_avatar(app: &mut axum::Router, cookie: &str) -> String {
+    let req = Request::builder()
+        .uri(routes::API_USER_INFO)
+        .method("GET")
+        .header("Cookie", cookie)
+        .body(Body::empty())
+        .unwrap();
+    let (status, bytes) = print_response("用户信息", app.call(req).await.unwrap()).await;
+    assert_eq!(status, StatusCode::OK);
+    let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    json["avatar"].as_str().unwrap().to_string()
+}
+
+async fn get_avatar(
+    app: &mut axum::Router,
+    uri: &str,
+    if_none_match: Option<&str>,
+) -> (StatusCode, axum::http::HeaderMap, Vec<u8>) {
+    let mut req = Request::builder().uri(uri).method("GET");
+    if let Some(etag) = if_none_match {
+        req = req.header("If-None-Match", etag);
+    }
+    let resp = app.call(req.body(Body::empty()).unwrap()).await.unwrap();
+    let status = resp.status();
+    let headers = resp.headers().clone();
+    let body = to_bytes(resp.into_body(), 1024 * 1024).await.unwrap();
+    (status, headers, body.to_vec())
+}
+
+#[tokio::test]
+async fn test_user_avatar_upload_and_get() {
+    let mut app = app::create_app(AppConfig::default()).await;
+    let cookie_header =
+        register_and_get_cookie(&mut app, "test_avatar", "AvatarUser", "p@ssw0rd").await;
+    assert_eq!(user_info_avatar(&mut app, &cookie_header).await, "");
+
+    let upload_resp = upload_avatar_text(&mut app, &cookie_header, &avatar_data_url()).await;
+    let (upload_status, upload_bytes) = print_response("头像上传", upload_resp).await;
+    assert_eq!(upload_status, StatusCode::OK);
+    let upload_json: serde_json::Value = serde_json::from_slice(&upload_bytes).unwrap();
+    assert!(upload_json["success"].as_bool().unwrap());
+    let avatar = upload_json["avatar"].as_str().unwrap().to_string();
+    assert!(avatar.starts_with("/api/user/avatar/"), "{avatar}");
+    // 用户信息只返回头像地址
+    assert_eq!(user_info_avatar(&mut app, &cookie_header).await, avatar);
+
+    // 头像无需登录即可访问，默认返回最大尺寸
+    let (status, headers, body) = get_avatar(&mut app, &avatar, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(headers["content-type"], "image/jpeg");
+    assert!(
+        headers["cache-control"]
+            .to_str()
+            .unwrap()
+            .contains("max-age")
+    );
+    let etag = headers["etag"].to_str().unwrap().to_string();
+    let img = image::load_from_memory(&body).unwrap();
+    assert_eq!((img.width(), img.height()), (256, 256));
+
+    let (status, headers, body) = get_avatar(&mut app, &avatar, Some(&etag)).await;
+    assert_eq!(status, StatusCode::NOT_MODIFIED);
+    assert_eq!(headers["etag"], etag.as_str());
+    assert!(body.is_empty());
+
+    // 按请求尺寸选择不小于它的最小一档
+    let small_uri = format!("{}&size=50", avatar);
+    let (status, headers, body) = get_avatar(&mut app, &small_uri, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_ne!(headers["etag"], etag.as_str());
+    let img = image::load_from_memory(&body).unwrap();
+    assert_eq!((img.width(), img.height()), (64, 64));
+
+    let bad = upload_avatar_text(&mut app, &cookie_header, "data:image/jpeg;base64,ZmFrZQ==").await;
+    assert_eq!(bad.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(user_info_avatar(&mut app, &cookie_header).await, avatar);
+
+    let (status, _, _) = get_avatar(&mut app, "/api/user/avatar/999999999", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_legacy_base64_avatars_are_migrated_on_startup() {
+    use sea_orm::{ConnectionTrait, Database, DbBackend, Statement};
+
+    let temp = tempfile::TempDir::new().unwrap();
+    let mut config = AppConfig::default();
+    config.db.path = temp.path().join("sport.db").to_string_lossy().to_string();
+    let mut app = app::create_app(config.clone()).await;
+    let cookie_a = register_and_get_cookie(&mut app, "legacy_avatar_a", "A", "p@ssw0rd").await;
+    let cookie_b = register_and_get_cookie(&mut app, "legacy_avatar_b", "B", "p@ssw0rd").await;
+
+    // 模拟旧版本写入的 base64 头像：A 在 users.avatar 列，B 在 avatars 表且内容无法解码
+    let conn = Database::connect(format!("sqlite://{}", config.db.path))
+        .await
+        .unwrap();
+    conn.execute(Statement::from_sql_and_values(
+        DbBackend::Sqlite,
+        "UPDATE users SET avatar = ? WHERE name LIKE 'legacy_avatar_a_%'",
+        [avatar_data_url().into()],
+    ))
+    .await
+    .unwrap();
+    conn.execute(Statement::from_string(
+        DbBackend::Sqlite,
+        "INSERT INTO avatars (uid, data) SELECT id, 'data:image/jpeg;base64,ZmFrZQ==' \
+         FROM users WHERE name LIKE 'legacy_avatar_b_%'",
+    ))
+    .await
+    .unwrap();
+
+    let mut app = app::create_app(config.clone()).await;
+    let avatar = user_info_avatar(&mut app, &cookie_a).await;
+    assert!(avatar.starts_with("/api/user/avatar/"), "{avatar}");
+    let (status, headers, _) = get_avatar(&mut app, &avatar, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(headers["content-type"], "image/jpeg");
+    assert_eq!(user_info_avatar(&mut app, &cookie_b).await, "");
+
+    let row = conn
+        .query_one(Statement::from_string(
+            DbBackend::Sqlite,
+            "SELECT (SELECT COUNT(*) FROM users WHERE avatar != '') + \
+             (SELECT COUNT(*) FROM avatars) AS legacy",
+        ))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(row.try_get::<i64>("", "legacy").unwrap(), 0);
+}
+
 #[tokio::test]
 async fn test_llm_mock_set_result_controls_response() {
     let mock = Arc::new(TestMockLLM::new());
@@ -1062,13 +1625,13 @@ async fn test_image_endpoint_comparison() {
     );
     println!("{:-<30}-+-{:-<10}-+-{:-<10}", "", "", "");
 
-    for model in models {
+    for (idx, model) in models.into_iter().enumerate() {
         let mut config = AppConfig::default();
         config.ai.model = model.to_string();
 
         let mut app = app::create_app(config).await;
 
-        let prefix = format!("test_{}", model.replace("-", "_"));
+        let prefix = format!("test_model_{idx}");
         let cookie_header =
             register_and_get_cookie(&mut app, &prefix, "ImageUser", "p@ssw0rd").await;
 
@@ -1146,3 +1709,176 @@ async fn test_image_endpoint_comparison() {
         }
     }
 }
+
+/// 以 cookie 登录态插入一条运动记录，附带额外请求头，返回状态码
+async fn insert_sport_with_headers(
+    app: &mut axum::Router,
+    cookie: &str,
+    headers: &[(&str, &str)],
+) -> StatusCode {
+    let sport_body = serde_json::json!({
+        "type": "Running",
+        "start_time": Utc::now().timestamp(),
+        "calories": 100,
+        "distance_meter": 1000,
+        "duration_second": 360,
+        "heart_rate_avg": 140,
+        "heart_rate_max": 160,
+        "pace_average": "6'00''"
+    });
+    let mut builder = Request::builder()
+        .uri(routes::API_SPORT_INSERT)
+        .method("POST")
+        .header("Content-Type", "application/json")
+        .header("Host", "slam.example.com")
+        .header("Cookie", cookie);
+    for (k, v) in headers {
+        builder = builder.header(*k, *v);
+    }
+    let resp = app
+        .call(builder.body(Body::from(sport_body.to_string())).unwrap())
+        .await
+        .unwrap();
+    print_response("CSRF 运动插入", resp).await.0
+}
+
+#[tokio::test]
+async fn test_csrf_origin_and_referer_checks() {
+    let mut config = AppConfig::default();
+    config.security.csrf.allowed_origins = vec!["https://app.example.com/".to_string()];
+    let mut app = app::create_app(config).await;
+    let cookie = register_and_get_cookie(&mut app, "csrf_origin", "Csrf", "p@ssw0rd").await;
+
+    // 非浏览器客户端不带 Origin/Referer，不受影响
+    assert_eq!(
+        insert_sport_with_headers(&mut app, &cookie, &[]).await,
+        StatusCode::OK
+    );
+    for headers in [
+        [("Origin", "https://slam.example.com")],
+        [("Origin", "https://app.example.com")],
+        [("Referer", "https://slam.example.com/sports?page=1")],
+    ] {
+        assert_eq!(
+            insert_sport_with_headers(&mut app, &cookie, &headers).await,
+            StatusCode::OK
+        );
+    }
+    for headers in [
+        [("Origin", "https://evil.example.com")],
+        [("Origin", "null")],
+        [("Origin", "https://slam.example.com.evil.example")],
+        [("Referer", "https://evil.example.com/form.html")],
+    ] {
+        assert_eq!(
+            insert_sport_with_headers(&mut app, &cookie, &headers).await,
+            StatusCode::FORBIDDEN
+        );
+    }
+    // Origin 优先于 Referer
+    assert_eq!(
+        insert_sport_with_headers(
+            &mut app,
+            &cookie,
+            &[
+                ("Origin", "https://evil.example.com"),
+                ("Referer", "https://slam.example.com/")
+            ]
+        )
+        .await,
+        StatusCode::FORBIDDEN
+    );
+
+    // 读请求不做来源检查
+    let req = Request::builder()
+        .uri(format!("{}?page=0&size=20", routes::API_SPORT_LIST))
+        .method("GET")
+        .header("Cookie", cookie.clone())
+        .header("Origin", "https://evil.example.com")
+        .body(Body::empty())
+        .unwrap();
+    assert_eq!(app.call(req).await.unwrap().status(), StatusCode::OK);
+
+    // 使用个人访问令牌的请求不受 CSRF 限制
+    let req = Request::builder()
+        .uri(routes::API_USER_TOKENS)
+        .method("POST")
+        .header("Content-Type", "application/json")
+        .header("Cookie", cookie.clone())
+        .body(Body::from(
+            serde_json::json!({ "name": "csrf", "scopes": ["sports:write"] }).to_string(),
+        ))
+        .unwrap();
+    let (_, body) = print_response("创建访问令牌", app.call(req).await.unwrap()).await;
+    let token: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let bearer = format!("Bearer {}", token["token"].as_str().unwrap());
+    assert_eq!(
+        insert_sport_with_headers(
+            &mut app,
+            "",
+            &[
+                ("Authorization", bearer.as_str()),
+                ("Origin", "https://evil.example.com")
+            ]
+        )
+        .await,
+        StatusCode::OK
+    );
+}
+
+#[tokio::test]
+async fn test_csrf_double_submit_token() {
+    let mut config = AppConfig::default();
+    config.security.csrf.double_submit = true;
+    let mut app = app::create_app(config).await;
+
+    // 未登录的请求（如登录、注册）不要求令牌，响应中下发令牌 cookie
+    let unique = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis();
+    let register_body = serde_json::json!({
+        "name": format!("csrf_token_{unique}"),
+        "password": "p@ssw0rd",
+        "nickname": "Csrf"
+    });
+    let req = Request::builder()
+        .uri(routes::API_USER_REGISTER)
+        .method("POST")
+        .header("Content-Type", "application/json")
+        .body(Body::from(register_body.to_string()))
+        .unwrap();
+    let resp = app.call(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let cookies: Vec<String> = resp
+        .headers()
+        .get_all("set-cookie")
+        .iter()
+        .map(|v| v.to_str().unwrap().split(';').next().unwrap().to_string())
+        .collect();
+    let session = cookies.iter().find(|c| c.starts_with("slam=")).unwrap();
+    let csrf = cookies
+        .iter()
+        .find(|c| c.starts_with("slam_csrf="))
+        .unwrap();
+    let token = csrf.trim_start_matches("slam_csrf=");
+    let cookie = format!("{session}; {csrf}");
+
+    assert_eq!(
+        insert_sport_with_headers(&mut app, &cookie, &[]).await,
+        StatusCode::FORBIDDEN
+    );
+    assert_eq!(
+        insert_sport_with_headers(&mut app, &cookie, &[("X-CSRF-Token", "forged")]).await,
+        StatusCode::FORBIDDEN
+    );
+    // 只有请求头、没有对应 cookie 也不行
+    assert_eq!(
+        insert_sport_with_headers(&mut app, session, &[("X-CSRF-Token", token)]).await,
+        StatusCode::FORBIDDEN
+    );
+    assert_eq!(
+        insert_sport_with_headers(&mut app, &cookie, &[("X-CSRF-Token", token)]).await,
+        StatusCode::OK
+    );
+}
@@ -0,0 +1,1349 @@
+use aes::Aes256;
+use std::net::SocketAddr;
+
+use axum::body::{Body, to_bytes};
+use axum::extract::ConnectInfo;
+use axum::http::{Request, StatusCode};
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64_ENGINE};
+use cbc::Encryptor;
+use cipher::{BlockEncryptMut, KeyIvInit, block_padding::Pkcs7};
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use sea_orm::ConnectionTrait;
+use sha2::{Digest, Sha256};
+use slam_server::app::{self, AppConfig, routes};
+use slam_server::config::{JwtKeyConfig, RegistrationMode, SecurityConfig};
+use slam_server::dao::Repository;
+use slam_server::dao::idl::UserDao;
+use slam_server::handlers::jwt_keys::JwtKeySet;
+use slam_server::model::user::User;
+use slam_server::service::totp;
+use tempfile::TempDir;
+use tower::Service;
+
+fn isolated_config(temp: &TempDir) -> AppConfig {
+    let mut config = AppConfig::default();
+    config.db.path = temp.path().join("sport.db").to_string_lossy().to_string();
+    config.ai.job_dir = temp.path().join("ai-jobs").to_string_lossy().to_string();
+    config
+}
+
+async fn response_json(response: axum::response::Response) -> (StatusCode, serde_json::Value) {
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+    let value = serde_json::from_slice(&bytes).unwrap_or_else(
+        |_| serde_json::json!({ "raw": String::from_utf8_lossy(&bytes).to_string() }),
+    );
+    (status, value)
+}
+
+async fn post_json(
+    app: &mut axum::Router,
+    uri: &str,
+    body: serde_json::Value,
+) -> (StatusCode, serde_json::Value) {
+    let request = Request::builder()
+        .uri(uri)
+        .method("POST")
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    response_json(app.call(request).await.unwrap()).await
+}
+
+async fn login(app: &mut axum::Router, name: &str, password: &str) -> StatusCode {
+    let body = serde_json::json!({ "name": name, "password": password });
+    post_json(app, routes::API_USER_LOGIN, body).await.0
+}
+
+/// 升级前的密码存储格式：固定密钥与 IV 的 AES-256-CBC 密文
+fn legacy_ciphertext(security: &SecurityConfig, password: &str) -> String {
+    let key: [u8; 32] = Sha256::digest(security.key.as_bytes()).into();
+    let iv_full = Sha256::digest(format!("iv:{}", security.salt).as_bytes());
+    let mut iv = [0u8; 16];
+    iv.copy_from_slice(&iv_full[..16]);
+    let mut buf = password.as_bytes().to_vec();
+    buf.extend(std::iter::repeat_n(0u8, 16 - password.len() % 16));
+    let ct = Encryptor::<Aes256>::new(&key.into(), &iv.into())
+        .encrypt_padded_mut::<Pkcs7>(&mut buf, password.len())
+        .unwrap();
+    BASE64_ENGINE.encode(ct)
+}
+
+#[tokio::test]
+async fn register_stores_salted_argon2id_hash() {
+    let temp = TempDir::new().unwrap();
+    let config = isolated_config(&temp);
+    let mut app = app::create_app(config.clone()).await;
+    for name in ["alice", "bob"] {
+        let body = serde_json::json!({ "name": name, "password": "same-secret", "nickname": name });
+        let (status, _) = post_json(&mut app, routes::API_USER_REGISTER, body).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let repository = Repository::new(&config.db.path).await.unwrap();
+    let alice = repository.get_by_name("alice").await.unwrap().unwrap();
+    let bob = repository.get_by_name("bob").await.unwrap().unwrap();
+    assert!(alice.password.starts_with("$argon2id$"));
+    assert!(bob.password.starts_with("$argon2id$"));
+    assert_ne!(alice.password, bob.password);
+    assert_ne!(
+        alice.password,
+        legacy_ciphertext(&config.security, "same-secret")
+    );
+
+    assert_eq!(
+        login(&mut app, "alice", "same-secret").await,
+        StatusCode::OK
+    );
+    assert_ne!(
+        login(&mut app, "alice", "wrong-secret").await,
+        StatusCode::OK
+    );
+    assert_ne!(
+        login(&mut app, "nobody", "same-secret").await,
+        StatusCode::OK
+    );
+}
+
+#[tokio::test]
+async fn register_validates_fields_and_rejects_taken_name() {
+    let temp = TempDir::new().unwrap();
+    let config = isolated_config(&temp);
+    let mut app = app::create_app(config).await;
+
+    let body = serde_json::json!({ "name": "", "password": "", "nickname": "  " });
+    let (status, json) = post_json(&mut app, routes::API_USER_REGISTER, body).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(json["fields"]["name"].is_string());
+    assert!(json["fields"]["password"].is_string());
+    assert!(json["fields"]["nickname"].is_string());
+
+    for (name, password) in [
+        ("_hidden", "p@ssw0rd"),
+        ("bad name", "p@ssw0rd"),
+        ("ab", "p@ssw0rd"),
+        ("weakling", "password"),
+        ("weakling", "12345678"),
+        ("weakling", "p@ss1"),
+        ("weakling1", "Weakling1"),
+    ] {
+        let body = serde_json::json!({ "name": name, "password": password, "nickname": "n" });
+        let (status, json) = post_json(&mut app, routes::API_USER_REGISTER, body).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{name} / {password}");
+        assert_eq!(json["fields"].as_object().unwrap().len(), 1, "{json}");
+    }
+
+    let body = serde_json::json!({ "name": "harry", "password": "p@ssw0rd", "nickname": "Harry" });
+    let (status, _) = post_json(&mut app, routes::API_USER_REGISTER, body.clone()).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, json) = post_json(&mut app, routes::API_USER_REGISTER, body).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert!(json["error"].is_string());
+}
+
+#[tokio::test]
+async fn invite_and_closed_registration_modes() {
+    let temp = TempDir::new().unwrap();
+    let mut config = isolated_config(&temp);
+    config.security.registration_mode = RegistrationMode::Invite;
+    config.security.invite_codes = vec!["friends-2026".to_string()];
+    let mut app = app::create_app(config.clone()).await;
+
+    let body = serde_json::json!({ "name": "ivan", "password": "p@ssw0rd", "nickname": "Ivan" });
+    let (status, _) = post_json(&mut app, routes::API_USER_REGISTER, body).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let body = serde_json::json!({
+        "name": "ivan", "password": "p@ssw0rd", "nickname": "Ivan", "invite_code": "guess"
+    });
+    let (status, _) = post_json(&mut app, routes::API_USER_REGISTER, body).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let body = serde_json::json!({
+        "name": "ivan", "password": "p@ssw0rd", "nickname": "Ivan", "invite_code": "friends-2026"
+    });
+    let (status, _) = post_json(&mut app, routes::API_USER_REGISTER, body).await;
+    assert_eq!(status, StatusCode::OK);
+
+    config.security.registration_mode = RegistrationMode::Closed;
+    let mut app = app::create_app(config).await;
+    let body = serde_json::json!({
+        "name": "judy", "password": "p@ssw0rd", "nickname": "Judy", "invite_code": "friends-2026"
+    });
+    let (status, _) = post_json(&mut app, routes::API_USER_REGISTER, body).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(login(&mut app, "ivan", "p@ssw0rd").await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn legacy_password_key_survives_a_changed_security_key() {
+    let temp = TempDir::new().unwrap();
+    let mut config = isolated_config(&temp);
+    let legacy = legacy_ciphertext(&config.security, "old-secret");
+    config.security.legacy_password_key = Some(config.security.key.clone());
+    config.security.key = "a-new-jwt-key".to_string();
+    let mut app = app::create_app(config.clone()).await;
+    let repository = Repository::new(&config.db.path).await.unwrap();
+    repository
+        .insert(User {
+            id: 0,
+            name: "oldtimer".to_string(),
+            password: legacy,
+            nickname: "oldtimer".to_string(),
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(
+        login(&mut app, "oldtimer", "old-secret").await,
+        StatusCode::OK
+    );
+    let upgraded = repository.get_by_name("oldtimer").await.unwrap().unwrap();
+    assert!(upgraded.password.starts_with("$argon2id$"));
+}
+
+#[tokio::test]
+async fn legacy_password_is_rehashed_on_successful_login() {
+    let temp = TempDir::new().unwrap();
+    let config = isolated_config(&temp);
+    let mut app = app::create_app(config.clone()).await;
+    let repository = Repository::new(&config.db.path).await.unwrap();
+    let legacy = legacy_ciphertext(&config.security, "old-secret");
+    repository
+        .insert(User {
+            id: 0,
+            name: "veteran".to_string(),
+            password: legacy.clone(),
+            nickname: "veteran".to_string(),
+        })
+        .await
+        .unwrap();
+
+    assert_ne!(
+        login(&mut app, "veteran", "wrong-secret").await,
+        StatusCode::OK
+    );
+    let stored = repository.get_by_name("veteran").await.unwrap().unwrap();
+    assert_eq!(stored.password, legacy);
+
+    assert_eq!(
+        login(&mut app, "veteran", "old-secret").await,
+        StatusCode::OK
+    );
+    let upgraded = repository.get_by_name("veteran").await.unwrap().unwrap();
+    assert!(upgraded.password.starts_with("$argon2id$"));
+
+    assert_eq!(
+        login(&mut app, "veteran", "old-secret").await,
+        StatusCode::OK
+    );
+    assert_ne!(
+        login(&mut app, "veteran", "wrong-secret").await,
+        StatusCode::OK
+    );
+}
+
+async fn register_with(app: &mut axum::Router, name: &str, headers: &[(&str, &str)]) -> String {
+    let mut builder = Request::builder()
+        .uri(routes::API_USER_REGISTER)
+        .method("POST")
+        .header("content-type", "application/json");
+    for (k, v) in headers {
+        builder = builder.header(*k, *v);
+    }
+    let body = serde_json::json!({ "name": name, "password": "p@ssw0rd", "nickname": name });
+    let response = app
+        .call(builder.body(Body::from(body.to_string())).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    session_cookie_of(&response).expect("register set-cookie")
+}
+
+async fn login_cookie(app: &mut axum::Router, name: &str, headers: &[(&str, &str)]) -> String {
+    let mut builder = Request::builder()
+        .uri(routes::API_USER_LOGIN)
+        .method("POST")
+        .header("content-type", "application/json");
+    for (k, v) in headers {
+        builder = builder.header(*k, *v);
+    }
+    let body = serde_json::json!({ "name": name, "password": "p@ssw0rd" });
+    let response = app
+        .call(builder.body(Body::from(body.to_string())).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    session_cookie_of(&response).expect("login set-cookie")
+}
+
+fn session_cookie_of(response: &axum::response::Response) -> Option<String> {
+    response
+        .headers()
+        .get_all("set-cookie")
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .find(|v| v.starts_with("slam="))
+        .map(|v| v.split(';').next().unwrap().to_string())
+}
+
+async fn call_with_cookie(
+    app: &mut axum::Router,
+    method: &str,
+    uri: &str,
+    cookie: &str,
+) -> axum::response::Response {
+    let request = Request::builder()
+        .uri(uri)
+        .method(method)
+        .header("cookie", cookie)
+        .body(Body::empty())
+        .unwrap();
+    app.call(request).await.unwrap()
+}
+
+#[tokio::test]
+async fn sessions_can_be_listed_revoked_and_logged_out_everywhere() {
+    let temp = TempDir::new().unwrap();
+    let mut config = isolated_config(&temp);
+    config.security.trusted_proxies = vec!["10.0.0.0/8".to_string()];
+    let mut app = app::create_app(config).await;
+    let chrome_mac = "Mozilla/5.0 (Macintosh; Intel Mac OS X 14_0) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0 Safari/537.36";
+    let laptop = register_with(&mut app, "carol", &[("user-agent", chrome_mac)]).await;
+    let response = login_via(
+        &mut app,
+        "carol",
+        "p@ssw0rd",
+        "10.0.0.2",
+        &[
+            (
+                "user-agent",
+                "Mozilla/5.0 (iPhone; CPU iPhone OS 17_0) Safari/604.1",
+            ),
+            ("x-forwarded-for", "203.0.113.9, 10.0.0.1"),
+        ],
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let phone = session_cookie_of(&response).unwrap();
+
+    let response = call_with_cookie(&mut app, "GET", routes::API_USER_SESSIONS, &laptop).await;
+    let (status, json) = response_json(response).await;
+    assert_eq!(status, StatusCode::OK);
+    let sessions = json.as_array().unwrap();
+    assert_eq!(sessions.len(), 2);
+    let current = sessions.iter().find(|s| s["current"] == true).unwrap();
+    assert_eq!(current["device"], "Chrome on macOS");
+    assert_eq!(current["user_agent"], chrome_mac);
+    let other = sessions.iter().find(|s| s["current"] == false).unwrap();
+    assert_eq!(other["device"], "Safari on iOS");
+    assert_eq!(other["ip"], "203.0.113.9");
+
+    // 撤销手机会话后，手机上的 token 立即失效
+    let uri = format!("/api/user/sessions/{}", other["id"].as_str().unwrap());
+    let response = call_with_cookie(&mut app, "DELETE", &uri, &laptop).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = call_with_cookie(&mut app, "GET", routes::API_USER_INFO, &phone).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let response = call_with_cookie(&mut app, "DELETE", &uri, &laptop).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    // 注销后即使保留旧 cookie 也无法继续使用
+    let response = call_with_cookie(&mut app, "POST", routes::API_USER_LOGOUT, &laptop).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = call_with_cookie(&mut app, "GET", routes::API_USER_INFO, &laptop).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let first = login_cookie(&mut app, "carol", &[]).await;
+    let second = login_cookie(&mut app, "carol", &[]).await;
+    let response = call_with_cookie(&mut app, "POST", routes::API_USER_LOGOUT_ALL, &first).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    for cookie in [&first, &second] {
+        let response = call_with_cookie(&mut app, "GET", routes::API_USER_INFO, cookie).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+}
+
+#[tokio::test]
+async fn token_past_half_life_is_refreshed_and_legacy_token_rejected() {
+    let temp = TempDir::new().unwrap();
+    let config = isolated_config(&temp);
+    let mut app = app::create_app(config.clone()).await;
+    let cookie = register_with(&mut app, "dave", &[]).await;
+
+    let response = call_with_cookie(&mut app, "GET", routes::API_USER_INFO, &cookie).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(session_cookie_of(&response).is_none());
+
+    let response = call_with_cookie(&mut app, "GET", routes::API_USER_SESSIONS, &cookie).await;
+    let (_, json) = response_json(response).await;
+    let session = json[0].clone();
+    let claims: serde_json::Value = jsonwebtoken::decode::<serde_json::Value>(
+        cookie.trim_start_matches("slam="),
+        &DecodingKey::from_secret(config.security.key.as_bytes()),
+        &Validation::new(Algorithm::HS256),
+    )
+    .unwrap()
+    .claims;
+
+    // 构造一个已过半衰期的 token，请求后应下发新的 cookie 并延长会话
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    let ttl = config.security.jwt_ttl_seconds as i64;
+    let sign = |claims: serde_json::Value| {
+        jsonwebtoken::encode(
+            &Header::new(Algorithm::HS256),
+            &claims,
+            &EncodingKey::from_secret(config.security.key.as_bytes()),
+        )
+        .unwrap()
+    };
+    let aged = sign(serde_json::json!({
+        "uid": claims["uid"],
+        "jti": session["id"],
+        "iat": now - ttl + 60,
+        "exp": now + 60,
+    }));
+    let response = call_with_cookie(
+        &mut app,
+        "GET",
+        routes::API_USER_INFO,
+        &format!("slam={aged}"),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let renewed = session_cookie_of(&response).expect("refreshed cookie");
+    assert_ne!(renewed, format!("slam={aged}"));
+    let response = call_with_cookie(&mut app, "GET", routes::API_USER_SESSIONS, &renewed).await;
+    let (status, json) = response_json(response).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json[0]["id"], session["id"]);
+    assert!(json[0]["expires_at"].as_i64().unwrap() >= now + ttl);
+
+    // 没有会话 id 的旧版 token 不再被接受
+    let legacy = sign(serde_json::json!({
+        "uid": claims["uid"],
+        "iat": now,
+        "exp": now + ttl,
+    }));
+    let response = call_with_cookie(
+        &mut app,
+        "GET",
+        routes::API_USER_INFO,
+        &format!("slam={legacy}"),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+async fn call_with_bearer(
+    app: &mut axum::Router,
+    method: &str,
+    uri: &str,
+    token: &str,
+    body: Option<serde_json::Value>,
+) -> axum::response::Response {
+    let request = Request::builder()
+        .uri(uri)
+        .method(method)
+        .header("authorization", format!("Bearer {token}"))
+        .header("content-type", "application/json")
+        .body(body.map(|b| Body::from(b.to_string())).unwrap_or_default())
+        .unwrap();
+    app.call(request).await.unwrap()
+}
+
+async fn create_token(
+    app: &mut axum::Router,
+    cookie: &str,
+    body: serde_json::Value,
+) -> (StatusCode, serde_json::Value) {
+    let request = Request::builder()
+        .uri(routes::API_USER_TOKENS)
+        .method("POST")
+        .header("cookie", cookie)
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    response_json(app.call(request).await.unwrap()).await
+}
+
+#[tokio::test]
+async fn personal_access_tokens_enforce_scopes_per_route() {
+    let temp = TempDir::new().unwrap();
+    let mut app = app::create_app(isolated_config(&temp)).await;
+    let cookie = register_with(&mut app, "erin", &[]).await;
+
+    let (status, _) = create_token(
+        &mut app,
+        &cookie,
+        serde_json::json!({ "name": "bad", "scopes": ["admin:all"] }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, reader) = create_token(
+        &mut app,
+        &cookie,
+        serde_json::json!({ "name": "dashboard", "scopes": ["sports:read"] }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let read_token = reader["token"].as_str().unwrap().to_string();
+    assert!(read_token.starts_with("slam_pat_"));
+    let (_, writer) = create_token(
+        &mut app,
+        &cookie,
+        serde_json::json!({ "name": "import script", "scopes": ["sports:write"], "expires_in_days": 7 }),
+    )
+    .await;
+    let write_token = writer["token"].as_str().unwrap().to_string();
+
+    let response = call_with_cookie(&mut app, "GET", routes::API_USER_TOKENS, &cookie).await;
+    let (status, json) = response_json(response).await;
+    assert_eq!(status, StatusCode::OK);
+    let tokens = json.as_array().unwrap();
+    assert_eq!(tokens.len(), 2);
+    assert!(tokens.iter().all(|t| t.get("token").is_none()));
+    assert!(read_token.starts_with(reader["token_hint"].as_str().unwrap()));
+
+    let sport = serde_json::json!({
+        "type": "Running",
+        "start_time": 1_735_700_000,
+        "calories": 300,
+        "distance_meter": 5000,
+        "duration_second": 1500,
+        "heart_rate_avg": 150,
+        "heart_rate_max": 170,
+        "pace_average": ""
+    });
+    let response = call_with_bearer(
+        &mut app,
+        "POST",
+        routes::API_SPORT_INSERT,
+        &read_token,
+        Some(sport.clone()),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let response = call_with_bearer(
+        &mut app,
+        "POST",
+        routes::API_SPORT_INSERT,
+        &write_token,
+        Some(sport),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let list_uri = format!("{}?page=0&size=10", routes::API_SPORT_LIST);
+    let response = call_with_bearer(&mut app, "GET", &list_uri, &read_token, None).await;
+    let (status, json) = response_json(response).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json.as_array().unwrap().len(), 1);
+    let response = call_with_bearer(&mut app, "GET", &list_uri, &write_token, None).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    // 账号管理类接口不接受个人访问令牌
+    let response =
+        call_with_bearer(&mut app, "GET", routes::API_USER_TOKENS, &read_token, None).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let response = call_with_bearer(
+        &mut app,
+        "GET",
+        &list_uri,
+        "slam_pat_not-a-real-token",
+        None,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let uri = format!("/api/user/tokens/{}", reader["id"].as_str().unwrap());
+    let response = call_with_cookie(&mut app, "DELETE", &uri, &cookie).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = call_with_bearer(&mut app, "GET", &list_uri, &read_token, None).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+async fn send_json_with_cookie(
+    app: &mut axum::Router,
+    method: &str,
+    uri: &str,
+    cookie: &str,
+    body: serde_json::Value,
+) -> (StatusCode, serde_json::Value) {
+    let request = Request::builder()
+        .uri(uri)
+        .method(method)
+        .header("cookie", cookie)
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    response_json(app.call(request).await.unwrap()).await
+}
+
+#[tokio::test]
+async fn password_change_keeps_current_session_and_profile_is_editable() {
+    let temp = TempDir::new().unwrap();
+    let mut app = app::create_app(isolated_config(&temp)).await;
+    let laptop = register_with(&mut app, "frank", &[]).await;
+    let phone = login_cookie(&mut app, "frank", &[]).await;
+
+    let (status, _) = send_json_with_cookie(
+        &mut app,
+        "POST",
+        routes::API_USER_PASSWORD,
+        &laptop,
+        serde_json::json!({ "old_password": "wrong", "new_password": "n3w-secret" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = send_json_with_cookie(
+        &mut app,
+        "POST",
+        routes::API_USER_PASSWORD,
+        &laptop,
+        serde_json::json!({ "old_password": "p@ssw0rd", "new_password": "n3w-secret" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_ne!(login(&mut app, "frank", "p@ssw0rd").await, StatusCode::OK);
+    assert_eq!(login(&mut app, "frank", "n3w-secret").await, StatusCode::OK);
+    let response = call_with_cookie(&mut app, "GET", routes::API_USER_INFO, &phone).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let (status, _) = send_json_with_cookie(
+        &mut app,
+        "PUT",
+        routes::API_USER_PROFILE,
+        &laptop,
+        serde_json::json!({ "nickname": "  ", "bio": "" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = send_json_with_cookie(
+        &mut app,
+        "PUT",
+        routes::API_USER_PROFILE,
+        &laptop,
+        serde_json::json!({ "nickname": "Frankie", "bio": "marathon in spring" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let response = call_with_cookie(&mut app, "GET", routes::API_USER_INFO, &laptop).await;
+    let (status, json) = response_json(response).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["nickname"], "Frankie");
+    assert_eq!(json["bio"], "marathon in spring");
+}
+
+/// 以 peer 为连接对端地址登录，headers 为附加请求头
+async fn login_via(
+    app: &mut axum::Router,
+    name: &str,
+    password: &str,
+    peer: &str,
+    headers: &[(&str, &str)],
+) -> axum::response::Response {
+    let peer: SocketAddr = format!("{peer}:40000").parse().unwrap();
+    let mut builder = Request::builder()
+        .uri(routes::API_USER_LOGIN)
+        .method("POST")
+        .header("content-type", "application/json")
+        .extension(ConnectInfo(peer));
+    for (k, v) in headers {
+        builder = builder.header(*k, *v);
+    }
+    let request = builder
+        .body(Body::from(
+            serde_json::json!({ "name": name, "password": password }).to_string(),
+        ))
+        .unwrap();
+    app.call(request).await.unwrap()
+}
+
+async fn login_from(
+    app: &mut axum::Router,
+    name: &str,
+    password: &str,
+    ip: &str,
+) -> axum::response::Response {
+    login_via(app, name, password, ip, &[]).await
+}
+
+#[tokio::test]
+async fn repeated_login_failures_lock_account_and_ip_with_backoff() {
+    let temp = TempDir::new().unwrap();
+    let mut config = isolated_config(&temp);
+    config.security.login_throttle.max_failures_per_account = 3;
+    config.security.login_throttle.max_failures_per_ip = 5;
+    config.security.login_throttle.lockout_base_seconds = 1;
+    let db_path = config.db.path.clone();
+    let mut app = app::create_app(config).await;
+    register_with(&mut app, "grace", &[]).await;
+
+    for _ in 0..2 {
+        let response = login_from(&mut app, "grace", "wrong", "198.51.100.1").await;
+        assert_ne!(response.status(), StatusCode::OK);
+        assert_ne!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+    let response = login_from(&mut app, "grace", "wrong", "198.51.100.2").await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(response.headers()["retry-after"], "1");
+    // 锁定期内即使密码正确也被拒绝
+    let response = login_from(&mut app, "grace", "p@ssw0rd", "198.51.100.3").await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    // 锁定结束后再次失败，锁定时长翻倍
+    tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+    let response = login_from(&mut app, "grace", "wrong", "198.51.100.3").await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(response.headers()["retry-after"], "2");
+    tokio::time::sleep(std::time::Duration::from_millis(2100)).await;
+    let response = login_from(&mut app, "grace", "p@ssw0rd", "198.51.100.3").await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // 同一 IP 尝试多个账号也会被限制，其他 IP 不受影响
+    for name in ["h1", "h2", "h3", "h4"] {
+        let response = login_from(&mut app, name, "wrong", "203.0.113.7").await;
+        assert_ne!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+    let response = login_from(&mut app, "h5", "wrong", "203.0.113.7").await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    let response = login_from(&mut app, "grace", "p@ssw0rd", "203.0.113.7").await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    let response = login_from(&mut app, "grace", "p@ssw0rd", "203.0.113.8").await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let conn = sea_orm::Database::connect(format!("sqlite://{db_path}"))
+        .await
+        .unwrap();
+    let rows = conn
+        .query_all(sea_orm::Statement::from_string(
+            sea_orm::DbBackend::Sqlite,
+            "SELECT uid, ip, detail FROM audit_logs WHERE action = 'login_lockout' ORDER BY id",
+        ))
+        .await
+        .unwrap();
+    assert_eq!(rows.len(), 3);
+    let uid: Option<i32> = rows[0].try_get("", "uid").unwrap();
+    assert!(uid.is_some());
+    let ip: String = rows[2].try_get("", "ip").unwrap();
+    assert_eq!(ip, "203.0.113.7");
+}
+
+/// 登录响应所建立会话记录的 IP
+async fn latest_session_ip(app: &mut axum::Router, response: axum::response::Response) -> String {
+    assert_eq!(response.status(), StatusCode::OK);
+    let cookie = session_cookie_of(&response).unwrap();
+    let response = call_with_cookie(app, "GET", routes::API_USER_SESSIONS, &cookie).await;
+    let (status, sessions) = response_json(response).await;
+    assert_eq!(status, StatusCode::OK);
+    let current = sessions
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|s| s["current"] == true)
+        .unwrap();
+    current["ip"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn spoofed_forwarding_headers_do_not_bypass_ip_lockout() {
+    let temp = TempDir::new().unwrap();
+    let mut config = isolated_config(&temp);
+    config.security.login_throttle.max_failures_per_ip = 3;
+    config.security.trusted_proxies = vec!["10.0.0.0/8".to_string()];
+    let mut app = app::create_app(config).await;
+    register_with(&mut app, "victim", &[]).await;
+
+    // 直连的客户端每次换一个伪造地址，仍按真实对端地址计数
+    for (i, name) in ["m1", "m2"].iter().enumerate() {
+        let spoofed = format!("198.18.0.{i}");
+        let response = login_via(
+            &mut app,
+            name,
+            "wrong",
+            "198.51.100.20",
+            &[("x-real-ip", &spoofed), ("x-forwarded-for", &spoofed)],
+        )
+        .await;
+        assert_ne!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+    let response = login_via(
+        &mut app,
+        "m3",
+        "wrong",
+        "198.51.100.20",
+        &[("x-real-ip", "198.18.0.9")],
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    // 冒用受害者的地址不会把受害者锁在外面
+    for name in ["v1", "v2", "v3", "v4"] {
+        login_via(
+            &mut app,
+            name,
+            "wrong",
+            "198.51.100.21",
+            &[("x-forwarded-for", "203.0.113.50")],
+        )
+        .await;
+    }
+    let response = login_from(&mut app, "victim", "p@ssw0rd", "203.0.113.50").await;
+    assert_eq!(latest_session_ip(&mut app, response).await, "203.0.113.50");
+    let response = login_via(
+        &mut app,
+        "victim",
+        "p@ssw0rd",
+        "198.51.100.22",
+        &[("x-real-ip", "203.0.113.50")],
+    )
+    .await;
+    assert_eq!(latest_session_ip(&mut app, response).await, "198.51.100.22");
+}
+
+#[tokio::test]
+async fn trusted_proxy_forwarding_headers_resolve_the_client_ip() {
+    let temp = TempDir::new().unwrap();
+    let mut config = isolated_config(&temp);
+    config.security.trusted_proxies = vec!["10.0.0.0/8".to_string(), "192.0.2.1".to_string()];
+    let mut app = app::create_app(config).await;
+    register_with(&mut app, "nina", &[]).await;
+
+    // 客户端自带的 X-Forwarded-For 条目在左侧，取最右侧的非可信代理地址
+    let response = login_via(
+        &mut app,
+        "nina",
+        "p@ssw0rd",
+        "10.0.0.2",
+        &[("x-forwarded-for", "198.18.0.1, 203.0.113.60")],
+    )
+    .await;
+    assert_eq!(latest_session_ip(&mut app, response).await, "203.0.113.60");
+    // 多级代理
+    let response = login_via(
+        &mut app,
+        "nina",
+        "p@ssw0rd",
+        "10.0.0.2",
+        &[("x-forwarded-for", "203.0.113.61, 192.0.2.1, 10.0.0.3")],
+    )
+    .await;
+    assert_eq!(latest_session_ip(&mut app, response).await, "203.0.113.61");
+    // 没有 X-Forwarded-For 时采用 X-Real-IP
+    let response = login_via(
+        &mut app,
+        "nina",
+        "p@ssw0rd",
+        "10.0.0.2",
+        &[("x-real-ip", "203.0.113.62")],
+    )
+    .await;
+    assert_eq!(latest_session_ip(&mut app, response).await, "203.0.113.62");
+    // 代理本身没有转发头时就是对端地址
+    let response = login_via(&mut app, "nina", "p@ssw0rd", "10.0.0.2", &[]).await;
+    assert_eq!(latest_session_ip(&mut app, response).await, "10.0.0.2");
+}
+
+#[tokio::test]
+async fn admin_can_disable_accounts_and_issue_reset_codes() {
+    let temp = TempDir::new().unwrap();
+    let mut config = isolated_config(&temp);
+    config.security.admins = vec!["root".to_string()];
+    let db_path = config.db.path.clone();
+    let mut app = app::create_app(config.clone()).await;
+    let root = register_with(&mut app, "root", &[]).await;
+    // 注册配置中的用户名不会得到管理员权限，只在启动时提升已有账号
+    let response = call_with_cookie(&mut app, "GET", routes::API_ADMIN_USERS, &root).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let mut app = app::create_app(config).await;
+    let kate = register_with(&mut app, "kate", &[]).await;
+    let (_, token) = create_token(
+        &mut app,
+        &kate,
+        serde_json::json!({ "name": "sync", "scopes": ["sports:read"] }),
+    )
+    .await;
+    let token = token["token"].as_str().unwrap().to_string();
+
+    let response = call_with_cookie(&mut app, "GET", routes::API_ADMIN_USERS, &kate).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let response = call_with_bearer(&mut app, "GET", routes::API_ADMIN_USERS, &token, None).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = call_with_cookie(&mut app, "GET", routes::API_ADMIN_USERS, &root).await;
+    let (status, users) = response_json(response).await;
+    assert_eq!(status, StatusCode::OK);
+    let users = users.as_array().unwrap();
+    assert_eq!(users.len(), 2);
+    assert_eq!(users[0]["role"], "admin");
+    assert_eq!(users[1]["name"], "kate");
+    assert_eq!(users[1]["role"], "user");
+    let kate_id = users[1]["id"].as_i64().unwrap();
+    let root_id = users[0]["id"].as_i64().unwrap();
+
+    let uri = format!("/api/admin/users/{root_id}/disable");
+    let response = call_with_cookie(&mut app, "POST", &uri, &root).await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    // 禁用后会话与访问令牌立即失效，也不能重新登录
+    let uri = format!("/api/admin/users/{kate_id}/disable");
+    let response = call_with_cookie(&mut app, "POST", &uri, &root).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = call_with_cookie(&mut app, "GET", routes::API_USER_INFO, &kate).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let response = call_with_bearer(&mut app, "GET", routes::API_SPORT_LIST, &token, None).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        login(&mut app, "kate", "p@ssw0rd").await,
+        StatusCode::FORBIDDEN
+    );
+
+    let uri = format!("/api/admin/users/{kate_id}/enable");
+    let response = call_with_cookie(&mut app, "POST", &uri, &root).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let kate = login_cookie(&mut app, "kate", &[]).await;
+
+    let uri = format!("/api/admin/users/{kate_id}/password-reset");
+    let (status, issued) =
+        response_json(call_with_cookie(&mut app, "POST", &uri, &root).await).await;
+    assert_eq!(status, StatusCode::OK);
+    let code = issued["code"].as_str().unwrap().to_string();
+    assert_eq!(code.len(), 19);
+
+    let reset = |code: &str, password: &str| serde_json::json!({ "name": "kate", "code": code, "new_password": password });
+    let (status, _) = post_json(
+        &mut app,
+        routes::API_USER_PASSWORD_RESET,
+        reset("0000-0000-0000-0000", "r3set-secret"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = post_json(
+        &mut app,
+        routes::API_USER_PASSWORD_RESET,
+        reset(&code, "short"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    // 重置码不区分大小写，分隔符可省略
+    let typed = code.replace('-', "").to_lowercase();
+    let (status, _) = post_json(
+        &mut app,
+        routes::API_USER_PASSWORD_RESET,
+        reset(&typed, "r3set-secret"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = post_json(
+        &mut app,
+        routes::API_USER_PASSWORD_RESET,
+        reset(&code, "an0ther-secret"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let response = call_with_cookie(&mut app, "GET", routes::API_USER_INFO, &kate).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        login(&mut app, "kate", "r3set-secret").await,
+        StatusCode::OK
+    );
+
+    let conn = sea_orm::Database::connect(format!("sqlite://{db_path}"))
+        .await
+        .unwrap();
+    let rows = conn
+        .query_all(sea_orm::Statement::from_string(
+            sea_orm::DbBackend::Sqlite,
+            "SELECT uid, action FROM audit_logs WHERE action LIKE 'admin_%' ORDER BY id",
+        ))
+        .await
+        .unwrap();
+    let actions: Vec<String> = rows
+        .iter()
+        .map(|row| row.try_get("", "action").unwrap())
+        .collect();
+    assert_eq!(
+        actions,
+        [
+            "admin_disable_user",
+            "admin_enable_user",
+            "admin_reset_password"
+        ]
+    );
+    let uid: Option<i32> = rows[0].try_get("", "uid").unwrap();
+    assert_eq!(uid, Some(root_id as i32));
+}
+
+#[tokio::test]
+async fn security_events_are_audited_for_owner_and_admin() {
+    let temp = TempDir::new().unwrap();
+    let mut config = isolated_config(&temp);
+    config.security.admins = vec!["auditor".to_string()];
+    let db_path = config.db.path.clone();
+    let mut app = app::create_app(config.clone()).await;
+    let auditor = register_with(&mut app, "auditor", &[]).await;
+    let mut app = app::create_app(config).await;
+    register_with(&mut app, "lena", &[]).await;
+
+    let response = login_from(&mut app, "lena", "wrong", "192.0.2.10").await;
+    assert_ne!(response.status(), StatusCode::OK);
+    let response = login_from(&mut app, "lena", "p@ssw0rd", "192.0.2.10").await;
+    let lena = session_cookie_of(&response).unwrap();
+    let (status, token) = create_token(
+        &mut app,
+        &lena,
+        serde_json::json!({ "name": "ci", "scopes": ["sports:read"] }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let uri = format!("/api/user/tokens/{}", token["id"].as_str().unwrap());
+    let response = call_with_cookie(&mut app, "DELETE", &uri, &lena).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let (status, _) = send_json_with_cookie(
+        &mut app,
+        "POST",
+        routes::API_USER_PASSWORD,
+        &lena,
+        serde_json::json!({ "old_password": "p@ssw0rd", "new_password": "n3w-secret" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let response = call_with_cookie(&mut app, "POST", routes::API_USER_LOGOUT, &lena).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = login_from(&mut app, "lena", "n3w-secret", "192.0.2.11").await;
+    let lena = session_cookie_of(&response).unwrap();
+
+    let response = call_with_cookie(&mut app, "GET", routes::API_USER_AUDIT, &lena).await;
+    let (status, entries) = response_json(response).await;
+    assert_eq!(status, StatusCode::OK);
+    let actions: Vec<&str> = entries
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["action"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        actions,
+        [
+            "login_success",
+            "logout",
+            "password_change",
+            "token_revoke",
+            "token_create",
+            "login_success",
+            "login_failure"
+        ]
+    );
+    assert_eq!(entries[0]["ip"], "192.0.2.11");
+    assert_eq!(entries[6]["ip"], "192.0.2.10");
+    let lena_id = entries[0]["uid"].as_i64().unwrap();
+
+    let uri = format!("{}?action=login_failure", routes::API_ADMIN_AUDIT);
+    let response = call_with_cookie(&mut app, "GET", &uri, &lena).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    // 不存在的账号登录失败也会记录，uid 为空
+    let response = login_from(&mut app, "ghost", "whatever1", "192.0.2.99").await;
+    assert_ne!(response.status(), StatusCode::OK);
+    let response = call_with_cookie(&mut app, "GET", &uri, &auditor).await;
+    let (status, failures) = response_json(response).await;
+    assert_eq!(status, StatusCode::OK);
+    let failures = failures.as_array().unwrap();
+    assert_eq!(failures.len(), 2);
+    assert!(failures[0]["uid"].is_null());
+    assert_eq!(failures[1]["uid"].as_i64(), Some(lena_id));
+    let uri = format!("{}?uid={lena_id}&size=3", routes::API_ADMIN_AUDIT);
+    let (_, page) = response_json(call_with_cookie(&mut app, "GET", &uri, &auditor).await).await;
+    assert_eq!(page.as_array().unwrap().len(), 3);
+
+    let conn = sea_orm::Database::connect(format!("sqlite://{db_path}"))
+        .await
+        .unwrap();
+    let tamper = conn
+        .execute(sea_orm::Statement::from_string(
+            sea_orm::DbBackend::Sqlite,
+            "DELETE FROM audit_logs",
+        ))
+        .await;
+    assert!(tamper.is_err());
+}
+
+async fn second_factor(
+    app: &mut axum::Router,
+    challenge: &str,
+    code: &str,
+) -> axum::response::Response {
+    let request = Request::builder()
+        .uri(routes::API_USER_LOGIN_TWO_FACTOR)
+        .method("POST")
+        .header("content-type", "application/json")
+        .body(Body::from(
+            serde_json::json!({ "challenge": challenge, "code": code }).to_string(),
+        ))
+        .unwrap();
+    app.call(request).await.unwrap()
+}
+
+async fn login_challenge(app: &mut axum::Router, name: &str) -> String {
+    let response = login_from(app, name, "p@ssw0rd", "192.0.2.20").await;
+    assert!(session_cookie_of(&response).is_none());
+    let (status, json) = response_json(response).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["two_factor_required"], true);
+    json["challenge"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn totp_two_factor_login_with_recovery_codes() {
+    let temp = TempDir::new().unwrap();
+    let mut app = app::create_app(isolated_config(&temp)).await;
+    let cookie = register_with(&mut app, "tina", &[]).await;
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    let response =
+        call_with_cookie(&mut app, "POST", routes::API_USER_TWO_FACTOR_SETUP, &cookie).await;
+    let (status, setup) = response_json(response).await;
+    assert_eq!(status, StatusCode::OK);
+    let secret_b32 = setup["secret"].as_str().unwrap();
+    assert!(
+        setup["provisioning_uri"]
+            .as_str()
+            .unwrap()
+            .starts_with(&format!("otpauth://totp/Slam:tina?secret={secret_b32}&"))
+    );
+    let secret = totp::base32_decode(secret_b32).unwrap();
+    // 未启用前登录不受影响
+    login_cookie(&mut app, "tina", &[]).await;
+
+    let (status, _) = send_json_with_cookie(
+        &mut app,
+        "POST",
+        routes::API_USER_TWO_FACTOR_ENABLE,
+        &cookie,
+        serde_json::json!({ "code": totp::code_at(&secret, now - 3600) }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let enable_code = totp::code_at(&secret, now);
+    let (status, codes) = send_json_with_cookie(
+        &mut app,
+        "POST",
+        routes::API_USER_TWO_FACTOR_ENABLE,
+        &cookie,
+        serde_json::json!({ "code": enable_code }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let recovery: Vec<String> = codes["recovery_codes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c.as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(recovery.len(), 10);
+
+    // 登录先拿到挑战令牌；启用时用过的验证码不能重放
+    let challenge = login_challenge(&mut app, "tina").await;
+    let response = second_factor(&mut app, &challenge, &enable_code).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let response = second_factor(&mut app, &challenge, &totp::code_at(&secret, now + 30)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let session = session_cookie_of(&response).expect("second factor set-cookie");
+    let response = call_with_cookie(&mut app, "GET", routes::API_USER_INFO, &session).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = second_factor(&mut app, &challenge, &totp::code_at(&secret, now + 60)).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    // 恢复码忽略大小写，只能使用一次
+    let challenge = login_challenge(&mut app, "tina").await;
+    let response = second_factor(&mut app, &challenge, &recovery[0].to_lowercase()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let challenge = login_challenge(&mut app, "tina").await;
+    let response = second_factor(&mut app, &challenge, &recovery[0]).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = call_with_cookie(&mut app, "GET", routes::API_USER_TWO_FACTOR, &cookie).await;
+    let (_, status) = response_json(response).await;
+    assert_eq!(status["enabled"], true);
+    assert_eq!(status["recovery_codes_remaining"], 9);
+
+    let (status, _) = send_json_with_cookie(
+        &mut app,
+        "POST",
+        routes::API_USER_TWO_FACTOR_DISABLE,
+        &cookie,
+        serde_json::json!({ "password": "wrong", "code": recovery[1] }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = send_json_with_cookie(
+        &mut app,
+        "POST",
+        routes::API_USER_TWO_FACTOR_DISABLE,
+        &cookie,
+        serde_json::json!({ "password": "p@ssw0rd", "code": recovery[1] }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    login_cookie(&mut app, "tina", &[]).await;
+
+    let response = call_with_cookie(&mut app, "GET", routes::API_USER_AUDIT, &cookie).await;
+    let (_, entries) = response_json(response).await;
+    let actions: Vec<&str> = entries
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["action"].as_str().unwrap())
+        .collect();
+    assert!(actions.contains(&"totp_enable"));
+    assert!(actions.contains(&"totp_disable"));
+}
+
+fn jwt_key(kid: &str, algorithm: Algorithm) -> JwtKeyConfig {
+    JwtKeyConfig {
+        kid: kid.to_string(),
+        algorithm,
+        secret: String::new(),
+        private_key_file: None,
+        public_key_file: None,
+    }
+}
+
+fn test_key_file(name: &str) -> Option<String> {
+    Some(format!("{}/tests/{name}", env!("CARGO_MANIFEST_DIR")))
+}
+
+#[tokio::test]
+async fn jwt_keys_rotate_by_kid_and_publish_jwks() {
+    let temp = TempDir::new().unwrap();
+    let mut config = isolated_config(&temp);
+    let old = JwtKeyConfig {
+        secret: "old-secret-for-tests".to_string(),
+        ..jwt_key("old", Algorithm::HS256)
+    };
+    let new = JwtKeyConfig {
+        private_key_file: test_key_file("jwt_ed25519_key.pem"),
+        ..jwt_key("new", Algorithm::EdDSA)
+    };
+    config.security.jwt_keys = vec![old.clone()];
+    let mut app = app::create_app(config.clone()).await;
+    let old_cookie = register_with(&mut app, "uma", &[]).await;
+    let header = jsonwebtoken::decode_header(old_cookie.trim_start_matches("slam=")).unwrap();
+    assert_eq!(header.kid.as_deref(), Some("old"));
+    assert_eq!(header.alg, Algorithm::HS256);
+
+    // 新密钥签发，旧密钥仍可验证
+    config.security.jwt_keys = vec![old.clone(), new.clone()];
+    config.security.jwt_signing_kid = Some("new".to_string());
+    let mut app = app::create_app(config.clone()).await;
+    let response = call_with_cookie(&mut app, "GET", routes::API_USER_INFO, &old_cookie).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let new_cookie = login_cookie(&mut app, "uma", &[]).await;
+    let new_token = new_cookie.trim_start_matches("slam=");
+    let header = jsonwebtoken::decode_header(new_token).unwrap();
+    assert_eq!(header.kid.as_deref(), Some("new"));
+    assert_eq!(header.alg, Algorithm::EdDSA);
+
+    // 其他服务可以用 JWKS 验证，HS256 密钥不会公开
+    let response = call_with_cookie(&mut app, "GET", routes::API_JWKS, "").await;
+    let (status, json) = response_json(response).await;
+    assert_eq!(status, StatusCode::OK);
+    let jwks: JwkSet = serde_json::from_value(json).unwrap();
+    assert_eq!(jwks.keys.len(), 1);
+    let jwk = jwks.find("new").unwrap();
+    let claims = jsonwebtoken::decode::<serde_json::Value>(
+        new_token,
+        &DecodingKey::from_jwk(jwk).unwrap(),
+        &Validation::new(Algorithm::EdDSA),
+    )
+    .unwrap()
+    .claims;
+
+    // 用 HMAC 冒充非对称密钥的 token 被拒绝
+    let forged = jsonwebtoken::encode(
+        &Header {
+            kid: Some("new".to_string()),
+            ..Header::new(Algorithm::HS256)
+        },
+        &claims,
+        &EncodingKey::from_secret(old.secret.as_bytes()),
+    )
+    .unwrap();
+    let response = call_with_cookie(
+        &mut app,
+        "GET",
+        routes::API_USER_INFO,
+        &format!("slam={forged}"),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    // 移除旧密钥后旧 token 失效；只配置公钥的密钥仍能验证外部签发的 token
+    config.security.jwt_keys = vec![
+        new,
+        JwtKeyConfig {
+            public_key_file: test_key_file("oidc_issuer_pub.pem"),
+            ..jwt_key("partner", Algorithm::RS256)
+        },
+    ];
+    config.security.jwt_signing_kid = None;
+    let mut app = app::create_app(config.clone()).await;
+    let response = call_with_cookie(&mut app, "GET", routes::API_USER_INFO, &old_cookie).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let response = call_with_cookie(&mut app, "GET", routes::API_USER_INFO, &new_cookie).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let partner = jsonwebtoken::encode(
+        &Header {
+            kid: Some("partner".to_string()),
+            ..Header::new(Algorithm::RS256)
+        },
+        &claims,
+        &EncodingKey::from_rsa_pem(include_bytes!("oidc_issuer_key.pem")).unwrap(),
+    )
+    .unwrap();
+    let response = call_with_cookie(
+        &mut app,
+        "GET",
+        routes::API_USER_INFO,
+        &format!("slam={partner}"),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = call_with_cookie(&mut app, "GET", routes::API_JWKS, "").await;
+    let (_, json) = response_json(response).await;
+    let jwks: JwkSet = serde_json::from_value(json).unwrap();
+    assert!(jwks.find("partner").is_some());
+
+    // 只有公钥的密钥不能用于签发
+    config.security.jwt_signing_kid = Some("partner".to_string());
+    assert!(JwtKeySet::from_config(&config.security).is_err());
+}
+
+#[test]
+fn placeholder_jwt_key_is_reported_as_insecure() {
+    let mut security = SecurityConfig::default();
+    assert_eq!(security.insecure_jwt_key(), Some("default"));
+    security.key = "a-real-secret".to_string();
+    assert_eq!(security.insecure_jwt_key(), None);
+
+    security.jwt_keys = vec![
+        JwtKeyConfig {
+            private_key_file: test_key_file("jwt_ed25519_key.pem"),
+            ..jwt_key("ed", Algorithm::EdDSA)
+        },
+        JwtKeyConfig {
+            secret: "change-me-key".to_string(),
+            ..jwt_key("legacy", Algorithm::HS256)
+        },
+    ];
+    assert_eq!(security.insecure_jwt_key(), Some("legacy"));
+    security.jwt_keys.pop();
+    assert_eq!(security.insecure_jwt_key(), None);
+}
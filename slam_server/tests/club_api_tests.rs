@@ -0,0 +1,458 @@
+use axum::body::{Body, to_bytes};
+use axum::http::{Request, StatusCode};
+use sea_orm::ConnectionTrait;
+use slam_server::app::{self, AppConfig, routes};
+use tempfile::TempDir;
+use tower::Service;
+
+fn isolated_config(temp: &TempDir) -> AppConfig {
+    let mut config = AppConfig::default();
+    config.db.path = temp.path().join("sport.db").to_string_lossy().to_string();
+    config.ai.job_dir = temp.path().join("ai-jobs").to_string_lossy().to_string();
+    config
+}
+
+async fn call(
+    app: &mut axum::Router,
+    method: &str,
+    uri: &str,
+    cookie: &str,
+    body: Option<serde_json::Value>,
+) -> (StatusCode, serde_json::Value) {
+    let request = Request::builder()
+        .uri(uri)
+        .method(method)
+        .header("content-type", "application/json")
+        .header("cookie", cookie)
+        .body(body.map_or_else(Body::empty, |b| Body::from(b.to_string())))
+        .unwrap();
+    let response = app.call(request).await.unwrap();
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+    let value = serde_json::from_slice(&bytes).unwrap_or_else(
+        |_| serde_json::json!({ "raw": String::from_utf8_lossy(&bytes).to_string() }),
+    );
+    (status, value)
+}
+
+/// 注册用户，返回 (cookie, uid)
+async fn register(app: &mut axum::Router, name: &str) -> (String, i64) {
+    let body = serde_json::json!({ "name": name, "password": "p@ssw0rd-1", "nickname": name });
+    let request = Request::builder()
+        .uri(routes::API_USER_REGISTER)
+        .method("POST")
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    let response = app.call(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let cookie = response.headers()["set-cookie"]
+        .to_str()
+        .unwrap()
+        .split(';')
+        .next()
+        .unwrap()
+        .to_string();
+    let (_, info) = call(app, "GET", routes::API_USER_INFO, &cookie, None).await;
+    (cookie, info["uid"].as_i64().unwrap())
+}
+
+/// 插入一条关注者可见的运动
+async fn insert_sport(
+    app: &mut axum::Router,
+    cookie: &str,
+    sport_type: &str,
+    start_time: i64,
+    distance_meter: i32,
+) {
+    insert_sport_as(
+        app,
+        cookie,
+        sport_type,
+        start_time,
+        distance_meter,
+        "followers",
+    )
+    .await;
+}
+
+async fn insert_sport_as(
+    app: &mut axum::Router,
+    cookie: &str,
+    sport_type: &str,
+    start_time: i64,
+    distance_meter: i32,
+    visibility: &str,
+) {
+    let body = serde_json::json!({
+        "type": sport_type,
+        "visibility": visibility,
+        "start_time": start_time,
+        "calories": 300,
+        "distance_meter": distance_meter,
+        "duration_second": 1800,
+        "pace_average": "6'00''",
+        "tracks": []
+    });
+    let (status, resp) = call(app, "POST", routes::API_SPORT_INSERT, cookie, Some(body)).await;
+    assert_eq!(status, StatusCode::OK, "{resp}");
+}
+
+async fn import_csv(app: &mut axum::Router, cookie: &str, csv: &str) {
+    let boundary = "club-test-boundary";
+    let body = format!(
+        "--{boundary}\r\nContent-Disposition: form-data; name=\"vendor\"\r\n\r\nxiaomi\r\n\
+         --{boundary}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"sports.csv\"\r\n\
+         Content-Type: text/csv\r\n\r\n{csv}\r\n--{boundary}--\r\n"
+    );
+    let request = Request::builder()
+        .uri(routes::API_SPORT_IMPORT)
+        .method("POST")
+        .header(
+            "content-type",
+            format!("multipart/form-data; boundary={boundary}"),
+        )
+        .header("cookie", cookie)
+        .body(Body::from(body))
+        .unwrap();
+    let response = app.call(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+/// 导入的运动默认私密，把当前用户的全部运动改为关注者可见
+async fn share_all_sports(app: &mut axum::Router, cookie: &str) {
+    let (_, sports) = call(app, "GET", routes::API_SPORT_LIST, cookie, None).await;
+    for mut sport in sports.as_array().unwrap().clone() {
+        sport["visibility"] = "followers".into();
+        let (status, resp) = call(app, "POST", routes::API_SPORT_UPDATE, cookie, Some(sport)).await;
+        assert_eq!(status, StatusCode::OK, "{resp}");
+    }
+}
+
+fn club_uri(route: &str, club_id: i64) -> String {
+    route.replace(":id", &club_id.to_string())
+}
+
+fn leaderboard(board: &serde_json::Value) -> Vec<(String, i64, i64)> {
+    board["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| {
+            (
+                e["user"]["nickname"].as_str().unwrap().to_string(),
+                e["value"].as_i64().unwrap(),
+                e["rank"].as_i64().unwrap(),
+            )
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn members_join_by_code_or_invite_and_only_owners_manage() {
+    let temp = TempDir::new().unwrap();
+    let mut app = app::create_app(isolated_config(&temp)).await;
+    let (alice, _) = register(&mut app, "alice").await;
+    let (bob, bob_uid) = register(&mut app, "bob").await;
+    let (carol, carol_uid) = register(&mut app, "carol").await;
+
+    let (status, club) = call(
+        &mut app,
+        "POST",
+        routes::API_CLUBS,
+        &alice,
+        Some(serde_json::json!({ "name": "Morning Runners" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{club}");
+    assert_eq!(club["role"], "owner");
+    let club_id = club["id"].as_i64().unwrap();
+    let code = club["join_code"].as_str().unwrap().to_lowercase();
+
+    let (status, _) = call(
+        &mut app,
+        "GET",
+        &club_uri(routes::API_CLUB, club_id),
+        &bob,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, joined) = call(
+        &mut app,
+        "POST",
+        routes::API_CLUBS_JOIN,
+        &bob,
+        Some(serde_json::json!({ "code": code })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(joined["role"], "member");
+    assert_eq!(joined["member_count"], 2);
+    assert!(joined.get("join_code").is_none());
+
+    // 普通成员不能邀请或管理成员
+    let invites = club_uri(routes::API_CLUB_INVITES, club_id);
+    let invite_carol = Some(serde_json::json!({ "uid": carol_uid }));
+    let (status, _) = call(&mut app, "POST", &invites, &bob, invite_carol.clone()).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = call(&mut app, "POST", &invites, &alice, invite_carol).await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, pending) = call(&mut app, "GET", routes::API_CLUBS_INVITES, &carol, None).await;
+    assert_eq!(pending[0]["club_name"], "Morning Runners");
+    let (status, _) = call(
+        &mut app,
+        "POST",
+        &club_uri(routes::API_CLUB_INVITE_ACCEPT, club_id),
+        &carol,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, members) = call(
+        &mut app,
+        "GET",
+        &club_uri(routes::API_CLUB_MEMBERS, club_id),
+        &carol,
+        None,
+    )
+    .await;
+    assert_eq!(members.as_array().unwrap().len(), 3);
+
+    // 最后一位管理员需要先移交才能退出
+    let leave = club_uri(routes::API_CLUB_LEAVE, club_id);
+    let (status, _) = call(&mut app, "POST", &leave, &alice, None).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    let bob_member =
+        club_uri(routes::API_CLUB_MEMBER, club_id).replace(":uid", &bob_uid.to_string());
+    let (status, _) = call(
+        &mut app,
+        "PUT",
+        &bob_member,
+        &alice,
+        Some(serde_json::json!({ "role": "owner" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = call(&mut app, "POST", &leave, &alice, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, clubs) = call(&mut app, "GET", routes::API_CLUBS, &alice, None).await;
+    assert!(clubs.as_array().unwrap().is_empty());
+
+    // 重置加入码后旧码失效
+    let (_, reset) = call(
+        &mut app,
+        "POST",
+        &club_uri(routes::API_CLUB_JOIN_CODE, club_id),
+        &bob,
+        None,
+    )
+    .await;
+    assert_ne!(reset["join_code"].as_str().unwrap().to_lowercase(), code);
+    let (status, _) = call(
+        &mut app,
+        "POST",
+        routes::API_CLUBS_JOIN,
+        &alice,
+        Some(serde_json::json!({ "code": code })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn leaderboard_follows_member_sports_inside_the_window() {
+    let temp = TempDir::new().unwrap();
+    let mut app = app::create_app(isolated_config(&temp)).await;
+    let (alice, _) = register(&mut app, "alice").await;
+    let (bob, _) = register(&mut app, "bob").await;
+    let (carol, _) = register(&mut app, "carol").await;
+    let (dave, _) = register(&mut app, "dave").await;
+
+    let (_, club) = call(
+        &mut app,
+        "POST",
+        routes::API_CLUBS,
+        &alice,
+        Some(serde_json::json!({ "name": "Track Club" })),
+    )
+    .await;
+    let club_id = club["id"].as_i64().unwrap();
+    for cookie in [&bob, &carol] {
+        call(
+            &mut app,
+            "POST",
+            routes::API_CLUBS_JOIN,
+            cookie,
+            Some(serde_json::json!({ "code": club["join_code"] })),
+        )
+        .await;
+    }
+
+    let challenges = club_uri(routes::API_CLUB_CHALLENGES, club_id);
+    let running = serde_json::json!({
+        "name": "Run 100K",
+        "metric": "distance",
+        "sport_type": "Running",
+        "start_time": 1_700_000_000,
+        "end_time": 1_700_100_000
+    });
+    let (status, _) = call(&mut app, "POST", &challenges, &bob, Some(running.clone())).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let mut invalid = running.clone();
+    invalid["end_time"] = invalid["start_time"].clone();
+    let (status, _) = call(&mut app, "POST", &challenges, &alice, Some(invalid)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, challenge) = call(&mut app, "POST", &challenges, &alice, Some(running)).await;
+    assert_eq!(status, StatusCode::OK, "{challenge}");
+    let board_uri = club_uri(routes::API_CLUB_LEADERBOARD, club_id)
+        .replace(":challenge_id", &challenge["id"].to_string());
+
+    insert_sport(&mut app, &alice, "Running", 1_700_000_100, 5000).await;
+    insert_sport(&mut app, &bob, "Running", 1_700_000_200, 6000).await;
+    insert_sport(&mut app, &bob, "Running", 1_700_000_300, 4000).await;
+    // 窗口外、其他类型和非成员的运动不计入
+    insert_sport(&mut app, &bob, "Running", 1_700_100_000, 9000).await;
+    insert_sport(&mut app, &alice, "Swimming", 1_700_000_400, 1500).await;
+    insert_sport(&mut app, &dave, "Running", 1_700_000_500, 42000).await;
+    // 私密运动不会通过排行榜暴露给其他成员
+    insert_sport_as(&mut app, &carol, "Running", 1_700_000_700, 21000, "private").await;
+
+    let (_, board) = call(&mut app, "GET", &board_uri, &carol, None).await;
+    assert_eq!(
+        leaderboard(&board),
+        vec![
+            ("bob".to_string(), 10000, 1),
+            ("alice".to_string(), 5000, 2),
+            ("carol".to_string(), 0, 3),
+        ]
+    );
+    assert_eq!(board["entries"][0]["sessions"], 2);
+
+    insert_sport(&mut app, &alice, "Running", 1_700_000_600, 5000).await;
+    let (_, board) = call(&mut app, "GET", &board_uri, &carol, None).await;
+    let ranks: Vec<i64> = leaderboard(&board).iter().map(|e| e.2).collect();
+    assert_eq!(ranks, vec![1, 1, 3]);
+
+    let (_, sports) = call(&mut app, "GET", routes::API_SPORT_LIST, &bob, None).await;
+    let bob_run = sports
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|s| s["distance_meter"] == 6000)
+        .unwrap()["id"]
+        .clone();
+    let (status, _) = call(
+        &mut app,
+        "POST",
+        routes::API_SPORT_DELETE,
+        &bob,
+        Some(serde_json::json!({ "id": bob_run })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, board) = call(&mut app, "GET", &board_uri, &carol, None).await;
+    assert_eq!(leaderboard(&board)[0], ("alice".to_string(), 10000, 1));
+
+    // 导入的运动同样计入按次数统计的挑战
+    let (_, sessions) = call(
+        &mut app,
+        "POST",
+        &challenges,
+        &alice,
+        Some(serde_json::json!({
+            "name": "Summer sessions",
+            "metric": "count",
+            "start_time": 1_731_000_000,
+            "end_time": 1_732_000_000
+        })),
+    )
+    .await;
+    import_csv(
+        &mut app,
+        &carol,
+        "Uid,Sid,Key,Time,Category,Value,UpdateTime\n\
+         1,1,pool_swimm,1731888000,swimming,{\"anaerobic_train_e\":1},1731888000\n\
+         1,1,pool_swimm,1731889000,swimming,{\"anaerobic_train_e\":1},1731889000",
+    )
+    .await;
+    let sessions_uri = club_uri(routes::API_CLUB_LEADERBOARD, club_id)
+        .replace(":challenge_id", &sessions["id"].to_string());
+    let (_, board) = call(&mut app, "GET", &sessions_uri, &alice, None).await;
+    assert_eq!(leaderboard(&board)[0], ("alice".to_string(), 0, 1));
+    share_all_sports(&mut app, &carol).await;
+    let (_, board) = call(&mut app, "GET", &sessions_uri, &alice, None).await;
+    assert_eq!(leaderboard(&board)[0], ("carol".to_string(), 2, 1));
+
+    let (status, _) = call(&mut app, "GET", &board_uri, &dave, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn deleting_the_last_owner_hands_the_club_to_one_earliest_member() {
+    let temp = TempDir::new().unwrap();
+    let config = isolated_config(&temp);
+    let db_path = config.db.path.clone();
+    let mut app = app::create_app(config).await;
+    let (alice, _) = register(&mut app, "alice").await;
+    let (bob, bob_uid) = register(&mut app, "bob").await;
+    let (carol, _) = register(&mut app, "carol").await;
+    let (_, club) = call(
+        &mut app,
+        "POST",
+        routes::API_CLUBS,
+        &alice,
+        Some(serde_json::json!({ "name": "Morning Runners" })),
+    )
+    .await;
+    let club_id = club["id"].as_i64().unwrap();
+    for cookie in [&carol, &bob] {
+        let (status, _) = call(
+            &mut app,
+            "POST",
+            routes::API_CLUBS_JOIN,
+            cookie,
+            Some(serde_json::json!({ "code": club["join_code"] })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+    // 两名成员同时加入
+    let conn = sea_orm::Database::connect(format!("sqlite://{db_path}"))
+        .await
+        .unwrap();
+    conn.execute(sea_orm::Statement::from_sql_and_values(
+        sea_orm::DbBackend::Sqlite,
+        "UPDATE club_members SET joined_at = 1 WHERE club_id = ? AND role = 'member'",
+        [club_id.into()],
+    ))
+    .await
+    .unwrap();
+
+    let (status, _) = call(
+        &mut app,
+        "DELETE",
+        routes::API_USER_ACCOUNT,
+        &alice,
+        Some(serde_json::json!({ "password": "p@ssw0rd-1" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, members) = call(
+        &mut app,
+        "GET",
+        &club_uri(routes::API_CLUB_MEMBERS, club_id),
+        &carol,
+        None,
+    )
+    .await;
+    let owners: Vec<i64> = members
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|m| m["role"] == "owner")
+        .map(|m| m["uid"].as_i64().unwrap())
+        .collect();
+    assert_eq!(owners, vec![bob_uid]);
+    assert_eq!(members.as_array().unwrap().len(), 2);
+}
@@ -0,0 +1,569 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use axum::body::{Body, to_bytes};
+use axum::extract::{Form, Query, State};
+use axum::http::{Request, StatusCode};
+use axum::response::{IntoResponse, Redirect};
+use axum::routing::{get, post};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use reqwest::Url;
+use sea_orm::ConnectionTrait;
+use sha2::{Digest, Sha256};
+use slam_server::app::{self, AppConfig, routes};
+use slam_server::config::OidcConfig;
+use slam_server::dao::Repository;
+use slam_server::dao::idl::UserDao;
+use slam_server::model::user::PASSWORD_UNSET;
+use slam_server::service::totp;
+use tempfile::TempDir;
+use tower::Service;
+
+const CLIENT_ID: &str = "slam-test";
+const CLIENT_SECRET: &str = "s3cret";
+const REDIRECT_URI: &str = "http://localhost/api/user/oidc/callback";
+const KEY_ID: &str = "test-key";
+/// tests/oidc_issuer_key.pem 的公钥模数
+const KEY_MODULUS: &str = "vujxygJ02js0jlrZVnWTTCZYIxOE6sKDeg1QMMNeuzmm0JPB1PpFWzpZJh79r5rt9lgGKay3mPIOzXk8So8zhK6o57aW_Xvia0O_s-GcnwmgPqMlro4psCDA_h1yafjXkZJxkxv--WTRjnghAOT0Kd9BxfXGMkIprUpa5q4XzxOx7E1s410LyvTR8DNZhFOvTiXrMw2KJJF08RbIur2vKK787RNjYMPnOM4Nxa1AomN-ulcMKSqLT8p7MWSPmYt0mEuukl8X4GjtVArxxngiTaxcUZzymrPYDNGY-TjNIokrk8YLMTcEoy19TMFbd2hf7y6MA03zVITMj8cJAih4tQ";
+
+/// 已签发、尚未兑换的授权码
+struct PendingCode {
+    subject: String,
+    nonce: String,
+    code_challenge: String,
+}
+
+#[derive(Clone)]
+struct Issuer {
+    url: String,
+    codes: Arc<Mutex<HashMap<String, PendingCode>>>,
+}
+
+/// 本地起一个最小的身份提供方：discovery、授权、换取 token 与 JWKS
+async fn start_issuer() -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let issuer = Issuer {
+        url: url.clone(),
+        codes: Arc::new(Mutex::new(HashMap::new())),
+    };
+    let router = axum::Router::new()
+        .route("/.well-known/openid-configuration", get(discovery))
+        .route("/authorize", get(authorize))
+        .route("/token", post(token))
+        .route("/jwks", get(jwks))
+        .with_state(issuer);
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+    url
+}
+
+async fn discovery(State(issuer): State<Issuer>) -> axum::Json<serde_json::Value> {
+    axum::Json(serde_json::json!({
+        "issuer": issuer.url,
+        "authorization_endpoint": format!("{}/authorize", issuer.url),
+        "token_endpoint": format!("{}/token", issuer.url),
+        "jwks_uri": format!("{}/jwks", issuer.url),
+    }))
+}
+
+/// 直接以 login_hint 作为登录用户，带授权码跳回客户端
+async fn authorize(
+    State(issuer): State<Issuer>,
+    Query(q): Query<HashMap<String, String>>,
+) -> axum::response::Response {
+    assert_eq!(q["client_id"], CLIENT_ID);
+    assert_eq!(q["redirect_uri"], REDIRECT_URI);
+    assert_eq!(q["code_challenge_method"], "S256");
+    let code = format!("code-{}", issuer.codes.lock().unwrap().len());
+    issuer.codes.lock().unwrap().insert(
+        code.clone(),
+        PendingCode {
+            subject: q["login_hint"].clone(),
+            nonce: q["nonce"].clone(),
+            code_challenge: q["code_challenge"].clone(),
+        },
+    );
+    Redirect::to(&format!("{REDIRECT_URI}?code={code}&state={}", q["state"])).into_response()
+}
+
+async fn token(
+    State(issuer): State<Issuer>,
+    Form(form): Form<HashMap<String, String>>,
+) -> axum::response::Response {
+    let Some(pending) = issuer.codes.lock().unwrap().remove(&form["code"]) else {
+        return (StatusCode::BAD_REQUEST, "invalid_grant").into_response();
+    };
+    let challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(form["code_verifier"].as_bytes()));
+    if challenge != pending.code_challenge
+        || form["client_id"] != CLIENT_ID
+        || form["client_secret"] != CLIENT_SECRET
+        || form["redirect_uri"] != REDIRECT_URI
+    {
+        return (StatusCode::BAD_REQUEST, "invalid_grant").into_response();
+    }
+    let now = jsonwebtoken::get_current_timestamp();
+    let claims = serde_json::json!({
+        "iss": issuer.url,
+        "aud": CLIENT_ID,
+        "sub": pending.subject,
+        "nonce": pending.nonce,
+        "iat": now,
+        "exp": now + 300,
+        "preferred_username": pending.subject,
+        "email": format!("{}@example.com", pending.subject),
+        "name": format!("{} (SSO)", pending.subject),
+    });
+    let mut header = Header::new(Algorithm::RS256);
+    header.kid = Some(KEY_ID.to_string());
+    let key = EncodingKey::from_rsa_pem(include_bytes!("oidc_issuer_key.pem")).unwrap();
+    let id_token = jsonwebtoken::encode(&header, &claims, &key).unwrap();
+    axum::Json(serde_json::json!({
+        "access_token": "unused",
+        "token_type": "Bearer",
+        "id_token": id_token,
+    }))
+    .into_response()
+}
+
+async fn jwks() -> axum::Json<serde_json::Value> {
+    axum::Json(serde_json::json!({
+        "keys": [{ "kty": "RSA", "kid": KEY_ID, "alg": "RS256", "use": "sig", "n": KEY_MODULUS, "e": "AQAB" }]
+    }))
+}
+
+fn oidc_config(temp: &TempDir, issuer: &str) -> AppConfig {
+    let mut config = AppConfig::default();
+    config.db.path = temp.path().join("sport.db").to_string_lossy().to_string();
+    config.ai.job_dir = temp.path().join("ai-jobs").to_string_lossy().to_string();
+    config.security.oidc = Some(OidcConfig {
+        issuer: issuer.to_string(),
+        client_id: CLIENT_ID.to_string(),
+        client_secret: CLIENT_SECRET.to_string(),
+        redirect_uri: REDIRECT_URI.to_string(),
+        scopes: vec!["openid".to_string(), "profile".to_string()],
+        auto_register: true,
+        post_login_redirect: "/app".to_string(),
+    });
+    config
+}
+
+fn cookie_of(response: &axum::response::Response, name: &str) -> Option<String> {
+    response
+        .headers()
+        .get_all("set-cookie")
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .find(|v| v.starts_with(&format!("{name}=")))
+        .map(|v| v.split(';').next().unwrap().to_string())
+}
+
+fn location_of(response: &axum::response::Response) -> String {
+    response.headers()["location"].to_str().unwrap().to_string()
+}
+
+async fn call(
+    app: &mut axum::Router,
+    method: &str,
+    uri: &str,
+    cookie: Option<&str>,
+    body: Option<serde_json::Value>,
+) -> axum::response::Response {
+    let mut builder = Request::builder().uri(uri).method(method);
+    if let Some(cookie) = cookie {
+        builder = builder.header("cookie", cookie);
+    }
+    let body = match body {
+        Some(v) => {
+            builder = builder.header("content-type", "application/json");
+            Body::from(v.to_string())
+        }
+        None => Body::empty(),
+    };
+    app.call(builder.body(body).unwrap()).await.unwrap()
+}
+
+async fn json_of(response: axum::response::Response) -> serde_json::Value {
+    let bytes = to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+    serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null)
+}
+
+/// 在身份提供方以 subject 登录，返回回调地址（相对路径）与 state cookie
+async fn authorize_at_issuer(
+    app: &mut axum::Router,
+    start: &str,
+    session: Option<&str>,
+    subject: &str,
+) -> (String, String) {
+    let response = call(app, "GET", start, session, None).await;
+    assert_eq!(response.status(), StatusCode::FOUND);
+    let state_cookie = cookie_of(&response, "slam_oidc").expect("state cookie");
+    let mut authorize = Url::parse(&location_of(&response)).unwrap();
+    authorize
+        .query_pairs_mut()
+        .append_pair("login_hint", subject);
+    let client = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .unwrap();
+    let resp = client.get(authorize).send().await.unwrap();
+    let callback = Url::parse(resp.headers()["location"].to_str().unwrap()).unwrap();
+    (
+        format!("{}?{}", callback.path(), callback.query().unwrap()),
+        state_cookie,
+    )
+}
+
+/// 走完整个授权码流程登录，返回会话 cookie
+async fn oidc_login(app: &mut axum::Router, subject: &str) -> String {
+    let (callback, state) =
+        authorize_at_issuer(app, routes::API_USER_OIDC_LOGIN, None, subject).await;
+    let response = call(app, "GET", &callback, Some(&state), None).await;
+    assert_eq!(response.status(), StatusCode::FOUND);
+    assert_eq!(location_of(&response), "/app");
+    cookie_of(&response, "slam").expect("session cookie")
+}
+
+async fn user_info(app: &mut axum::Router, session: &str) -> serde_json::Value {
+    let response = call(app, "GET", routes::API_USER_INFO, Some(session), None).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    json_of(response).await
+}
+
+async fn identities(app: &mut axum::Router, session: &str) -> Vec<serde_json::Value> {
+    let response = call(
+        app,
+        "GET",
+        routes::API_USER_OIDC_IDENTITIES,
+        Some(session),
+        None,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    json_of(response).await.as_array().unwrap().clone()
+}
+
+async fn register(app: &mut axum::Router, name: &str) -> axum::response::Response {
+    let body = serde_json::json!({ "name": name, "password": "p@ssw0rd", "nickname": name });
+    call(app, "POST", routes::API_USER_REGISTER, None, Some(body)).await
+}
+
+#[tokio::test]
+async fn first_oidc_login_registers_and_later_logins_reuse_account() {
+    let temp = TempDir::new().unwrap();
+    let issuer = start_issuer().await;
+    let config = oidc_config(&temp, &issuer);
+    let mut app = app::create_app(config.clone()).await;
+    let repository = Repository::new(&config.db.path).await.unwrap();
+
+    let session = oidc_login(&mut app, "carol").await;
+    assert_eq!(
+        user_info(&mut app, &session).await["nickname"],
+        "carol (SSO)"
+    );
+    let carol = repository.get_by_name("carol").await.unwrap().unwrap();
+    assert_eq!(carol.password, PASSWORD_UNSET);
+    let linked = identities(&mut app, &session).await;
+    assert_eq!(linked.len(), 1);
+    assert_eq!(linked[0]["subject"], "carol");
+    assert_eq!(linked[0]["email"], "carol@example.com");
+
+    let again = oidc_login(&mut app, "carol").await;
+    assert_eq!(identities(&mut app, &again).await[0]["id"], linked[0]["id"]);
+
+    // 本地已有同名用户时追加序号，不会自动并入该账号
+    assert_eq!(register(&mut app, "dave").await.status(), StatusCode::OK);
+    oidc_login(&mut app, "dave").await;
+    let dave = repository.get_by_name("dave-2").await.unwrap().unwrap();
+    assert_eq!(dave.password, PASSWORD_UNSET);
+
+    // 自动注册的账号没有本地密码
+    let body = serde_json::json!({ "name": "carol", "password": "!" });
+    let response = call(&mut app, "POST", routes::API_USER_LOGIN, None, Some(body)).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = call(&mut app, "GET", routes::API_USER_AUDIT, Some(&again), None).await;
+    let audit = json_of(response).await;
+    assert!(
+        audit
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|e| e["action"] == "login_success"
+                && e["detail"].as_str().unwrap().contains("oidc"))
+    );
+}
+
+#[tokio::test]
+async fn callback_rejects_foreign_or_replayed_state() {
+    let temp = TempDir::new().unwrap();
+    let issuer = start_issuer().await;
+    let mut app = app::create_app(oidc_config(&temp, &issuer)).await;
+
+    // 没有发起授权的浏览器带来的回调
+    let (callback, state) =
+        authorize_at_issuer(&mut app, routes::API_USER_OIDC_LOGIN, None, "erin").await;
+    let response = call(&mut app, "GET", &callback, None, None).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let response = call(&mut app, "GET", &callback, Some("slam_oidc=other"), None).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = call(&mut app, "GET", &callback, Some(&state), None).await;
+    assert_eq!(response.status(), StatusCode::FOUND);
+    // 同一个 state 只能用一次
+    let response = call(&mut app, "GET", &callback, Some(&state), None).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = call(
+        &mut app,
+        "GET",
+        "/api/user/oidc/callback?error=access_denied",
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn identities_can_be_linked_and_unlinked() {
+    let temp = TempDir::new().unwrap();
+    let issuer = start_issuer().await;
+    let mut app = app::create_app(oidc_config(&temp, &issuer)).await;
+
+    let response = register(&mut app, "frank").await;
+    let frank = cookie_of(&response, "slam").unwrap();
+
+    // 未登录不能发起关联
+    let response = call(&mut app, "GET", routes::API_USER_OIDC_LINK, None, None).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let (callback, state) = authorize_at_issuer(
+        &mut app,
+        routes::API_USER_OIDC_LINK,
+        Some(&frank),
+        "frank-sso",
+    )
+    .await;
+    let response = call(&mut app, "GET", &callback, Some(&state), None).await;
+    assert_eq!(response.status(), StatusCode::FOUND);
+    assert!(cookie_of(&response, "slam").is_none());
+
+    // 关联后用外部身份登录进入同一个账号
+    let session = oidc_login(&mut app, "frank-sso").await;
+    assert_eq!(user_info(&mut app, &session).await["nickname"], "frank");
+
+    // 已被他人关联的身份不能再关联
+    let grace = oidc_login(&mut app, "grace").await;
+    let (callback, state) =
+        authorize_at_issuer(&mut app, routes::API_USER_OIDC_LINK, Some(&frank), "grace").await;
+    let response = call(&mut app, "GET", &callback, Some(&state), None).await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    let linked = identities(&mut app, &frank).await;
+    assert_eq!(linked.len(), 1);
+    assert_eq!(linked[0]["subject"], "frank-sso");
+    assert_eq!(linked[0]["issuer"], issuer.as_str());
+    let frank_identity = linked[0]["id"].as_i64().unwrap();
+
+    // grace 没有本地密码，唯一的外部身份不能解除
+    let grace_identity = identities(&mut app, &grace).await[0]["id"]
+        .as_i64()
+        .unwrap();
+    let uri = format!("/api/user/oidc/identities/{grace_identity}");
+    let response = call(&mut app, "DELETE", &uri, Some(&grace), None).await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    // 不能解除别人的身份
+    let response = call(&mut app, "DELETE", &uri, Some(&frank), None).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let uri = format!("/api/user/oidc/identities/{frank_identity}");
+    let response = call(&mut app, "DELETE", &uri, Some(&frank), None).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(identities(&mut app, &frank).await.is_empty());
+}
+
+#[tokio::test]
+async fn oidc_only_account_is_deleted_after_fresh_reauthentication() {
+    let temp = TempDir::new().unwrap();
+    let issuer = start_issuer().await;
+    let config = oidc_config(&temp, &issuer);
+    let mut app = app::create_app(config.clone()).await;
+    let repository = Repository::new(&config.db.path).await.unwrap();
+
+    let session = oidc_login(&mut app, "ivy").await;
+    assert_eq!(
+        repository
+            .get_by_name("ivy")
+            .await
+            .unwrap()
+            .unwrap()
+            .password,
+        PASSWORD_UNSET
+    );
+
+    // 登录已过去很久，没有密码可填，需要重新认证
+    let conn = sea_orm::Database::connect(format!("sqlite://{}", config.db.path))
+        .await
+        .unwrap();
+    conn.execute(sea_orm::Statement::from_string(
+        sea_orm::DbBackend::Sqlite,
+        "UPDATE oidc_identities SET last_login_at = 0",
+    ))
+    .await
+    .unwrap();
+    for body in [
+        serde_json::json!({}),
+        serde_json::json!({ "password": "!" }),
+        serde_json::json!({ "code": "123456" }),
+    ] {
+        let response = call(
+            &mut app,
+            "DELETE",
+            routes::API_USER_ACCOUNT,
+            Some(&session),
+            Some(body),
+        )
+        .await;
+        assert_ne!(response.status(), StatusCode::OK);
+    }
+    let response = call(&mut app, "GET", routes::API_USER_INFO, Some(&session), None).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // 已关联的身份重新走一遍授权即视为重新认证
+    let (callback, state) =
+        authorize_at_issuer(&mut app, routes::API_USER_OIDC_LINK, Some(&session), "ivy").await;
+    let response = call(&mut app, "GET", &callback, Some(&state), None).await;
+    assert_eq!(response.status(), StatusCode::FOUND);
+    let response = call(
+        &mut app,
+        "DELETE",
+        routes::API_USER_ACCOUNT,
+        Some(&session),
+        Some(serde_json::json!({})),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = call(&mut app, "GET", routes::API_USER_INFO, Some(&session), None).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert!(repository.get_by_name("ivy").await.unwrap().is_none());
+    // 外部身份随账号删除，再次登录会创建新账号
+    let session = oidc_login(&mut app, "ivy").await;
+    assert_eq!(identities(&mut app, &session).await.len(), 1);
+}
+
+#[tokio::test]
+async fn oidc_login_requires_the_second_factor_when_enabled() {
+    let temp = TempDir::new().unwrap();
+    let issuer = start_issuer().await;
+    let mut app = app::create_app(oidc_config(&temp, &issuer)).await;
+    let response = register(&mut app, "judy").await;
+    let judy = cookie_of(&response, "slam").unwrap();
+    let (callback, state) = authorize_at_issuer(
+        &mut app,
+        routes::API_USER_OIDC_LINK,
+        Some(&judy),
+        "judy-sso",
+    )
+    .await;
+    let response = call(&mut app, "GET", &callback, Some(&state), None).await;
+    assert_eq!(response.status(), StatusCode::FOUND);
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let response = call(
+        &mut app,
+        "POST",
+        routes::API_USER_TWO_FACTOR_SETUP,
+        Some(&judy),
+        None,
+    )
+    .await;
+    let setup = json_of(response).await;
+    let secret = totp::base32_decode(setup["secret"].as_str().unwrap()).unwrap();
+    let response = call(
+        &mut app,
+        "POST",
+        routes::API_USER_TWO_FACTOR_ENABLE,
+        Some(&judy),
+        Some(serde_json::json!({ "code": totp::code_at(&secret, now) })),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // 外部身份登录只得到挑战令牌，不下发会话
+    let (callback, state) =
+        authorize_at_issuer(&mut app, routes::API_USER_OIDC_LOGIN, None, "judy-sso").await;
+    let response = call(&mut app, "GET", &callback, Some(&state), None).await;
+    assert_eq!(response.status(), StatusCode::FOUND);
+    assert!(cookie_of(&response, "slam").is_none());
+    let location = location_of(&response);
+    let challenge = location
+        .strip_prefix("/app#two_factor_challenge=")
+        .expect("challenge fragment")
+        .to_string();
+
+    let second_factor =
+        |code: String| serde_json::json!({ "challenge": challenge.clone(), "code": code });
+    let response = call(
+        &mut app,
+        "POST",
+        routes::API_USER_LOGIN_TWO_FACTOR,
+        None,
+        Some(second_factor("000000".to_string())),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let response = call(
+        &mut app,
+        "POST",
+        routes::API_USER_LOGIN_TWO_FACTOR,
+        None,
+        Some(second_factor(totp::code_at(&secret, now + 30))),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let session = cookie_of(&response, "slam").expect("session cookie");
+    assert_eq!(user_info(&mut app, &session).await["nickname"], "judy");
+}
+
+#[tokio::test]
+async fn auto_register_and_password_login_can_be_turned_off() {
+    let temp = TempDir::new().unwrap();
+    let issuer = start_issuer().await;
+    let mut config = oidc_config(&temp, &issuer);
+    config.security.oidc.as_mut().unwrap().auto_register = false;
+    let mut app = app::create_app(config.clone()).await;
+
+    let (callback, state) =
+        authorize_at_issuer(&mut app, routes::API_USER_OIDC_LOGIN, None, "heidi").await;
+    let response = call(&mut app, "GET", &callback, Some(&state), None).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let temp = TempDir::new().unwrap();
+    let mut config = oidc_config(&temp, &issuer);
+    config.security.password_login = false;
+    let mut app = app::create_app(config).await;
+    assert_eq!(
+        register(&mut app, "ivan").await.status(),
+        StatusCode::FORBIDDEN
+    );
+    let body = serde_json::json!({ "name": "ivan", "password": "p@ssw0rd" });
+    let response = call(&mut app, "POST", routes::API_USER_LOGIN, None, Some(body)).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let session = oidc_login(&mut app, "ivan").await;
+    assert_eq!(identities(&mut app, &session).await[0]["subject"], "ivan");
+}
+
+#[tokio::test]
+async fn oidc_endpoints_are_absent_when_not_configured() {
+    let temp = TempDir::new().unwrap();
+    let mut config = oidc_config(&temp, "http://127.0.0.1:9");
+    config.security.oidc = None;
+    let mut app = app::create_app(config).await;
+    let response = call(&mut app, "GET", routes::API_USER_OIDC_LOGIN, None, None).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
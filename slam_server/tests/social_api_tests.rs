@@ -0,0 +1,727 @@
+use axum::body::{Body, to_bytes};
+use axum::http::{Request, StatusCode};
+use slam_server::app::{self, AppConfig, routes};
+use tempfile::TempDir;
+use tower::Service;
+
+fn isolated_config(temp: &TempDir) -> AppConfig {
+    let mut config = AppConfig::default();
+    config.db.path = temp.path().join("sport.db").to_string_lossy().to_string();
+    config.ai.job_dir = temp.path().join("ai-jobs").to_string_lossy().to_string();
+    config
+}
+
+async fn call(
+    app: &mut axum::Router,
+    method: &str,
+    uri: &str,
+    cookie: &str,
+    body: Option<serde_json::Value>,
+) -> (StatusCode, serde_json::Value) {
+    let request = Request::builder()
+        .uri(uri)
+        .method(method)
+        .header("content-type", "application/json")
+        .header("cookie", cookie)
+        .body(body.map_or_else(Body::empty, |b| Body::from(b.to_string())))
+        .unwrap();
+    let response = app.call(request).await.unwrap();
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+    let value = serde_json::from_slice(&bytes).unwrap_or_else(
+        |_| serde_json::json!({ "raw": String::from_utf8_lossy(&bytes).to_string() }),
+    );
+    (status, value)
+}
+
+/// 注册用户，返回 (cookie, uid)
+async fn register(app: &mut axum::Router, name: &str) -> (String, i64) {
+    let body = serde_json::json!({ "name": name, "password": "p@ssw0rd-1", "nickname": name });
+    let request = Request::builder()
+        .uri(routes::API_USER_REGISTER)
+        .method("POST")
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    let response = app.call(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let cookie = response.headers()["set-cookie"]
+        .to_str()
+        .unwrap()
+        .split(';')
+        .next()
+        .unwrap()
+        .to_string();
+    let (_, info) = call(app, "GET", routes::API_USER_INFO, &cookie, None).await;
+    (cookie, info["uid"].as_i64().unwrap())
+}
+
+async fn insert_sport(app: &mut axum::Router, cookie: &str, start_time: i64, visibility: &str) {
+    let mut body = serde_json::json!({
+        "type": "Running",
+        "start_time": start_time,
+        "calories": 300,
+        "distance_meter": 5000,
+        "duration_second": 1800,
+        "heart_rate_avg": 140,
+        "heart_rate_max": 170,
+        "pace_average": "6'00''",
+        "tracks": [],
+        "visibility": visibility
+    });
+    if visibility.is_empty() {
+        body.as_object_mut().unwrap().remove("visibility");
+    }
+    let (status, resp) = call(app, "POST", routes::API_SPORT_INSERT, cookie, Some(body)).await;
+    assert_eq!(status, StatusCode::OK, "{resp}");
+}
+
+fn start_times(page: &serde_json::Value) -> Vec<i64> {
+    page["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|i| i["sport"]["start_time"].as_i64().unwrap())
+        .collect()
+}
+
+fn user_uri(route: &str, uid: i64) -> String {
+    route.replace(":uid", &uid.to_string())
+}
+
+#[tokio::test]
+async fn feed_lists_followed_sports_by_visibility_with_cursor() {
+    let temp = TempDir::new().unwrap();
+    let mut app = app::create_app(isolated_config(&temp)).await;
+    let (alice, alice_uid) = register(&mut app, "alice").await;
+    let (bob, _) = register(&mut app, "bob").await;
+    let (carol, _) = register(&mut app, "carol").await;
+    insert_sport(&mut app, &alice, 1_700_000_000, "private").await;
+    insert_sport(&mut app, &alice, 1_700_100_000, "followers").await;
+    insert_sport(&mut app, &alice, 1_700_200_000, "public").await;
+    // 未指定可见范围的运动默认仅自己可见
+    insert_sport(&mut app, &alice, 1_700_300_000, "").await;
+
+    let (status, followed) = call(
+        &mut app,
+        "POST",
+        &user_uri(routes::API_SOCIAL_FOLLOWING_USER, alice_uid),
+        &bob,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(followed["status"], "accepted");
+
+    let (_, page) = call(&mut app, "GET", routes::API_SOCIAL_FEED, &bob, None).await;
+    assert_eq!(start_times(&page), vec![1_700_200_000, 1_700_100_000]);
+    assert_eq!(page["items"][0]["user"]["nickname"], "alice");
+    assert!(page.get("next_cursor").is_none());
+
+    let (_, first) = call(
+        &mut app,
+        "GET",
+        &format!("{}?size=1", routes::API_SOCIAL_FEED),
+        &bob,
+        None,
+    )
+    .await;
+    assert_eq!(start_times(&first), vec![1_700_200_000]);
+    let cursor = first["next_cursor"].as_str().unwrap();
+    let (_, second) = call(
+        &mut app,
+        "GET",
+        &format!("{}?size=1&cursor={cursor}", routes::API_SOCIAL_FEED),
+        &bob,
+        None,
+    )
+    .await;
+    assert_eq!(start_times(&second), vec![1_700_100_000]);
+    assert!(second.get("next_cursor").is_none());
+    let (status, _) = call(
+        &mut app,
+        "GET",
+        &format!("{}?cursor=bogus", routes::API_SOCIAL_FEED),
+        &bob,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // 非粉丝只能看到公开运动，本人能看到全部
+    let alice_sports = user_uri(routes::API_SOCIAL_USER_SPORTS, alice_uid);
+    let (_, page) = call(&mut app, "GET", &alice_sports, &carol, None).await;
+    assert_eq!(start_times(&page), vec![1_700_200_000]);
+    let (_, page) = call(&mut app, "GET", &alice_sports, &bob, None).await;
+    assert_eq!(start_times(&page), vec![1_700_200_000, 1_700_100_000]);
+    let (_, page) = call(&mut app, "GET", &alice_sports, &alice, None).await;
+    assert_eq!(page["items"].as_array().unwrap().len(), 4);
+    let (_, carol_feed) = call(&mut app, "GET", routes::API_SOCIAL_FEED, &carol, None).await;
+    assert!(carol_feed["items"].as_array().unwrap().is_empty());
+
+    let (_, profile) = call(
+        &mut app,
+        "GET",
+        &user_uri(routes::API_SOCIAL_USER, alice_uid),
+        &bob,
+        None,
+    )
+    .await;
+    assert_eq!(profile["followers"], 1);
+    assert_eq!(profile["follow_status"], "accepted");
+
+    let (status, _) = call(
+        &mut app,
+        "DELETE",
+        &user_uri(routes::API_SOCIAL_FOLLOWING_USER, alice_uid),
+        &bob,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, page) = call(&mut app, "GET", routes::API_SOCIAL_FEED, &bob, None).await;
+    assert!(page["items"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn private_account_requires_follow_approval() {
+    let temp = TempDir::new().unwrap();
+    let mut app = app::create_app(isolated_config(&temp)).await;
+    let (alice, alice_uid) = register(&mut app, "alice").await;
+    let (bob, bob_uid) = register(&mut app, "bob").await;
+    let (carol, carol_uid) = register(&mut app, "carol").await;
+    insert_sport(&mut app, &alice, 1_700_000_000, "public").await;
+
+    let (status, settings) = call(
+        &mut app,
+        "PUT",
+        routes::API_SOCIAL_SETTINGS,
+        &alice,
+        Some(serde_json::json!({ "private_account": true })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(settings["private_account"], true);
+
+    // 私密账号的公开运动对非粉丝也不可见
+    let alice_sports = user_uri(routes::API_SOCIAL_USER_SPORTS, alice_uid);
+    let (_, page) = call(&mut app, "GET", &alice_sports, &bob, None).await;
+    assert!(page["items"].as_array().unwrap().is_empty());
+
+    let follow_alice = user_uri(routes::API_SOCIAL_FOLLOWING_USER, alice_uid);
+    for cookie in [&bob, &carol] {
+        let (_, followed) = call(&mut app, "POST", &follow_alice, cookie, None).await;
+        assert_eq!(followed["status"], "pending");
+    }
+    let (_, page) = call(&mut app, "GET", routes::API_SOCIAL_FEED, &bob, None).await;
+    assert!(page["items"].as_array().unwrap().is_empty());
+
+    let (_, pending) = call(
+        &mut app,
+        "GET",
+        &format!("{}?status=pending", routes::API_SOCIAL_FOLLOWERS),
+        &alice,
+        None,
+    )
+    .await;
+    assert_eq!(pending.as_array().unwrap().len(), 2);
+
+    let (status, _) = call(
+        &mut app,
+        "POST",
+        &user_uri(routes::API_SOCIAL_FOLLOWER_APPROVE, bob_uid),
+        &alice,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, page) = call(&mut app, "GET", routes::API_SOCIAL_FEED, &bob, None).await;
+    assert_eq!(start_times(&page), vec![1_700_000_000]);
+
+    let (status, _) = call(
+        &mut app,
+        "DELETE",
+        &user_uri(routes::API_SOCIAL_FOLLOWER, carol_uid),
+        &alice,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, following) = call(&mut app, "GET", routes::API_SOCIAL_FOLLOWING, &carol, None).await;
+    assert!(following.as_array().unwrap().is_empty());
+
+    // 改回公开账号后，待处理的申请自动通过
+    let (_, followed) = call(&mut app, "POST", &follow_alice, &carol, None).await;
+    assert_eq!(followed["status"], "pending");
+    call(
+        &mut app,
+        "PUT",
+        routes::API_SOCIAL_SETTINGS,
+        &alice,
+        Some(serde_json::json!({ "private_account": false })),
+    )
+    .await;
+    let (_, followers) = call(&mut app, "GET", routes::API_SOCIAL_FOLLOWERS, &alice, None).await;
+    let statuses: Vec<_> = followers
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|f| f["status"].as_str().unwrap())
+        .collect();
+    assert_eq!(statuses, vec!["accepted", "accepted"]);
+
+    let (status, _) = call(&mut app, "POST", &follow_alice, &alice, None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = call(
+        &mut app,
+        "POST",
+        &user_uri(routes::API_SOCIAL_FOLLOWING_USER, 999_999),
+        &bob,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, found) = call(
+        &mut app,
+        "GET",
+        &format!("{}?name=alice", routes::API_SOCIAL_USERS),
+        &bob,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(found["uid"].as_i64().unwrap(), alice_uid);
+}
+
+#[tokio::test]
+async fn share_link_is_public_counts_views_and_can_be_revoked() {
+    let temp = TempDir::new().unwrap();
+    let mut app = app::create_app(isolated_config(&temp)).await;
+    let (alice, _) = register(&mut app, "alice").await;
+    let (bob, _) = register(&mut app, "bob").await;
+    insert_sport(&mut app, &alice, 1_700_000_000, "private").await;
+    let (_, sports) = call(&mut app, "GET", routes::API_SPORT_LIST, &alice, None).await;
+    let sport_id = sports[0]["id"].as_i64().unwrap();
+
+    // 只能分享自己的运动记录
+    let (status, _) = call(
+        &mut app,
+        "POST",
+        routes::API_SPORT_SHARES,
+        &bob,
+        Some(serde_json::json!({ "sport_id": sport_id })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = call(
+        &mut app,
+        "POST",
+        routes::API_SPORT_SHARES,
+        &alice,
+        Some(serde_json::json!({ "sport_id": sport_id, "expires_in_days": 0 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, created) = call(
+        &mut app,
+        "POST",
+        routes::API_SPORT_SHARES,
+        &alice,
+        Some(serde_json::json!({ "sport_id": sport_id, "expires_in_days": 7 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{created}");
+    let url = created["url"].as_str().unwrap().to_string();
+
+    // 私有运动通过分享链接也能匿名查看
+    for expected_views in [1, 2] {
+        let (status, shared) = call(&mut app, "GET", &url, "", None).await;
+        assert_eq!(status, StatusCode::OK, "{shared}");
+        assert_eq!(shared["sport"]["start_time"], 1_700_000_000);
+        assert_eq!(shared["user"]["nickname"], "alice");
+        assert_eq!(shared["views"], expected_views);
+    }
+    let request = Request::builder()
+        .uri(created["card_url"].as_str().unwrap())
+        .body(Body::empty())
+        .unwrap();
+    let response = app.call(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "image/png");
+    let png = to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+    assert!(png.starts_with(b"\x89PNG"));
+
+    let (_, shares) = call(
+        &mut app,
+        "GET",
+        &format!("{}?sport_id={sport_id}", routes::API_SPORT_SHARES),
+        &alice,
+        None,
+    )
+    .await;
+    assert_eq!(shares[0]["views"], 2);
+    assert!(shares[0].get("token").is_none());
+
+    let revoke = routes::API_SPORT_SHARE.replace(":id", created["id"].as_str().unwrap());
+    let (status, _) = call(&mut app, "DELETE", &revoke, &bob, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = call(&mut app, "DELETE", &revoke, &alice, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = call(&mut app, "GET", &url, "", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = call(&mut app, "GET", "/api/share/not-a-token", "", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+async fn call_for(
+    app: &mut axum::Router,
+    uri: &str,
+    cookie: &str,
+    athlete: i64,
+) -> (StatusCode, serde_json::Value) {
+    let request = Request::builder()
+        .uri(uri)
+        .header("cookie", cookie)
+        .header("x-acting-for", athlete.to_string())
+        .body(Body::empty())
+        .unwrap();
+    let response = app.call(request).await.unwrap();
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+    let value = serde_json::from_slice(&bytes).unwrap_or_else(
+        |_| serde_json::json!({ "raw": String::from_utf8_lossy(&bytes).to_string() }),
+    );
+    (status, value)
+}
+
+#[tokio::test]
+async fn coach_reads_athlete_data_within_granted_scopes() {
+    let temp = TempDir::new().unwrap();
+    let mut app = app::create_app(isolated_config(&temp)).await;
+    let (alice, alice_uid) = register(&mut app, "alice").await;
+    let (coach, coach_uid) = register(&mut app, "coach").await;
+    insert_sport(&mut app, &alice, 1_700_000_000, "").await;
+    insert_sport(&mut app, &alice, 1_700_100_000, "").await;
+    let list = routes::API_SPORT_LIST;
+    let stats = format!("{}?kind=total&year=2023", routes::API_SPORT_STATS);
+
+    // 没有授权时不能代为访问
+    let (status, _) = call_for(&mut app, list, &coach, alice_uid).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, grant) = call(
+        &mut app,
+        "POST",
+        routes::API_COACH_GRANTS,
+        &alice,
+        Some(serde_json::json!({ "coach_uid": coach_uid, "scopes": ["sports:read"] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{grant}");
+    assert_eq!(grant["status"], "pending");
+    let (status, _) = call_for(&mut app, list, &coach, alice_uid).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (_, athletes) = call(&mut app, "GET", routes::API_COACH_ATHLETES, &coach, None).await;
+    assert_eq!(athletes[0]["user"]["uid"], alice_uid);
+    let accept = user_uri(routes::API_COACH_ATHLETE_ACCEPT, alice_uid);
+    let (status, _) = call(&mut app, "POST", &accept, &coach, None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, sports) = call_for(&mut app, list, &coach, alice_uid).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(sports.as_array().unwrap().len(), 2);
+    let (_, own) = call(&mut app, "GET", list, &coach, None).await;
+    assert!(own.as_array().unwrap().is_empty());
+    // 统计需要 stats:read，写接口不支持代理访问
+    let (status, _) = call_for(&mut app, &stats, &coach, alice_uid).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = call_for(&mut app, routes::API_USER_INFO, &coach, alice_uid).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let grant_uri = user_uri(routes::API_COACH_GRANT, coach_uid);
+    let (status, _) = call(
+        &mut app,
+        "PUT",
+        &grant_uri,
+        &alice,
+        Some(serde_json::json!({ "scopes": ["sports:read", "stats:read", "bogus"] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = call(
+        &mut app,
+        "PUT",
+        &grant_uri,
+        &alice,
+        Some(serde_json::json!({ "scopes": ["sports:read", "stats:read"] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, summary) = call_for(&mut app, &stats, &coach, alice_uid).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(summary["total_count"], 2);
+
+    // 每次代理访问都记入运动员的审计日志
+    let (_, entries) = call(&mut app, "GET", routes::API_USER_AUDIT, &alice, None).await;
+    let accesses = entries
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|e| e["action"] == "coach_access")
+        .count();
+    assert_eq!(accesses, 2);
+
+    let (status, _) = call(&mut app, "DELETE", &grant_uri, &alice, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = call_for(&mut app, list, &coach, alice_uid).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (_, grants) = call(&mut app, "GET", routes::API_COACH_GRANTS, &alice, None).await;
+    assert!(grants.as_array().unwrap().is_empty());
+}
+
+/// 按开始时间找到自己的运动记录 id
+async fn sport_id_at(app: &mut axum::Router, cookie: &str, start_time: i64) -> i64 {
+    let (_, sports) = call(app, "GET", routes::API_SPORT_LIST, cookie, None).await;
+    sports
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|s| s["start_time"] == start_time)
+        .unwrap()["id"]
+        .as_i64()
+        .unwrap()
+}
+
+fn sport_uri(route: &str, sport_id: i64) -> String {
+    route.replace(":id", &sport_id.to_string())
+}
+
+#[tokio::test]
+async fn comments_and_reactions_follow_sport_visibility() {
+    let temp = TempDir::new().unwrap();
+    let mut app = app::create_app(isolated_config(&temp)).await;
+    let (alice, alice_uid) = register(&mut app, "alice").await;
+    let (bob, _) = register(&mut app, "bob").await;
+    let (carol, _) = register(&mut app, "carol").await;
+    insert_sport(&mut app, &alice, 1_700_000_000, "followers").await;
+    insert_sport(&mut app, &alice, 1_700_100_000, "private").await;
+    let shared = sport_id_at(&mut app, &alice, 1_700_000_000).await;
+    let private = sport_id_at(&mut app, &alice, 1_700_100_000).await;
+    let comments = sport_uri(routes::API_SOCIAL_SPORT_COMMENTS, shared);
+    let say = |text: &str| Some(serde_json::json!({ "content": text }));
+
+    // 陌生人看不到仅粉丝可见的运动
+    let (status, _) = call(&mut app, "POST", &comments, &bob, say("nice")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    call(
+        &mut app,
+        "POST",
+        &user_uri(routes::API_SOCIAL_FOLLOWING_USER, alice_uid),
+        &bob,
+        None,
+    )
+    .await;
+    let (status, bob_comment) = call(&mut app, "POST", &comments, &bob, say("  nice run ")).await;
+    assert_eq!(status, StatusCode::OK, "{bob_comment}");
+    assert_eq!(bob_comment["content"], "nice run");
+    let private_comments = sport_uri(routes::API_SOCIAL_SPORT_COMMENTS, private);
+    let (status, _) = call(&mut app, "POST", &private_comments, &bob, say("hi")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // 同一俱乐部的成员按粉丝处理
+    let (_, club) = call(
+        &mut app,
+        "POST",
+        routes::API_CLUBS,
+        &alice,
+        Some(serde_json::json!({ "name": "Runners" })),
+    )
+    .await;
+    call(
+        &mut app,
+        "POST",
+        routes::API_CLUBS_JOIN,
+        &carol,
+        Some(serde_json::json!({ "code": club["join_code"] })),
+    )
+    .await;
+    let (status, carol_comment) = call(&mut app, "POST", &comments, &carol, say("go!")).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let bob_comment_uri = format!("{comments}/{}", bob_comment["id"]);
+    let carol_comment_uri = format!("{comments}/{}", carol_comment["id"]);
+    let (status, _) = call(&mut app, "PUT", &bob_comment_uri, &carol, say("edited")).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = call(&mut app, "PUT", &bob_comment_uri, &bob, say("great pace")).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = call(&mut app, "DELETE", &carol_comment_uri, &bob, None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = call(&mut app, "DELETE", &carol_comment_uri, &alice, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, list) = call(&mut app, "GET", &comments, &carol, None).await;
+    assert_eq!(list.as_array().unwrap().len(), 1);
+    assert_eq!(list[0]["content"], "great pace");
+    assert!(list[0]["updated_at"].is_i64());
+
+    let kudos = sport_uri(routes::API_SOCIAL_SPORT_REACTION, shared).replace(":kind", "kudos");
+    call(&mut app, "PUT", &kudos, &bob, None).await;
+    let (_, summary) = call(&mut app, "PUT", &kudos, &bob, None).await;
+    assert_eq!(summary["counts"][0]["count"], 1);
+    assert_eq!(summary["mine"][0], "kudos");
+    let (_, summary) = call(&mut app, "PUT", &kudos, &carol, None).await;
+    assert_eq!(summary["counts"][0]["count"], 2);
+    let (status, _) = call(
+        &mut app,
+        "PUT",
+        &kudos.replace("kudos", "boo"),
+        &carol,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // 主人收到评论和回应的通知，自己的互动和重复回应不通知
+    let (_, page) = call(&mut app, "GET", routes::API_NOTIFICATIONS, &alice, None).await;
+    assert_eq!(page["unread"], 4);
+    let notifications = &page["items"];
+    let kinds: Vec<&str> = notifications
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|n| n["kind"].as_str().unwrap())
+        .collect();
+    assert_eq!(kinds, vec!["reaction", "reaction", "comment", "comment"]);
+    assert_eq!(notifications[3]["actor"]["nickname"], "bob");
+    assert_eq!(notifications[3]["sport_id"], shared);
+
+    // 删除运动时一并删除评论和回应
+    let (status, _) = call(
+        &mut app,
+        "POST",
+        routes::API_SPORT_DELETE,
+        &alice,
+        Some(serde_json::json!({ "id": shared })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = call(&mut app, "GET", &comments, &bob, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    insert_sport(&mut app, &alice, 1_700_200_000, "public").await;
+    let reused = sport_id_at(&mut app, &alice, 1_700_200_000).await;
+    let (_, list) = call(
+        &mut app,
+        "GET",
+        &sport_uri(routes::API_SOCIAL_SPORT_COMMENTS, reused),
+        &bob,
+        None,
+    )
+    .await;
+    assert!(list.as_array().unwrap().is_empty());
+    let (_, summary) = call(
+        &mut app,
+        "GET",
+        &sport_uri(routes::API_SOCIAL_SPORT_REACTIONS, reused),
+        &bob,
+        None,
+    )
+    .await;
+    assert!(summary["counts"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn account_deletion_removes_notifications_about_the_user() {
+    let temp = TempDir::new().unwrap();
+    let mut app = app::create_app(isolated_config(&temp)).await;
+    let (alice, _) = register(&mut app, "alice").await;
+    let (bob, _) = register(&mut app, "bob").await;
+    let (carol, _) = register(&mut app, "carol").await;
+    insert_sport(&mut app, &alice, 1_700_000_000, "public").await;
+    let sport = sport_id_at(&mut app, &alice, 1_700_000_000).await;
+    let comments = sport_uri(routes::API_SOCIAL_SPORT_COMMENTS, sport);
+    for cookie in [&bob, &carol] {
+        let body = Some(serde_json::json!({ "content": "nice run" }));
+        let (status, _) = call(&mut app, "POST", &comments, cookie, body).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+    let (_, page) = call(&mut app, "GET", routes::API_NOTIFICATIONS, &alice, None).await;
+    assert_eq!(page["unread"], 2);
+
+    let (status, _) = call(
+        &mut app,
+        "DELETE",
+        routes::API_USER_ACCOUNT,
+        &bob,
+        Some(serde_json::json!({ "password": "p@ssw0rd-1" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, page) = call(&mut app, "GET", routes::API_NOTIFICATIONS, &alice, None).await;
+    assert_eq!(page["unread"], 1);
+    let items = page["items"].as_array().unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0]["actor"]["nickname"], "carol");
+}
+
+async fn insert_run(
+    app: &mut axum::Router,
+    cookie: &str,
+    start_time: i64,
+    distance_meter: i32,
+    duration_second: i32,
+) {
+    let body = serde_json::json!({
+        "type": "Running",
+        "start_time": start_time,
+        "calories": 300,
+        "distance_meter": distance_meter,
+        "duration_second": duration_second,
+        "pace_average": "",
+        "tracks": []
+    });
+    let (status, resp) = call(app, "POST", routes::API_SPORT_INSERT, cookie, Some(body)).await;
+    assert_eq!(status, StatusCode::OK, "{resp}");
+}
+
+#[tokio::test]
+async fn improved_personal_bests_are_notified() {
+    let temp = TempDir::new().unwrap();
+    let mut app = app::create_app(isolated_config(&temp)).await;
+    let (alice, _) = register(&mut app, "alice").await;
+    // 第一条纪录和没有超过纪录的运动都不通知
+    insert_run(&mut app, &alice, 1_700_000_000, 5000, 1800).await;
+    insert_run(&mut app, &alice, 1_700_100_000, 4000, 1700).await;
+    let (_, page) = call(&mut app, "GET", routes::API_NOTIFICATIONS, &alice, None).await;
+    assert_eq!(page["unread"], 0);
+
+    // 更远、更久且更快
+    insert_run(&mut app, &alice, 1_700_200_000, 10000, 3000).await;
+    let sport = sport_id_at(&mut app, &alice, 1_700_200_000).await;
+    let (_, page) = call(&mut app, "GET", routes::API_NOTIFICATIONS, &alice, None).await;
+    let items = page["items"].as_array().unwrap();
+    assert_eq!(items.len(), 3);
+    let mut records: Vec<(String, i64, i64)> = items
+        .iter()
+        .map(|n| {
+            assert_eq!(n["kind"], "personal_record");
+            assert_eq!(n["sport_id"], sport);
+            assert!(n.get("actor").is_none());
+            let detail: serde_json::Value =
+                serde_json::from_str(n["detail"].as_str().unwrap()).unwrap();
+            assert_eq!(detail["type"], "Running");
+            (
+                detail["metric"].as_str().unwrap().to_string(),
+                detail["value"].as_i64().unwrap(),
+                detail["previous_value"].as_i64().unwrap(),
+            )
+        })
+        .collect();
+    records.sort();
+    assert_eq!(
+        records,
+        vec![
+            ("fastest_pace".to_string(), 300, 360),
+            ("longest_distance".to_string(), 10000, 5000),
+            ("longest_duration".to_string(), 3000, 1800),
+        ]
+    );
+}
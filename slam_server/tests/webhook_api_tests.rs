@@ -0,0 +1,425 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::body::{Body, to_bytes};
+use axum::extract::State;
+use axum::http::{HeaderMap, Request, StatusCode};
+use axum::routing::post;
+use hmac::{Hmac, Mac};
+use sea_orm::ConnectionTrait;
+use sha2::Sha256;
+use slam_server::app::{self, AppConfig, routes};
+use tempfile::TempDir;
+use tower::Service;
+
+fn isolated_config(temp: &TempDir) -> AppConfig {
+    let mut config = AppConfig::default();
+    config.db.path = temp.path().join("sport.db").to_string_lossy().to_string();
+    config.ai.job_dir = temp.path().join("ai-jobs").to_string_lossy().to_string();
+    config.webhook.max_attempts = 3;
+    config.webhook.retry_base_seconds = 0;
+    // 接收方监听在本机
+    config.webhook.allow_private_targets = true;
+    config
+}
+
+/// 本地接收方：按队列返回状态码，队列为空时返回 200，并记下收到的请求
+#[derive(Clone, Default)]
+struct Receiver {
+    statuses: Arc<Mutex<VecDeque<u16>>>,
+    received: Arc<Mutex<Vec<(HeaderMap, String)>>>,
+}
+
+impl Receiver {
+    fn fail_next(&self, statuses: &[u16]) {
+        self.statuses.lock().unwrap().extend(statuses);
+    }
+
+    fn received(&self) -> Vec<(HeaderMap, String)> {
+        self.received.lock().unwrap().clone()
+    }
+}
+
+async fn receive(State(receiver): State<Receiver>, headers: HeaderMap, body: String) -> StatusCode {
+    receiver.received.lock().unwrap().push((headers, body));
+    let status = receiver.statuses.lock().unwrap().pop_front().unwrap_or(200);
+    StatusCode::from_u16(status).unwrap()
+}
+
+async fn start_receiver() -> (String, Receiver) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    let receiver = Receiver::default();
+    let router = axum::Router::new()
+        .route("/hook", post(receive))
+        .with_state(receiver.clone());
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+    (url, receiver)
+}
+
+async fn call(
+    app: &mut axum::Router,
+    method: &str,
+    uri: &str,
+    cookie: &str,
+    body: Option<serde_json::Value>,
+) -> (StatusCode, serde_json::Value) {
+    let request = Request::builder()
+        .uri(uri)
+        .method(method)
+        .header("content-type", "application/json")
+        .header("cookie", cookie)
+        .body(body.map_or_else(Body::empty, |b| Body::from(b.to_string())))
+        .unwrap();
+    let response = app.call(request).await.unwrap();
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+    let value = serde_json::from_slice(&bytes).unwrap_or_else(
+        |_| serde_json::json!({ "raw": String::from_utf8_lossy(&bytes).to_string() }),
+    );
+    (status, value)
+}
+
+/// 注册用户，返回 (cookie, uid)
+async fn register(app: &mut axum::Router, name: &str) -> (String, i64) {
+    let body = serde_json::json!({ "name": name, "password": "p@ssw0rd-1", "nickname": name });
+    let request = Request::builder()
+        .uri(routes::API_USER_REGISTER)
+        .method("POST")
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    let response = app.call(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let cookie = response.headers()["set-cookie"]
+        .to_str()
+        .unwrap()
+        .split(';')
+        .next()
+        .unwrap()
+        .to_string();
+    let (_, info) = call(app, "GET", routes::API_USER_INFO, &cookie, None).await;
+    (cookie, info["uid"].as_i64().unwrap())
+}
+
+fn running(start_time: i64) -> serde_json::Value {
+    serde_json::json!({
+        "type": "Running",
+        "start_time": start_time,
+        "calories": 300,
+        "distance_meter": 5000,
+        "duration_second": 1800,
+        "heart_rate_avg": 140,
+        "heart_rate_max": 170,
+        "pace_average": "6'00''",
+        "tracks": []
+    })
+}
+
+async fn insert_sport(app: &mut axum::Router, cookie: &str, start_time: i64) -> i64 {
+    let (status, resp) = call(
+        app,
+        "POST",
+        routes::API_SPORT_INSERT,
+        cookie,
+        Some(running(start_time)),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{resp}");
+    resp["id"].as_i64().unwrap()
+}
+
+fn deliveries_uri(webhook_id: &str) -> String {
+    routes::API_WEBHOOK_DELIVERIES.replace(":id", webhook_id)
+}
+
+/// 轮询投递记录直到满足条件
+async fn wait_for_log(
+    app: &mut axum::Router,
+    cookie: &str,
+    webhook_id: &str,
+    done: impl Fn(&[serde_json::Value]) -> bool,
+) -> Vec<serde_json::Value> {
+    for _ in 0..100 {
+        let (status, log) = call(app, "GET", &deliveries_uri(webhook_id), cookie, None).await;
+        assert_eq!(status, StatusCode::OK, "{log}");
+        let log = log.as_array().unwrap().clone();
+        if done(&log) {
+            return log;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    panic!("webhook deliveries did not settle");
+}
+
+fn all_settled(log: &[serde_json::Value], count: usize) -> bool {
+    log.len() == count
+        && log
+            .iter()
+            .all(|d| d["status"] == "succeeded" || d["status"] == "failed")
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> &'a str {
+    headers[name].to_str().unwrap()
+}
+
+#[tokio::test]
+async fn sport_events_are_signed_retried_and_redeliverable() {
+    let temp = TempDir::new().unwrap();
+    let mut app = app::create_app(isolated_config(&temp)).await;
+    let (url, receiver) = start_receiver().await;
+    let (alice, alice_uid) = register(&mut app, "alice").await;
+    let (bob, _) = register(&mut app, "bob").await;
+
+    for body in [
+        serde_json::json!({ "url": "ftp://example.com/hook", "events": ["sport.created"] }),
+        serde_json::json!({ "url": url, "events": ["sport.renamed"] }),
+        serde_json::json!({ "url": url, "events": [] }),
+    ] {
+        let (status, _) = call(&mut app, "POST", routes::API_WEBHOOKS, &alice, Some(body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+    let (status, created) = call(
+        &mut app,
+        "POST",
+        routes::API_WEBHOOKS,
+        &alice,
+        Some(serde_json::json!({
+            "url": url,
+            "events": ["sport.created", "sport.updated", "sport.deleted"]
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{created}");
+    let webhook_id = created["id"].as_str().unwrap().to_string();
+    let secret = created["secret"].as_str().unwrap().to_string();
+    assert!(secret.starts_with("whsec_"));
+    assert_eq!(created["all_users"], false);
+    let (_, listed) = call(&mut app, "GET", routes::API_WEBHOOKS, &alice, None).await;
+    assert_eq!(listed.as_array().unwrap().len(), 1);
+    assert!(listed[0].get("secret").is_none());
+
+    // 别人的运动不投递；第一次收到 500 后自动重试
+    insert_sport(&mut app, &bob, 1_700_000_000).await;
+    receiver.fail_next(&[500]);
+    let sport_id = insert_sport(&mut app, &alice, 1_700_100_000).await;
+    let log = wait_for_log(&mut app, &alice, &webhook_id, |log| all_settled(log, 1)).await;
+    assert_eq!(log[0]["status"], "succeeded");
+    assert_eq!(log[0]["event"], "sport.created");
+    assert_eq!(log[0]["attempts"], 2);
+    assert_eq!(log[0]["response_status"], 200);
+    assert!(log[0].get("last_error").is_none());
+
+    let received = receiver.received();
+    assert_eq!(received.len(), 2);
+    let (headers, body) = &received[1];
+    assert_eq!(header(headers, "content-type"), "application/json");
+    assert_eq!(header(headers, "x-slam-event"), "sport.created");
+    assert_eq!(header(headers, "x-slam-delivery"), log[0]["id"]);
+    let timestamp = header(headers, "x-slam-timestamp");
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+    mac.update(format!("{timestamp}.{body}").as_bytes());
+    let expected: String = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    assert_eq!(
+        header(headers, "x-slam-signature"),
+        format!("sha256={expected}")
+    );
+    let payload: serde_json::Value = serde_json::from_str(body).unwrap();
+    assert_eq!(payload["event"], "sport.created");
+    assert_eq!(payload["uid"], alice_uid);
+    assert_eq!(payload["data"]["id"], sport_id);
+    assert_eq!(payload["data"]["distance_meter"], 5000);
+    assert_eq!(received[0].1, *body);
+
+    let mut updated = running(1_700_100_000);
+    updated["id"] = serde_json::json!(sport_id);
+    updated["distance_meter"] = serde_json::json!(6000);
+    let (status, _) = call(
+        &mut app,
+        "POST",
+        routes::API_SPORT_UPDATE,
+        &alice,
+        Some(updated),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = call(
+        &mut app,
+        "POST",
+        routes::API_SPORT_DELETE,
+        &alice,
+        Some(serde_json::json!({ "id": sport_id })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let log = wait_for_log(&mut app, &alice, &webhook_id, |log| all_settled(log, 3)).await;
+    let events: Vec<&str> = log.iter().map(|d| d["event"].as_str().unwrap()).collect();
+    assert_eq!(
+        events,
+        vec!["sport.deleted", "sport.updated", "sport.created"]
+    );
+    let deleted: serde_json::Value =
+        serde_json::from_str(log[0]["payload"].as_str().unwrap()).unwrap();
+    assert_eq!(deleted["data"], serde_json::json!({ "id": sport_id }));
+    let updated: serde_json::Value =
+        serde_json::from_str(log[1]["payload"].as_str().unwrap()).unwrap();
+    assert_eq!(updated["data"]["distance_meter"], 6000);
+
+    // 重新投递生成新的记录，请求体与事件 id 不变
+    let original = log[2].clone();
+    let redeliver = routes::API_WEBHOOK_REDELIVER
+        .replace(":id", &webhook_id)
+        .replace(":delivery_id", original["id"].as_str().unwrap());
+    let (status, _) = call(&mut app, "POST", &redeliver, &bob, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = call(&mut app, "GET", &deliveries_uri(&webhook_id), &bob, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, again) = call(&mut app, "POST", &redeliver, &alice, None).await;
+    assert_eq!(status, StatusCode::OK, "{again}");
+    assert_ne!(again["id"], original["id"]);
+    assert_eq!(again["payload"], original["payload"]);
+    let log = wait_for_log(&mut app, &alice, &webhook_id, |log| all_settled(log, 4)).await;
+    assert_eq!(log[0]["id"], again["id"]);
+    assert_eq!(log[0]["status"], "succeeded");
+    assert_eq!(receiver.received().len(), 5);
+
+    let (status, _) = call(
+        &mut app,
+        "DELETE",
+        &routes::API_WEBHOOK.replace(":id", &webhook_id),
+        &alice,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, listed) = call(&mut app, "GET", routes::API_WEBHOOKS, &alice, None).await;
+    assert_eq!(listed, serde_json::json!([]));
+    let (status, _) = call(&mut app, "GET", &deliveries_uri(&webhook_id), &alice, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn admin_webhooks_receive_every_user_and_stop_after_max_attempts() {
+    let temp = TempDir::new().unwrap();
+    let mut config = isolated_config(&temp);
+    config.webhook.max_attempts = 2;
+    config.security.admins = vec!["root".to_string()];
+    let mut app = app::create_app(config.clone()).await;
+    let (root, _) = register(&mut app, "root").await;
+    // 管理员在启动时按已有账号提升
+    let mut app = app::create_app(config).await;
+    let (url, receiver) = start_receiver().await;
+    let (kate, kate_uid) = register(&mut app, "kate").await;
+
+    let body = serde_json::json!({ "url": url, "events": ["sport.created"] });
+    let (status, _) = call(
+        &mut app,
+        "POST",
+        routes::API_ADMIN_WEBHOOKS,
+        &kate,
+        Some(body.clone()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, created) = call(
+        &mut app,
+        "POST",
+        routes::API_ADMIN_WEBHOOKS,
+        &root,
+        Some(body),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{created}");
+    assert_eq!(created["all_users"], true);
+    let webhook_id = created["id"].as_str().unwrap().to_string();
+
+    // 两次都失败后不再重试
+    receiver.fail_next(&[503, 503]);
+    insert_sport(&mut app, &kate, 1_700_000_000).await;
+    let log = wait_for_log(&mut app, &root, &webhook_id, |log| all_settled(log, 1)).await;
+    assert_eq!(log[0]["status"], "failed");
+    assert_eq!(log[0]["attempts"], 2);
+    assert_eq!(log[0]["response_status"], 503);
+    assert!(
+        log[0]["last_error"]
+            .as_str()
+            .unwrap()
+            .starts_with("HTTP 503")
+    );
+    assert!(log[0].get("next_attempt_at").is_none());
+    let payload: serde_json::Value =
+        serde_json::from_str(log[0]["payload"].as_str().unwrap()).unwrap();
+    assert_eq!(payload["uid"], kate_uid);
+
+    let redeliver = routes::API_WEBHOOK_REDELIVER
+        .replace(":id", &webhook_id)
+        .replace(":delivery_id", log[0]["id"].as_str().unwrap());
+    let (status, _) = call(&mut app, "POST", &redeliver, &root, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let log = wait_for_log(&mut app, &root, &webhook_id, |log| all_settled(log, 2)).await;
+    assert_eq!(log[0]["status"], "succeeded");
+    assert_eq!(log[1]["status"], "failed");
+    assert_eq!(receiver.received().len(), 3);
+}
+
+#[tokio::test]
+async fn private_targets_are_rejected_by_default() {
+    let temp = TempDir::new().unwrap();
+    let mut config = isolated_config(&temp);
+    config.webhook.allow_private_targets = false;
+    config.webhook.max_attempts = 1;
+    let db_path = config.db.path.clone();
+    let mut app = app::create_app(config).await;
+    let (url, receiver) = start_receiver().await;
+    let (alice, _) = register(&mut app, "alice").await;
+
+    for target in [
+        url.as_str(),
+        "http://localhost/hook",
+        "http://10.1.2.3/hook",
+        "http://169.254.169.254/latest/meta-data",
+        "http://[::1]/hook",
+        "http://[::ffff:192.168.1.1]/hook",
+        "http://0.0.0.0/hook",
+    ] {
+        let body = serde_json::json!({ "url": target, "events": ["sport.created"] });
+        let (status, resp) = call(&mut app, "POST", routes::API_WEBHOOKS, &alice, Some(body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{target}: {resp}");
+    }
+    let (status, created) = call(
+        &mut app,
+        "POST",
+        routes::API_WEBHOOKS,
+        &alice,
+        Some(
+            serde_json::json!({ "url": "http://93.184.216.34/hook", "events": ["sport.created"] }),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{created}");
+    let webhook_id = created["id"].as_str().unwrap().to_string();
+
+    // 已保存的地址改为指向本机后，投递前的检查拒绝请求
+    let conn = sea_orm::Database::connect(format!("sqlite://{db_path}"))
+        .await
+        .unwrap();
+    conn.execute(sea_orm::Statement::from_sql_and_values(
+        sea_orm::DbBackend::Sqlite,
+        "UPDATE webhooks SET url = ? WHERE id = ?",
+        [url.clone().into(), webhook_id.clone().into()],
+    ))
+    .await
+    .unwrap();
+    insert_sport(&mut app, &alice, 1_700_000_000).await;
+    let log = wait_for_log(&mut app, &alice, &webhook_id, |log| all_settled(log, 1)).await;
+    assert_eq!(log[0]["status"], "failed");
+    assert!(log[0].get("response_status").is_none());
+    assert!(log[0]["last_error"].as_str().unwrap().contains("内网"));
+    assert!(receiver.received().is_empty());
+}